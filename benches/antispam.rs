@@ -0,0 +1,141 @@
+// benches/antispam.rs
+//
+// Levenshtein vs SimHash anti-spam throughput on a synthetic headline stream.
+// Std-only (no criterion): `cargo bench --bench antispam`.
+// ANTISPAM_BENCH_N overrides the stream length (default 2000).
+
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use dow_sentiment_analyzer::analyze::antispam::{AntiSpam, AntiSpamMethod, AntiSpamParams};
+
+const SUBJECTS: &[&str] = &[
+    "Fed",
+    "ECB",
+    "Treasury",
+    "Apple",
+    "Boeing",
+    "Oil",
+    "Dow futures",
+    "Goldman",
+    "OPEC",
+    "White House",
+    "Nvidia",
+    "Bank of Japan",
+];
+const VERBS: &[&str] = &[
+    "signals",
+    "warns of",
+    "rules out",
+    "confirms",
+    "weighs",
+    "delays",
+    "accelerates",
+    "denies",
+];
+const OBJECTS: &[&str] = &[
+    "rate cuts",
+    "new tariffs",
+    "a buyback",
+    "layoffs",
+    "supply cuts",
+    "a stimulus package",
+    "an investigation",
+    "higher guidance",
+    "export controls",
+    "a merger",
+];
+const TAILS: &[&str] = &[
+    "as markets rally",
+    "after weak data",
+    "amid inflation worries",
+    "ahead of earnings",
+    "despite pressure from investors",
+    "in a surprise move",
+    "as yields climb",
+];
+
+/// Deterministic xorshift so runs are comparable.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn pick<'a>(&mut self, xs: &[&'a str]) -> &'a str {
+        xs[(self.next() % xs.len() as u64) as usize]
+    }
+}
+
+/// Headlines with ~20% near-duplicate reposts of a recent item.
+fn stream(n: usize) -> Vec<String> {
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+    let mut out: Vec<String> = Vec::with_capacity(n);
+    for i in 0..n {
+        if i > 10 && rng.next().is_multiple_of(5) {
+            let back = 1 + (rng.next() % 10) as usize;
+            let orig = out[i - back].clone();
+            out.push(match rng.next() % 3 {
+                0 => format!("BREAKING: {orig}"),
+                1 => format!("{orig}!"),
+                _ => orig.to_uppercase(),
+            });
+        } else {
+            out.push(format!(
+                "{} {} {} {} (#{i})",
+                rng.pick(SUBJECTS),
+                rng.pick(VERBS),
+                rng.pick(OBJECTS),
+                rng.pick(TAILS)
+            ));
+        }
+    }
+    out
+}
+
+fn run(method: AntiSpamMethod, window: usize, items: &[String]) -> (Duration, usize) {
+    let mut anti = AntiSpam::new(AntiSpamParams {
+        window_size: window,
+        time_window_secs: 24 * 3600,
+        method,
+        ..AntiSpamParams::default()
+    });
+    let t0 = Instant::now();
+    let mut blocked = 0;
+    for (i, text) in items.iter().enumerate() {
+        let ts: SystemTime = UNIX_EPOCH + Duration::from_secs(1_700_000_000 + i as u64);
+        if anti.should_block(ts, text) {
+            blocked += 1;
+        }
+    }
+    (t0.elapsed(), blocked)
+}
+
+fn main() {
+    let n = std::env::var("ANTISPAM_BENCH_N")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(2000);
+    let items = stream(n);
+    println!("antispam: {n} headlines");
+    println!(
+        "{:>7} | {:>12} {:>10} {:>8} | {:>12} {:>10} {:>8} | {:>8}",
+        "window", "levenshtein", "items/s", "blocked", "simhash", "items/s", "blocked", "speedup"
+    );
+    for window in [64, 256, 1024, 2048] {
+        let (lt, lb) = run(AntiSpamMethod::Levenshtein, window, &items);
+        let (st, sb) = run(AntiSpamMethod::SimHash, window, &items);
+        let rate = |d: Duration| n as f64 / d.as_secs_f64();
+        println!(
+            "{window:>7} | {:>12.2?} {:>10.0} {lb:>8} | {:>12.2?} {:>10.0} {sb:>8} | {:>7.1}x",
+            lt,
+            rate(lt),
+            st,
+            rate(st),
+            lt.as_secs_f64() / st.as_secs_f64()
+        );
+    }
+}
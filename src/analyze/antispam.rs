@@ -3,20 +3,60 @@
 //! Goal: suppress near-duplicate texts that arrive within a short time window.
 //!
 //! Simple API, no external crates:
-//! - Configure with `AntiSpamParams { window_size, similarity_threshold, time_window_secs }`
+//! - Configure with `AntiSpamParams { window_size, similarity_threshold, time_window_secs, .. }`
 //! - Call `should_block(ts, text)` for stream processing: returns `true` if the item
 //!   should be filtered out (spam/near-duplicate), otherwise `false` (and the item is remembered)
+//! - `should_block_from(ts, source, text)` does the same with a source; with
+//!   `AntiSpamScope::PerSource` only items from the same source are compared.
 //! - Optionally call `filter_batch(items)` to keep only non-blocked items in one pass.
 //!
-//! Similarity metric: normalized Levenshtein similarity in [0.0, 1.0].
-//! An item is considered spam if there exists any recent (within the time window) remembered text
-//! whose similarity >= `similarity_threshold`.
+//! Methods (`AntiSpamParams::method`):
+//! - `Levenshtein` (default): normalized Levenshtein similarity in [0.0, 1.0]. An item is spam
+//!   if any recent (within the time window) remembered text has similarity >=
+//!   `similarity_threshold`. Exact, but every check scans the whole window (O(n·m²)).
+//! - `SimHash`: 64-bit SimHash over character 4-grams. An item is spam if a recent fingerprint
+//!   is within `simhash_max_distance` bits (Hamming). Lookups go through an LSH index: the
+//!   fingerprint is split into `simhash_max_distance + 1` bands, so any fingerprint within the
+//!   distance shares at least one band exactly (pigeonhole) and only bucket hits are compared.
+//!   Buckets are FIFO queues, so remembering or evicting an item costs one push or pop per
+//!   band. A lookup compares only the items sharing a band (near-duplicates and band
+//!   collisions), so it stays cheap as the window grows unless many texts collide; meant for
+//!   streaming ingest.
+//!
+//! Both methods evict by time (`time_window_secs`) and capacity (`window_size`).
+//! `cargo bench --bench antispam` compares them.
 //!
 //! NOTE: This module is intentionally self-contained and zero-deps.
 
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::time::{Duration, SystemTime};
 
+/// Near-duplicate detection method.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AntiSpamMethod {
+    /// Pairwise normalized Levenshtein against the whole window.
+    #[default]
+    Levenshtein,
+    /// SimHash fingerprints in an LSH band index.
+    SimHash,
+}
+
+/// Which remembered items a new item is compared against.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AntiSpamScope {
+    /// Any source (a wire story re-posted by another outlet is a duplicate).
+    #[default]
+    CrossSource,
+    /// Only items from the same source (normalized name).
+    PerSource,
+}
+
+/// Upper bound for `simhash_max_distance` (keeps LSH bands at least 4 bits wide).
+pub const SIMHASH_MAX_DISTANCE_CAP: u32 = 15;
+
+/// Character shingle length for SimHash features.
+const SHINGLE: usize = 4;
+
 /// Configuration for the anti-spam filter.
 #[derive(Clone, Debug)]
 pub struct AntiSpamParams {
@@ -26,6 +66,13 @@ pub struct AntiSpamParams {
     pub similarity_threshold: f32,
     /// Time window in seconds; only items newer than (ts - time_window_secs) are considered.
     pub time_window_secs: u64,
+    /// Detection method (`similarity_threshold` applies to Levenshtein only).
+    pub method: AntiSpamMethod,
+    /// Cross-source or per-source comparison.
+    pub scope: AntiSpamScope,
+    /// SimHash: max Hamming distance (of 64 bits) for near-duplicates; capped at
+    /// `SIMHASH_MAX_DISTANCE_CAP`. 6 is roughly Levenshtein 0.90 on headlines.
+    pub simhash_max_distance: u32,
 }
 
 impl Default for AntiSpamParams {
@@ -34,34 +81,68 @@ impl Default for AntiSpamParams {
             window_size: 128,
             similarity_threshold: 0.90,
             time_window_secs: 10 * 60, // 10 minutes
+            method: AntiSpamMethod::Levenshtein,
+            scope: AntiSpamScope::CrossSource,
+            simhash_max_distance: 6,
         }
     }
 }
 
+impl AntiSpamParams {
+    /// SimHash method with a window sized for streaming ingest.
+    pub fn simhash(window_size: usize, time_window_secs: u64) -> Self {
+        Self {
+            window_size,
+            time_window_secs,
+            method: AntiSpamMethod::SimHash,
+            ..Self::default()
+        }
+    }
+
+    fn sanitized(mut self) -> Self {
+        if self.window_size == 0 {
+            self.window_size = 1;
+        }
+        self.similarity_threshold = self.similarity_threshold.clamp(0.0, 1.0);
+        if self.time_window_secs == 0 {
+            self.time_window_secs = 1;
+        }
+        self.simhash_max_distance = self.simhash_max_distance.min(SIMHASH_MAX_DISTANCE_CAP);
+        self
+    }
+}
+
 #[derive(Clone, Debug)]
 struct SeenItem {
+    /// Monotonic id; ids in `window` are contiguous (front = oldest).
+    id: u64,
     ts: SystemTime,
+    /// Source key (0 in cross-source scope).
+    scope: u64,
+    /// Normalized text (Levenshtein only; empty for SimHash).
     text: String,
+    /// SimHash fingerprint (SimHash only).
+    fp: u64,
 }
 
+/// Bucket key: (scope, band index, band value).
+type BandKey = (u64, u8, u64);
+
 /// In-memory sliding-window anti-spam filter.
 #[derive(Debug)]
 pub struct AntiSpam {
     params: AntiSpamParams,
     window: VecDeque<SeenItem>,
+    /// LSH buckets of remembered item ids, oldest first (SimHash only).
+    bands: HashMap<BandKey, VecDeque<u64>>,
+    next_id: u64,
 }
 
 impl AntiSpam {
     /// Create a new filter with given params.
-    pub fn new(mut params: AntiSpamParams) -> Self {
+    pub fn new(params: AntiSpamParams) -> Self {
         // Basic parameter hygiene
-        if params.window_size == 0 {
-            params.window_size = 1;
-        }
-        params.similarity_threshold = params.similarity_threshold.clamp(0.0, 1.0);
-        if params.time_window_secs == 0 {
-            params.time_window_secs = 1;
-        }
+        let params = params.sanitized();
 
         // Save capacity before moving params
         let ws = params.window_size;
@@ -69,6 +150,8 @@ impl AntiSpam {
         Self {
             params,
             window: VecDeque::with_capacity(ws),
+            bands: HashMap::new(),
+            next_id: 0,
         }
     }
 
@@ -78,42 +161,76 @@ impl AntiSpam {
     }
 
     /// Update parameters at runtime (keeps current memory/window).
+    /// Changing `method`, `scope` or `simhash_max_distance` clears the memory, since
+    /// remembered items are keyed by them.
     pub fn set_params(&mut self, params: AntiSpamParams) {
-        let mut p = params;
-        if p.window_size == 0 {
-            p.window_size = 1;
-        }
-        p.similarity_threshold = p.similarity_threshold.clamp(0.0, 1.0);
-        if p.time_window_secs == 0 {
-            p.time_window_secs = 1;
-        }
+        let p = params.sanitized();
+        let rekey = p.method != self.params.method
+            || p.scope != self.params.scope
+            || p.simhash_max_distance != self.params.simhash_max_distance;
         self.params = p;
+        if rekey {
+            self.clear();
+        }
         // Shrink if needed
         while self.window.len() > self.params.window_size {
-            self.window.pop_front();
+            self.pop_front();
         }
     }
 
     /// Clears the remembered sliding window.
     pub fn clear(&mut self) {
         self.window.clear();
+        self.bands.clear();
+    }
+
+    /// Number of remembered items.
+    pub fn len(&self) -> usize {
+        self.window.len()
+    }
+
+    /// `true` when no item is remembered.
+    pub fn is_empty(&self) -> bool {
+        self.window.is_empty()
     }
 
     /// Decide whether to block the given text observed at `ts`.
     pub fn should_block(&mut self, ts: SystemTime, text: &str) -> bool {
+        self.should_block_from(ts, "", text)
+    }
+
+    /// Like `should_block`, for an item from `source` (used by `AntiSpamScope::PerSource`).
+    pub fn should_block_from(&mut self, ts: SystemTime, source: &str, text: &str) -> bool {
         let norm_text = normalize(text);
+        let scope = match self.params.scope {
+            AntiSpamScope::CrossSource => 0,
+            AntiSpamScope::PerSource => fnv1a(normalize(source).chars()),
+        };
         self.evict_old(ts);
 
-        // Check against recent memory
-        for item in self.window.iter().rev() {
-            let sim = normalized_levenshtein(&norm_text, &item.text);
-            if sim >= self.params.similarity_threshold {
-                return true;
+        match self.params.method {
+            AntiSpamMethod::Levenshtein => {
+                // Check against recent memory
+                for item in self.window.iter().rev() {
+                    if item.scope != scope {
+                        continue;
+                    }
+                    let sim = normalized_levenshtein(&norm_text, &item.text);
+                    if sim >= self.params.similarity_threshold {
+                        return true;
+                    }
+                }
+                // Otherwise accept and remember the item
+                self.remember(ts, scope, norm_text, 0);
+            }
+            AntiSpamMethod::SimHash => {
+                let fp = simhash(&norm_text);
+                if self.simhash_hit(scope, fp) {
+                    return true;
+                }
+                self.remember(ts, scope, String::new(), fp);
             }
         }
-
-        // Otherwise accept and remember the item
-        self.remember(ts, norm_text);
         false
     }
 
@@ -134,16 +251,62 @@ impl AntiSpam {
 
     // -- internals --
 
-    fn remember(&mut self, ts: SystemTime, norm_text: String) {
+    fn remember(&mut self, ts: SystemTime, scope: u64, norm_text: String, fp: u64) {
         if self.window.len() == self.params.window_size {
-            self.window.pop_front();
+            self.pop_front();
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        if self.params.method == AntiSpamMethod::SimHash {
+            for key in band_keys(scope, fp, self.params.simhash_max_distance) {
+                self.bands.entry(key).or_default().push_back(id);
+            }
         }
         self.window.push_back(SeenItem {
+            id,
             ts,
+            scope,
             text: norm_text,
+            fp,
         });
     }
 
+    /// Drop the oldest item (and its LSH bucket entries). Items leave in the order
+    /// they were remembered, so the item is at the front of each of its buckets.
+    fn pop_front(&mut self) {
+        let Some(old) = self.window.pop_front() else {
+            return;
+        };
+        if self.params.method == AntiSpamMethod::SimHash {
+            for key in band_keys(old.scope, old.fp, self.params.simhash_max_distance) {
+                if let Some(ids) = self.bands.get_mut(&key) {
+                    debug_assert_eq!(ids.front(), Some(&old.id));
+                    ids.pop_front();
+                    if ids.is_empty() {
+                        self.bands.remove(&key);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Any remembered fingerprint in `scope` within the max Hamming distance of `fp`.
+    fn simhash_hit(&self, scope: u64, fp: u64) -> bool {
+        let Some(first_id) = self.window.front().map(|i| i.id) else {
+            return false;
+        };
+        let max = self.params.simhash_max_distance;
+        band_keys(scope, fp, max).any(|key| {
+            self.bands.get(&key).is_some_and(|ids| {
+                ids.iter().any(|&id| {
+                    self.window
+                        .get((id - first_id) as usize)
+                        .is_some_and(|it| (it.fp ^ fp).count_ones() <= max)
+                })
+            })
+        })
+    }
+
     fn evict_old(&mut self, now: SystemTime) {
         let horizon = Duration::from_secs(self.params.time_window_secs);
         while let Some(front) = self.window.front() {
@@ -152,7 +315,7 @@ impl AntiSpam {
                 .unwrap_or_else(|_| Duration::from_secs(0))
                 > horizon
             {
-                self.window.pop_front();
+                self.pop_front();
             } else {
                 break;
             }
@@ -160,6 +323,66 @@ impl AntiSpam {
     }
 }
 
+/// LSH bands of `fp`: `max_distance + 1` near-equal slices of the 64 bits.
+fn band_keys(scope: u64, fp: u64, max_distance: u32) -> impl Iterator<Item = BandKey> {
+    let bands = max_distance as u64 + 1;
+    (0..bands).map(move |b| {
+        let lo = b * 64 / bands;
+        let hi = (b + 1) * 64 / bands;
+        let mask = if hi - lo == 64 {
+            u64::MAX
+        } else {
+            (1u64 << (hi - lo)) - 1
+        };
+        (scope, b as u8, (fp >> lo) & mask)
+    })
+}
+
+/// 64-bit SimHash over character shingles of already normalized text.
+fn simhash(norm: &str) -> u64 {
+    let chars: Vec<char> = norm.chars().collect();
+    let mut acc = [0i32; 64];
+    let mut add = |h: u64| {
+        for (bit, a) in acc.iter_mut().enumerate() {
+            if (h >> bit) & 1 == 1 {
+                *a += 1;
+            } else {
+                *a -= 1;
+            }
+        }
+    };
+    if chars.len() < SHINGLE {
+        add(mix64(fnv1a(chars.iter().copied())));
+    } else {
+        for w in chars.windows(SHINGLE) {
+            add(mix64(fnv1a(w.iter().copied())));
+        }
+    }
+    acc.iter()
+        .enumerate()
+        .filter(|(_, &a)| a > 0)
+        .fold(0u64, |fp, (bit, _)| fp | (1 << bit))
+}
+
+/// FNV-1a over chars (stable across runs, unlike `DefaultHasher` seeds).
+fn fnv1a(chars: impl Iterator<Item = char>) -> u64 {
+    let mut h: u64 = 0xcbf2_9ce4_8422_2325;
+    for c in chars {
+        for b in (c as u32).to_le_bytes() {
+            h ^= b as u64;
+            h = h.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+    h
+}
+
+/// SplitMix64 finalizer; spreads FNV output over all 64 bits.
+fn mix64(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
 /// Normalize text before similarity
 fn normalize(s: &str) -> String {
     let mut out = String::with_capacity(s.len());
@@ -225,10 +448,83 @@ mod tests {
     use super::*;
     use std::time::UNIX_EPOCH;
 
-    #[allow(dead_code)]
     fn ts(sec: u64) -> SystemTime {
         UNIX_EPOCH + Duration::from_secs(1_700_000_000 + sec)
     }
 
-    // … testy beze změn …
+    const HEADLINE: &str = "Fed signals rate cuts; Dow futures rally strongly on dovish outlook.";
+
+    #[test]
+    fn simhash_blocks_near_duplicates_only() {
+        let mut anti = AntiSpam::new(AntiSpamParams::simhash(1024, 600));
+        assert!(!anti.should_block(ts(0), HEADLINE));
+        assert!(anti.should_block(ts(10), &format!("{HEADLINE}!")));
+        assert!(anti.should_block(
+            ts(20),
+            "Fed signals rate cut; Dow futures rally strongly on a dovish outlook"
+        ));
+        assert!(!anti.should_block(ts(30), "Apple beats earnings estimates on iPhone demand"));
+        assert!(!anti.should_block(
+            ts(40),
+            "Apple misses earnings estimates on weak iPhone demand"
+        ));
+        assert_eq!(anti.len(), 3);
+    }
+
+    #[test]
+    fn simhash_evicts_by_time_and_capacity() {
+        let mut anti = AntiSpam::new(AntiSpamParams::simhash(2, 600));
+        assert!(!anti.should_block(ts(0), HEADLINE));
+        // Outside the time window the headline is new again; buckets were cleaned up.
+        assert!(!anti.should_block(ts(700), HEADLINE));
+        assert_eq!(anti.len(), 1);
+        let bucket_entries: usize = anti.bands.values().map(VecDeque::len).sum();
+        assert_eq!(bucket_entries, 7, "one entry per band");
+
+        assert!(!anti.should_block(ts(710), "Oil jumps after drone attack on Saudi facility"));
+        assert!(!anti.should_block(ts(720), "Stocks fall as tariffs bite into margins"));
+        // Capacity 2: the headline was pushed out.
+        assert!(!anti.should_block(ts(730), HEADLINE));
+    }
+
+    #[test]
+    fn per_source_scope_for_both_methods() {
+        for method in [AntiSpamMethod::Levenshtein, AntiSpamMethod::SimHash] {
+            let params = AntiSpamParams {
+                method,
+                scope: AntiSpamScope::PerSource,
+                ..AntiSpamParams::default()
+            };
+            let mut anti = AntiSpam::new(params.clone());
+            assert!(!anti.should_block_from(ts(0), "Reuters", HEADLINE));
+            assert!(
+                !anti.should_block_from(ts(1), "Bloomberg", HEADLINE),
+                "{method:?}"
+            );
+            assert!(
+                anti.should_block_from(ts(2), " reuters ", HEADLINE),
+                "{method:?}"
+            );
+
+            let mut cross = AntiSpam::new(AntiSpamParams {
+                scope: AntiSpamScope::CrossSource,
+                ..params
+            });
+            assert!(!cross.should_block_from(ts(0), "Reuters", HEADLINE));
+            assert!(
+                cross.should_block_from(ts(1), "Bloomberg", HEADLINE),
+                "{method:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn band_keys_cover_all_bits() {
+        for d in [0, 3, 6, SIMHASH_MAX_DISTANCE_CAP] {
+            let keys: Vec<_> = band_keys(0, u64::MAX, d).collect();
+            assert_eq!(keys.len() as u32, d + 1);
+            let bits: u32 = keys.iter().map(|(_, _, v)| v.count_ones()).sum();
+            assert_eq!(bits, 64);
+        }
+    }
 }
@@ -5,6 +5,7 @@ pub mod ai_adapter;
 pub mod antispam;
 pub mod debug;
 pub mod ner;
+pub mod pipeline;
 pub mod rerank;
 pub mod rules;
 pub mod scoring;
@@ -17,6 +18,8 @@ use std::time::SystemTime;
 
 // Re-export convenient types.
 pub use crate::analyze::antispam::{AntiSpam, AntiSpamParams};
+pub use crate::analyze::ner::{Entity, HotReloadNer, NerEngine};
+pub use crate::analyze::pipeline::{PipelineReport, PipelineToggles};
 pub use crate::analyze::rules::{HotReloadRules, RuleSet};
 pub use crate::analyze::scoring::{base_confidence, ScoreInputs};
 pub use crate::analyze::weights::{HotReloadWeights, Weights};
@@ -24,6 +27,24 @@ pub use crate::analyze::weights::{HotReloadWeights, Weights};
 /// Global hot-reloaded configs.
 static HOT_WEIGHTS: OnceLock<HotReloadWeights> = OnceLock::new();
 static HOT_RULES: OnceLock<HotReloadRules> = OnceLock::new();
+static HOT_NER: OnceLock<HotReloadNer> = OnceLock::new();
+
+/// Current rules from `RULES_CONFIG_PATH` (default `config/rules.json`; shared hot-reload handle).
+pub fn current_rules() -> std::sync::Arc<RuleSet> {
+    HOT_RULES
+        .get_or_init(|| {
+            let path = std::env::var("RULES_CONFIG_PATH").ok();
+            HotReloadRules::new(path.as_deref().map(std::path::Path::new))
+        })
+        .current()
+}
+
+/// Current NER engine for `NER_CONFIG_DIR` (default `config/ner`; shared hot-reload handle).
+pub fn current_ner() -> std::sync::Arc<NerEngine> {
+    HOT_NER
+        .get_or_init(HotReloadNer::from_env)
+        .current(&ner::config_dir())
+}
 
 /// Final response returned by the /decide endpoint.
 #[derive(Debug, Serialize)]
@@ -43,15 +64,14 @@ pub fn analyze_and_decide(input_text: &str) -> DecisionResult {
 
 /// Main analysis function with explicit scoring inputs (Phase 3 integration).
 /// Order:
-/// 1) NER enrichment (config/*.json)
+/// 1) NER enrichment (config/ner/*.json)
 /// 2) Base confidence from calibrated weights (config/weights.json)
 /// 3) Contextual rules (config/rules.json) that can set action / boost confidence / add reasons
 pub fn analyze_and_decide_with_signals(input_text: &str, inputs: ScoreInputs) -> DecisionResult {
     // (0) Hot configs
     let hot_w = HOT_WEIGHTS.get_or_init(|| HotReloadWeights::new(None));
     let w = hot_w.current();
-    let hot_r = HOT_RULES.get_or_init(|| HotReloadRules::new(None));
-    let rules = hot_r.current();
+    let rules = current_rules();
 
     // (1) Initial reasons (placeholder: extend by your pipeline)
     let mut reasons: Vec<String> = Vec::new();
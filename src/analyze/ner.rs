@@ -1,17 +1,34 @@
 // src/analyze/ner.rs
-//! NER extraction from JSON configs and simple integration helpers.
+//! NER extraction from JSON pattern files, compiled once and hot-reloaded.
 //!
-//! - Looks for *.json in a config dir (NER_CONFIG_DIR or ./config).
-//! - Each JSON: {"patterns":[{"regex":"...","keyword":"..."}]}
-//! - For each match, adds "category: keyword" where category = file stem (e.g. rates.json -> "rates").
+//! - Pattern files live in a dedicated dir: `NER_CONFIG_DIR` (default `config/ner`).
+//! - Each `*.json` file: {"patterns":[{"regex":"...","keyword":"..."}]}; category = file stem
+//!   (`rates.json` -> "rates").
+//! - `NerEngine::extract` returns structured [`Entity`] matches (category, keyword, byte span,
+//!   matched text); reason strings (`"category: keyword"`) are derived from them.
 //!
 //! Notes:
-//! - Reads files on every call (fine for dev/CI). Caching lze přidat později.
+//! - `HotReloadNer` recompiles only when a file in the dir is added, removed or modified
+//!   (mtime), or when `NER_CONFIG_DIR` points elsewhere. The dir is scanned at most once per
+//!   check interval (`NER_RELOAD_CHECK_MS`, default 2000), not on every call.
 //! - Regex kompatibilní s crate `regex` (bez lookaround), case-insensitive přes `(?i)`.
+//! - Unreadable files and invalid patterns are skipped with a warning.
 
 use regex::Regex;
-use serde::Deserialize;
-use std::{fs, path::PathBuf};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+    time::{Duration, Instant, SystemTime},
+};
+
+/// Default pattern directory.
+pub const DEFAULT_NER_DIR: &str = "config/ner";
+
+/// Default minimum time between two scans of the pattern dir.
+pub const DEFAULT_RELOAD_CHECK: Duration = Duration::from_secs(2);
 
 #[derive(Debug, Deserialize)]
 struct Pattern {
@@ -26,83 +43,260 @@ struct ConfigFile {
     pub patterns: Vec<Pattern>,
 }
 
-fn candidate_config_dirs() -> Vec<PathBuf> {
-    let mut dirs = Vec::new();
+/// One pattern match in a text.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Entity {
+    /// File stem of the pattern file (e.g. "inflation").
+    pub category: String,
+    /// Pattern keyword (e.g. "CPI").
+    pub keyword: String,
+    /// Byte range of the match in the input text.
+    pub span: Range<usize>,
+    /// Matched text as written in the input.
+    pub text: String,
+}
 
-    if let Ok(s) = std::env::var("NER_CONFIG_DIR") {
-        if !s.trim().is_empty() {
-            dirs.push(PathBuf::from(s));
-        }
+impl Entity {
+    /// Reason string in the form `"category: keyword"`.
+    pub fn reason(&self) -> String {
+        format!("{}: {}", self.category, self.keyword)
     }
-    // Fallback na relativní ./config vůči aktuálnímu CWD
-    dirs.push(PathBuf::from("config"));
+}
 
-    dirs
+#[derive(Debug)]
+struct CompiledPattern {
+    regex: Regex,
+    keyword: String,
 }
 
-/// Extracts named-entity reasons from `text` using JSON configs.
-/// Each match pushes a string in the form `"category: keyword"`.
-pub fn extract_reasons_from_configs(text: &str) -> Vec<String> {
-    let mut reasons = Vec::new();
+#[derive(Debug)]
+struct Category {
+    name: String,
+    patterns: Vec<CompiledPattern>,
+}
+
+/// Compiled NER patterns, grouped by category.
+#[derive(Debug, Default)]
+pub struct NerEngine {
+    categories: Vec<Category>,
+}
 
-    // Projdi kandidátní adresáře v pořadí (ENV -> ./config).
-    // Jakmile v jednom něco najdeme, další už není třeba číst (zamezí duplicitám).
-    for dir in candidate_config_dirs() {
-        if !dir.exists() {
-            continue;
+impl NerEngine {
+    /// Compile every `*.json` pattern file in `dir` (categories in file-name order).
+    /// A missing dir yields an empty engine.
+    pub fn load_dir(dir: &Path) -> Self {
+        let mut engine = Self::default();
+        for path in json_files(dir) {
+            let Some(category) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            let cfg: ConfigFile = match fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|c| serde_json::from_str(&c).map_err(|e| e.to_string()))
+            {
+                Ok(cfg) => cfg,
+                Err(e) => {
+                    tracing::warn!(path = %path.display(), error = %e, "skipping NER pattern file");
+                    continue;
+                }
+            };
+            engine.add_category(category, cfg.patterns);
         }
+        engine
+    }
 
-        let read_dir = match fs::read_dir(&dir) {
-            Ok(rd) => rd,
-            Err(_) => continue,
-        };
+    /// Compile one category from a JSON pattern file body.
+    pub fn add_category_json(&mut self, category: &str, json: &str) -> serde_json::Result<()> {
+        let cfg: ConfigFile = serde_json::from_str(json)?;
+        self.add_category(category, cfg.patterns);
+        Ok(())
+    }
 
-        let mut local_found = false;
+    fn add_category(&mut self, name: &str, patterns: Vec<Pattern>) {
+        let patterns = patterns
+            .into_iter()
+            .filter_map(|p| match Regex::new(&p.regex) {
+                Ok(regex) => Some(CompiledPattern {
+                    regex,
+                    keyword: p.keyword,
+                }),
+                Err(e) => {
+                    tracing::warn!(category = name, keyword = %p.keyword, error = %e, "skipping invalid NER regex");
+                    None
+                }
+            })
+            .collect();
+        self.categories.push(Category {
+            name: name.to_string(),
+            patterns,
+        });
+    }
 
-        for entry in read_dir.flatten() {
-            let path = entry.path();
+    /// Number of compiled patterns across all categories.
+    pub fn pattern_count(&self) -> usize {
+        self.categories.iter().map(|c| c.patterns.len()).sum()
+    }
 
-            // Jen *.json soubory
-            let is_json = path
-                .extension()
-                .and_then(|e| e.to_str())
-                .map(|e| e.eq_ignore_ascii_case("json"))
-                .unwrap_or(false);
-            if !is_json {
-                continue;
+    pub fn is_empty(&self) -> bool {
+        self.pattern_count() == 0
+    }
+
+    /// All matches in `text`, ordered by position (then category, pattern order).
+    pub fn extract(&self, text: &str) -> Vec<Entity> {
+        let mut out = Vec::new();
+        for cat in &self.categories {
+            for p in &cat.patterns {
+                out.extend(p.regex.find_iter(text).map(|m| Entity {
+                    category: cat.name.clone(),
+                    keyword: p.keyword.clone(),
+                    span: m.range(),
+                    text: m.as_str().to_string(),
+                }));
             }
+        }
+        out.sort_by_key(|e| e.span.start);
+        out
+    }
 
-            // Kategorie = stem souboru (rates.json -> "rates")
-            let Some(category) = path.file_stem().and_then(|s| s.to_str()) else {
-                continue;
-            };
+    /// Distinct `"category: keyword"` reasons for `text`, in order of first match.
+    pub fn reasons(&self, text: &str) -> Vec<String> {
+        let mut reasons: Vec<String> = Vec::new();
+        for e in self.extract(text) {
+            let r = e.reason();
+            if !reasons.contains(&r) {
+                reasons.push(r);
+            }
+        }
+        reasons
+    }
+}
 
-            let content = match fs::read_to_string(&path) {
-                Ok(c) => c,
-                Err(_) => continue,
-            };
+/// Pattern dir from `NER_CONFIG_DIR` (default `config/ner`).
+pub fn config_dir() -> PathBuf {
+    std::env::var("NER_CONFIG_DIR")
+        .ok()
+        .filter(|s| !s.trim().is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_NER_DIR))
+}
 
-            let cfg: ConfigFile = match serde_json::from_str(&content) {
-                Ok(v) => v,
-                Err(_) => continue, // tichý skip rozbitých configů
-            };
+/// `*.json` files in `dir`, sorted by name.
+fn json_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|rd| {
+            rd.flatten()
+                .map(|e| e.path())
+                .filter(|p| {
+                    p.extension()
+                        .and_then(|e| e.to_str())
+                        .is_some_and(|e| e.eq_ignore_ascii_case("json"))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    files
+}
 
-            for pat in cfg.patterns {
-                if let Ok(re) = Regex::new(&pat.regex) {
-                    if re.is_match(text) {
-                        reasons.push(format!("{category}: {}", pat.keyword));
-                        local_found = true;
-                    }
-                }
+/// Files and mtimes; a change in any of them triggers a recompile.
+type Fingerprint = Vec<(PathBuf, Option<SystemTime>)>;
+
+fn fingerprint(dir: &Path) -> Fingerprint {
+    json_files(dir)
+        .into_iter()
+        .map(|p| {
+            let mtime = fs::metadata(&p).and_then(|m| m.modified()).ok();
+            (p, mtime)
+        })
+        .collect()
+}
+
+#[derive(Debug)]
+struct State {
+    dir: Option<PathBuf>,
+    fingerprint: Fingerprint,
+    /// Last scan of `dir`.
+    checked_at: Option<Instant>,
+    engine: Arc<NerEngine>,
+}
+
+/// Hot-reloaded [`NerEngine`]: recompiles when the pattern dir changes.
+#[derive(Debug)]
+pub struct HotReloadNer {
+    check_interval: Duration,
+    inner: RwLock<State>,
+}
+
+impl Default for HotReloadNer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HotReloadNer {
+    pub fn new() -> Self {
+        Self {
+            check_interval: DEFAULT_RELOAD_CHECK,
+            inner: RwLock::new(State {
+                dir: None,
+                fingerprint: Vec::new(),
+                checked_at: None,
+                engine: Arc::new(NerEngine::default()),
+            }),
+        }
+    }
+
+    /// Check interval from `NER_RELOAD_CHECK_MS` (default [`DEFAULT_RELOAD_CHECK`]).
+    pub fn from_env() -> Self {
+        let interval = std::env::var("NER_RELOAD_CHECK_MS")
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_RELOAD_CHECK);
+        Self::new().with_check_interval(interval)
+    }
+
+    /// Minimum time between two scans of the dir (zero = scan on every call).
+    pub fn with_check_interval(mut self, interval: Duration) -> Self {
+        self.check_interval = interval;
+        self
+    }
+
+    /// Engine compiled from `dir`, recompiled only if the dir or its files changed.
+    /// Within the check interval the current engine is returned without touching the
+    /// filesystem; a different `dir` is always loaded right away.
+    pub fn current(&self, dir: &Path) -> Arc<NerEngine> {
+        {
+            let guard = self.inner.read().unwrap();
+            let fresh = guard
+                .checked_at
+                .is_some_and(|t| t.elapsed() < self.check_interval);
+            if guard.dir.as_deref() == Some(dir) && fresh {
+                return guard.engine.clone();
             }
         }
 
-        if local_found {
-            break; // našli jsme něco v tomto diru; další diry už nečteme
+        let fp = fingerprint(dir);
+        let mut guard = self.inner.write().unwrap();
+        guard.checked_at = Some(Instant::now());
+        if guard.dir.as_deref() != Some(dir) || guard.fingerprint != fp {
+            guard.engine = Arc::new(NerEngine::load_dir(dir));
+            guard.dir = Some(dir.to_path_buf());
+            guard.fingerprint = fp;
         }
+        guard.engine.clone()
     }
+}
 
-    reasons
+/// Structured entities in `text` from the shared engine (`NER_CONFIG_DIR`).
+pub fn extract_entities(text: &str) -> Vec<Entity> {
+    super::current_ner().extract(text)
+}
+
+/// Extracts named-entity reasons from `text` using the shared engine.
+/// Each distinct match yields a string in the form `"category: keyword"`.
+pub fn extract_reasons_from_configs(text: &str) -> Vec<String> {
+    super::current_ner().reasons(text)
 }
 
 /// Enrich an existing reasons vector with NER reasons extracted from `text`.
@@ -137,4 +331,99 @@ mod tests {
         // jen sanity — zachováme existující důvody
         assert!(out.iter().any(|s| s == "pipeline: base reason"));
     }
+
+    #[test]
+    fn entities_carry_span_and_matched_text() {
+        let mut ner = NerEngine::default();
+        ner.add_category_json(
+            "inflation",
+            r#"{"patterns":[{"regex":"(?i)\\bCPI\\b","keyword":"CPI"},
+                {"regex":"(?i)\\binflation\\b","keyword":"inflation"},
+                {"regex":"(unclosed","keyword":"broken"}]}"#,
+        )
+        .unwrap();
+        ner.add_category_json(
+            "rates",
+            r#"{"patterns":[{"regex":"(?i)\\brates?\\b","keyword":"rates"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(ner.pattern_count(), 3);
+
+        let text = "Hot cpi print: inflation sticky, rates up; CPI again";
+        let es = ner.extract(text);
+        assert_eq!(es.len(), 4);
+        assert_eq!(
+            es[0],
+            Entity {
+                category: "inflation".into(),
+                keyword: "CPI".into(),
+                span: 4..7,
+                text: "cpi".into(),
+            }
+        );
+        assert_eq!(&text[es[2].span.clone()], "rates");
+        assert_eq!(
+            ner.reasons(text),
+            vec!["inflation: CPI", "inflation: inflation", "rates: rates"]
+        );
+    }
+
+    #[test]
+    fn hot_reload_recompiles_on_change() {
+        let dir = std::env::temp_dir().join(format!("ner_hot_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("rates.json"),
+            r#"{"patterns":[{"regex":"(?i)\\brates?\\b","keyword":"rates"}]}"#,
+        )
+        .unwrap();
+        fs::write(dir.join("notes.txt"), "ignored").unwrap();
+
+        let hot = HotReloadNer::new().with_check_interval(Duration::ZERO);
+        let first = hot.current(&dir);
+        assert_eq!(first.pattern_count(), 1);
+        // Unchanged dir -> same compiled engine.
+        assert!(Arc::ptr_eq(&first, &hot.current(&dir)));
+
+        fs::write(
+            dir.join("earnings.json"),
+            r#"{"patterns":[{"regex":"(?i)\\bEPS\\b","keyword":"EPS"}]}"#,
+        )
+        .unwrap();
+        let second = hot.current(&dir);
+        assert_eq!(second.pattern_count(), 2);
+        assert_eq!(
+            second.reasons("EPS and rates"),
+            vec!["earnings: EPS", "rates: rates"]
+        );
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn hot_reload_scans_at_most_once_per_interval() {
+        let dir = std::env::temp_dir().join(format!("ner_throttle_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("rates.json"),
+            r#"{"patterns":[{"regex":"(?i)\\brates?\\b","keyword":"rates"}]}"#,
+        )
+        .unwrap();
+
+        let hot = HotReloadNer::new().with_check_interval(Duration::from_secs(3600));
+        assert_eq!(hot.current(&dir).pattern_count(), 1);
+        fs::write(
+            dir.join("earnings.json"),
+            r#"{"patterns":[{"regex":"(?i)\\bEPS\\b","keyword":"EPS"}]}"#,
+        )
+        .unwrap();
+        // Within the interval the new file is not seen yet...
+        assert_eq!(hot.current(&dir).pattern_count(), 1);
+        // ...but switching dirs is never throttled.
+        let other = dir.join("none");
+        assert_eq!(hot.current(&other).pattern_count(), 0);
+        assert_eq!(hot.current(&dir).pattern_count(), 2);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
 }
@@ -0,0 +1,609 @@
+//! Phase-3 stages wired into `POST /decide`.
+//!
+//! Order inside `decide::Scorer` / `decide::Finisher` (the handler and the backtest):
+//! 1. `antispam` — drop items that near-duplicate an earlier one in the same batch.
+//!    With `PIPELINE_ANTISPAM_ACROSS_REQUESTS=1` the window spans requests (it lives
+//!    in the API state), so a repost in a later request is dropped too.
+//! 2. relevance → sentiment → disruption (unchanged).
+//! 3. `rerank`   — per source, the latest relevant statement stands; earlier
+//!    near-duplicates get their disruption weights decayed (and stop triggering).
+//! 4. `ner`      — `category: keyword` reasons from `config/ner/*.json`.
+//! 5. `weights`  — calibrated `config/weights.json` applied to the triggers behind
+//!    the verdict: confidence moves by the change in trigger quality against
+//!    neutral 1/1/1 weights.
+//! 6. `rules`    — `config/rules.json` evaluated per item (text, source,
+//!    sentiment/relevance score, statement time): action override, confidence
+//!    delta (each matching rule counts once per decision) and reasons, applied
+//!    after `make_decision` and before the stabilizer.
+//!
+//! Each stage can be switched off via `PIPELINE_ANTISPAM`, `PIPELINE_RERANK`,
+//! `PIPELINE_NER`, `PIPELINE_WEIGHTS`, `PIPELINE_RULES` (`0` disables; default
+//! on). What the stages did is returned as the `pipeline` object of the response.
+
+use std::collections::HashSet;
+use std::time::{Duration, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use crate::analyze::antispam::AntiSpam;
+use crate::analyze::ner::enrich_reasons;
+use crate::analyze::rerank::{
+    rerank_keep_last_and_decay_duplicates, Statement, DEFAULT_DUPLICATE_DECAY,
+    DEFAULT_RELEVANCE_THRESHOLD, DEFAULT_SIMILARITY_THRESHOLD,
+};
+use crate::analyze::rules::{self, RuleContext, RuleSet};
+use crate::analyze::scoring::{base_confidence, ScoreInputs};
+use crate::analyze::weights::Weights;
+use crate::decision::{Decision, Reason, ReasonKind, Verdict};
+use crate::disruption::DisruptionResult;
+use crate::engine;
+use crate::sentiment::BatchItem;
+
+/// Per-stage switches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PipelineToggles {
+    pub antispam: bool,
+    /// Keep the anti-spam window across requests instead of per batch (off by
+    /// default: clients that re-send their recent feed would lose every item).
+    pub antispam_across_requests: bool,
+    pub rerank: bool,
+    pub ner: bool,
+    pub weights: bool,
+    pub rules: bool,
+}
+
+impl Default for PipelineToggles {
+    fn default() -> Self {
+        Self {
+            antispam: true,
+            antispam_across_requests: false,
+            rerank: true,
+            ner: true,
+            weights: true,
+            rules: true,
+        }
+    }
+}
+
+fn env_on(key: &str) -> bool {
+    std::env::var(key).map(|v| v.trim() != "0").unwrap_or(true)
+}
+
+fn env_opt_in(key: &str) -> bool {
+    std::env::var(key).is_ok_and(|v| v.trim() == "1")
+}
+
+impl PipelineToggles {
+    pub fn all_off() -> Self {
+        Self {
+            antispam: false,
+            antispam_across_requests: false,
+            rerank: false,
+            ner: false,
+            weights: false,
+            rules: false,
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self {
+            antispam: env_on("PIPELINE_ANTISPAM"),
+            antispam_across_requests: env_opt_in("PIPELINE_ANTISPAM_ACROSS_REQUESTS"),
+            rerank: env_on("PIPELINE_RERANK"),
+            ner: env_on("PIPELINE_NER"),
+            weights: env_on("PIPELINE_WEIGHTS"),
+            rules: env_on("PIPELINE_RULES"),
+        }
+    }
+
+    /// Enabled stages in execution order.
+    pub fn enabled(&self) -> Vec<&'static str> {
+        [
+            ("antispam", self.antispam),
+            ("rerank", self.rerank),
+            ("ner", self.ner),
+            ("weights", self.weights),
+            ("rules", self.rules),
+        ]
+        .into_iter()
+        .filter_map(|(name, on)| on.then_some(name))
+        .collect()
+    }
+}
+
+/// What the stages did for one `/decide` call (the `pipeline` response field).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct PipelineReport {
+    pub stages: Vec<&'static str>,
+    pub antispam_dropped: usize,
+    pub rerank_decayed: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub ner: Vec<String>,
+    /// Confidence change from the calibrated weights (see `weights_delta`).
+    #[serde(skip_serializing_if = "is_zero")]
+    pub weights_delta: f32,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rules_matched: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule_action: Option<String>,
+    /// Priority of the rule that set `rule_action`.
+    #[serde(skip)]
+    pub rule_action_priority: i32,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rule_conflicts: Vec<String>,
+    #[serde(skip_serializing_if = "is_zero")]
+    pub rule_confidence_delta: f32,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rule_reasons: Vec<String>,
+}
+
+fn is_zero(x: &f32) -> bool {
+    *x == 0.0
+}
+
+impl PipelineReport {
+    pub fn new(toggles: PipelineToggles) -> Self {
+        Self {
+            stages: toggles.enabled(),
+            ..Self::default()
+        }
+    }
+
+    /// Apply the rule outcome to the decision and add reasons for every stage
+    /// that changed something. Call before the stabilizer.
+    pub fn annotate(&self, d: &mut Decision, total: usize) {
+        if self.antispam_dropped > 0 {
+            d.reasons.push(
+                Reason::new(format!(
+                    "Anti-spam dropped {}/{} near-duplicate items",
+                    self.antispam_dropped, total
+                ))
+                .kind(ReasonKind::Threshold)
+                .weighted((self.antispam_dropped as f32 / total.max(1) as f32).min(1.0)),
+            );
+        }
+        if self.rerank_decayed > 0 {
+            d.reasons.push(
+                Reason::new(format!(
+                    "Rerank decayed {} earlier near-duplicate(s) of a source's latest statement (x{:.2})",
+                    self.rerank_decayed, DEFAULT_DUPLICATE_DECAY
+                ))
+                .kind(ReasonKind::Recency)
+                .weighted(0.2),
+            );
+        }
+        for r in &self.ner {
+            d.reasons.push(
+                Reason::new(format!("NER {r}"))
+                    .kind(ReasonKind::Other)
+                    .weighted(0.1),
+            );
+        }
+        if self.weights_delta != 0.0 {
+            let before = d.confidence;
+            d.confidence = (before + self.weights_delta).clamp(0.0, 0.99);
+            d.reasons.push(
+                Reason::new(format!(
+                    "Calibrated weights adjusted confidence {:+.3} ({before:.3}->{:.3})",
+                    self.weights_delta, d.confidence
+                ))
+                .kind(ReasonKind::Other)
+                .weighted(0.2),
+            );
+        }
+
+        if let Some(action) = &self.rule_action {
+            match parse_verdict(action) {
+                Some(v) if v != d.decision => {
+                    d.reasons.push(
+                        Reason::new(format!(
+                            "Rule action {} overrides {}",
+                            action.to_ascii_uppercase(),
+                            verdict_str(d.decision)
+                        ))
+                        .kind(ReasonKind::Other)
+                        .weighted(0.5),
+                    );
+                    d.decision = v;
+                }
+                Some(_) => {}
+                None => d.reasons.push(
+                    Reason::new(format!(
+                        "Rule action {action} (not a verdict; recorded only)"
+                    ))
+                    .kind(ReasonKind::Other)
+                    .weighted(0.1),
+                ),
+            }
+        }
+        if self.rule_confidence_delta != 0.0 {
+            let before = d.confidence;
+            d.confidence = (before + self.rule_confidence_delta).clamp(0.0, 0.99);
+            d.reasons.push(
+                Reason::new(format!(
+                    "Rules adjusted confidence {:+.2} ({before:.3}->{:.3})",
+                    self.rule_confidence_delta, d.confidence
+                ))
+                .kind(ReasonKind::Other)
+                .weighted(0.2),
+            );
+        }
+        for r in &self.rule_reasons {
+            d.reasons.push(
+                Reason::new(format!("Rule: {r}"))
+                    .kind(ReasonKind::Other)
+                    .weighted(0.3),
+            );
+        }
+        for c in &self.rule_conflicts {
+            d.reasons.push(
+                Reason::new(format!("Rule conflict: {c}"))
+                    .kind(ReasonKind::Other)
+                    .weighted(0.1),
+            );
+        }
+    }
+}
+
+fn parse_verdict(s: &str) -> Option<Verdict> {
+    match s.trim().to_ascii_uppercase().as_str() {
+        "BUY" => Some(Verdict::Buy),
+        "SELL" => Some(Verdict::Sell),
+        "HOLD" => Some(Verdict::Hold),
+        _ => None,
+    }
+}
+
+fn verdict_str(v: Verdict) -> &'static str {
+    match v {
+        Verdict::Buy => "BUY",
+        Verdict::Sell => "SELL",
+        Verdict::Hold => "HOLD",
+    }
+}
+
+/// Stage 1: drop items that near-duplicate a text `anti` already saw (earlier in
+/// the batch or in a previous call), in input order. Returns the kept items and
+/// the number dropped.
+pub fn antispam_filter<T>(
+    anti: &mut AntiSpam,
+    items: Vec<T>,
+    key: impl Fn(&T) -> (u64, &str),
+) -> (Vec<T>, usize) {
+    let total = items.len();
+    let kept: Vec<T> = items
+        .into_iter()
+        .filter(|it| {
+            let (ts, text) = key(it);
+            !anti.should_block(UNIX_EPOCH + Duration::from_secs(ts), text)
+        })
+        .collect();
+    let dropped = total - kept.len();
+    (kept, dropped)
+}
+
+/// Per-item facts collected while scoring (parallel to the scored batch).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ItemMeta {
+    /// Statement time as posted (before session carry-over).
+    pub ts_unix: u64,
+    /// Raw sentiment score (before the relevance gate).
+    pub score: i32,
+    /// Relevance score; `0.0` = neutralized by the gate.
+    pub relevance: f32,
+}
+
+/// Stage 3: rerank per source and decay earlier near-duplicates in place.
+/// `meta[i]` belongs to `scored[i]`. Returns the number of decayed items.
+pub fn rerank_decay(scored: &mut [(BatchItem, i32, DisruptionResult)], meta: &[ItemMeta]) -> usize {
+    let statements: Vec<Statement> = scored
+        .iter()
+        .zip(meta)
+        .map(|((it, _, _), m)| Statement {
+            source: it.source.clone(),
+            timestamp: m.ts_unix as i64,
+            text: it.text.clone(),
+            weight: 1.0,
+            relevance: if m.relevance > 0.0 { 1.0 } else { 0.0 },
+        })
+        .collect();
+    let reranked = rerank_keep_last_and_decay_duplicates(
+        statements,
+        DEFAULT_RELEVANCE_THRESHOLD,
+        DEFAULT_SIMILARITY_THRESHOLD,
+        DEFAULT_DUPLICATE_DECAY,
+    );
+
+    // Map decayed statements back to their (first unmatched) input position.
+    let mut used = vec![false; scored.len()];
+    let mut decayed = 0usize;
+    for st in reranked.iter().filter(|s| s.weight < 1.0) {
+        let pos = (0..scored.len()).find(|&i| {
+            !used[i]
+                && meta[i].ts_unix as i64 == st.timestamp
+                && scored[i].0.source == st.source
+                && scored[i].0.text == st.text
+        });
+        if let Some(i) = pos {
+            used[i] = true;
+            scored[i].2 = scored[i].2.decayed(st.weight);
+            decayed += 1;
+        }
+    }
+    decayed
+}
+
+/// Stage 4: sorted, de-duplicated NER reasons over all texts.
+pub fn ner_reasons<'a>(texts: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    texts.into_iter().fold(Vec::new(), enrich_reasons)
+}
+
+/// How much the triggers' quality term of `make_decision` counts (0.10 × avg).
+const QUALITY_SCALE: f32 = 0.10;
+
+/// Stage 5: confidence delta from calibrated `w` for the triggers behind `verdict`.
+///
+/// The triggers' `w_source`, `w_strength` and recency are averaged into
+/// `ScoreInputs`; the delta is `QUALITY_SCALE × (base_confidence(w) −
+/// base_confidence(1/1/1))`, so the default weights change nothing. HOLD or no
+/// matching trigger gives 0.
+pub fn weights_delta(
+    scored: &[(BatchItem, i32, DisruptionResult)],
+    verdict: Verdict,
+    w: &Weights,
+) -> f32 {
+    let direction = match verdict {
+        Verdict::Buy => 1,
+        Verdict::Sell => -1,
+        Verdict::Hold => return 0.0,
+    };
+    let triggers: Vec<&DisruptionResult> = scored
+        .iter()
+        .filter(|(_, score, res)| res.triggered && score.signum() == direction)
+        .map(|(_, _, res)| res)
+        .collect();
+    if triggers.is_empty() {
+        return 0.0;
+    }
+    let n = triggers.len() as f32;
+    let mean =
+        |f: &dyn Fn(&DisruptionResult) -> f32| triggers.iter().map(|r| f(r)).sum::<f32>() / n;
+    let inputs = ScoreInputs::new(
+        mean(&|r| r.w_source),
+        mean(&|r| r.w_strength),
+        mean(&|r| engine::recency_weight(r.age_secs)),
+    );
+    QUALITY_SCALE * (base_confidence(&inputs, w) - base_confidence(&inputs, &Weights::default()))
+}
+
+/// Stage 6: evaluate `rules` against every item. Across items the action of
+/// the highest-priority rule wins (first item on ties) and losing actions are
+/// reported as conflicts; each matching rule's confidence delta counts once,
+/// however many items it matched; reasons and matched rule names are
+/// de-duplicated in order.
+pub fn apply_rules<'a>(
+    report: &mut PipelineReport,
+    items: impl IntoIterator<Item = RuleContext<'a>>,
+    rules: &RuleSet,
+) {
+    fn push_unique(v: &mut Vec<String>, x: String) {
+        if !v.contains(&x) {
+            v.push(x);
+        }
+    }
+
+    let mut counted: HashSet<usize> = HashSet::new();
+    for ctx in items {
+        let out = rules::evaluate(&ctx, rules);
+        if let Some(action) = out.action {
+            match &report.rule_action {
+                None => {
+                    report.rule_action = Some(action);
+                    report.rule_action_priority = out.action_priority;
+                }
+                Some(current) if !current.eq_ignore_ascii_case(&action) => {
+                    let loser = if out.action_priority > report.rule_action_priority {
+                        let prev = report.rule_action.replace(action.clone());
+                        report.rule_action_priority = out.action_priority;
+                        prev.unwrap_or_default()
+                    } else {
+                        action.clone()
+                    };
+                    let winner = report.rule_action.clone().unwrap_or_default();
+                    push_unique(
+                        &mut report.rule_conflicts,
+                        format!("{loser} from another item lost to {winner}"),
+                    );
+                }
+                Some(_) => {}
+            }
+        }
+        for &i in &out.matched_rules {
+            if counted.insert(i) {
+                report.rule_confidence_delta += rules.rules[i].then.boost_confidence.unwrap_or(0.0);
+            }
+        }
+        for r in out.reasons {
+            push_unique(&mut report.rule_reasons, r);
+        }
+        for m in out.matched {
+            push_unique(&mut report.rules_matched, m);
+        }
+        for c in out.conflicts {
+            push_unique(&mut report.rule_conflicts, c);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyze::antispam::AntiSpamParams;
+
+    fn item(source: &str, text: &str) -> BatchItem {
+        BatchItem {
+            source: source.into(),
+            text: text.into(),
+        }
+    }
+
+    #[test]
+    fn antispam_drops_near_duplicates_in_order() {
+        let items = vec![
+            (100, "Fed signals rate cuts; Dow futures rally."),
+            (110, "Fed signals rate cuts; Dow futures rally!"),
+            (120, "Oil prices slide on supply glut."),
+        ];
+        let mut anti = AntiSpam::new(AntiSpamParams::default());
+        let (kept, dropped) = antispam_filter(&mut anti, items, |(ts, t)| (*ts, *t));
+        assert_eq!(dropped, 1);
+        assert_eq!(kept.len(), 2);
+        assert_eq!(kept[1].0, 120);
+
+        // The window carries over to the next batch.
+        let again = vec![(130, "Oil prices slide on supply glut!")];
+        let (kept, dropped) = antispam_filter(&mut anti, again, |(ts, t)| (*ts, *t));
+        assert_eq!((kept.len(), dropped), (0, 1));
+    }
+
+    #[test]
+    fn weights_shift_confidence_only_when_calibrated() {
+        let scored = vec![
+            (
+                item("Fed", "cut"),
+                2,
+                DisruptionResult::triggered(0.9, 1.0, 0),
+            ),
+            (
+                item("Trump", "up"),
+                2,
+                DisruptionResult::triggered(0.8, 1.0, 900),
+            ),
+        ];
+        assert_eq!(
+            weights_delta(&scored, Verdict::Buy, &Weights::default()),
+            0.0
+        );
+        let recency_heavy = Weights {
+            w_source: 0.3,
+            w_strength: 0.3,
+            w_recency: 2.4,
+        };
+        // Inputs (0.85, 1.0, 0.75): leaning on recency lowers the quality.
+        let d = weights_delta(&scored, Verdict::Buy, &recency_heavy);
+        assert!(d < 0.0 && d > -QUALITY_SCALE, "{d}");
+        assert_eq!(weights_delta(&scored, Verdict::Sell, &recency_heavy), 0.0);
+        assert_eq!(weights_delta(&scored, Verdict::Hold, &recency_heavy), 0.0);
+    }
+
+    #[test]
+    fn rule_delta_counts_once_per_decision() {
+        let rules: RuleSet = serde_json::from_str(
+            r#"{"rules":[{"name":"cut","when":{"any_contains":["cut"]},"then":{"boost_confidence":0.05}},
+                {"name":"cut","when":{"any_contains":["rates"]},"then":{"boost_confidence":0.01}}]}"#,
+        )
+        .unwrap();
+        let mut report = PipelineReport::new(PipelineToggles::default());
+        apply_rules(
+            &mut report,
+            [
+                RuleContext::text("Fed cut"),
+                RuleContext::text("ECB cut"),
+                RuleContext::text("BoE cut rates"),
+            ],
+            &rules,
+        );
+        // Both rules share a label; each still counts once.
+        assert!((report.rule_confidence_delta - 0.06).abs() < 1e-6);
+        assert_eq!(report.rules_matched, vec!["cut"]);
+    }
+
+    #[test]
+    fn rerank_decays_earlier_duplicate_of_same_source() {
+        let text = "Fed signals rate cuts; Dow futures rally.";
+        let mut scored = vec![
+            (
+                item("Fed", text),
+                2,
+                DisruptionResult::triggered(0.9, 1.0, 60),
+            ),
+            (
+                item("Fed", text),
+                2,
+                DisruptionResult::triggered(0.9, 1.0, 0),
+            ),
+            (
+                item("Trump", text),
+                2,
+                DisruptionResult::triggered(0.95, 1.0, 0),
+            ),
+        ];
+        let meta = |ts_unix| ItemMeta {
+            ts_unix,
+            score: 2,
+            relevance: 0.8,
+        };
+        let n = rerank_decay(&mut scored, &[meta(100), meta(160), meta(160)]);
+        assert_eq!(n, 1);
+        assert!(!scored[0].2.triggered);
+        assert!((scored[0].2.w_source - 0.63).abs() < 1e-6);
+        assert!(scored[1].2.triggered && scored[2].2.triggered);
+    }
+
+    #[test]
+    fn rule_action_overrides_verdict_and_adds_reasons() {
+        let rules: RuleSet = serde_json::from_str(
+            r#"{"rules":[{"name":"halt","when":{"any_contains":["trading halt"]},
+                "then":{"set_action":"HOLD","boost_confidence":-0.1,"add_reason":"halt"}}]}"#,
+        )
+        .unwrap();
+        let mut report = PipelineReport::new(PipelineToggles::default());
+        apply_rules(
+            &mut report,
+            [RuleContext::text("NYSE trading halt announced")],
+            &rules,
+        );
+        assert_eq!(report.rule_action.as_deref(), Some("HOLD"));
+
+        let mut d = Decision::buy(0.8);
+        report.annotate(&mut d, 1);
+        assert_eq!(d.decision, Verdict::Hold);
+        assert!((d.confidence - 0.7).abs() < 1e-6);
+        assert!(d.reasons.iter().any(|r| r.message == "Rule: halt"));
+        assert_eq!(
+            PipelineToggles {
+                rerank: false,
+                ..PipelineToggles::default()
+            }
+            .enabled(),
+            vec!["antispam", "ner", "weights", "rules"]
+        );
+    }
+
+    #[test]
+    fn highest_priority_action_wins_across_items() {
+        let rules: RuleSet = serde_json::from_str(
+            r#"{"rules":[
+                {"name":"cut","when":{"any_contains":["cut"]},"then":{"set_action":"BUY"}},
+                {"name":"war","priority":5,"when":{"any_contains":["war"],"source":["reuters"]},
+                 "then":{"set_action":"SELL"}}]}"#,
+        )
+        .unwrap();
+        let mut report = PipelineReport::new(PipelineToggles::default());
+        let war = RuleContext {
+            source: Some("Reuters"),
+            ..RuleContext::text("war escalates")
+        };
+        apply_rules(
+            &mut report,
+            [RuleContext::text("Fed rate cut"), war],
+            &rules,
+        );
+        assert_eq!(report.rule_action.as_deref(), Some("SELL"));
+        assert_eq!(report.rules_matched, vec!["cut", "war"]);
+        assert_eq!(
+            report.rule_conflicts,
+            vec!["BUY from another item lost to SELL"]
+        );
+    }
+}
@@ -1,34 +1,63 @@
 //! Contextual rules engine (hot-reloaded from `config/rules.json`).
 //!
-//! Minimal JSON DSL for conditions over the input text (case-insensitive):
+//! JSON DSL; all conditions of one `when` must hold (AND):
 //! - `any_contains`: match if ANY of phrases appears
 //! - `all_contains`: match if ALL of phrases appear
 //! - `not_contains`: match if NONE of phrases appear
 //! - `min_len`:      match if input length >= min_len (chars)
+//! - `regex`:        case-insensitive regex (crate `regex` syntax) over the raw text
+//! - `source`:       source equals one of the names, compared after alias
+//!   resolution via `SourceWeightsConfig` (`"@POTUS"` ≙ `"trump"`)
+//! - `score`:        sentiment score range `{ "min": 2, "max": 5 }` (inclusive, either side optional)
+//! - `relevance`:    relevance score range, same shape
+//! - `time`:         `{ "from": "09:30", "to": "16:00", "weekdays": ["mon", "fri"],
+//!   "tz": "America/New_York" }` on the statement time; `from > to` wraps midnight
+//! - `all` / `any` / `not`: nested `when` objects for boolean composition
+//!
+//! Conditions on context a caller did not provide (source, scores, time) do not match.
 //!
 //! Actions when a rule matches:
 //! - `set_action`:        "BUY" | "SELL" | "HOLD" | custom
 //! - `boost_confidence`:  f32 delta added to confidence (clamped later to [0,1])
 //! - `add_reason`:        string appended to reasons
 //!
-//! The file is hot-reloaded on mtime change at each `apply_rules()` call.
+//! Precedence: rules run by descending `priority` (default 0; file order within a
+//! priority). The first matching rule that sets an action wins; a later,
+//! different `set_action` is reported in `RuleOutcome::conflicts` instead of
+//! overriding it. A matching rule with `"stop": true` ends processing.
+//!
+//! The file is hot-reloaded on mtime change at each `current()` call; a file
+//! that fails `RuleSet::validate` is rejected and the previous rules stay.
+//! Regexes are compiled once at load, and `current()` hands out the loaded set
+//! behind an `Arc`, so evaluation never recompiles.
 
+use chrono::{DateTime, Datelike, NaiveTime, Timelike, Weekday};
+use chrono_tz::Tz;
+use regex::{Regex, RegexBuilder};
 use serde::Deserialize;
 use std::{
     fs, io,
     path::{Path, PathBuf},
-    sync::RwLock,
+    sync::{Arc, OnceLock, RwLock},
     time::SystemTime,
 };
 
+use crate::source_weights::SourceWeightsConfig;
+
 #[derive(Debug, Clone, Deserialize, Default)]
 pub struct RuleSet {
     pub rules: Vec<Rule>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize)]
 pub struct Rule {
     pub name: Option<String>,
+    /// Higher runs first.
+    #[serde(default)]
+    pub priority: i32,
+    /// Stop processing further rules once this one matches.
+    #[serde(default)]
+    pub stop: bool,
     #[serde(default)]
     pub when: When,
     #[serde(default)]
@@ -41,6 +70,43 @@ pub struct When {
     pub all_contains: Option<Vec<String>>,
     pub not_contains: Option<Vec<String>>,
     pub min_len: Option<usize>,
+    pub regex: Option<String>,
+    pub source: Option<Vec<String>>,
+    pub score: Option<Range>,
+    pub relevance: Option<Range>,
+    pub time: Option<TimeWindow>,
+    pub all: Option<Vec<When>>,
+    pub any: Option<Vec<When>>,
+    pub not: Option<Box<When>>,
+    /// Compiled `regex`, set by `RuleSet::validate` (or lazily on first use);
+    /// `None` inside = invalid pattern, never matches.
+    #[serde(skip)]
+    compiled: OnceLock<Option<Regex>>,
+}
+
+/// Inclusive numeric range; a missing bound is open.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct Range {
+    pub min: Option<f32>,
+    pub max: Option<f32>,
+}
+
+impl Range {
+    fn contains(&self, x: f32) -> bool {
+        self.min.is_none_or(|m| x >= m) && self.max.is_none_or(|m| x <= m)
+    }
+}
+
+/// Time-of-day / weekday window evaluated in `tz` (default `America/New_York`).
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TimeWindow {
+    /// `HH:MM`, inclusive.
+    pub from: Option<String>,
+    /// `HH:MM`, exclusive.
+    pub to: Option<String>,
+    /// `mon`..`sun` (or full names).
+    pub weekdays: Option<Vec<String>>,
+    pub tz: Option<String>,
 }
 
 #[derive(Debug, Clone, Default, Deserialize)]
@@ -50,6 +116,76 @@ pub struct Then {
     pub add_reason: Option<String>,
 }
 
+/// Facts a rule can test. Only `text` is required.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RuleContext<'a> {
+    pub text: &'a str,
+    pub source: Option<&'a str>,
+    /// Sentiment score of the text.
+    pub score: Option<i32>,
+    /// Relevance score in `[0.0, 1.0]`.
+    pub relevance: Option<f32>,
+    /// Statement time (UNIX seconds).
+    pub ts_unix: Option<u64>,
+    /// Alias table for `source` conditions (plain normalized compare without it).
+    pub sources: Option<&'a SourceWeightsConfig>,
+}
+
+impl<'a> RuleContext<'a> {
+    pub fn text(text: &'a str) -> Self {
+        Self {
+            text,
+            ..Self::default()
+        }
+    }
+}
+
+/// Result of running a rule set against one context.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RuleOutcome {
+    pub action: Option<String>,
+    /// Rule that set `action`.
+    pub action_rule: Option<String>,
+    pub action_priority: i32,
+    pub confidence_delta: f32,
+    pub reasons: Vec<String>,
+    /// Labels of all matching rules, in evaluation order.
+    pub matched: Vec<String>,
+    /// File positions of the rules in `matched` (labels need not be unique).
+    pub matched_rules: Vec<usize>,
+    /// Actions that lost to an earlier (higher-priority) rule.
+    pub conflicts: Vec<String>,
+    /// Label of the rule that stopped processing.
+    pub stopped_by: Option<String>,
+}
+
+impl Rule {
+    /// `name`, or `#<index>` (position in the file) for unnamed rules.
+    pub fn label(&self, index: usize) -> String {
+        self.name.clone().unwrap_or_else(|| format!("#{index}"))
+    }
+}
+
+impl RuleSet {
+    /// Problems that would make rules silently never match (bad regex, time,
+    /// weekday or time zone); empty when the set is valid. Valid regexes are
+    /// compiled and kept for evaluation.
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        for (i, rule) in self.rules.iter().enumerate() {
+            validate_when(&rule.when, &rule.label(i), &mut errors);
+        }
+        errors
+    }
+
+    /// Indices in evaluation order: descending priority, file order within one.
+    fn order(&self) -> Vec<usize> {
+        let mut idx: Vec<usize> = (0..self.rules.len()).collect();
+        idx.sort_by_key(|&i| std::cmp::Reverse(self.rules[i].priority));
+        idx
+    }
+}
+
 #[derive(Debug)]
 pub struct HotReloadRules {
     path: PathBuf,
@@ -58,7 +194,7 @@ pub struct HotReloadRules {
 
 #[derive(Debug)]
 struct State {
-    rules: RuleSet,
+    rules: Arc<RuleSet>,
     last_modified: Option<SystemTime>,
 }
 
@@ -70,13 +206,16 @@ impl HotReloadRules {
         Self {
             path,
             inner: RwLock::new(State {
-                rules: RuleSet::default(),
+                rules: Arc::new(RuleSet::default()),
                 last_modified: None,
             }),
         }
     }
 
-    pub fn current(&self) -> RuleSet {
+    /// The loaded rules, reloaded first if the file's mtime changed. A file that
+    /// fails to parse or validate is logged once and the previous rules stay in use
+    /// until it changes again.
+    pub fn current(&self) -> Arc<RuleSet> {
         // Check if reload is needed
         let (needs_reload, _new_mtime) = match fs::metadata(&self.path).and_then(|m| m.modified()) {
             Ok(mtime) => {
@@ -95,10 +234,15 @@ impl HotReloadRules {
         if let Ok(meta) = fs::metadata(&self.path) {
             if let Ok(mtime) = meta.modified() {
                 if guard.last_modified != Some(mtime) {
-                    if let Ok(rules) = load_rules_file(&self.path) {
-                        guard.rules = rules;
-                        guard.last_modified = Some(mtime);
+                    match load_rules_file(&self.path) {
+                        Ok(rules) => guard.rules = Arc::new(rules),
+                        Err(e) => tracing::warn!(
+                            path = %self.path.display(),
+                            error = %e,
+                            "rules file rejected; keeping the previous rules"
+                        ),
                     }
+                    guard.last_modified = Some(mtime);
                 }
             }
         }
@@ -110,42 +254,98 @@ pub fn load_rules_file(path: &Path) -> io::Result<RuleSet> {
     let bytes = fs::read(path)?;
     let rules: RuleSet = serde_json::from_slice(&bytes)
         .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let errors = rules.validate();
+    if !errors.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            errors.join("; "),
+        ));
+    }
     Ok(rules)
 }
 
-/// Apply rules to `(action, confidence, reasons)` given the `input_text`.
+/// Apply rules to `(action, confidence, reasons)` given the `input_text` only.
 /// Returns possibly updated `(action, confidence_delta, appended_reasons)`.
 pub fn apply_rules_to_text(
     input_text: &str,
     rules: &RuleSet,
 ) -> (Option<String>, f32, Vec<String>) {
-    let text = normalize(input_text);
+    let out = evaluate(&RuleContext::text(input_text), rules);
+    (out.action, out.confidence_delta, out.reasons)
+}
 
-    let mut new_action: Option<String> = None;
-    let mut delta_conf: f32 = 0.0;
-    let mut extra_reasons: Vec<String> = Vec::new();
+/// Run `rules` against `ctx` (see the module docs for precedence).
+pub fn evaluate(ctx: &RuleContext<'_>, rules: &RuleSet) -> RuleOutcome {
+    let text = normalize(ctx.text);
+    let mut out = RuleOutcome::default();
 
-    for rule in &rules.rules {
-        if matches_when(&text, &rule.when) {
-            if let Some(a) = &rule.then.set_action {
-                // Last matching rule wins for action (simple precedence).
-                new_action = Some(a.clone());
-            }
-            if let Some(d) = rule.then.boost_confidence {
-                delta_conf += d;
-            }
-            if let Some(r) = &rule.then.add_reason {
-                extra_reasons.push(r.clone());
+    for i in rules.order() {
+        let rule = &rules.rules[i];
+        if !matches_when(ctx, &text, &rule.when) {
+            continue;
+        }
+        let label = rule.label(i);
+        out.matched.push(label.clone());
+        out.matched_rules.push(i);
+
+        if let Some(a) = &rule.then.set_action {
+            match &out.action {
+                None => {
+                    out.action = Some(a.clone());
+                    out.action_rule = Some(label.clone());
+                    out.action_priority = rule.priority;
+                }
+                Some(current) if !current.eq_ignore_ascii_case(a) => {
+                    out.conflicts.push(format!(
+                        "{label} wanted {a}; kept {current} from {} (priority {})",
+                        out.action_rule.as_deref().unwrap_or("?"),
+                        out.action_priority
+                    ));
+                }
+                Some(_) => {}
             }
         }
+        if let Some(d) = rule.then.boost_confidence {
+            out.confidence_delta += d;
+        }
+        if let Some(r) = &rule.then.add_reason {
+            out.reasons.push(r.clone());
+        }
+        if rule.stop {
+            out.stopped_by = Some(label);
+            break;
+        }
     }
 
-    (new_action, delta_conf, extra_reasons)
+    out
+}
+
+/// How often each rule matched across `outcomes`, in file order (rules that
+/// never fired are listed with 0). Counted by position, so rules sharing a
+/// name are kept apart.
+pub fn fire_counts<'a>(
+    rules: &RuleSet,
+    outcomes: impl IntoIterator<Item = &'a RuleOutcome>,
+) -> Vec<(String, usize)> {
+    let mut counts: Vec<(String, usize)> = rules
+        .rules
+        .iter()
+        .enumerate()
+        .map(|(i, r)| (r.label(i), 0))
+        .collect();
+    for out in outcomes {
+        for &i in &out.matched_rules {
+            if let Some(c) = counts.get_mut(i) {
+                c.1 += 1;
+            }
+        }
+    }
+    counts
 }
 
 // --- internals ---
 
-fn matches_when(text: &str, w: &When) -> bool {
+fn matches_when(ctx: &RuleContext<'_>, text: &str, w: &When) -> bool {
     if let Some(min) = w.min_len {
         if text.chars().count() < min {
             return false;
@@ -166,9 +366,144 @@ fn matches_when(text: &str, w: &When) -> bool {
             return false;
         }
     }
+    if let Some(pattern) = &w.regex {
+        let re = w.compiled.get_or_init(|| compile_regex(pattern).ok());
+        if !re.as_ref().is_some_and(|re| re.is_match(ctx.text)) {
+            return false;
+        }
+    }
+    if let Some(names) = &w.source {
+        let Some(src) = ctx.source else {
+            return false;
+        };
+        let canon = |s: &str| match ctx.sources {
+            Some(cfg) => cfg.canonical(s),
+            None => normalize(s),
+        };
+        let src = canon(src);
+        if !names.iter().any(|n| canon(n) == src) {
+            return false;
+        }
+    }
+    if let Some(r) = &w.score {
+        if !ctx.score.is_some_and(|s| r.contains(s as f32)) {
+            return false;
+        }
+    }
+    if let Some(r) = &w.relevance {
+        if !ctx.relevance.is_some_and(|s| r.contains(s)) {
+            return false;
+        }
+    }
+    if let Some(tw) = &w.time {
+        if !ctx.ts_unix.is_some_and(|ts| time_matches(tw, ts)) {
+            return false;
+        }
+    }
+    if let Some(all) = &w.all {
+        if !all.iter().all(|c| matches_when(ctx, text, c)) {
+            return false;
+        }
+    }
+    if let Some(any) = &w.any {
+        if !any.iter().any(|c| matches_when(ctx, text, c)) {
+            return false;
+        }
+    }
+    if let Some(not) = &w.not {
+        if matches_when(ctx, text, not) {
+            return false;
+        }
+    }
     true
 }
 
+fn compile_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(pattern).case_insensitive(true).build()
+}
+
+fn parse_hhmm(s: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(s.trim(), "%H:%M").ok()
+}
+
+fn parse_tz(tw: &TimeWindow) -> Option<Tz> {
+    match &tw.tz {
+        Some(name) => name.parse().ok(),
+        None => Some(chrono_tz::America::New_York),
+    }
+}
+
+fn time_matches(tw: &TimeWindow, ts: u64) -> bool {
+    let (Some(tz), Some(utc)) = (parse_tz(tw), DateTime::from_timestamp(ts as i64, 0)) else {
+        return false;
+    };
+    let local = utc.with_timezone(&tz);
+
+    if let Some(days) = &tw.weekdays {
+        let today = local.weekday();
+        if !days
+            .iter()
+            .any(|d| d.trim().parse::<Weekday>().is_ok_and(|d| d == today))
+        {
+            return false;
+        }
+    }
+
+    let t =
+        NaiveTime::from_hms_opt(local.hour(), local.minute(), local.second()).unwrap_or_default();
+    let from = tw.from.as_deref().map(parse_hhmm);
+    let to = tw.to.as_deref().map(parse_hhmm);
+    match (from, to) {
+        (Some(None), _) | (_, Some(None)) => false,
+        (Some(Some(f)), Some(Some(e))) if f <= e => t >= f && t < e,
+        // Wraps midnight (e.g. 18:00 → 09:30).
+        (Some(Some(f)), Some(Some(e))) => t >= f || t < e,
+        (Some(Some(f)), None) => t >= f,
+        (None, Some(Some(e))) => t < e,
+        (None, None) => true,
+    }
+}
+
+fn validate_when(w: &When, label: &str, errors: &mut Vec<String>) {
+    if let Some(p) = &w.regex {
+        match compile_regex(p) {
+            Ok(re) => {
+                // Already set when validated before; same pattern either way.
+                let _ = w.compiled.set(Some(re));
+            }
+            Err(e) => errors.push(format!("{label}: invalid regex {p:?}: {e}")),
+        }
+    }
+    if let Some(tw) = &w.time {
+        for hhmm in [&tw.from, &tw.to].into_iter().flatten() {
+            if parse_hhmm(hhmm).is_none() {
+                errors.push(format!("{label}: invalid time {hhmm:?} (expected HH:MM)"));
+            }
+        }
+        for d in tw.weekdays.iter().flatten() {
+            if d.trim().parse::<Weekday>().is_err() {
+                errors.push(format!("{label}: invalid weekday {d:?}"));
+            }
+        }
+        if parse_tz(tw).is_none() {
+            errors.push(format!("{label}: unknown time zone {:?}", tw.tz));
+        }
+    }
+    for r in [&w.score, &w.relevance].into_iter().flatten() {
+        if let (Some(lo), Some(hi)) = (r.min, r.max) {
+            if lo > hi {
+                errors.push(format!("{label}: empty range min {lo} > max {hi}"));
+            }
+        }
+    }
+    for c in w.all.iter().chain(w.any.iter()).flatten() {
+        validate_when(c, label, errors);
+    }
+    if let Some(n) = &w.not {
+        validate_when(n, label, errors);
+    }
+}
+
 fn contains(text: &str, pat: &str) -> bool {
     // Normalize both sides (lowercase + condensed spaces),
     // then plain `contains(&str)`.
@@ -212,12 +547,14 @@ mod tests {
                     all_contains: None,
                     not_contains: None,
                     min_len: None,
+                    ..Default::default()
                 },
                 then: Then {
                     set_action: Some("BUY".into()),
                     boost_confidence: Some(0.2),
                     add_reason: Some("Matched rule: policy easing".into()),
                 },
+                ..Default::default()
             }],
         };
 
@@ -240,6 +577,7 @@ mod tests {
                     add_reason: Some("found".into()),
                     ..Default::default()
                 },
+                ..Default::default()
             }],
         };
         let (a, d, extra) = apply_rules_to_text("  POLICY   EASING\tconfirmed ", &rules);
@@ -247,4 +585,221 @@ mod tests {
         assert_eq!(d, 0.0);
         assert_eq!(extra, vec!["found"]);
     }
+
+    fn rules(json: &str) -> RuleSet {
+        let rs: RuleSet = serde_json::from_str(json).unwrap();
+        assert!(rs.validate().is_empty(), "{:?}", rs.validate());
+        rs
+    }
+
+    #[test]
+    fn regex_source_alias_and_ranges() {
+        let rs = rules(
+            r#"{"rules":[{"name":"potus tariffs",
+                "when":{"regex":"tariffs?\\s+on","source":["trump"],
+                        "score":{"max":-2},"relevance":{"min":0.5}},
+                "then":{"set_action":"SELL"}}]}"#,
+        );
+        let sw: SourceWeightsConfig =
+            serde_json::from_str(r#"{"weights":{"trump":0.95},"aliases":{"@potus":"trump"}}"#)
+                .unwrap();
+        let ctx = RuleContext {
+            text: "New TARIFFS on imports announced",
+            source: Some("@POTUS"),
+            score: Some(-3),
+            relevance: Some(0.8),
+            ts_unix: None,
+            sources: Some(&sw),
+        };
+        assert_eq!(evaluate(&ctx, &rs).action.as_deref(), Some("SELL"));
+
+        // Each condition must hold; missing context never matches.
+        for miss in [
+            RuleContext {
+                score: Some(-1),
+                ..ctx
+            },
+            RuleContext {
+                relevance: Some(0.2),
+                ..ctx
+            },
+            RuleContext {
+                source: Some("Reuters"),
+                ..ctx
+            },
+            RuleContext {
+                sources: None,
+                ..ctx
+            },
+            RuleContext {
+                text: "tariff relief",
+                ..ctx
+            },
+            RuleContext::text(ctx.text),
+        ] {
+            assert!(evaluate(&miss, &rs).matched.is_empty(), "{miss:?}");
+        }
+    }
+
+    #[test]
+    fn time_window_and_weekdays_in_new_york() {
+        let rs = rules(
+            r#"{"rules":[{"name":"cash open","when":{"time":{"from":"09:30","to":"10:00",
+                "weekdays":["mon","tue","wed","thu","fri"]}},"then":{"add_reason":"open"}},
+              {"name":"overnight","when":{"time":{"from":"18:00","to":"09:30","tz":"UTC"}},
+               "then":{"add_reason":"overnight"}}]}"#,
+        );
+        let at = |ts: u64| {
+            evaluate(
+                &RuleContext {
+                    ts_unix: Some(ts),
+                    ..RuleContext::text("x")
+                },
+                &rs,
+            )
+            .reasons
+        };
+        // Mon 2025-09-08 13:45Z = 09:45 EDT.
+        assert_eq!(at(1_757_339_100), vec!["open"]);
+        // Same time on Sunday 2025-09-07.
+        assert!(at(1_757_339_100 - 86_400).is_empty());
+        // 03:00Z wraps midnight in the UTC window.
+        assert_eq!(at(1_757_300_400), vec!["overnight"]);
+    }
+
+    #[test]
+    fn boolean_composition_nests() {
+        let rs = rules(
+            r#"{"rules":[{"name":"fed not hawkish",
+                "when":{"any":[{"any_contains":["fed"]},{"any_contains":["fomc"]}],
+                        "not":{"all":[{"any_contains":["hike"]},{"any_contains":["surprise"]}]}},
+                "then":{"add_reason":"hit"}}]}"#,
+        );
+        let hit = |t: &str| !evaluate(&RuleContext::text(t), &rs).matched.is_empty();
+        assert!(hit("FOMC holds steady"));
+        assert!(hit("Fed hike expected"));
+        assert!(!hit("Fed surprise hike"));
+        assert!(!hit("ECB holds steady"));
+    }
+
+    #[test]
+    fn priority_decides_action_and_stop_ends_processing() {
+        let rs = rules(
+            r#"{"rules":[
+                {"name":"late buy","when":{"any_contains":["cut"]},"then":{"set_action":"BUY","add_reason":"b"}},
+                {"name":"halt","priority":10,"when":{"any_contains":["halt"]},
+                 "then":{"set_action":"HOLD","add_reason":"h"}},
+                {"name":"hard stop","priority":5,"stop":true,"when":{"any_contains":["halt"]},
+                 "then":{"boost_confidence":-0.1}},
+                {"name":"catch-all","when":{},"then":{"add_reason":"tail"}}]}"#,
+        );
+        let out = evaluate(&RuleContext::text("Trading halt after rate cut"), &rs);
+        assert_eq!(out.action.as_deref(), Some("HOLD"));
+        assert_eq!(out.action_rule.as_deref(), Some("halt"));
+        assert_eq!(out.matched, vec!["halt", "hard stop"]);
+        assert_eq!(out.stopped_by.as_deref(), Some("hard stop"));
+        assert!((out.confidence_delta + 0.1).abs() < 1e-6);
+
+        // Without the stop rule, the lower-priority BUY is reported, not applied.
+        let mut no_stop = rs.clone();
+        no_stop.rules.remove(2);
+        let out = evaluate(&RuleContext::text("Trading halt after rate cut"), &no_stop);
+        assert_eq!(out.action.as_deref(), Some("HOLD"));
+        assert_eq!(out.conflicts.len(), 1);
+        assert!(out.conflicts[0].starts_with("late buy wanted BUY; kept HOLD from halt"));
+        assert_eq!(out.reasons, vec!["h", "b", "tail"]);
+    }
+
+    #[test]
+    fn validate_reports_bad_patterns() {
+        let rs: RuleSet = serde_json::from_str(
+            r#"{"rules":[{"when":{"regex":"(unclosed","all":[{"time":{"from":"25:00","tz":"Mars/Base"}}]}},
+                {"name":"r","when":{"score":{"min":3,"max":1},"time":{"weekdays":["funday"]}}}]}"#,
+        )
+        .unwrap();
+        let errs = rs.validate();
+        assert_eq!(errs.len(), 5, "{errs:?}");
+        assert!(errs[0].starts_with("#0: invalid regex"));
+        assert!(errs.iter().any(|e| e.starts_with("r: invalid weekday")));
+        // Invalid regex never matches at runtime.
+        let bad: RuleSet =
+            serde_json::from_str(r#"{"rules":[{"when":{"regex":"(unclosed"}}]}"#).unwrap();
+        assert!(evaluate(&RuleContext::text("(unclosed"), &bad)
+            .matched
+            .is_empty());
+    }
+
+    #[test]
+    fn fire_counts_include_silent_rules() {
+        let rs = rules(
+            r#"{"rules":[{"name":"cut","when":{"any_contains":["rate cut"]}},
+                {"when":{"any_contains":["tariff"]}},
+                {"name":"never","when":{"any_contains":["zzz"]}}]}"#,
+        );
+        let outs: Vec<_> = ["rate cut", "tariff and rate cut", "nothing"]
+            .iter()
+            .map(|t| evaluate(&RuleContext::text(t), &rs))
+            .collect();
+        assert_eq!(
+            fire_counts(&rs, &outs),
+            vec![("cut".into(), 2), ("#1".into(), 1), ("never".into(), 0)]
+        );
+
+        // Duplicate names are counted per rule, not merged into the first.
+        let dup = rules(
+            r#"{"rules":[{"name":"x","when":{"any_contains":["cut"]}},
+                {"name":"x","when":{"any_contains":["tariff"]}}]}"#,
+        );
+        let outs = [evaluate(&RuleContext::text("tariff"), &dup)];
+        assert_eq!(
+            fire_counts(&dup, &outs),
+            vec![("x".into(), 0), ("x".into(), 1)]
+        );
+    }
+
+    #[test]
+    fn hot_reload_compiles_once_and_shares_the_set() {
+        let path = std::env::temp_dir().join(format!("rules_hot_{}.json", std::process::id()));
+        fs::write(
+            &path,
+            r#"{"rules":[{"name":"re","when":{"regex":"tariffs?\\s+on"}}]}"#,
+        )
+        .unwrap();
+        let hot = HotReloadRules::new(Some(&path));
+        let a = hot.current();
+        let b = hot.current();
+        assert!(Arc::ptr_eq(&a, &b));
+        assert!(matches!(a.rules[0].when.compiled.get(), Some(Some(_))));
+        assert_eq!(
+            evaluate(&RuleContext::text("Tariffs on steel"), &a).matched,
+            vec!["re"]
+        );
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn hot_reload_keeps_previous_rules_until_invalid_file_changes() {
+        let path = std::env::temp_dir().join(format!("rules_bad_{}.json", std::process::id()));
+        fs::write(
+            &path,
+            r#"{"rules":[{"name":"ok","when":{"any_contains":["tariff"]}}]}"#,
+        )
+        .unwrap();
+        let hot = HotReloadRules::new(Some(&path));
+        let good = hot.current();
+        assert_eq!(good.rules.len(), 1);
+
+        fs::write(&path, r#"{"rules":[{"name":"bad","when":{"regex":"("}}]}"#).unwrap();
+        let bumped = SystemTime::now() + std::time::Duration::from_secs(5);
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(bumped)
+            .unwrap();
+        assert!(Arc::ptr_eq(&hot.current(), &good));
+        // The rejected file's mtime is recorded, so it is not parsed again.
+        assert_eq!(hot.inner.read().unwrap().last_modified, Some(bumped));
+        let _ = fs::remove_file(&path);
+    }
 }
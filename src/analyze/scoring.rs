@@ -8,10 +8,12 @@
 //! Base confidence = w_source*source + w_strength*strength + w_recency*recency
 //! (normalizace a clamp do [0,1] je součástí výpočtu).
 
+use serde::{Deserialize, Serialize};
+
 use super::Weights;
 
 /// Normalized inputs in [0,1]. Keep it small and clear.
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
 pub struct ScoreInputs {
     pub source_score: f32,
     pub strength_score: f32,
@@ -8,8 +8,15 @@
 //! }
 //!
 //! On each `current()` call we check the file's modified time and reload if changed.
+//! The path is `config/weights.json`, overridable via `WEIGHTS_CONFIG_PATH`.
+//!
+//! `fit_weights` searches weights that minimize log-loss or Brier score of
+//! `base_confidence` against labeled outcomes (used by the `fit_weights` binary).
+//! `base_confidence` normalizes by the weight sum, so only ratios matter: the
+//! search runs over the simplex and the result is scaled to a mean of 1.0.
 
-use serde::Deserialize;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
 use std::{
     fs, io,
     path::{Path, PathBuf},
@@ -17,7 +24,9 @@ use std::{
     time::SystemTime,
 };
 
-#[derive(Clone, Copy, Debug, Deserialize)]
+use super::scoring::{base_confidence, ScoreInputs};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Weights {
     pub w_source: f32,
     pub w_strength: f32,
@@ -62,6 +71,12 @@ impl HotReloadWeights {
         }
     }
 
+    /// Watch `WEIGHTS_CONFIG_PATH` (default `config/weights.json`).
+    pub fn from_env() -> Self {
+        let path = std::env::var("WEIGHTS_CONFIG_PATH").ok();
+        Self::new(path.as_deref().map(Path::new))
+    }
+
     /// Get the latest weights, reloading if the config file changed.
     pub fn current(&self) -> Weights {
         // Fast path: check metadata without grabbing write lock yet.
@@ -107,6 +122,192 @@ pub fn load_weights_file(path: &Path) -> io::Result<Weights> {
     Ok(w)
 }
 
+/// Write `w` as pretty JSON via a temp file in the same dir + rename, so a
+/// hot-reloading reader never sees half a file.
+pub fn save_weights_file(path: &Path, w: &Weights) -> io::Result<()> {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    let mut body = serde_json::to_vec_pretty(w)?;
+    body.push(b'\n');
+    fs::write(&tmp, body)?;
+    fs::rename(&tmp, path)
+}
+
+// --- fitting ---
+
+/// Historical `ScoreInputs` with the observed outcome.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct LabeledInputs {
+    #[serde(flatten)]
+    pub inputs: ScoreInputs,
+    /// Whether the decision made with these inputs turned out right.
+    #[serde(alias = "correct", alias = "hit")]
+    pub outcome: bool,
+}
+
+/// Objective for `fit_weights`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Loss {
+    #[default]
+    LogLoss,
+    Brier,
+}
+
+impl Loss {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Loss::LogLoss => "log_loss",
+            Loss::Brier => "brier",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().replace('-', "_").as_str() {
+            "log_loss" | "logloss" | "log" => Some(Loss::LogLoss),
+            "brier" => Some(Loss::Brier),
+            _ => None,
+        }
+    }
+}
+
+/// Both losses for one set of weights.
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct LossReport {
+    pub log_loss: f64,
+    pub brier: f64,
+}
+
+/// Result of `fit_weights`.
+#[derive(Clone, Debug, Serialize)]
+pub struct WeightsFit {
+    pub loss: &'static str,
+    pub samples: usize,
+    pub initial: Weights,
+    pub fitted: Weights,
+    pub before: LossReport,
+    pub after: LossReport,
+}
+
+/// Coarse grid step on the simplex, then a finer step around the best point.
+const COARSE_STEP: f64 = 0.05;
+const FINE_STEP: f64 = 0.005;
+/// Probability clamp for log-loss.
+const EPS: f64 = 1e-6;
+
+/// Mean log-loss and Brier score of `base_confidence` with `w` over `samples`.
+pub fn evaluate_weights(samples: &[LabeledInputs], w: &Weights) -> LossReport {
+    let n = samples.len().max(1) as f64;
+    let (mut ll, mut br) = (0.0, 0.0);
+    for s in samples {
+        let p = (base_confidence(&s.inputs, w) as f64).clamp(EPS, 1.0 - EPS);
+        let y = if s.outcome { 1.0 } else { 0.0 };
+        ll -= y * p.ln() + (1.0 - y) * (1.0 - p).ln();
+        br += (p - y) * (p - y);
+    }
+    LossReport {
+        log_loss: ll / n,
+        brier: br / n,
+    }
+}
+
+fn objective(samples: &[LabeledInputs], w: &Weights, loss: Loss) -> f64 {
+    let r = evaluate_weights(samples, w);
+    match loss {
+        Loss::LogLoss => r.log_loss,
+        Loss::Brier => r.brier,
+    }
+}
+
+/// Weights with the given simplex shares, scaled to a mean of 1.0.
+fn from_shares(source: f64, strength: f64) -> Weights {
+    let recency = (1.0 - source - strength).max(0.0);
+    let round = |x: f64| ((x * 3.0 * 1000.0).round() / 1000.0) as f32;
+    Weights {
+        w_source: round(source),
+        w_strength: round(strength),
+        w_recency: round(recency),
+    }
+}
+
+/// Source/strength shares in `[lo, hi]` (recency takes the rest), one `step` apart.
+struct Grid {
+    source: (f64, f64),
+    strength: (f64, f64),
+    step: f64,
+}
+
+/// Lower `best` to the best grid point, if any is strictly better.
+fn grid_search(samples: &[LabeledInputs], loss: Loss, grid: Grid, best: &mut (Weights, f64)) {
+    let steps = |(lo, hi): (f64, f64)| ((hi - lo) / grid.step).round() as usize;
+    for i in 0..=steps(grid.source) {
+        let s = grid.source.0 + i as f64 * grid.step;
+        for j in 0..=steps(grid.strength) {
+            let t = grid.strength.0 + j as f64 * grid.step;
+            if s + t > 1.0 + 1e-9 {
+                break;
+            }
+            let w = from_shares(s, t);
+            let l = objective(samples, &w, loss);
+            if l < best.1 - 1e-12 {
+                *best = (w, l);
+            }
+        }
+    }
+}
+
+/// Grid search over weight shares for the lowest `loss`; `initial` is kept
+/// unless a candidate is strictly better.
+pub fn fit_weights(samples: &[LabeledInputs], loss: Loss, initial: Weights) -> WeightsFit {
+    let mut best = (initial, objective(samples, &initial, loss));
+    let coarse = Grid {
+        source: (0.0, 1.0),
+        strength: (0.0, 1.0),
+        step: COARSE_STEP,
+    };
+    grid_search(samples, loss, coarse, &mut best);
+
+    let w = best.0;
+    let sum = (w.w_source + w.w_strength + w.w_recency).max(1e-6) as f64;
+    let around = |x: f64| ((x - COARSE_STEP).max(0.0), (x + COARSE_STEP).min(1.0));
+    let fine = Grid {
+        source: around(w.w_source as f64 / sum),
+        strength: around(w.w_strength as f64 / sum),
+        step: FINE_STEP,
+    };
+    grid_search(samples, loss, fine, &mut best);
+    let best = best.0;
+
+    WeightsFit {
+        loss: loss.as_str(),
+        samples: samples.len(),
+        initial,
+        fitted: best,
+        before: evaluate_weights(samples, &initial),
+        after: evaluate_weights(samples, &best),
+    }
+}
+
+/// Load labeled inputs from JSONL (`{"source_score":..,"strength_score":..,
+/// "recency_score":..,"outcome":true}` per line; `#` comments allowed).
+/// Inputs are clamped to [0,1].
+pub fn load_labeled_inputs<P: AsRef<Path>>(path: P) -> anyhow::Result<Vec<LabeledInputs>> {
+    let path = path.as_ref();
+    let raw = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+    raw.lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty() && !l.trim_start().starts_with('#'))
+        .map(|(i, l)| {
+            let mut s: LabeledInputs =
+                serde_json::from_str(l).with_context(|| format!("{}:{}", path.display(), i + 1))?;
+            let x = s.inputs;
+            s.inputs = ScoreInputs::new(x.source_score, x.strength_score, x.recency_score);
+            Ok(s)
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -146,12 +347,14 @@ mod tests {
         // Ensure different mtime (Windows granularity can be coarse).
         thread::sleep(Duration::from_millis(1100));
 
-        // Update file
-        {
-            let mut f = fs::File::create(&path).unwrap();
-            write!(f, r#"{{"w_source":2.0,"w_strength":2.0,"w_recency":2.0}}"#).unwrap();
-            f.sync_all().unwrap();
-        }
+        // Update file the way `fit_weights` does (temp file + rename).
+        let fitted = Weights {
+            w_source: 2.0,
+            w_strength: 2.0,
+            w_recency: 2.0,
+        };
+        save_weights_file(&path, &fitted).unwrap();
+        assert!(!path.with_extension("json.tmp").exists());
 
         let w2 = hot.current();
         assert!((w2.w_source - 2.0).abs() < f32::EPSILON);
@@ -162,4 +365,44 @@ mod tests {
         let _ = fs::remove_file(&path);
         let _ = fs::remove_dir_all(&tmpdir);
     }
+
+    #[test]
+    fn fit_prefers_the_predictive_signal() {
+        // Strength tracks the outcome; source and recency are noise.
+        let samples: Vec<LabeledInputs> = (0..200)
+            .map(|i| {
+                let strength = (i % 10) as f32 / 10.0 + 0.05;
+                LabeledInputs {
+                    inputs: ScoreInputs::new(((i * 7) % 10) as f32 / 10.0, strength, 0.9),
+                    outcome: (i * 37 % 100) as f32 / 100.0 < strength,
+                }
+            })
+            .collect();
+
+        for loss in [Loss::LogLoss, Loss::Brier] {
+            let fit = fit_weights(&samples, loss, Weights::default());
+            let w = fit.fitted;
+            assert!(
+                w.w_strength > w.w_source && w.w_strength > w.w_recency,
+                "{w:?}"
+            );
+            assert!((w.w_source + w.w_strength + w.w_recency - 3.0).abs() < 0.01);
+            assert!(fit.after.log_loss < fit.before.log_loss, "{fit:?}");
+            assert!(fit.after.brier < fit.before.brier, "{fit:?}");
+        }
+
+        // Nothing to learn: the initial weights stay.
+        let flat = vec![
+            LabeledInputs {
+                inputs: ScoreInputs::new(0.5, 0.5, 0.5),
+                outcome: true,
+            },
+            LabeledInputs {
+                inputs: ScoreInputs::new(0.5, 0.5, 0.5),
+                outcome: false,
+            },
+        ];
+        let fit = fit_weights(&flat, Loss::LogLoss, Weights::default());
+        assert_eq!(fit.fitted, Weights::default());
+    }
 }
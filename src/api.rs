@@ -1,35 +1,58 @@
 //! HTTP API Layer
 
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
+use std::io;
+use std::path::PathBuf;
 use std::sync::OnceLock as StdOnceLock;
-use std::sync::{Arc, OnceLock, RwLock};
+use std::sync::{Arc, Mutex, RwLock, Weak};
 
 use axum::{
-    extract::Query,
+    extract::{Query, State},
     http::{header, HeaderValue, Method},
     response::IntoResponse,
-    routing::{get, post},
-    Json, Router,
+    routing::{get, post, MethodRouter},
+    Extension, Json, Router,
 };
 use serde_json::Value;
 use tower_http::cors::{Any, CorsLayer};
 
-use crate::disruption::{self, evaluate_with_weights, DisruptionInput};
+use crate::auth::{self, ApiKeys, AuditLog, AuditRecord, Rejection, Scope};
+use crate::calendar;
+use crate::calibration::Calibration;
+use crate::clock::{self, SharedClock};
+use crate::decide::{DecideInput, Finisher, Scorer};
+use crate::disruption::{self, evaluate_with_weights_at, DisruptionInput};
 use crate::engine;
-use crate::history::History;
+use crate::events::EventCalendar;
+use crate::export::{self, ExportQuery};
+use crate::grade::GradeScale;
+use crate::history::{History, HistoryPage, HistoryQuery};
+use crate::paper::{Fill, PaperTrader, PnlSummary, PositionView};
 use crate::rolling::RollingWindow;
 use crate::sentiment::{BatchItem, SentimentAnalyzer};
-use crate::source_weights::SourceWeightsConfig;
+use crate::source_weights::{
+    self, ChangeLog, ChangeRecord, LoadError, SourceWeightsConfig, SourceWeightsDiff,
+    SourceWeightsPatch,
+};
+use crate::stabilizer::{StabilizerParams, VerdictStabilizer};
+use crate::store::{DecisionStore, Record, Replay, StoreConfig};
+use crate::stream::{self, DecisionBus, StreamFilter, StreamMessage};
+use crate::webhooks::{
+    NewSubscription, Subscription, SubscriptionError, SubscriptionView, Webhooks,
+};
 
 // relevance helpers (engine/handle/state + dev logs)
 use crate::relevance::{
-    ai_client_from_env, ai_gate_should_call, anon_hash, dev_logging_enabled, truncate_vec,
-    AppState as RelevanceAppState, RelevanceHandle,
+    ai_client_from_env, truncate_vec, AppState as RelevanceAppState, RelevanceHandle,
 };
 
 // AI sanitize helper
 use crate::analyze::ai_adapter::sanitize_reason;
+use crate::analyze::rules::{self, RuleContext, RuleSet};
+use crate::analyze::{
+    self, AntiSpam, AntiSpamParams, HotReloadWeights, PipelineReport, PipelineToggles,
+};
 
 // tracing for dev-only audit logs
 use tracing::info;
@@ -40,12 +63,8 @@ use metrics::{
 };
 use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
 
-const VOLUME_WINDOW_SECS: u64 = 600; // 10 min
-
-/// Global API state (so the Router can remain `Router<()>`).
-static API_STATE: OnceLock<Arc<ApiState>> = OnceLock::new();
-
-/// Global Prometheus handle (installed once).
+/// Prometheus handle (installed once). The `metrics` recorder is process-wide, so
+/// every router's `/metrics` renders the same registry.
 static PROM: StdOnceLock<PrometheusHandle> = StdOnceLock::new();
 
 fn init_metrics_once() {
@@ -83,6 +102,21 @@ fn init_metrics_once() {
             Unit::Count,
             "Decisions where AI was used"
         );
+        describe_counter!(
+            "api_validation_errors_total",
+            Unit::Count,
+            "Requests rejected by /v1/decide validation"
+        );
+        describe_counter!(
+            "api_requests_rejected_total",
+            Unit::Count,
+            "Requests rejected by API key checks (reason: unauthorized, forbidden, rate_limited)"
+        );
+        describe_counter!(
+            "webhook_deliveries_total",
+            Unit::Count,
+            "Webhook delivery attempts (outcome: delivered, retry, failed)"
+        );
         describe_histogram!(
             "ai_decision_duration_ms",
             Unit::Milliseconds,
@@ -108,10 +142,6 @@ fn init_metrics_once() {
     });
 }
 
-fn app_state() -> &'static ApiState {
-    API_STATE.get().expect("API_STATE not initialized").as_ref()
-}
-
 /// Daily AI usage counter (shared across requests within the process).
 #[derive(Clone, Debug)]
 struct DailyAiCounter {
@@ -120,20 +150,84 @@ struct DailyAiCounter {
     used: usize,
 }
 
-/// Internal API state used by handlers.
+/// Internal API state used by handlers; one per router (Axum `State`).
 #[derive(Clone)]
 struct ApiState {
+    /// Time source for decisions, history and rolling stats.
+    clock: SharedClock,
     analyzer: Arc<SentimentAnalyzer>,
     rolling: Arc<RollingWindow>,
     history: Arc<History>,
+    /// Verdict hysteresis applied right before `History::push`.
+    stabilizer: Arc<VerdictStabilizer>,
+    /// Fitted confidence calibration, applied before grading.
+    calibration: Calibration,
+    /// Confidence-to-grade mapping for the five-level `signal`.
+    grades: GradeScale,
+    /// Paper-trading simulator (`PAPER_TRADING=1`), fed by every decision.
+    paper: Option<Arc<PaperTrader>>,
+    /// Scheduled economic releases that boost weights inside their windows.
+    events: Arc<EventCalendar>,
+    /// Phase-3 stages (antispam, rerank, NER, weights, rules) enabled for `/decide`.
+    pipeline: PipelineToggles,
+    /// Near-duplicate window shared by all `/decide` calls of this router (only with
+    /// `PIPELINE_ANTISPAM_ACROSS_REQUESTS=1`; otherwise each batch has its own).
+    antispam: Arc<Mutex<AntiSpam>>,
+    /// Calibrated analyze weights (`WEIGHTS_CONFIG_PATH`), hot-reloaded.
+    weights: Arc<HotReloadWeights>,
+    /// Item count / text length limits enforced by `/v1/decide`.
+    decide_limits: DecideLimits,
     source_weights: Arc<RwLock<SourceWeightsConfig>>,
+    /// File behind `source_weights` (`SOURCE_WEIGHTS_PATH`), rewritten by the admin API.
+    source_weights_path: std::path::PathBuf,
+    /// History of admin changes to the source weights (`SOURCE_WEIGHTS_CHANGE_LOG`).
+    source_weights_log: Arc<ChangeLog>,
+    /// Serializes admin edits and reloads. Holds why the file was refused at startup;
+    /// while set, edits answer 409 (the file is left alone) until a reload succeeds.
+    source_weights_edit: Arc<Mutex<Option<String>>>,
     relevance: RelevanceHandle,
     /// AI adapter. Called only when the relevance gate decides it makes sense.
     ai: Arc<dyn crate::analyze::ai_adapter::AiClient + Send + Sync>,
     /// Daily limiter for AI header/calls.
     ai_daily: Arc<RwLock<DailyAiCounter>>,
+    /// Durable history (`HISTORY_STORE_DIR`): history, rolling samples, AI counter.
+    store: Option<Arc<DecisionStore>>,
     /// Simple cache for AI reason keyed by input (hash of corpus).
     ai_cache: Arc<RwLock<HashMap<u64, String>>>,
+    /// API keys, scopes and per-key rate limits (`config/api_keys.json`; off without it).
+    auth: Arc<ApiKeys>,
+    /// JSONL log of admin-scope calls (`ADMIN_AUDIT_LOG`).
+    audit: Arc<AuditLog>,
+    /// Outbound webhook subscriptions (`SUBSCRIPTIONS_PATH`, shared per file), fed from
+    /// the stream bus.
+    webhooks: Arc<Webhooks>,
+    /// Bus behind `/stream` and the webhooks; `History` publishes decisions on it.
+    bus: DecisionBus,
+    /// `X-AI-Cache` expiry per request key (method + path + body).
+    ai_cache_expiry: Arc<DashMap<String, Instant>>,
+    /// Paths this router registered, listed by `/debug/routes`.
+    routes: Arc<StdOnceLock<Vec<&'static str>>>,
+}
+
+/// Router under construction that remembers the paths it registers.
+struct Routes {
+    router: Router<Arc<ApiState>>,
+    paths: Vec<&'static str>,
+}
+
+impl Routes {
+    fn new() -> Self {
+        Self {
+            router: Router::new(),
+            paths: Vec::new(),
+        }
+    }
+
+    fn route(mut self, path: &'static str, method_router: MethodRouter<Arc<ApiState>>) -> Self {
+        self.router = self.router.route(path, method_router);
+        self.paths.push(path);
+        self
+    }
 }
 
 fn debug_enabled() -> bool {
@@ -149,11 +243,6 @@ fn debug_routes_enabled() -> bool {
             .unwrap_or(false)
 }
 
-// Return current UNIX time as string (for UI "time" field)
-fn now_string() -> String {
-    current_unix().to_string()
-}
-
 fn current_day(unix: u64) -> u64 {
     unix / 86_400
 }
@@ -164,35 +253,140 @@ fn hash_bytes(bytes: &[u8]) -> u64 {
     h.finish()
 }
 
+/// Stores opened by the routers of this process, one per directory.
+static OPEN_STORES: Lazy<Mutex<HashMap<PathBuf, Weak<DecisionStore>>>> =
+    Lazy::new(Default::default);
+
+/// Open `HISTORY_STORE_DIR` (if set) and replay it. Routers on one directory share
+/// its store (one writer, so appends never interleave); it closes with the last of them.
+fn open_store(
+    clock: &SharedClock,
+    max_history: usize,
+    rolling_secs: u64,
+) -> Option<(Arc<DecisionStore>, Replay)> {
+    let mut cfg = StoreConfig::from_env()?;
+    let mut open = OPEN_STORES.lock().expect("open stores poisoned");
+    if let Some(store) = open.get(&cfg.dir).and_then(Weak::upgrade) {
+        return match store.replay(clock.now_unix()) {
+            Ok(replay) => Some((store, replay)),
+            Err(e) => {
+                tracing::error!(error = %e, "history store unreadable; history stays in memory");
+                None
+            }
+        };
+    }
+    cfg.max_history = cfg.max_history.max(max_history);
+    cfg.rolling_secs = rolling_secs;
+    match DecisionStore::open(cfg, clock.clone()) {
+        Ok((store, replay)) => {
+            let store = Arc::new(store);
+            open.retain(|_, s| s.strong_count() > 0);
+            open.insert(store.config().dir.clone(), Arc::downgrade(&store));
+            Some((store, replay))
+        }
+        Err(e) => {
+            tracing::error!(error = %e, "history store unavailable; history stays in memory");
+            None
+        }
+    }
+}
+
 /// Build the Router. Accepts the AppState from `main.rs` (with a configured RelevanceHandle).
-/// Returns `Router(())` and initializes the global `API_STATE`.
+/// Each call builds its own state (history, caches, keys) from env, so several routers
+/// can live in one process. Routers on one `SUBSCRIPTIONS_PATH` share its webhook
+/// subscriptions, with one dispatcher per bus, and routers on one `HISTORY_STORE_DIR`
+/// share its store.
 pub fn router(state_from_main: RelevanceAppState) -> Router<()> {
+    router_with_clock(state_from_main, clock::system())
+}
+
+/// Same as `router`, with an injected clock (tests/time travel).
+///
+/// Uses the process-wide `stream::bus()`, which ingest also publishes to. Routers that
+/// share it see each other's decisions on `/stream` and in their webhooks; use
+/// `router_with_bus` to keep them apart.
+pub fn router_with_clock(state_from_main: RelevanceAppState, clock: SharedClock) -> Router<()> {
+    router_with_bus(state_from_main, clock, stream::bus().clone())
+}
+
+/// Same as `router_with_clock`, publishing to and streaming from `bus`.
+pub fn router_with_bus(
+    state_from_main: RelevanceAppState,
+    clock: SharedClock,
+    bus: DecisionBus,
+) -> Router<()> {
     // Ensure metrics recorder is ready before any metrics are emitted.
     init_metrics_once();
 
     // Load source weights from file
-    let sw = SourceWeightsConfig::load_from_file("source_weights.json");
-    let now = current_unix();
+    let sw_path = source_weights::path_from_env();
+    let (sw, sw_error) = SourceWeightsConfig::load_or_seed(&sw_path);
+    let now = clock.now_unix();
+
+    let mut history = History::with_capacity(2000)
+        .with_clock(clock.clone())
+        .with_bus(bus.clone());
+    let mut rolling = RollingWindow::new_48h().with_clock(clock.clone());
+    let mut ai_daily = DailyAiCounter {
+        day: current_day(now),
+        used: 0,
+    };
+
+    // Durable history: replay what the previous process recorded.
+    let store = open_store(&clock, history.capacity(), rolling.window_secs());
+    if let Some((store, replay)) = &store {
+        info!(
+            history = replay.history.len(),
+            rolling = replay.rolling.len(),
+            "history store replayed"
+        );
+        history = history.with_store(store.clone());
+        history.restore(replay.history.clone());
+        rolling = rolling.with_store(store.clone());
+        rolling.restore(replay.rolling.iter().copied());
+        if let Some((_, used)) = replay.ai_daily.filter(|(day, _)| *day == ai_daily.day) {
+            ai_daily.used = used;
+        }
+    }
 
     // Build full API state (reuse the relevance handle provided by main)
     let state = Arc::new(ApiState {
+        clock: clock.clone(),
         analyzer: Arc::new(SentimentAnalyzer::new()),
-        rolling: Arc::new(RollingWindow::new_48h()),
-        history: Arc::new(History::with_capacity(2000)),
+        rolling: Arc::new(rolling),
+        history: Arc::new(history),
+        stabilizer: Arc::new(VerdictStabilizer::new(StabilizerParams::from_env())),
+        calibration: Calibration::from_env(),
+        grades: GradeScale::from_env(),
+        paper: PaperTrader::from_env().map(Arc::new),
+        events: Arc::new(EventCalendar::from_env()),
+        pipeline: PipelineToggles::from_env(),
+        antispam: Arc::new(Mutex::new(AntiSpam::new(AntiSpamParams::default()))),
+        weights: Arc::new(HotReloadWeights::from_env()),
+        decide_limits: DecideLimits::from_env(),
         source_weights: Arc::new(RwLock::new(sw)),
+        source_weights_path: sw_path,
+        source_weights_log: Arc::new(ChangeLog::from_env()),
+        source_weights_edit: Arc::new(Mutex::new(sw_error.map(|e| e.to_string()))),
         relevance: state_from_main.relevance,
         ai: ai_client_from_env(),
-        ai_daily: Arc::new(RwLock::new(DailyAiCounter {
-            day: current_day(now),
-            used: 0,
-        })),
+        ai_daily: Arc::new(RwLock::new(ai_daily)),
+        store: store.map(|(store, _)| store),
         ai_cache: Arc::new(RwLock::new(HashMap::new())),
+        auth: Arc::new(ApiKeys::from_env(now)),
+        audit: Arc::new(AuditLog::from_env()),
+        webhooks: Webhooks::shared_from_env(clock.clone()),
+        bus,
+        ai_cache_expiry: Arc::new(DashMap::new()),
+        routes: Arc::new(StdOnceLock::new()),
     });
 
-    let _ = API_STATE.set(state);
-
-    // Izolace testů: nově vytvořený router začne s prázdnou AI-cache.
-    clear_ai_cache();
+    if tokio::runtime::Handle::try_current().is_ok() {
+        state.webhooks.spawn(&state.bus);
+        if let Some(paper) = &state.paper {
+            PaperTrader::spawn_marker(paper, clock.clone());
+        }
+    }
 
     // --- CORS whitelist controlled by env variable ---
     // ALLOWED_ORIGINS="http://localhost:5173,https://app.example.com"
@@ -207,85 +401,480 @@ pub fn router(state_from_main: RelevanceAppState) -> Router<()> {
     let cors = if origins.is_empty() {
         // Fallback: allow all origins but only basic headers/methods
         CorsLayer::new()
-            .allow_methods([Method::GET, Method::POST, Method::OPTIONS])
-            .allow_headers([header::CONTENT_TYPE])
+            .allow_methods([
+                Method::GET,
+                Method::POST,
+                Method::PUT,
+                Method::PATCH,
+                Method::DELETE,
+                Method::OPTIONS,
+            ])
+            .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION, X_API_KEY])
             .allow_origin(Any)
     } else {
         CorsLayer::new()
-            .allow_methods([Method::GET, Method::POST, Method::OPTIONS])
-            .allow_headers([header::CONTENT_TYPE])
+            .allow_methods([
+                Method::GET,
+                Method::POST,
+                Method::PUT,
+                Method::PATCH,
+                Method::DELETE,
+                Method::OPTIONS,
+            ])
+            .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION, X_API_KEY])
             .allow_origin(origins)
     };
 
     // Build router with explicit `S = ()`
-    let mut r = Router::<()>::new()
-        .route("/health", get(|| async { "OK" }))
-        .route(
-            "/metrics",
-            get(|| async move {
-                // Render Prometheus exposition format (text/plain; version=0.0.4)
-                let body = PROM.get().map(|h| h.render()).unwrap_or_default();
-                axum::response::Response::builder()
-                    .status(200)
-                    .header(
-                        axum::http::header::CONTENT_TYPE,
-                        "text/plain; version=0.0.4",
-                    )
-                    .body(axum::body::Body::from(body))
-                    .unwrap()
-            }),
-        )
+    let mut r = Routes::new()
+        .route("/health", get(health))
+        .route("/metrics", get(metrics))
+        // OpenAPI spec + interactive docs (spec lives in `ApiDoc`)
+        .route("/openapi.json", get(openapi_json))
+        .route("/docs", get(docs_page))
         // UI primary endpoint (Step 3). Keep POST so the dev proxy can forward as-is.
         .route("/analyze", post(analyze))
         // Batch scoring (internal/dev)
         .route("/batch", post(analyze_batch))
         // Decision endpoint: GET = stable shape for change-detector, POST = full decision
-        .route("/decide", get(decide_get).post(decide));
+        .route("/decide", get(decide_get).post(decide))
+        // Versioned decide: strict validation, 422 with per-item errors
+        .route("/v1/decide", post(decide_v1))
+        // Recorded decisions: time range / verdict / confidence / source filters, paged
+        .route("/history", get(history_query))
+        // Same history as CSV / NDJSON (optionally gzipped) for notebooks
+        .route("/export", get(export_history))
+        // Live decisions / verdict changes / ingested events: SSE and WebSocket
+        .route("/stream", get(stream_sse))
+        .route("/stream/ws", get(stream_ws))
+        // Evaluate a candidate rule set without touching config/rules.json
+        .route("/rules/dry-run", post(rules_dry_run));
+
+    // Webhook subscriptions make the service call out, so like the admin routes they
+    // need a key (`webhooks` scope) or, without keys, DEBUG_ROUTES.
+    if state.auth.enabled() || debug_routes_enabled() {
+        r = r
+            .route(
+                "/subscriptions",
+                get(subscriptions_list).post(subscriptions_create),
+            )
+            .route(
+                "/subscriptions/{id}",
+                get(subscription_get).delete(subscription_delete),
+            );
+    }
+
+    // Paper trading (simulated fills; only when configured)
+    if state.paper.is_some() {
+        r = r
+            .route("/paper/positions", get(paper_positions))
+            .route("/paper/fills", get(paper_fills))
+            .route("/paper/pnl", get(paper_pnl));
+    }
+
+    // Admin routes need the `admin` scope once API keys are configured; without keys
+    // they stay behind DEBUG_ROUTES with the debug views.
+    if state.auth.enabled() && !debug_routes_enabled() {
+        r = r
+            .route(
+                "/admin/reload-source-weights",
+                get(admin_reload_source_weights).post(admin_reload_source_weights),
+            )
+            .route(
+                "/admin/source-weights",
+                get(admin_source_weights_get)
+                    .put(admin_source_weights_put)
+                    .patch(admin_source_weights_patch),
+            )
+            .route(
+                "/admin/source-weights/history",
+                get(admin_source_weights_history),
+            );
+    }
 
     // Debug / introspection when enabled
     if debug_routes_enabled() {
         r = r
+            .route("/debug/routes", get(debug_routes))
             .route("/debug/rolling", get(debug_rolling))
             .route("/debug/history", get(debug_history))
             .route("/debug/last-decision", get(debug_last_decision))
             .route("/debug/source-weight", get(debug_source_weight))
             .route(
                 "/admin/reload-source-weights",
-                get(admin_reload_source_weights),
+                get(admin_reload_source_weights).post(admin_reload_source_weights),
+            )
+            .route(
+                "/admin/source-weights",
+                get(admin_source_weights_get)
+                    .put(admin_source_weights_put)
+                    .patch(admin_source_weights_patch),
+            )
+            .route(
+                "/admin/source-weights/history",
+                get(admin_source_weights_history),
             );
     }
 
-    // Apply CORS and the X-AI-Cache middleware
-    r.layer(cors).layer(axum::middleware::from_fn(ai_cache_mw))
+    let _ = state.routes.set(r.paths);
+
+    // X-AI-Cache middleware, then API keys, with CORS outermost so rejections carry it
+    r.router
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            ai_cache_mw,
+        ))
+        .layer(axum::middleware::from_fn_with_state(state.clone(), auth_mw))
+        .layer(cors)
+        .with_state(state)
+}
+
+// ---- API keys: scope check, rate limit and admin audit ----
+
+const X_API_KEY: header::HeaderName = header::HeaderName::from_static("x-api-key");
+
+/// Key from `Authorization: Bearer`, `X-API-Key`, or `?api_key=` on `/stream*`
+/// (EventSource cannot set headers; the query string shows up in proxy logs).
+fn presented_key(req: &axum::http::Request<axum::body::Body>) -> Option<String> {
+    let headers = req.headers();
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    let header_key = headers.get(&X_API_KEY).and_then(|v| v.to_str().ok());
+    if let Some(k) = bearer.or(header_key) {
+        return Some(k.to_string());
+    }
+    if !req.uri().path().starts_with("/stream") {
+        return None;
+    }
+    let Query(mut q) = Query::<HashMap<String, String>>::try_from_uri(req.uri()).ok()?;
+    q.remove("api_key")
+}
+
+/// Drop `api_key` from the query, so no handler (or anything logging the URI) sees it.
+fn strip_api_key(req: &mut axum::http::Request<axum::body::Body>) {
+    let is_key = |p: &&str| *p == "api_key" || p.starts_with("api_key=");
+    let Some(query) = req
+        .uri()
+        .query()
+        .filter(|q| q.split('&').any(|p| is_key(&p)))
+    else {
+        return;
+    };
+    let kept: Vec<&str> = query.split('&').filter(|p| !is_key(p)).collect();
+    let path = req.uri().path();
+    let path_and_query = if kept.is_empty() {
+        path.to_string()
+    } else {
+        format!("{path}?{}", kept.join("&"))
+    };
+    let mut parts = req.uri().clone().into_parts();
+    parts.path_and_query = path_and_query.parse().ok();
+    if let Ok(uri) = axum::http::Uri::from_parts(parts) {
+        *req.uri_mut() = uri;
+    }
+}
+
+/// Id of the API key that authorized the request (absent when access control is off).
+#[derive(Clone)]
+struct KeyId(String);
+
+fn auth_rejected(rej: &Rejection) -> axum::response::Response {
+    use axum::http::StatusCode;
+    match rej {
+        Rejection::Unauthorized => api_error(
+            StatusCode::UNAUTHORIZED,
+            "unauthorized",
+            "missing or unknown API key",
+            Vec::new(),
+        ),
+        Rejection::Forbidden { key, scope } => api_error(
+            StatusCode::FORBIDDEN,
+            "forbidden",
+            format!("key `{key}` lacks the `{}` scope", scope.as_str()),
+            Vec::new(),
+        ),
+        Rejection::RateLimited { key, retry_after } => {
+            let mut resp = api_error(
+                StatusCode::TOO_MANY_REQUESTS,
+                "rate_limited",
+                format!("rate limit for key `{key}` exceeded; retry in {retry_after}s"),
+                Vec::new(),
+            );
+            resp.headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(*retry_after));
+            resp
+        }
+    }
+}
+
+/// Enforce `auth::required_scope` per request; admin- and webhooks-scope calls are
+/// audited, rejected ones included.
+async fn auth_mw(
+    State(state): State<Arc<ApiState>>,
+    mut req: axum::http::Request<axum::body::Body>,
+    next: axum::middleware::Next,
+) -> axum::response::Response {
+    let Some(scope) = auth::required_scope(req.method().as_str(), req.uri().path()) else {
+        return next.run(req).await;
+    };
+    let now = state.clock.now_unix();
+    // The path only: a query string could carry `api_key`.
+    let (method, path) = (req.method().to_string(), req.uri().path().to_string());
+    let presented = presented_key(&req);
+    strip_api_key(&mut req);
+
+    let (key, resp) = match state.auth.authorize(presented.as_deref(), scope, now) {
+        Ok(key) => {
+            if let Some(id) = &key {
+                req.extensions_mut().insert(KeyId(id.clone()));
+            }
+            (key, next.run(req).await)
+        }
+        Err(rej) => {
+            counter!(
+                "api_requests_rejected_total",
+                "reason" => rej.reason(),
+                "scope" => scope.as_str()
+            )
+            .increment(1);
+            let key = match &rej {
+                Rejection::Unauthorized => None,
+                Rejection::Forbidden { key, .. } | Rejection::RateLimited { key, .. } => {
+                    Some(key.clone())
+                }
+            };
+            (key, auth_rejected(&rej))
+        }
+    };
+
+    if matches!(scope, Scope::Admin | Scope::Webhooks) {
+        state.audit.record(&AuditRecord {
+            ts_unix: now,
+            key,
+            method,
+            path,
+            status: resp.status().as_u16(),
+        });
+    }
+    resp
+}
+
+// ---- OpenAPI: spec generated from the handler/request/response types ----
+
+/// OpenAPI 3 document for the public routes (debug/admin routes are left out).
+/// `tests/api_openapi.rs` replays every example against the router, so a handler
+/// that drifts from its annotation fails the build.
+#[derive(utoipa::OpenApi)]
+#[openapi(
+    info(
+        title = "Dow Sentiment Analyzer API",
+        description = "Sentiment decisions (BUY/SELL/HOLD) for Dow futures from news and statements."
+    ),
+    servers((url = "/api")),
+    modifiers(&ApiKeySchemes),
+    paths(
+        health,
+        metrics,
+        analyze,
+        analyze_batch,
+        decide_get,
+        decide,
+        decide_v1,
+        history_query,
+        export_history,
+        stream_sse,
+        rules_dry_run,
+        subscriptions_create,
+        subscriptions_list,
+        subscription_get,
+        subscription_delete,
+        paper_positions,
+        paper_fills,
+        paper_pnl,
+        openapi_json,
+        docs_page
+    ),
+    tags(
+        (name = "decision", description = "Scoring and decisions"),
+        (name = "rules", description = "Rule set tooling"),
+        (name = "history", description = "Recorded decisions (durable with `HISTORY_STORE_DIR`)"),
+        (name = "stream", description = "Live decision stream (SSE; WebSocket at `/stream/ws`)"),
+        (name = "subscriptions", description = "Webhook callbacks for stream events (HMAC-signed, retried)"),
+        (name = "paper", description = "Paper trading (only when `PAPER_TRADING=1`)"),
+        (name = "meta", description = "Health, metrics and this spec")
+    )
+)]
+pub struct ApiDoc;
+
+/// Security schemes accepted when API keys are configured (see `auth`).
+struct ApiKeySchemes;
+
+impl utoipa::Modify for ApiKeySchemes {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        use utoipa::openapi::security::{
+            ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme,
+        };
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::with_description(
+                "X-API-Key",
+                "Needed on every route but /health, /metrics, /openapi.json and /docs when config/api_keys.json exists",
+            ))),
+        );
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+    }
+}
+
+impl ApiDoc {
+    /// The spec served at `/openapi.json`.
+    pub fn spec() -> utoipa::openapi::OpenApi {
+        <Self as utoipa::OpenApi>::openapi()
+    }
+}
+
+/// GET /health — liveness.
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "meta",
+    responses((status = 200, description = "Service is up", body = String, content_type = "text/plain"))
+)]
+async fn health() -> &'static str {
+    "OK"
+}
+
+/// GET /metrics — Prometheus exposition format.
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "meta",
+    responses((status = 200, description = "Prometheus metrics", body = String, content_type = "text/plain"))
+)]
+async fn metrics() -> axum::response::Response {
+    // Render Prometheus exposition format (text/plain; version=0.0.4)
+    let body = PROM.get().map(|h| h.render()).unwrap_or_default();
+    axum::response::Response::builder()
+        .status(200)
+        .header(
+            axum::http::header::CONTENT_TYPE,
+            "text/plain; version=0.0.4",
+        )
+        .body(axum::body::Body::from(body))
+        .unwrap()
+}
+
+/// GET /openapi.json — this API's OpenAPI document.
+#[utoipa::path(
+    get,
+    path = "/openapi.json",
+    tag = "meta",
+    responses((status = 200, description = "OpenAPI 3 document", body = Object))
+)]
+async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::spec())
 }
 
-#[derive(serde::Deserialize, Default)]
+/// Exact `swagger-ui-dist` release loaded by `/docs` (never a floating tag).
+pub const SWAGGER_UI_VERSION: &str = "5.17.14";
+
+/// Swagger UI (from a CDN) pointed at the sibling `openapi.json`. The assets are
+/// pinned to [`SWAGGER_UI_VERSION`] and carry `integrity` hashes from
+/// `DOCS_SWAGGER_CSS_SRI` / `DOCS_SWAGGER_JS_SRI` (`sha384-...`).
+fn docs_html() -> String {
+    let sri = |var: &str| {
+        std::env::var(var)
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+            .map(|v| format!(r#" integrity="{v}""#))
+            .unwrap_or_default()
+    };
+    format!(
+        r##"<!doctype html>
+<html>
+<head>
+  <meta charset="utf-8">
+  <title>Dow Sentiment Analyzer API</title>
+  <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@{v}/swagger-ui.css"{css} crossorigin="anonymous">
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="https://unpkg.com/swagger-ui-dist@{v}/swagger-ui-bundle.js"{js} crossorigin="anonymous"></script>
+  <script>
+    window.ui = SwaggerUIBundle({{ url: "openapi.json", dom_id: "#swagger-ui" }});
+  </script>
+</body>
+</html>
+"##,
+        v = SWAGGER_UI_VERSION,
+        css = sri("DOCS_SWAGGER_CSS_SRI"),
+        js = sri("DOCS_SWAGGER_JS_SRI"),
+    )
+}
+
+/// GET /docs — interactive docs for `/openapi.json`.
+#[utoipa::path(
+    get,
+    path = "/docs",
+    tag = "meta",
+    responses((status = 200, description = "Swagger UI page", body = String, content_type = "text/html"))
+)]
+async fn docs_page() -> axum::response::Html<String> {
+    axum::response::Html(docs_html())
+}
+
+#[derive(serde::Deserialize, Default, utoipa::ToSchema)]
 struct AnalyzeReq {
     /// Text to analyze. Defaults to empty so `{}` works in dev.
     #[serde(default)]
     text: String,
+    /// Who said it (source weight); unknown sources get the default weight.
+    #[serde(default)]
+    source: Option<String>,
 }
 
-#[derive(serde::Deserialize)]
+#[derive(serde::Deserialize, utoipa::ToSchema)]
 struct DecideItem {
     source: String,
     text: String,
     #[serde(default)]
     ts_unix: Option<u64>,
+    /// Link to the original statement/article (kept on contributors as evidence).
+    #[serde(default)]
+    url: Option<String>,
+}
+
+/// `POST /decide` body shapes accepted by the handler (schema only; the handler
+/// normalizes the raw JSON and skips array items that do not parse).
+#[derive(utoipa::ToSchema)]
+#[serde(untagged)]
+#[allow(dead_code)]
+enum DecideRequest {
+    Items(Vec<DecideItem>),
+    Inputs { inputs: Vec<DecideItem> },
+    Wrapped { items: Vec<DecideItem> },
+    Single(DecideItem),
 }
 
 // ---------- UI Step 3: Response shape for POST /analyze ----------
 
-#[derive(serde::Serialize)]
+#[derive(serde::Serialize, utoipa::ToSchema)]
 struct ApiEvidence {
     title: String,
     source: String,
-    url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
     sentiment: String, // "pos" | "neg" | "neu"
-    time: String,      // human-readable or ISO/UNIX string
+    time: String,      // RFC 3339
 }
 
-#[derive(serde::Serialize)]
+#[derive(serde::Serialize, utoipa::ToSchema)]
 struct AnalyzeOut {
     decision: String,     // "BUY" | "SELL" | "HOLD"
     confidence: f32,      // 0..1
@@ -296,16 +885,18 @@ struct AnalyzeOut {
 
 // ---- /decide (GET): stable shape for change-detector ----
 
-#[derive(serde::Serialize)]
+#[derive(serde::Serialize, utoipa::ToSchema)]
 struct DecideOut {
     decision: String,
     confidence: f32,
     reasons: Vec<String>,
+    /// Five-level grade (`STRONG_BUY` … `STRONG_SELL`); `decision` keeps the 3 base values.
+    signal: String,
 }
 
 // ---- AI response metadata for /decide (POST) ----
 
-#[derive(serde::Serialize, Default)]
+#[derive(serde::Serialize, Default, utoipa::ToSchema)]
 struct ApiAiInfo {
     used: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -314,79 +905,176 @@ struct ApiAiInfo {
     limited: bool,
 }
 
-#[derive(serde::Serialize)]
+#[derive(serde::Serialize, utoipa::ToSchema)]
 struct DecideWithAi {
     #[serde(flatten)]
     inner: crate::decision::Decision,
     ai: ApiAiInfo,
+    /// What the Phase-3 stages did (see `analyze::pipeline`).
+    pipeline: PipelineReport,
 }
 
 // ----------------------------------------------------------------
 
-async fn analyze(Json(body): Json<AnalyzeReq>) -> Json<AnalyzeOut> {
-    let state = app_state();
+/// Evidence items returned by `/analyze` (most recent first).
+const ANALYZE_EVIDENCE_MAX: usize = 5;
+/// How many past decisions `/analyze` searches for evidence.
+const ANALYZE_EVIDENCE_LOOKBACK: usize = 50;
+
+/// POST /analyze — relevance → sentiment → disruption → engine on one text.
+/// The decision is not recorded in history; the gated sentiment score is added
+/// to the rolling window, as with `/batch`. Evidence comes from the top
+/// contributors of recent `/decide` decisions.
+#[utoipa::path(
+    post,
+    path = "/analyze",
+    tag = "decision",
+    request_body(
+        content = AnalyzeReq,
+        example = json!({ "text": "Fed signals rate cuts; Dow futures rally.", "source": "Fed" })
+    ),
+    responses((status = 200, description = "Decision for one text", body = AnalyzeOut))
+)]
+async fn analyze(
+    State(state): State<Arc<ApiState>>,
+    Json(body): Json<AnalyzeReq>,
+) -> Json<AnalyzeOut> {
     let t0 = std::time::Instant::now();
     if debug_enabled() {
         info!(target: "api_debug", event = "request", path = "/analyze", batch = false);
     }
 
-    let (score, _tokens) = state.analyzer.score_text(&body.text);
+    let now = state.clock.now_unix();
+    let source = body
+        .source
+        .filter(|s| !s.trim().is_empty())
+        .unwrap_or_else(|| "User".to_string());
+    let (raw_score, _tokens) = state.analyzer.score_text(&body.text);
+    let rel = state.relevance.score(&body.text);
+    let score = if rel.score > 0.0 { raw_score } else { 0 };
     state.rolling.record(score, None);
 
-    let verdict = if score > 0 {
-        "BUY"
-    } else if score < 0 {
-        "SELL"
-    } else {
-        "HOLD"
+    let res = {
+        let sw = state.source_weights.read().expect("rwlock poisoned");
+        let di = DisruptionInput {
+            source: source.clone(),
+            text: body.text.clone(),
+            score,
+            ts_unix: now,
+        };
+        evaluate_with_weights_at(&di, &sw, now)
+    };
+    let item = BatchItem {
+        source,
+        text: body.text.clone(),
     };
+    let mut decision = engine::make_decision_at(&[(item, score, res)], now);
+    state.calibration.apply_to(&mut decision);
+
+    let mut reasons = vec![format!("Sentiment score {raw_score:+}")];
+    if rel.score > 0.0 {
+        reasons.push(format!(
+            "Relevance {:.2}: {}",
+            rel.score,
+            truncate_vec(&rel.matched, 5).join(", ")
+        ));
+    } else {
+        reasons.push(format!(
+            "Relevance gate: not Dow-relevant (score {:.2}); sentiment neutralized",
+            rel.score
+        ));
+    }
+    reasons.extend(decision.reasons.iter().map(|r| r.message.clone()));
+    reasons.extend(
+        analyze::ner::extract_reasons_from_configs(&body.text)
+            .into_iter()
+            .map(|r| format!("NER {r}")),
+    );
+
+    let evidence = recent_evidence(&state.history);
+    let mut contributors: Vec<String> = Vec::new();
+    for e in &evidence {
+        if !contributors.contains(&e.source) {
+            contributors.push(e.source.clone());
+        }
+    }
 
-    let ts = now_string();
+    let verdict = match decision.decision {
+        crate::decision::Verdict::Buy => "BUY",
+        crate::decision::Verdict::Sell => "SELL",
+        crate::decision::Verdict::Hold => "HOLD",
+    };
 
     if debug_enabled() {
         info!(target: "api_debug", event = "decision", path = "/analyze", score = score, verdict = %verdict);
         info!(target: "api_debug", event = "latency_ms", path = "/analyze", ms = t0.elapsed().as_millis());
     }
 
-    let out = AnalyzeOut {
+    Json(AnalyzeOut {
         decision: verdict.to_string(),
-        confidence: 0.74,
-        reasons: vec![
-            "Futures rebound after dovish remarks in FOMC minutes".to_string(),
-            "Positive breadth in Dow components during pre-market".to_string(),
-            "Sentiment shift detected in key sources (low noise)".to_string(),
-        ],
-        evidence: vec![
-            ApiEvidence {
-                title: "Fed signals patience; markets react positively".to_string(),
-                source: "Reuters".to_string(),
-                url: "#".to_string(),
-                sentiment: "pos".to_string(),
-                time: ts.clone(),
-            },
-            ApiEvidence {
-                title: "Dow futures edge higher amid earnings beats".to_string(),
-                source: "Bloomberg".to_string(),
-                url: "#".to_string(),
-                sentiment: "pos".to_string(),
-                time: ts.clone(),
-            },
-            ApiEvidence {
-                title: "Mixed commentary on industrials; net neutral".to_string(),
-                source: "WSJ".to_string(),
-                url: "#".to_string(),
-                sentiment: "neu".to_string(),
-                time: ts,
-            },
-        ],
-        contributors: vec!["relevance-engine".to_string(), "sentiment-core".to_string()],
-    };
+        confidence: decision.confidence,
+        reasons,
+        evidence,
+        contributors,
+    })
+}
+
+/// Top contributors of recent decisions, newest first, one per (source, text).
+fn recent_evidence(history: &History) -> Vec<ApiEvidence> {
+    let mut seen: Vec<(String, String)> = Vec::new();
+    let mut out = Vec::new();
+    for entry in history
+        .snapshot_last_n(ANALYZE_EVIDENCE_LOOKBACK)
+        .into_iter()
+        .rev()
+    {
+        for c in entry.contributors {
+            let key = (c.source.clone(), c.text.clone());
+            if seen.contains(&key) {
+                continue;
+            }
+            seen.push(key);
+            out.push(ApiEvidence {
+                title: c.text,
+                source: c.source,
+                url: c.url,
+                sentiment: match c.score.signum() {
+                    1 => "pos",
+                    -1 => "neg",
+                    _ => "neu",
+                }
+                .to_string(),
+                time: rfc3339(entry.ts_unix),
+            });
+            if out.len() == ANALYZE_EVIDENCE_MAX {
+                return out;
+            }
+        }
+    }
+    out
+}
 
-    Json(out)
+fn rfc3339(unix: u64) -> String {
+    chrono::DateTime::from_timestamp(unix as i64, 0)
+        .map(|d| d.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+        .unwrap_or_default()
 }
 
-async fn analyze_batch(Json(items): Json<Vec<BatchItem>>) -> Json<Vec<(BatchItem, i32)>> {
-    let state = app_state();
+/// POST /batch — score texts one by one (internal/dev).
+#[utoipa::path(
+    post,
+    path = "/batch",
+    tag = "decision",
+    request_body(
+        content = Vec<BatchItem>,
+        example = json!([{ "source": "Reuters", "text": "Dow futures slide as yields jump." }])
+    ),
+    responses((status = 200, description = "Each item with its sentiment score", body = Vec<(BatchItem, i32)>))
+)]
+async fn analyze_batch(
+    State(state): State<Arc<ApiState>>,
+    Json(items): Json<Vec<BatchItem>>,
+) -> Json<Vec<(BatchItem, i32)>> {
     let t0 = std::time::Instant::now();
     if debug_enabled() {
         info!(target: "api_debug", event = "request", path = "/batch", batch = true);
@@ -397,12 +1085,16 @@ async fn analyze_batch(Json(items): Json<Vec<BatchItem>>) -> Json<Vec<(BatchItem
         .map(|it| {
             let (score, _) = state.analyzer.score_text(&it.text);
             state.rolling.record(score, None);
-            let _ = disruption::evaluate(&DisruptionInput {
-                source: it.source.clone(),
-                text: it.text.clone(),
-                score,
-                ts_unix: current_unix(),
-            });
+            let now = state.clock.now_unix();
+            let _ = disruption::evaluate_at(
+                &DisruptionInput {
+                    source: it.source.clone(),
+                    text: it.text.clone(),
+                    score,
+                    ts_unix: now,
+                },
+                now,
+            );
             (it, score)
         })
         .collect::<Vec<_>>();
@@ -473,11 +1165,19 @@ async fn ai_analyze_safely(
 }
 
 /// GET /decide — stable shape for change-detector
-async fn decide_get() -> Json<DecideOut> {
-    let state = app_state();
+#[utoipa::path(
+    get,
+    path = "/decide",
+    tag = "decision",
+    responses((status = 200, description = "Last decision from history (HOLD 0.50 when empty)", body = DecideOut))
+)]
+async fn decide_get(State(state): State<Arc<ApiState>>) -> Json<DecideOut> {
     // 1) Try last decision from history
     if let Some(h) = state.history.snapshot_last_n(1).pop() {
         let decision = format!("{:?}", h.verdict).to_uppercase();
+        let signal = h
+            .signal
+            .unwrap_or_else(|| state.grades.grade(h.verdict, h.confidence));
         let reasons = vec![format!(
             "from history: {} sources / {} scores in last snapshot",
             h.top_sources.len(),
@@ -487,6 +1187,7 @@ async fn decide_get() -> Json<DecideOut> {
             decision,
             confidence: h.confidence,
             reasons,
+            signal: signal.as_str().to_string(),
         });
     }
 
@@ -495,117 +1196,113 @@ async fn decide_get() -> Json<DecideOut> {
         decision: "HOLD".into(),
         confidence: 0.50,
         reasons: vec!["no history yet".into()],
+        signal: "HOLD".into(),
     })
 }
 
+/// POST /decide — full decision over a batch of items.
+#[utoipa::path(
+    post,
+    path = "/decide",
+    tag = "decision",
+    request_body(
+        content = DecideRequest,
+        examples(
+            ("items" = (value = json!([{ "source": "Fed", "text": "Fed signals rate cuts; Dow futures rally strongly.", "url": "https://example.com/fed" }]))),
+            ("inputs" = (value = json!({ "inputs": [{ "source": "Reuters", "text": "Dow futures plunge on recession fears.", "ts_unix": 1756733400 }] }))),
+            ("single" = (value = json!({ "source": "Bloomberg", "text": "Stocks steady ahead of CPI." })))
+        )
+    ),
+    responses((
+        status = 200,
+        description = "Decision with AI and pipeline metadata; `X-AI-Cache` tells whether the AI answer was cached",
+        body = DecideWithAi
+    ))
+)]
 #[axum::debug_handler]
-async fn decide(Json(body): Json<Value>) -> impl IntoResponse {
-    let t0 = std::time::Instant::now();
+async fn decide(State(state): State<Arc<ApiState>>, Json(body): Json<Value>) -> impl IntoResponse {
+    decide_items(state, normalize_decide_body(body)).await
+}
 
-    // -------- 1) PHASE BEFORE `await`: build everything from state in a dedicated scope --------
-    let (scored, neutralized, total, ai_corpus_opt, now) = {
-        let state = app_state();
-        let now = current_unix();
-        let mut items: Vec<DecideItem> = {
-            fn normalize_decide_body(v: Value) -> Vec<DecideItem> {
-                match v {
-                    Value::Array(arr) => arr
-                        .into_iter()
-                        .filter_map(|x| serde_json::from_value::<DecideItem>(x).ok())
-                        .collect(),
-                    Value::Object(map) => {
-                        if let Some(items) = map.get("inputs").or_else(|| map.get("items")) {
-                            if let Ok(vec_items) =
-                                serde_json::from_value::<Vec<DecideItem>>(items.clone())
-                            {
-                                return vec_items;
-                            }
-                        }
-                        serde_json::from_value::<DecideItem>(Value::Object(map))
-                            .ok()
-                            .map(|it| vec![it])
-                            .unwrap_or_default()
-                    }
-                    Value::Null => Vec::new(),
-                    _ => Vec::new(),
+/// Lenient `/decide` body parsing: items that fail to parse are skipped.
+fn normalize_decide_body(v: Value) -> Vec<DecideItem> {
+    match v {
+        Value::Array(arr) => arr
+            .into_iter()
+            .filter_map(|x| serde_json::from_value::<DecideItem>(x).ok())
+            .collect(),
+        Value::Object(map) => {
+            if let Some(items) = map.get("inputs").or_else(|| map.get("items")) {
+                if let Ok(vec_items) = serde_json::from_value::<Vec<DecideItem>>(items.clone()) {
+                    return vec_items;
                 }
             }
-            normalize_decide_body(body)
-        };
-
-        let mut scored = Vec::with_capacity(items.len());
-        let mut neutralized = 0usize;
-        let total = items.len();
-        let mut ai_gated_texts: Vec<String> = Vec::new();
-
-        for it in items.drain(..) {
-            let (raw_score, _tokens) = state.analyzer.score_text(&it.text);
-            let rel = state.relevance.score(&it.text);
-            let gated_score = if rel.score > 0.0 { raw_score } else { 0 };
-
-            if ai_gate_should_call(&it.source, &rel) {
-                ai_gated_texts.push(it.text.clone());
-            }
-
-            if dev_logging_enabled() {
-                let event = if rel.score > 0.0 {
-                    "api_pass"
-                } else {
-                    "api_neutralized"
-                };
-                info!(
-                    target: "relevance",
-                    event,
-                    id = %anon_hash(&it.text),
-                    matched = ?truncate_vec(&rel.matched, 5),
-                    reasons = ?truncate_vec(&rel.reasons, 5),
-                    rel_score = rel.score,
-                    raw = raw_score,
-                    gated = gated_score
-                );
-            }
-
-            if gated_score == 0 && raw_score != 0 {
-                neutralized += 1;
-            }
-
-            state.rolling.record(gated_score, None);
-            let ts = it.ts_unix.unwrap_or(now);
-
-            let di = DisruptionInput {
-                source: it.source.clone(),
-                text: it.text.clone(),
-                score: gated_score,
-                ts_unix: ts,
-            };
-            let res = {
-                let guard = state.source_weights.read().expect("rwlock poisoned");
-                evaluate_with_weights(&di, &guard)
-            };
-
-            let bi = BatchItem {
-                source: it.source,
-                text: it.text,
-            };
-            scored.push((bi, gated_score, res));
+            serde_json::from_value::<DecideItem>(Value::Object(map))
+                .ok()
+                .map(|it| vec![it])
+                .unwrap_or_default()
         }
+        Value::Null => Vec::new(),
+        _ => Vec::new(),
+    }
+}
 
-        // Prepare AI corpus (if any)
-        let ai_corpus_opt = if !ai_gated_texts.is_empty() {
-            let mut s = String::new();
-            for t in ai_gated_texts.iter().take(8) {
-                if !s.is_empty() {
-                    s.push_str("\n\n");
-                }
-                s.push_str(t);
-            }
-            Some(s)
+/// Shared core of `/decide` and `/v1/decide`: score, decide, record and respond.
+async fn decide_items(state: Arc<ApiState>, items: Vec<DecideItem>) -> axum::response::Response {
+    let t0 = std::time::Instant::now();
+
+    // -------- 1) PHASE BEFORE `await`: score the batch under short-lived guards --------
+    let now = state.clock.now_unix();
+    let toggles = state.pipeline;
+    let inputs: Vec<DecideInput> = items
+        .into_iter()
+        .map(|it| DecideInput {
+            source: it.source,
+            text: it.text,
+            ts_unix: it.ts_unix,
+            url: it.url,
+        })
+        .collect();
+    let scored = {
+        let sw = state.source_weights.read().expect("rwlock poisoned");
+        let rules = if toggles.rules {
+            analyze::current_rules()
         } else {
-            None
+            Arc::default()
         };
+        let scorer = Scorer {
+            analyzer: &state.analyzer,
+            relevance: &state.relevance,
+            source_weights: &sw,
+            events: &state.events,
+            carry_overnight: calendar::carry_overnight_from_env(),
+            toggles,
+            rules: &rules,
+        };
+        if toggles.antispam_across_requests {
+            let mut anti = state.antispam.lock().expect("antispam poisoned");
+            scorer.score(inputs, Some(&mut anti), now)
+        } else {
+            scorer.score(inputs, None, now)
+        }
+    }; // <- guards dropped before the await
+    for (_, gated_score, _) in &scored.items {
+        state.rolling.record(*gated_score, None);
+    }
 
-        (scored, neutralized, total, ai_corpus_opt, now)
-    }; // <- state dropped before the await
+    // Prepare AI corpus (if any)
+    let ai_corpus_opt = if !scored.ai_texts.is_empty() {
+        let mut s = String::new();
+        for t in scored.ai_texts.iter().take(8) {
+            if !s.is_empty() {
+                s.push_str("\n\n");
+            }
+            s.push_str(t);
+        }
+        Some(s)
+    } else {
+        None
+    };
 
     // -------- 2) STILL BEFORE `await`: cache/limit flags (no lock held across await) --------
     let (ai_disabled, limit_opt) = (
@@ -627,7 +1324,7 @@ async fn decide(Json(body): Json<Value>) -> impl IntoResponse {
     if let (Some(cache_key), false) = (cache_key_opt, ai_disabled) {
         // 2a) read-cache
         if let Some(cached) = {
-            let st = app_state();
+            let st = &state;
             st.ai_cache
                 .read()
                 .ok()
@@ -640,8 +1337,8 @@ async fn decide(Json(body): Json<Value>) -> impl IntoResponse {
         } else {
             // 2b) check daily limit
             let over_limit = {
-                let today = current_day(current_unix());
-                let st = app_state();
+                let today = current_day(state.clock.now_unix());
+                let st = &state;
                 if let Some(lim) = limit_opt {
                     let mut g = st.ai_daily.write().expect("ai_daily poisoned");
                     if g.day != today {
@@ -664,28 +1361,34 @@ async fn decide(Json(body): Json<Value>) -> impl IntoResponse {
     // -------- 3) THE ONLY `await`: AI analysis (only if no cache hit and not over-limit) --------
     if ai_reason.is_none() && should_call_ai {
         if let Some(ai_corpus) = &ai_corpus_opt {
-            let ai_client = { app_state().ai.clone() }; // grab Arc; no guard
+            let ai_client = { state.ai.clone() }; // grab Arc; no guard
             if let Some(r) = ai_analyze_safely(ai_client, ai_corpus.clone()).await {
                 if ai_reason_counts_as_used(&r) {
                     ai_reason = Some(r.clone());
 
                     // 3a) write to cache
                     if let Some(cache_key) = cache_key_opt {
-                        let st = app_state();
+                        let st = &state;
                         if let Ok(mut c) = st.ai_cache.write() {
                             c.insert(cache_key, r);
                         }
                     }
                     // 3b) increment daily usage (if limit is set)
                     if limit_opt.is_some() {
-                        let today = current_day(current_unix());
-                        let st = app_state();
+                        let today = current_day(state.clock.now_unix());
+                        let st = &state;
                         let mut g = st.ai_daily.write().expect("ai_daily poisoned");
                         if g.day != today {
                             g.day = today;
                             g.used = 0;
                         }
                         g.used = g.used.saturating_add(1);
+                        if let Some(store) = &st.store {
+                            store.append(&Record::AiDaily {
+                                day: g.day,
+                                used: g.used,
+                            });
+                        }
                     }
                 }
             }
@@ -693,59 +1396,35 @@ async fn decide(Json(body): Json<Value>) -> impl IntoResponse {
     }
 
     // -------- 4) AFTER await: take state again and finish the response --------
-    let state = app_state();
-
-    let mut decision = engine::make_decision(&scored);
-
-    let (vf, recent_triggers, uniq_sources) = volume_factor_from_history(&state.history, now);
-    let old_conf = decision.confidence;
-    let new_conf = (old_conf * vf).clamp(0.0, 0.99);
-    decision.confidence = new_conf;
-    decision.reasons.push(
-        crate::decision::Reason::new(format!(
-            "Volume context (last {window}s): {rt} triggers from {us} sources -> confidence x{vf:.3} ({old:.3}->{new:.3})",
-            window = VOLUME_WINDOW_SECS, rt = recent_triggers, us = uniq_sources, vf = vf, old = old_conf, new = new_conf,
-        ))
-        .kind(crate::decision::ReasonKind::Threshold)
-        .weighted(((vf - 0.90) / (1.05 - 0.90)).clamp(0.0, 1.0)),
-    );
 
-    if neutralized > 0 && total > 0 {
-        let frac = neutralized as f32 / total as f32;
-        decision.reasons.push(
-            crate::decision::Reason::new(format!(
-                "Relevance gate neutralized {}/{} items before decision",
-                neutralized, total
-            ))
-            .kind(crate::decision::ReasonKind::Threshold)
-            .weighted(frac.clamp(0.0, 1.0)),
-        );
-    }
-
-    if let Some(r) = &ai_reason {
-        decision.reasons.push(
-            crate::decision::Reason::new(format!("AI hint: {}", r))
-                .kind(crate::decision::ReasonKind::Threshold)
-                .weighted(0.5),
-        );
-        let before = decision.confidence;
-        let after = (before + 0.02).clamp(0.0, 0.99);
-        if after != before {
-            decision.confidence = after;
-            decision.reasons.push(
-                crate::decision::Reason::new(format!(
-                    "AI hint nudged confidence +0.02 ({before:.3}->{after:.3})"
-                ))
-                .kind(crate::decision::ReasonKind::Threshold)
-                .weighted(0.1),
-            );
-        }
+    let weights = if toggles.weights {
+        state.weights.current()
+    } else {
+        Default::default()
+    };
+    let finisher = Finisher {
+        history: &state.history,
+        stabilizer: &state.stabilizer,
+        calibration: &state.calibration,
+        grades: &state.grades,
+        weights: &weights,
+        toggles,
+    };
+    let (decision, report) = finisher.finish(scored, ai_reason.as_deref(), now);
+    if ai_reason.is_some() {
         // metrics: AI used
         counter!("ai_decision_ai_used_total").increment(1);
     }
 
     state.history.push(&decision);
 
+    if let Some(paper) = &state.paper {
+        let fills = paper.on_decision(&decision, now).await;
+        if !fills.is_empty() {
+            counter!("paper_fills_total").increment(fills.len() as u64);
+        }
+    }
+
     // ---- Build AI meta + JSON body ----
     let ai_meta = ApiAiInfo {
         used: ai_reason.is_some(),
@@ -757,6 +1436,7 @@ async fn decide(Json(body): Json<Value>) -> impl IntoResponse {
     let body = DecideWithAi {
         inner: decision,
         ai: ai_meta,
+        pipeline: report,
     };
 
     // concise INFO log
@@ -789,39 +1469,574 @@ async fn decide(Json(body): Json<Value>) -> impl IntoResponse {
     resp
 }
 
-fn current_unix() -> u64 {
-    use std::time::{SystemTime, UNIX_EPOCH};
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_secs()
+// ---- /v1/decide: strict validation + error envelope ----
+
+/// Limits enforced by `/v1/decide` (`DECIDE_MAX_ITEMS`, `DECIDE_MAX_TEXT_CHARS`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct DecideLimits {
+    max_items: usize,
+    max_text_chars: usize,
 }
 
-fn volume_factor_from_history(hist: &History, now: u64) -> (f32, usize, usize) {
-    let rows = hist.snapshot_last_n(200);
-    let mut recent_triggers = 0usize;
-    let mut uniq: HashSet<String> = HashSet::new();
+impl Default for DecideLimits {
+    fn default() -> Self {
+        Self {
+            max_items: 100,
+            max_text_chars: 2000,
+        }
+    }
+}
 
-    for h in rows {
-        if now.saturating_sub(h.ts_unix) <= VOLUME_WINDOW_SECS
-            && matches!(
-                h.verdict,
-                crate::decision::Verdict::Buy | crate::decision::Verdict::Sell
-            )
+impl DecideLimits {
+    fn from_env() -> Self {
+        let d = Self::default();
+        let max_items = std::env::var("DECIDE_MAX_ITEMS")
+            .ok()
+            .and_then(|v| v.trim().parse::<usize>().ok())
+            .filter(|n| *n > 0)
+            .unwrap_or(d.max_items);
+        let max_text_chars = std::env::var("DECIDE_MAX_TEXT_CHARS")
+            .ok()
+            .and_then(|v| v.trim().parse::<usize>().ok())
+            .filter(|n| *n > 0)
+            .unwrap_or(d.max_text_chars);
+        Self {
+            max_items,
+            max_text_chars,
+        }
+    }
+}
+
+/// `POST /v1/decide` body: an array of items or `{"items": [...]}` (schema only).
+#[derive(utoipa::ToSchema)]
+#[serde(untagged)]
+#[allow(dead_code)]
+enum DecideV1Request {
+    Items(Vec<DecideItem>),
+    Wrapped { items: Vec<DecideItem> },
+}
+
+/// One validation problem; `index` is the item position, absent for body-level errors.
+#[derive(serde::Serialize, utoipa::ToSchema, Debug, PartialEq)]
+struct FieldError {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    index: Option<usize>,
+    field: String,
+    problem: String,
+}
+
+impl FieldError {
+    fn body(field: &str, problem: impl Into<String>) -> Self {
+        Self {
+            index: None,
+            field: field.to_string(),
+            problem: problem.into(),
+        }
+    }
+
+    fn item(index: usize, field: &str, problem: impl Into<String>) -> Self {
+        Self {
+            index: Some(index),
+            field: field.to_string(),
+            problem: problem.into(),
+        }
+    }
+}
+
+#[derive(serde::Serialize, utoipa::ToSchema)]
+struct ApiError {
+    /// `invalid_json` | `validation_failed` | `invalid_filter` | `invalid_subscription` |
+    /// `not_found` | `unauthorized` | `forbidden` | ...
+    code: String,
+    message: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    details: Vec<FieldError>,
+}
+
+/// Error envelope of every endpoint that reports errors in a body.
+#[derive(serde::Serialize, utoipa::ToSchema)]
+struct ErrorEnvelope {
+    error: ApiError,
+}
+
+fn api_error(
+    status: axum::http::StatusCode,
+    code: &str,
+    message: impl Into<String>,
+    details: Vec<FieldError>,
+) -> axum::response::Response {
+    let body = ErrorEnvelope {
+        error: ApiError {
+            code: code.to_string(),
+            message: message.into(),
+            details,
+        },
+    };
+    (status, Json(body)).into_response()
+}
+
+/// 400 `invalid_json` for a body that does not parse into the expected shape.
+/// `field: problem` messages (as `validate` methods produce them) as details.
+fn field_errors(errors: &[String]) -> Vec<FieldError> {
+    errors
+        .iter()
+        .map(|e| match e.split_once(": ") {
+            Some((field, problem)) => FieldError::body(field, problem),
+            None => FieldError::body("", e.as_str()),
+        })
+        .collect()
+}
+
+const DECIDE_ITEM_FIELDS: [&str; 4] = ["source", "text", "ts_unix", "url"];
+
+/// Validate a `/v1/decide` body; every problem is reported, not just the first.
+fn validate_decide_v1(
+    body: &Value,
+    limits: &DecideLimits,
+) -> Result<Vec<DecideItem>, Vec<FieldError>> {
+    let arr = match body {
+        Value::Array(arr) => arr,
+        Value::Object(map) => match map.get("items") {
+            Some(Value::Array(arr)) if map.len() == 1 => arr,
+            Some(Value::Array(_)) => {
+                let extra = map.keys().filter(|k| *k != "items");
+                return Err(extra
+                    .map(|k| FieldError::body(k, "unknown field"))
+                    .collect());
+            }
+            Some(_) => return Err(vec![FieldError::body("items", "expected an array")]),
+            None => {
+                return Err(vec![FieldError::body(
+                    "body",
+                    "expected an array of items or {\"items\": [...]}",
+                )])
+            }
+        },
+        _ => {
+            return Err(vec![FieldError::body(
+                "body",
+                "expected an array of items or {\"items\": [...]}",
+            )])
+        }
+    };
+    if arr.is_empty() {
+        return Err(vec![FieldError::body(
+            "items",
+            "must contain at least 1 item",
+        )]);
+    }
+    if arr.len() > limits.max_items {
+        return Err(vec![FieldError::body(
+            "items",
+            format!("too many items: {} (max {})", arr.len(), limits.max_items),
+        )]);
+    }
+
+    let mut errors = Vec::new();
+    for (i, v) in arr.iter().enumerate() {
+        let Some(obj) = v.as_object() else {
+            errors.push(FieldError::item(i, "item", "expected an object"));
+            continue;
+        };
+        for k in obj
+            .keys()
+            .filter(|k| !DECIDE_ITEM_FIELDS.contains(&k.as_str()))
         {
-            recent_triggers += 1;
-            for s in h.top_sources.iter().take(5) {
-                uniq.insert(s.clone());
+            errors.push(FieldError::item(i, k, "unknown field"));
+        }
+        for field in ["source", "text"] {
+            match obj.get(field) {
+                None | Some(Value::Null) => errors.push(FieldError::item(i, field, "required")),
+                Some(Value::String(s)) if s.trim().is_empty() => {
+                    errors.push(FieldError::item(i, field, "must not be empty"))
+                }
+                Some(Value::String(s)) => {
+                    let chars = s.chars().count();
+                    if field == "text" && chars > limits.max_text_chars {
+                        errors.push(FieldError::item(
+                            i,
+                            field,
+                            format!("too long: {chars} chars (max {})", limits.max_text_chars),
+                        ));
+                    }
+                }
+                Some(_) => errors.push(FieldError::item(i, field, "expected a string")),
             }
         }
+        match obj.get("ts_unix") {
+            None | Some(Value::Null) => {}
+            Some(v) if v.is_u64() => {}
+            Some(_) => errors.push(FieldError::item(
+                i,
+                "ts_unix",
+                "expected a non-negative integer (unix seconds)",
+            )),
+        }
+        match obj.get("url") {
+            None | Some(Value::Null) => {}
+            Some(Value::String(u)) if u.starts_with("http://") || u.starts_with("https://") => {}
+            Some(_) => errors.push(FieldError::item(i, "url", "expected an http(s) URL")),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors);
     }
 
-    let rt = recent_triggers.min(5) as f32;
-    let us = uniq.len().min(5) as f32;
-    let mut vf = 0.90 + 0.02 * rt + 0.01 * us;
-    vf = vf.clamp(0.90, 1.05);
+    arr.iter()
+        .map(|v| serde_json::from_value::<DecideItem>(v.clone()))
+        .collect::<Result<_, _>>()
+        .map_err(|e| vec![FieldError::body("items", e.to_string())])
+}
+
+/// POST /v1/decide — `/decide` with strict input validation.
+/// Invalid JSON is a 400 and invalid items a 422, both in the `ErrorEnvelope`;
+/// nothing is scored unless every item is valid.
+#[utoipa::path(
+    post,
+    path = "/v1/decide",
+    tag = "decision",
+    request_body(
+        content = DecideV1Request,
+        description = "At most `DECIDE_MAX_ITEMS` (100) items; `text` at most `DECIDE_MAX_TEXT_CHARS` (2000) characters; unknown fields are rejected.",
+        examples(
+            ("items" = (value = json!([{ "source": "Fed", "text": "Fed signals rate cuts; Dow futures rally strongly.", "ts_unix": 1756733400, "url": "https://example.com/fed" }]))),
+            ("wrapped" = (value = json!({ "items": [{ "source": "Reuters", "text": "Dow futures plunge on recession fears." }] })))
+        )
+    ),
+    responses(
+        (status = 200, description = "Decision, same shape as `POST /decide`", body = DecideWithAi),
+        (status = 400, description = "Body is not valid JSON", body = ErrorEnvelope),
+        (status = 422, description = "Invalid items: one entry per problem with item index and field", body = ErrorEnvelope)
+    )
+)]
+async fn decide_v1(
+    State(state): State<Arc<ApiState>>,
+    body: axum::body::Bytes,
+) -> axum::response::Response {
+    use axum::http::StatusCode;
+
+    let value: Value = match serde_json::from_slice(&body) {
+        Ok(v) => v,
+        Err(e) => {
+            return api_error(
+                StatusCode::BAD_REQUEST,
+                "invalid_json",
+                format!("body is not valid JSON: {e}"),
+                Vec::new(),
+            )
+        }
+    };
+    match validate_decide_v1(&value, &state.decide_limits) {
+        Ok(items) => decide_items(state, items).await,
+        Err(details) => {
+            counter!("api_validation_errors_total").increment(1);
+            api_error(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "validation_failed",
+                format!("{} invalid field(s)", details.len()),
+                details,
+            )
+        }
+    }
+}
 
-    (vf, recent_triggers, uniq.len())
+// ---- /history: recorded decisions with filters and cursor paging ----
+
+/// GET /history — recorded decisions, newest first, with their reasons and contributors.
+/// Follow `next_cursor` (same filters) for older entries.
+#[utoipa::path(
+    get,
+    path = "/history",
+    tag = "history",
+    params(
+        ("from" = Option<String>, Query, description = "Inclusive start: unix seconds or RFC 3339"),
+        ("to" = Option<String>, Query, description = "Exclusive end: unix seconds or RFC 3339"),
+        ("verdict" = Option<String>, Query, description = "Comma list of `BUY`, `HOLD`, `SELL`"),
+        ("min_confidence" = Option<f32>, Query, description = "Drop entries below this confidence (0..1)"),
+        ("source" = Option<String>, Query, description = "Case-insensitive substring of a contributor source"),
+        ("cursor" = Option<u64>, Query, description = "`next_cursor` of the previous page"),
+        ("limit" = Option<usize>, Query, description = "Page size, 1..=500 (default 50)")
+    ),
+    responses(
+        (status = 200, description = "One page of history", body = HistoryPage),
+        (status = 400, description = "Invalid filter", body = ErrorEnvelope),
+        (status = 500, description = "History store unreadable", body = ErrorEnvelope)
+    )
+)]
+async fn history_query(
+    State(state): State<Arc<ApiState>>,
+    Query(q): Query<HashMap<String, String>>,
+) -> axum::response::Response {
+    let hq = match HistoryQuery::from_query(&q) {
+        Ok(hq) => hq,
+        Err(e) => {
+            return api_error(
+                axum::http::StatusCode::BAD_REQUEST,
+                "invalid_filter",
+                e,
+                Vec::new(),
+            )
+        }
+    };
+    // Pages past the in-memory ring come from the store's segment files.
+    let page = tokio::task::spawn_blocking(move || state.history.query_durable(&hq)).await;
+    match page {
+        Ok(Ok(page)) => Json(page).into_response(),
+        Ok(Err(e)) => api_error(
+            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+            "history_unavailable",
+            format!("history store: {e}"),
+            Vec::new(),
+        ),
+        Err(e) => api_error(
+            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+            "history_unavailable",
+            format!("history query failed: {e}"),
+            Vec::new(),
+        ),
+    }
+}
+
+/// GET /export — recorded decisions (or their contributors, one row each) as CSV or
+/// NDJSON, oldest first, with the stable column schema of `export`. Covers the durable
+/// store when one is configured and streams the body as it is encoded.
+#[utoipa::path(
+    get,
+    path = "/export",
+    tag = "history",
+    params(
+        ("from" = Option<String>, Query, description = "Inclusive start: unix seconds or RFC 3339"),
+        ("to" = Option<String>, Query, description = "Exclusive end: unix seconds or RFC 3339"),
+        ("instrument" = Option<String>, Query, description = "Must be the tracked instrument (`EXPORT_INSTRUMENT`, default `YM`)"),
+        ("kind" = Option<String>, Query, description = "`decisions` (default) or `contributors`"),
+        ("format" = Option<String>, Query, description = "`csv` (default) or `ndjson`"),
+        ("gzip" = Option<bool>, Query, description = "Gzip the file (`application/gzip`)")
+    ),
+    responses(
+        (status = 200, description = "Export file (attachment)", content(
+            (String = "text/csv"),
+            (String = "application/x-ndjson"),
+            (String = "application/gzip")
+        )),
+        (status = 400, description = "Invalid parameter or untracked instrument", body = ErrorEnvelope)
+    )
+)]
+async fn export_history(
+    State(state): State<Arc<ApiState>>,
+    Query(q): Query<HashMap<String, String>>,
+) -> axum::response::Response {
+    let instrument = export::instrument_from_env();
+    let eq = match ExportQuery::from_query(&q).and_then(|eq| {
+        eq.check_instrument(&instrument)?;
+        Ok(eq)
+    }) {
+        Ok(eq) => eq,
+        Err(e) => {
+            return api_error(
+                axum::http::StatusCode::BAD_REQUEST,
+                "invalid_filter",
+                e,
+                Vec::new(),
+            )
+        }
+    };
+
+    // Read the entries up front, so an unreadable store is still a clean 500; the
+    // rows are then encoded on a blocking thread and streamed in chunks.
+    let history = state.clone();
+    let entries = match tokio::task::spawn_blocking(move || history.history.durable_entries())
+        .await
+        .map_err(io::Error::other)
+        .and_then(|r| r)
+    {
+        Ok(entries) => entries,
+        Err(e) => {
+            tracing::error!(error = %e, "export failed");
+            return api_error(
+                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                "export_failed",
+                "export failed",
+                Vec::new(),
+            );
+        }
+    };
+    let content_type = if eq.gzip {
+        "application/gzip"
+    } else {
+        eq.format.content_type()
+    };
+    let disposition = format!("attachment; filename=\"{}\"", eq.file_name(&instrument));
+    let (tx, rx) = tokio::sync::mpsc::channel(EXPORT_CHUNKS_IN_FLIGHT);
+    tokio::task::spawn_blocking(move || {
+        let out = ChunkWriter::new(tx.clone());
+        if let Err(e) = export::write_export(&entries, &eq, &instrument, out) {
+            // Mid-stream: all we can do is cut the response short.
+            tracing::warn!(error = %e, "export aborted");
+            let _ = tx.blocking_send(Err(e));
+        }
+    });
+    (
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        axum::body::Body::from_stream(tokio_stream::wrappers::ReceiverStream::new(rx)),
+    )
+        .into_response()
+}
+
+/// Bytes buffered before an export chunk is handed to the response body.
+const EXPORT_CHUNK_BYTES: usize = 64 * 1024;
+/// Chunks queued ahead of a slow client before the encoder waits.
+const EXPORT_CHUNKS_IN_FLIGHT: usize = 4;
+
+/// `io::Write` that hands fixed-size chunks to a response body stream; a gone client
+/// surfaces as `BrokenPipe`, which stops the export.
+struct ChunkWriter {
+    buf: Vec<u8>,
+    tx: tokio::sync::mpsc::Sender<io::Result<axum::body::Bytes>>,
+}
+
+impl ChunkWriter {
+    fn new(tx: tokio::sync::mpsc::Sender<io::Result<axum::body::Bytes>>) -> Self {
+        Self {
+            buf: Vec::with_capacity(EXPORT_CHUNK_BYTES),
+            tx,
+        }
+    }
+
+    fn send(&mut self) -> io::Result<()> {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+        let chunk = std::mem::replace(&mut self.buf, Vec::with_capacity(EXPORT_CHUNK_BYTES));
+        self.tx
+            .blocking_send(Ok(chunk.into()))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "export client went away"))
+    }
+}
+
+impl io::Write for ChunkWriter {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(data);
+        if self.buf.len() >= EXPORT_CHUNK_BYTES {
+            self.send()?;
+        }
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.send()
+    }
+}
+
+// ---- /stream: live decisions over SSE / WebSocket ----
+
+fn stream_filter_rejected(e: String) -> axum::response::Response {
+    api_error(
+        axum::http::StatusCode::BAD_REQUEST,
+        "invalid_filter",
+        e,
+        Vec::new(),
+    )
+}
+
+/// Payload sent when a client fell behind and `skipped` events were dropped.
+fn lagged_json(skipped: u64) -> String {
+    serde_json::json!({ "type": "lagged", "skipped": skipped }).to_string()
+}
+
+/// GET /stream — Server-Sent Events; one SSE event per bus message
+/// (`event:` = type, `id:` = seq, `data:` = JSON). Same filters as `/stream/ws`.
+#[utoipa::path(
+    get,
+    path = "/stream",
+    tag = "stream",
+    params(
+        ("types" = Option<String>, Query, description = "Comma list of `decision`, `verdict_change`, `ingest` (default `decision,verdict_change`)"),
+        ("changes_only" = Option<bool>, Query, description = "Only `verdict_change` events"),
+        ("min_confidence" = Option<f32>, Query, description = "Drop decisions/changes below this confidence (0..1)"),
+        ("verdicts" = Option<String>, Query, description = "Comma list of `BUY`, `HOLD`, `SELL`")
+    ),
+    responses(
+        (status = 200, description = "Event stream; `lagged` events report skipped messages", body = String, content_type = "text/event-stream"),
+        (status = 400, description = "Invalid filter", body = ErrorEnvelope)
+    )
+)]
+async fn stream_sse(
+    State(state): State<Arc<ApiState>>,
+    Query(q): Query<HashMap<String, String>>,
+) -> axum::response::Response {
+    use axum::response::sse::{Event, KeepAlive, Sse};
+    use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream};
+    use tokio_stream::StreamExt as _;
+
+    let filter = match StreamFilter::from_query(&q) {
+        Ok(f) => f,
+        Err(e) => return stream_filter_rejected(e),
+    };
+    let events = BroadcastStream::new(state.bus.subscribe()).filter_map(move |msg| {
+        let event = match msg {
+            Ok(m) if filter.matches(&m.event) => Event::default()
+                .event(m.event.kind())
+                .id(m.seq.to_string())
+                .data(serde_json::to_string(&m).ok()?),
+            Ok(_) => return None,
+            Err(BroadcastStreamRecvError::Lagged(n)) => {
+                Event::default().event("lagged").data(lagged_json(n))
+            }
+        };
+        Some(Ok::<_, std::convert::Infallible>(event))
+    });
+    Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+/// GET /stream/ws — the `/stream` events as WebSocket text messages (JSON).
+/// Client messages other than close are ignored.
+async fn stream_ws(
+    State(state): State<Arc<ApiState>>,
+    ws: axum::extract::ws::WebSocketUpgrade,
+    Query(q): Query<HashMap<String, String>>,
+) -> axum::response::Response {
+    let filter = match StreamFilter::from_query(&q) {
+        Ok(f) => f,
+        Err(e) => return stream_filter_rejected(e),
+    };
+    // Subscribe before the handshake completes so nothing published after it is missed.
+    let rx = state.bus.subscribe();
+    ws.on_upgrade(move |socket| stream_ws_session(socket, rx, filter))
+}
+
+async fn stream_ws_session(
+    mut socket: axum::extract::ws::WebSocket,
+    mut rx: tokio::sync::broadcast::Receiver<StreamMessage>,
+    filter: StreamFilter,
+) {
+    use axum::extract::ws::Message;
+    use tokio::sync::broadcast::error::RecvError;
+
+    loop {
+        let text = tokio::select! {
+            msg = rx.recv() => match msg {
+                Ok(m) if filter.matches(&m.event) => match serde_json::to_string(&m) {
+                    Ok(text) => text,
+                    Err(_) => continue,
+                },
+                Ok(_) => continue,
+                Err(RecvError::Lagged(n)) => lagged_json(n),
+                Err(RecvError::Closed) => break,
+            },
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => continue,
+            },
+        };
+        if socket.send(Message::Text(text.into())).await.is_err() {
+            break;
+        }
+    }
 }
 
 #[derive(serde::Serialize)]
@@ -831,8 +2046,7 @@ struct RollingInfo {
     count: usize,
 }
 
-async fn debug_rolling() -> Json<RollingInfo> {
-    let state = app_state();
+async fn debug_rolling(State(state): State<Arc<ApiState>>) -> Json<RollingInfo> {
     let (avg, n) = state.rolling.average_and_count();
     Json(RollingInfo {
         window_secs: state.rolling.window_secs(),
@@ -850,8 +2064,12 @@ struct HistoryOut {
     scores: Vec<i32>,
 }
 
-async fn debug_history() -> Json<Vec<HistoryOut>> {
-    let state = app_state();
+/// GET /debug/routes — every path this router registered, in registration order.
+async fn debug_routes(State(state): State<Arc<ApiState>>) -> Json<Vec<&'static str>> {
+    Json(state.routes.get().cloned().unwrap_or_default())
+}
+
+async fn debug_history(State(state): State<Arc<ApiState>>) -> Json<Vec<HistoryOut>> {
     let rows = state.history.snapshot_last_n(10);
     Json(
         rows.into_iter()
@@ -875,8 +2093,7 @@ struct LastOut {
     scores: Vec<i32>,
 }
 
-async fn debug_last_decision() -> Json<Option<LastOut>> {
-    let state = app_state();
+async fn debug_last_decision(State(state): State<Arc<ApiState>>) -> Json<Option<LastOut>> {
     let mut rows = state.history.snapshot_last_n(1);
     if let Some(h) = rows.pop() {
         return Json(Some(LastOut {
@@ -890,8 +2107,385 @@ async fn debug_last_decision() -> Json<Option<LastOut>> {
     Json(None)
 }
 
-async fn debug_source_weight(Query(q): Query<HashMap<String, String>>) -> String {
-    let state = app_state();
+// ---- /paper/*: paper-trading views (as of the last mark; reads never mark) ----
+
+#[derive(serde::Serialize, utoipa::ToSchema)]
+struct PaperPositionsOut {
+    positions: Vec<PositionView>,
+}
+
+#[derive(serde::Serialize, utoipa::ToSchema)]
+struct PaperFillsOut {
+    fills: Vec<Fill>,
+}
+
+/// GET /paper/positions — open positions at the last marked price.
+#[utoipa::path(
+    get,
+    path = "/paper/positions",
+    tag = "paper",
+    responses((status = 200, description = "Open positions", body = PaperPositionsOut))
+)]
+async fn paper_positions(State(state): State<Arc<ApiState>>) -> Json<PaperPositionsOut> {
+    let positions = state
+        .paper
+        .as_ref()
+        .and_then(|p| p.broker().position())
+        .into_iter()
+        .collect();
+    Json(PaperPositionsOut { positions })
+}
+
+// ---- /subscriptions: webhook callbacks ----
+
+/// `POST /subscriptions` response: the subscription with its signing secret (shown once).
+#[derive(serde::Serialize, utoipa::ToSchema)]
+struct SubscriptionCreated {
+    id: String,
+    url: String,
+    /// HMAC-SHA256 key for `X-Webhook-Signature`; not returned again.
+    secret: String,
+    filter: crate::webhooks::SubscriptionFilter,
+    description: Option<String>,
+    created_unix: u64,
+}
+
+impl From<Subscription> for SubscriptionCreated {
+    fn from(s: Subscription) -> Self {
+        Self {
+            id: s.id,
+            url: s.url,
+            secret: s.secret,
+            filter: s.filter,
+            description: s.description,
+            created_unix: s.created_unix,
+        }
+    }
+}
+
+#[derive(serde::Serialize, utoipa::ToSchema)]
+struct SubscriptionsOut {
+    subscriptions: Vec<SubscriptionView>,
+}
+
+fn subscription_not_found(id: &str) -> axum::response::Response {
+    api_error(
+        axum::http::StatusCode::NOT_FOUND,
+        "not_found",
+        format!("no subscription `{id}`"),
+        Vec::new(),
+    )
+}
+
+fn subscription_rejected(e: SubscriptionError) -> axum::response::Response {
+    use axum::http::StatusCode;
+    let (status, code) = match &e {
+        SubscriptionError::Invalid(_) => (StatusCode::UNPROCESSABLE_ENTITY, "invalid_subscription"),
+        SubscriptionError::Unavailable(_) => (StatusCode::CONFLICT, "subscriptions_unavailable"),
+        SubscriptionError::NotPersisted(_) => (StatusCode::INTERNAL_SERVER_ERROR, "persist_failed"),
+    };
+    api_error(status, code, e.to_string(), Vec::new())
+}
+
+/// POST /subscriptions — register a callback URL; matching stream events are POSTed to it.
+#[utoipa::path(
+    post,
+    path = "/subscriptions",
+    tag = "subscriptions",
+    request_body(
+        content = NewSubscription,
+        example = json!({ "url": "https://hooks.example.com/dow", "filter": { "events": ["verdict_change"], "min_confidence": 0.6 }, "description": "risk desk" })
+    ),
+    responses(
+        (status = 201, description = "Created; `secret` is only returned here", body = SubscriptionCreated),
+        (status = 400, description = "Body is not valid JSON", body = ErrorEnvelope),
+        (status = 409, description = "Subscriptions file unreadable; changes refused until fixed", body = ErrorEnvelope),
+        (status = 422, description = "Invalid or non-public URL, filter or secret", body = ErrorEnvelope)
+    )
+)]
+async fn subscriptions_create(
+    State(state): State<Arc<ApiState>>,
+    body: axum::body::Bytes,
+) -> axum::response::Response {
+    let req: NewSubscription = match serde_json::from_slice(&body) {
+        Ok(r) => r,
+        Err(e) => return invalid_json(e),
+    };
+    match state.webhooks.create(req) {
+        Ok(sub) => (
+            axum::http::StatusCode::CREATED,
+            Json(SubscriptionCreated::from(sub)),
+        )
+            .into_response(),
+        Err(e) => subscription_rejected(e),
+    }
+}
+
+/// GET /subscriptions — all subscriptions with their delivery status.
+#[utoipa::path(
+    get,
+    path = "/subscriptions",
+    tag = "subscriptions",
+    responses((status = 200, description = "Subscriptions (secrets omitted)", body = SubscriptionsOut))
+)]
+async fn subscriptions_list(State(state): State<Arc<ApiState>>) -> Json<SubscriptionsOut> {
+    Json(SubscriptionsOut {
+        subscriptions: state.webhooks.list(),
+    })
+}
+
+/// GET /subscriptions/{id} — one subscription with its recent deliveries.
+#[utoipa::path(
+    get,
+    path = "/subscriptions/{id}",
+    tag = "subscriptions",
+    params(("id" = String, Path, description = "Subscription id")),
+    responses(
+        (status = 200, description = "Subscription (secret omitted)", body = SubscriptionView),
+        (status = 404, description = "Unknown id", body = ErrorEnvelope)
+    )
+)]
+async fn subscription_get(
+    State(state): State<Arc<ApiState>>,
+    axum::extract::Path(id): axum::extract::Path<String>,
+) -> axum::response::Response {
+    match state.webhooks.get(&id) {
+        Some(view) => Json(view).into_response(),
+        None => subscription_not_found(&id),
+    }
+}
+
+/// DELETE /subscriptions/{id} — stop deliveries (pending retries are dropped).
+#[utoipa::path(
+    delete,
+    path = "/subscriptions/{id}",
+    tag = "subscriptions",
+    params(("id" = String, Path, description = "Subscription id")),
+    responses(
+        (status = 204, description = "Deleted"),
+        (status = 404, description = "Unknown id", body = ErrorEnvelope),
+        (status = 409, description = "Subscriptions file unreadable; changes refused until fixed", body = ErrorEnvelope)
+    )
+)]
+async fn subscription_delete(
+    State(state): State<Arc<ApiState>>,
+    axum::extract::Path(id): axum::extract::Path<String>,
+) -> axum::response::Response {
+    match state.webhooks.delete(&id) {
+        Ok(true) => axum::http::StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => subscription_not_found(&id),
+        Err(e) => subscription_rejected(e),
+    }
+}
+
+/// Upper bound on texts per `/rules/dry-run` request.
+const DRY_RUN_MAX_TEXTS: usize = 1000;
+
+#[derive(serde::Deserialize, utoipa::ToSchema)]
+struct DryRunReq {
+    /// Candidate rule set, same shape as `config/rules.json`.
+    #[schema(value_type = Object)]
+    rules: Value,
+    #[serde(default)]
+    texts: Vec<DryRunText>,
+}
+
+/// A bare string, or a `/decide`-style item so `source`/`time` conditions can match.
+#[derive(serde::Deserialize, utoipa::ToSchema)]
+#[serde(untagged)]
+enum DryRunText {
+    Plain(String),
+    Item {
+        text: String,
+        #[serde(default)]
+        source: Option<String>,
+        #[serde(default)]
+        ts_unix: Option<u64>,
+    },
+}
+
+#[derive(serde::Serialize, utoipa::ToSchema)]
+struct DryRunResult {
+    text: String,
+    source: Option<String>,
+    score: i32,
+    relevance: f32,
+    /// Rules that matched, in evaluation order.
+    matched: Vec<String>,
+    action: Option<String>,
+    action_rule: Option<String>,
+    confidence_delta: f32,
+    reasons: Vec<String>,
+    conflicts: Vec<String>,
+    stopped_by: Option<String>,
+}
+
+#[derive(serde::Serialize, utoipa::ToSchema)]
+struct RuleFireCount {
+    rule: String,
+    fires: usize,
+}
+
+#[derive(serde::Serialize, utoipa::ToSchema)]
+struct DryRunOut {
+    texts: usize,
+    /// Texts where at least one rule matched.
+    matched_texts: usize,
+    /// Action → number of texts that ended with it.
+    actions: std::collections::BTreeMap<String, usize>,
+    fire_counts: Vec<RuleFireCount>,
+    results: Vec<DryRunResult>,
+}
+
+/// 422 `validation_failed`: why the candidate rule set (or request) was rejected.
+fn dry_run_rejected(details: Vec<FieldError>) -> axum::response::Response {
+    api_error(
+        axum::http::StatusCode::UNPROCESSABLE_ENTITY,
+        "validation_failed",
+        format!("rule set rejected: {} problem(s)", details.len()),
+        details,
+    )
+}
+
+/// POST /rules/dry-run — run a candidate rule set over texts, scored like `/decide`.
+/// Nothing is read from or written to the live rules file.
+#[utoipa::path(
+    post,
+    path = "/rules/dry-run",
+    tag = "rules",
+    request_body(
+        content = DryRunReq,
+        example = json!({
+            "rules": { "rules": [{ "name": "tariffs", "when": { "any_contains": ["tariff"] }, "then": { "set_action": "SELL" } }] },
+            "texts": ["New tariff threats weigh on stocks", { "source": "Fed", "text": "Fed holds rates" }]
+        })
+    ),
+    responses(
+        (status = 200, description = "Per-text outcomes and rule fire counts", body = DryRunOut),
+        (status = 400, description = "Body is not valid JSON", body = ErrorEnvelope),
+        (status = 422, description = "Invalid rule set (`field` = rule label) or too many texts", body = ErrorEnvelope)
+    )
+)]
+async fn rules_dry_run(
+    State(state): State<Arc<ApiState>>,
+    body: axum::body::Bytes,
+) -> axum::response::Response {
+    let body: DryRunReq = match serde_json::from_slice(&body) {
+        Ok(b) => b,
+        Err(e) => return invalid_json(e),
+    };
+    let rule_set: RuleSet = match serde_json::from_value(body.rules) {
+        Ok(r) => r,
+        Err(e) => return dry_run_rejected(vec![FieldError::body("rules", e.to_string())]),
+    };
+    let errors = rule_set.validate();
+    if !errors.is_empty() {
+        return dry_run_rejected(field_errors(&errors));
+    }
+    if body.texts.len() > DRY_RUN_MAX_TEXTS {
+        return dry_run_rejected(vec![FieldError::body(
+            "texts",
+            format!("{} texts (max {DRY_RUN_MAX_TEXTS})", body.texts.len()),
+        )]);
+    }
+
+    let now = state.clock.now_unix();
+    let sw = state.source_weights.read().expect("rwlock poisoned");
+    let mut outcomes = Vec::with_capacity(body.texts.len());
+    let mut results = Vec::with_capacity(body.texts.len());
+    for t in body.texts {
+        let (text, source, ts_unix) = match t {
+            DryRunText::Plain(text) => (text, None, None),
+            DryRunText::Item {
+                text,
+                source,
+                ts_unix,
+            } => (text, source, ts_unix),
+        };
+        let (score, _tokens) = state.analyzer.score_text(&text);
+        let relevance = state.relevance.score(&text).score;
+        let ctx = RuleContext {
+            text: &text,
+            source: source.as_deref(),
+            score: Some(score),
+            relevance: Some(relevance),
+            ts_unix: Some(ts_unix.unwrap_or(now)),
+            sources: Some(&sw),
+        };
+        let out = rules::evaluate(&ctx, &rule_set);
+        results.push(DryRunResult {
+            text: text.clone(),
+            source: source.clone(),
+            score,
+            relevance,
+            matched: out.matched.clone(),
+            action: out.action.clone(),
+            action_rule: out.action_rule.clone(),
+            confidence_delta: out.confidence_delta,
+            reasons: out.reasons.clone(),
+            conflicts: out.conflicts.clone(),
+            stopped_by: out.stopped_by.clone(),
+        });
+        outcomes.push(out);
+    }
+
+    let mut actions = std::collections::BTreeMap::new();
+    for a in outcomes.iter().filter_map(|o| o.action.clone()) {
+        *actions.entry(a).or_insert(0) += 1;
+    }
+    let out = DryRunOut {
+        texts: results.len(),
+        matched_texts: outcomes.iter().filter(|o| !o.matched.is_empty()).count(),
+        actions,
+        fire_counts: rules::fire_counts(&rule_set, &outcomes)
+            .into_iter()
+            .map(|(rule, fires)| RuleFireCount { rule, fires })
+            .collect(),
+        results,
+    };
+    Json(out).into_response()
+}
+
+/// GET /paper/fills — most recent fills, oldest first.
+#[utoipa::path(
+    get,
+    path = "/paper/fills",
+    tag = "paper",
+    params(("limit" = Option<usize>, Query, description = "Max fills (default 100, capped at 1000)")),
+    responses((status = 200, description = "Recent fills", body = PaperFillsOut))
+)]
+async fn paper_fills(
+    State(state): State<Arc<ApiState>>,
+    Query(q): Query<HashMap<String, String>>,
+) -> Json<PaperFillsOut> {
+    let limit = q
+        .get("limit")
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(100)
+        .min(1000);
+    let fills = state
+        .paper
+        .as_ref()
+        .map(|p| p.broker().fills(limit))
+        .unwrap_or_default();
+    Json(PaperFillsOut { fills })
+}
+
+/// GET /paper/pnl — realized/unrealized P&L (null when paper trading is off).
+#[utoipa::path(
+    get,
+    path = "/paper/pnl",
+    tag = "paper",
+    responses((status = 200, description = "P&L summary", body = Option<PnlSummary>))
+)]
+async fn paper_pnl(State(state): State<Arc<ApiState>>) -> Json<Option<PnlSummary>> {
+    Json(state.paper.as_ref().map(|p| p.broker().pnl()))
+}
+
+async fn debug_source_weight(
+    State(state): State<Arc<ApiState>>,
+    Query(q): Query<HashMap<String, String>>,
+) -> String {
     let s = q.get("source").cloned().unwrap_or_default();
     let w = {
         let g = state.source_weights.read().expect("rwlock poisoned");
@@ -900,16 +2494,198 @@ async fn debug_source_weight(Query(q): Query<HashMap<String, String>>) -> String
     format!("source='{}' -> weight={:.2}", s, w)
 }
 
-async fn admin_reload_source_weights() -> String {
-    let state = app_state();
-    let fresh = SourceWeightsConfig::load_from_file("source_weights.json");
-    match state.source_weights.write() {
-        Ok(mut w) => {
-            *w = fresh;
-            "reloaded".to_string()
+/// Re-read the source weights file; an invalid file keeps the current weights (422),
+/// an unreadable one answers 500.
+async fn admin_reload_source_weights(
+    State(state): State<Arc<ApiState>>,
+    key: Option<Extension<KeyId>>,
+) -> axum::response::Response {
+    let mut refused = state.source_weights_edit.lock().expect("mutex poisoned");
+    let fresh = match SourceWeightsConfig::try_load_from_file(&state.source_weights_path) {
+        Ok(cfg) => cfg,
+        Err(LoadError::Invalid(errors)) => return source_weights_rejected(&errors),
+        Err(e @ LoadError::Read(_)) => {
+            return api_error(
+                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                "reload_failed",
+                format!("source weights not reloaded: {e}"),
+                Vec::new(),
+            )
         }
-        Err(_) => "failed: lock poisoned".to_string(),
+    };
+    let diff = {
+        let mut w = state.source_weights.write().expect("rwlock poisoned");
+        let diff = w.diff(&fresh);
+        *w = fresh;
+        diff
+    };
+    *refused = None;
+    drop(refused);
+    log_source_weights_change(&state, key, "reload", diff);
+    "reloaded".into_response()
+}
+
+// ---- /admin/source-weights: validated edits with diff and change history ----
+
+#[derive(serde::Serialize)]
+struct SourceWeightsUpdated {
+    config: SourceWeightsConfig,
+    diff: SourceWeightsDiff,
+}
+
+#[derive(serde::Serialize)]
+struct SourceWeightsChanges {
+    changes: Vec<ChangeRecord>,
+}
+
+/// Most entries returned by `/admin/source-weights/history`.
+const SOURCE_WEIGHTS_HISTORY_MAX: usize = 500;
+
+fn log_source_weights_change(
+    state: &ApiState,
+    key: Option<Extension<KeyId>>,
+    action: &str,
+    diff: SourceWeightsDiff,
+) {
+    if diff.is_empty() {
+        return;
     }
+    state.source_weights_log.append(&ChangeRecord {
+        ts_unix: state.clock.now_unix(),
+        key: key.map(|Extension(KeyId(id))| id),
+        action: action.to_string(),
+        diff,
+    });
+}
+
+/// GET /admin/source-weights — the live config (keys normalized, sorted).
+async fn admin_source_weights_get(State(state): State<Arc<ApiState>>) -> Json<SourceWeightsConfig> {
+    let sw = state.source_weights.read().expect("rwlock poisoned");
+    Json(sw.clone())
+}
+
+/// PUT /admin/source-weights — replace the whole config.
+async fn admin_source_weights_put(
+    State(state): State<Arc<ApiState>>,
+    key: Option<Extension<KeyId>>,
+    body: axum::body::Bytes,
+) -> axum::response::Response {
+    match serde_json::from_slice::<SourceWeightsConfig>(&body) {
+        Ok(cfg) => match cfg.collisions() {
+            errors if errors.is_empty() => {
+                update_source_weights(&state, key, "put", |_| cfg.normalized())
+            }
+            errors => source_weights_rejected(&errors),
+        },
+        Err(e) => invalid_json(e),
+    }
+}
+
+/// PATCH /admin/source-weights — set listed weights/aliases; `null` removes one.
+async fn admin_source_weights_patch(
+    State(state): State<Arc<ApiState>>,
+    key: Option<Extension<KeyId>>,
+    body: axum::body::Bytes,
+) -> axum::response::Response {
+    match serde_json::from_slice::<SourceWeightsPatch>(&body) {
+        Ok(patch) => match patch.collisions() {
+            errors if errors.is_empty() => {
+                update_source_weights(&state, key, "patch", |cur| cur.patched(&patch))
+            }
+            errors => source_weights_rejected(&errors),
+        },
+        Err(e) => invalid_json(e),
+    }
+}
+
+/// GET /admin/source-weights/history — applied changes, newest first (`limit`, default 50).
+async fn admin_source_weights_history(
+    State(state): State<Arc<ApiState>>,
+    Query(q): Query<HashMap<String, String>>,
+) -> axum::response::Response {
+    let limit = match q.get("limit").map(|v| v.parse::<usize>()) {
+        None => 50,
+        Some(Ok(n)) if (1..=SOURCE_WEIGHTS_HISTORY_MAX).contains(&n) => n,
+        Some(_) => {
+            return api_error(
+                axum::http::StatusCode::BAD_REQUEST,
+                "invalid_filter",
+                format!("limit: expected 1..={SOURCE_WEIGHTS_HISTORY_MAX}"),
+                Vec::new(),
+            )
+        }
+    };
+    Json(SourceWeightsChanges {
+        changes: state.source_weights_log.recent(limit),
+    })
+    .into_response()
+}
+
+fn invalid_json(e: serde_json::Error) -> axum::response::Response {
+    api_error(
+        axum::http::StatusCode::BAD_REQUEST,
+        "invalid_json",
+        format!("body is not valid JSON: {e}"),
+        Vec::new(),
+    )
+}
+
+fn source_weights_rejected(errors: &[String]) -> axum::response::Response {
+    api_error(
+        axum::http::StatusCode::UNPROCESSABLE_ENTITY,
+        "validation_failed",
+        "source weights rejected; nothing was changed",
+        field_errors(errors),
+    )
+}
+
+/// Validate `edit(current)`, write it to the file, then swap it in. Edits hold
+/// `source_weights_edit` so they apply one after the other; the weights lock is only
+/// taken to read the current config and to swap, never across the file write.
+fn update_source_weights(
+    state: &ApiState,
+    key: Option<Extension<KeyId>>,
+    action: &str,
+    edit: impl FnOnce(&SourceWeightsConfig) -> SourceWeightsConfig,
+) -> axum::response::Response {
+    use axum::http::StatusCode;
+
+    let refused = state.source_weights_edit.lock().expect("mutex poisoned");
+    if let Some(e) = refused.as_ref() {
+        return api_error(
+            StatusCode::CONFLICT,
+            "source_weights_unavailable",
+            format!(
+                "{} was refused at startup ({e}); fix it and reload before editing",
+                state.source_weights_path.display()
+            ),
+            Vec::new(),
+        );
+    }
+    let cur = state
+        .source_weights
+        .read()
+        .expect("rwlock poisoned")
+        .clone();
+    let next = edit(&cur);
+    let errors = next.validate();
+    if !errors.is_empty() {
+        return source_weights_rejected(&errors);
+    }
+    if let Err(e) = next.save_atomic(&state.source_weights_path) {
+        tracing::error!(path = %state.source_weights_path.display(), error = %e, "source weights not saved");
+        return api_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "persist_failed",
+            format!("source weights not saved: {e}"),
+            Vec::new(),
+        );
+    }
+    let diff = cur.diff(&next);
+    *state.source_weights.write().expect("rwlock poisoned") = next.clone();
+    drop(refused);
+    log_source_weights_change(state, key, action, diff.clone());
+    Json(SourceWeightsUpdated { config: next, diff }).into_response()
 }
 
 // -----------------------------------------------------------------------------
@@ -933,9 +2709,6 @@ use once_cell::sync::Lazy;
 use sha2::{Digest, Sha256};
 use tokio::time::{Duration, Instant};
 
-/// Map: cache-key -> expiry Instant (pevná expirace s malým negativním biasem)
-static AI_CACHE_EXPIRY: Lazy<DashMap<String, Instant>> = Lazy::new(DashMap::new);
-
 fn ai_cache_ttl() -> Duration {
     // Preferred: millisecond TTL for precise tests
     if let Ok(ms_str) = std::env::var("AI_DECISION_CACHE_TTL_MS") {
@@ -961,13 +2734,10 @@ fn ai_cache_bias() -> Duration {
     Duration::from_millis(ms)
 }
 
-/// Vyprázdění cache — volá se v `router()` pro izolaci testů.
-fn clear_ai_cache() {
-    AI_CACHE_EXPIRY.clear();
-}
-
 /// Axum middleware: vždy přidá `X-AI-Cache: miss|hit`.
-pub async fn ai_cache_mw(
+/// Expirace žijí ve stavu routeru (`ApiState::ai_cache_expiry`).
+async fn ai_cache_mw(
+    State(state): State<Arc<ApiState>>,
     req: Request<Body>,
     next: Next,
 ) -> Result<Response, axum::http::StatusCode> {
@@ -994,7 +2764,7 @@ pub async fn ai_cache_mw(
 
     // 1) Bezpečně zjisti HIT/MISS (guard se po tomto bloku uvolní)
     let is_hit = {
-        if let Some(expiry_at) = AI_CACHE_EXPIRY.get(&key) {
+        if let Some(expiry_at) = state.ai_cache_expiry.get(&key) {
             ttl > tokio::time::Duration::ZERO && now < *expiry_at
         } else {
             false
@@ -1009,7 +2779,7 @@ pub async fn ai_cache_mw(
     } else {
         let base = now.checked_add(ttl).unwrap_or(now);
         let new_expiry = base.checked_sub(bias).unwrap_or(now);
-        AI_CACHE_EXPIRY.insert(key.clone(), new_expiry);
+        state.ai_cache_expiry.insert(key.clone(), new_expiry);
         // metrics: record miss
         counter!("ai_decision_cache_misses_total").increment(1);
         "miss"
@@ -0,0 +1,596 @@
+//! # API keys, scopes and rate limits
+//! Optional access control for the `/api` router.
+//!
+//! - Keys come from `config/api_keys.json` (path via `API_KEYS_PATH`). Without that
+//!   file every route stays open, as before, and startup logs a warning saying so;
+//!   `API_KEYS_REQUIRED=1` refuses protected routes instead. With it, only `/health`,
+//!   `/metrics`, `/openapi.json` and `/docs` are public.
+//! - A key's secret is given as `key` (plain, dev only), `key_env` (environment
+//!   variable) or `sha256` (hex digest). Only digests are kept in memory.
+//! - Scopes: `read` (GET views, history, export, stream), `decide` (POSTs that run
+//!   the pipeline), `webhooks` (`/subscriptions*`, which makes the service call out)
+//!   and `admin` (`/admin/*`, `/debug/*`; grants every scope).
+//! - Each key has a token bucket (`rate_limit`, else `default_rate_limit`) refilled
+//!   from the API clock.
+//! - Requests authenticate with `Authorization: Bearer <key>` or `X-API-Key`;
+//!   `/stream` also accepts `?api_key=` (EventSource cannot set headers). Query strings
+//!   end up in proxy access logs, so give browser clients a `read`-only key. The router
+//!   strips the parameter before handlers run, and audit records carry only the path.
+//! - Admin calls are audited: one JSON line per call in `ADMIN_AUDIT_LOG`
+//!   (default `state/admin_audit.jsonl`) and a `audit` tracing event.
+//!
+//! JSON shape:
+//! ```json
+//! {
+//!   "default_rate_limit": { "per_minute": 120, "burst": 30 },
+//!   "keys": [
+//!     { "id": "notebook", "sha256": "<hex>", "scopes": ["read"] },
+//!     { "id": "ingest", "key_env": "INGEST_API_KEY", "scopes": ["decide", "read"],
+//!       "rate_limit": { "per_minute": 600, "burst": 100 } }
+//!   ]
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Mutex};
+use std::thread::JoinHandle;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+pub const DEFAULT_API_KEYS_PATH: &str = "config/api_keys.json";
+pub const ENV_API_KEYS_PATH: &str = "API_KEYS_PATH";
+pub const ENV_API_KEYS_REQUIRED: &str = "API_KEYS_REQUIRED";
+pub const DEFAULT_AUDIT_LOG_PATH: &str = "state/admin_audit.jsonl";
+pub const ENV_AUDIT_LOG_PATH: &str = "ADMIN_AUDIT_LOG";
+
+/// What a key may do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Scope {
+    Read,
+    Decide,
+    Webhooks,
+    Admin,
+}
+
+impl Scope {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Scope::Read => "read",
+            Scope::Decide => "decide",
+            Scope::Webhooks => "webhooks",
+            Scope::Admin => "admin",
+        }
+    }
+}
+
+/// Scope needed for `method path` (router-relative, e.g. `/decide`); `None` = public.
+pub fn required_scope(method: &str, path: &str) -> Option<Scope> {
+    if method.eq_ignore_ascii_case("OPTIONS") {
+        return None;
+    }
+    match path {
+        "/health" | "/metrics" | "/openapi.json" | "/docs" => None,
+        p if p.starts_with("/admin/") || p.starts_with("/debug/") => Some(Scope::Admin),
+        p if p.starts_with("/subscriptions") => Some(Scope::Webhooks),
+        "/decide" | "/v1/decide" | "/analyze" | "/batch" | "/rules/dry-run"
+            if method.eq_ignore_ascii_case("POST") =>
+        {
+            Some(Scope::Decide)
+        }
+        _ => Some(Scope::Read),
+    }
+}
+
+/// Token-bucket settings.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RateLimit {
+    /// Sustained requests per minute.
+    pub per_minute: f64,
+    /// Requests allowed at once on a full bucket.
+    pub burst: f64,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self {
+            per_minute: 120.0,
+            burst: 30.0,
+        }
+    }
+}
+
+/// One configured key (as written in the file).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeySpec {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_env: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    pub scopes: Vec<Scope>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimit>,
+}
+
+/// File contents of `config/api_keys.json`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ApiKeysConfig {
+    #[serde(default)]
+    pub default_rate_limit: RateLimit,
+    #[serde(default)]
+    pub keys: Vec<KeySpec>,
+}
+
+/// Hex SHA-256 of a presented key.
+pub fn hash_key(key: &str) -> String {
+    Sha256::digest(key.as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    last: u64,
+}
+
+#[derive(Debug)]
+struct Key {
+    id: String,
+    scopes: Vec<Scope>,
+    limit: RateLimit,
+    bucket: Mutex<Bucket>,
+}
+
+/// Why a request was turned away (also the `reason` label of the rejection counter).
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rejection {
+    /// No key, or one that is not configured.
+    Unauthorized,
+    /// Valid key without the needed scope.
+    Forbidden { key: String, scope: Scope },
+    /// Bucket empty; retry after this many seconds.
+    RateLimited { key: String, retry_after: u64 },
+}
+
+impl Rejection {
+    pub fn reason(&self) -> &'static str {
+        match self {
+            Rejection::Unauthorized => "unauthorized",
+            Rejection::Forbidden { .. } => "forbidden",
+            Rejection::RateLimited { .. } => "rate_limited",
+        }
+    }
+}
+
+/// Loaded keys; `disabled()` lets everything through.
+#[derive(Debug)]
+pub struct ApiKeys {
+    enabled: bool,
+    by_hash: HashMap<String, Key>,
+}
+
+impl ApiKeys {
+    /// Access control off (no key file).
+    pub fn disabled() -> Self {
+        Self {
+            enabled: false,
+            by_hash: HashMap::new(),
+        }
+    }
+
+    /// Keys from `cfg`; entries without a usable secret are skipped with a warning.
+    pub fn from_config(cfg: &ApiKeysConfig, now: u64) -> Self {
+        let mut by_hash = HashMap::new();
+        for spec in &cfg.keys {
+            let hash = match (&spec.sha256, &spec.key_env, &spec.key) {
+                (Some(h), _, _) => Some(h.trim().to_ascii_lowercase()),
+                (None, Some(var), _) => std::env::var(var)
+                    .ok()
+                    .filter(|v| !v.is_empty())
+                    .map(|v| hash_key(&v)),
+                (None, None, Some(k)) => Some(hash_key(k)),
+                (None, None, None) => None,
+            };
+            let Some(hash) = hash.filter(|h| h.len() == 64) else {
+                tracing::warn!(key = %spec.id, "api key has no usable secret; skipped");
+                continue;
+            };
+            let limit = spec.rate_limit.unwrap_or(cfg.default_rate_limit);
+            by_hash.insert(
+                hash,
+                Key {
+                    id: spec.id.clone(),
+                    scopes: spec.scopes.clone(),
+                    limit,
+                    bucket: Mutex::new(Bucket {
+                        tokens: limit.burst,
+                        last: now,
+                    }),
+                },
+            );
+        }
+        Self {
+            enabled: true,
+            by_hash,
+        }
+    }
+
+    /// Load `path`. A missing file disables access control (with a warning) unless
+    /// `required`; an unreadable or invalid one, or a missing one that is `required`,
+    /// enables it with no keys (fail closed).
+    pub fn load_from_file<P: AsRef<Path>>(path: P, now: u64, required: bool) -> Self {
+        let path = path.as_ref();
+        let raw = match fs::read_to_string(path) {
+            Ok(raw) => raw,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound && !required => {
+                tracing::warn!(
+                    path = %path.display(),
+                    "API KEYS NOT CONFIGURED: every /api route is open to anyone; set {ENV_API_KEYS_REQUIRED}=1 to refuse instead"
+                );
+                return Self::disabled();
+            }
+            Err(e) => {
+                tracing::error!(path = %path.display(), error = %e, "api keys unreadable; all protected routes refused");
+                return Self::from_config(&ApiKeysConfig::default(), now);
+            }
+        };
+        match serde_json::from_str::<ApiKeysConfig>(&raw) {
+            Ok(cfg) => Self::from_config(&cfg, now),
+            Err(e) => {
+                tracing::error!(path = %path.display(), error = %e, "api keys invalid; all protected routes refused");
+                Self::from_config(&ApiKeysConfig::default(), now)
+            }
+        }
+    }
+
+    /// Load from `API_KEYS_PATH` (default `config/api_keys.json`); with
+    /// `API_KEYS_REQUIRED=1` a missing file fails closed.
+    pub fn from_env(now: u64) -> Self {
+        let path = std::env::var(ENV_API_KEYS_PATH)
+            .ok()
+            .filter(|s| !s.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_API_KEYS_PATH.to_string());
+        let required = std::env::var(ENV_API_KEYS_REQUIRED)
+            .map(|v| matches!(v.trim(), "1" | "true"))
+            .unwrap_or(false);
+        Self::load_from_file(path.trim(), now, required)
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Check `presented` for `scope` and take a token; `Ok` carries the key id
+    /// (`None` when access control is off).
+    pub fn authorize(
+        &self,
+        presented: Option<&str>,
+        scope: Scope,
+        now: u64,
+    ) -> Result<Option<String>, Rejection> {
+        if !self.enabled {
+            return Ok(None);
+        }
+        let key = presented
+            .map(|k| hash_key(k.trim()))
+            .and_then(|h| self.by_hash.get(&h))
+            .ok_or(Rejection::Unauthorized)?;
+        if !key.scopes.iter().any(|s| *s == scope || *s == Scope::Admin) {
+            return Err(Rejection::Forbidden {
+                key: key.id.clone(),
+                scope,
+            });
+        }
+
+        let rate = key.limit.per_minute / 60.0;
+        let mut b = key.bucket.lock().expect("rate bucket poisoned");
+        let elapsed = now.saturating_sub(b.last) as f64;
+        b.tokens = (b.tokens + elapsed * rate).min(key.limit.burst);
+        b.last = b.last.max(now);
+        if b.tokens < 1.0 {
+            let retry_after = if rate > 0.0 {
+                ((1.0 - b.tokens) / rate).ceil() as u64
+            } else {
+                60
+            };
+            return Err(Rejection::RateLimited {
+                key: key.id.clone(),
+                retry_after: retry_after.max(1),
+            });
+        }
+        b.tokens -= 1.0;
+        Ok(Some(key.id.clone()))
+    }
+}
+
+/// One audited admin call.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub ts_unix: u64,
+    /// Key id (`null` when access control is off).
+    pub key: Option<String>,
+    pub method: String,
+    pub path: String,
+    pub status: u16,
+}
+
+/// Audit lines queued for the writer thread; beyond that, lines are dropped (and
+/// logged) rather than holding up the request.
+pub const AUDIT_QUEUE: usize = 1024;
+
+enum AuditOp {
+    Line(Vec<u8>),
+    Flush(mpsc::SyncSender<()>),
+}
+
+/// Append-only JSONL log of admin calls. `record` only queues the line; one writer
+/// thread owns the file, so the middleware never waits on disk.
+#[derive(Debug)]
+pub struct AuditLog {
+    path: PathBuf,
+    tx: Option<mpsc::SyncSender<AuditOp>>,
+    writer: Option<JoinHandle<()>>,
+}
+
+impl AuditLog {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let (tx, rx) = mpsc::sync_channel(AUDIT_QUEUE);
+        let file = path.clone();
+        let writer = std::thread::Builder::new()
+            .name("admin-audit".into())
+            .spawn(move || write_audit(&file, rx))
+            .map_err(|e| tracing::error!(error = %e, "admin audit writer not started"))
+            .ok();
+        Self {
+            path,
+            tx: writer.as_ref().map(|_| tx),
+            writer,
+        }
+    }
+
+    /// `ADMIN_AUDIT_LOG`, or `state/admin_audit.jsonl`.
+    pub fn from_env() -> Self {
+        let path = std::env::var(ENV_AUDIT_LOG_PATH)
+            .ok()
+            .filter(|s| !s.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_AUDIT_LOG_PATH.to_string());
+        Self::new(path.trim())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Record `rec`; write errors are logged, never returned to handlers.
+    /// `rec.path` carries no query string, so `?api_key=` never reaches the log.
+    pub fn record(&self, rec: &AuditRecord) {
+        tracing::info!(
+            target: "audit",
+            key = rec.key.as_deref().unwrap_or("-"),
+            method = %rec.method,
+            path = %rec.path,
+            status = rec.status,
+            "admin call"
+        );
+        let mut line = match serde_json::to_vec(rec) {
+            Ok(line) => line,
+            Err(e) => {
+                tracing::warn!(error = %e, "admin audit record not serializable");
+                return;
+            }
+        };
+        line.push(b'\n');
+        let sent = self
+            .tx
+            .as_ref()
+            .is_some_and(|tx| tx.try_send(AuditOp::Line(line)).is_ok());
+        if !sent {
+            tracing::warn!(path = %self.path.display(), "admin audit queue full or writer gone; record dropped");
+        }
+    }
+
+    /// Wait until every queued record is written.
+    pub fn flush(&self) {
+        let Some(tx) = &self.tx else { return };
+        let (done, wait) = mpsc::sync_channel(1);
+        if tx.send(AuditOp::Flush(done)).is_ok() {
+            let _ = wait.recv();
+        }
+    }
+}
+
+impl Drop for AuditLog {
+    /// Close the queue and let the writer drain it.
+    fn drop(&mut self) {
+        self.tx.take();
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+fn write_audit(path: &Path, rx: mpsc::Receiver<AuditOp>) {
+    for op in rx {
+        match op {
+            AuditOp::Line(line) => {
+                let res = (|| -> std::io::Result<()> {
+                    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+                        fs::create_dir_all(dir)?;
+                    }
+                    OpenOptions::new()
+                        .create(true)
+                        .append(true)
+                        .open(path)?
+                        .write_all(&line)
+                })();
+                if let Err(e) = res {
+                    tracing::warn!(path = %path.display(), error = %e, "admin audit write failed");
+                }
+            }
+            AuditOp::Flush(done) => {
+                let _ = done.send(());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(rate: RateLimit) -> ApiKeys {
+        let cfg = ApiKeysConfig {
+            default_rate_limit: rate,
+            keys: vec![
+                KeySpec {
+                    id: "reader".into(),
+                    key: Some("r-secret".into()),
+                    key_env: None,
+                    sha256: None,
+                    scopes: vec![Scope::Read],
+                    rate_limit: None,
+                },
+                KeySpec {
+                    id: "ops".into(),
+                    key: None,
+                    key_env: None,
+                    sha256: Some(hash_key("o-secret").to_uppercase()),
+                    scopes: vec![Scope::Admin],
+                    rate_limit: None,
+                },
+                KeySpec {
+                    id: "broken".into(),
+                    key: None,
+                    key_env: Some("AUTH_TEST_UNSET_VAR".into()),
+                    sha256: None,
+                    scopes: vec![Scope::Admin],
+                    rate_limit: None,
+                },
+            ],
+        };
+        ApiKeys::from_config(&cfg, 1000)
+    }
+
+    #[test]
+    fn routes_map_to_scopes() {
+        assert_eq!(required_scope("GET", "/health"), None);
+        assert_eq!(required_scope("OPTIONS", "/decide"), None);
+        assert_eq!(required_scope("GET", "/decide"), Some(Scope::Read));
+        assert_eq!(required_scope("POST", "/decide"), Some(Scope::Decide));
+        assert_eq!(required_scope("GET", "/export"), Some(Scope::Read));
+        assert_eq!(
+            required_scope("GET", "/admin/reload-source-weights"),
+            Some(Scope::Admin)
+        );
+        assert_eq!(required_scope("GET", "/debug/history"), Some(Scope::Admin));
+        assert_eq!(
+            required_scope("DELETE", "/subscriptions/sub_1"),
+            Some(Scope::Webhooks)
+        );
+    }
+
+    #[test]
+    fn scopes_and_secrets_are_checked() {
+        let k = keys(RateLimit::default());
+        assert_eq!(
+            k.authorize(Some("r-secret"), Scope::Read, 1000),
+            Ok(Some("reader".into()))
+        );
+        assert_eq!(
+            k.authorize(Some("r-secret"), Scope::Decide, 1000),
+            Err(Rejection::Forbidden {
+                key: "reader".into(),
+                scope: Scope::Decide
+            })
+        );
+        // Admin grants every scope; the hash is matched case-insensitively.
+        assert!(k.authorize(Some("o-secret"), Scope::Decide, 1000).is_ok());
+        assert_eq!(
+            k.authorize(Some("nope"), Scope::Read, 1000),
+            Err(Rejection::Unauthorized)
+        );
+        assert_eq!(
+            k.authorize(None, Scope::Read, 1000),
+            Err(Rejection::Unauthorized)
+        );
+        assert_eq!(k.by_hash.len(), 2, "key without a secret is skipped");
+        assert_eq!(
+            ApiKeys::disabled().authorize(None, Scope::Admin, 0),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn token_bucket_limits_and_refills() {
+        let k = keys(RateLimit {
+            per_minute: 60.0,
+            burst: 2.0,
+        });
+        assert!(k.authorize(Some("r-secret"), Scope::Read, 1000).is_ok());
+        assert!(k.authorize(Some("r-secret"), Scope::Read, 1000).is_ok());
+        assert_eq!(
+            k.authorize(Some("r-secret"), Scope::Read, 1000),
+            Err(Rejection::RateLimited {
+                key: "reader".into(),
+                retry_after: 1
+            })
+        );
+        // Other keys have their own bucket.
+        assert!(k.authorize(Some("o-secret"), Scope::Read, 1000).is_ok());
+        // One token per second at 60/min, capped at `burst`.
+        assert!(k.authorize(Some("r-secret"), Scope::Read, 1001).is_ok());
+        assert!(k.authorize(Some("r-secret"), Scope::Read, 1001).is_err());
+        assert!(k.authorize(Some("r-secret"), Scope::Read, 1100).is_ok());
+        assert!(k.authorize(Some("r-secret"), Scope::Read, 1100).is_ok());
+        assert!(k.authorize(Some("r-secret"), Scope::Read, 1100).is_err());
+    }
+
+    #[test]
+    fn missing_file_disables_unless_required_and_invalid_file_fails_closed() {
+        let dir = std::env::temp_dir().join(format!("auth_keys_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        assert!(!ApiKeys::load_from_file(dir.join("missing.json"), 0, false).enabled());
+        let k = ApiKeys::load_from_file(dir.join("missing.json"), 0, true);
+        assert!(k.enabled());
+        assert_eq!(
+            k.authorize(Some("anything"), Scope::Read, 0),
+            Err(Rejection::Unauthorized)
+        );
+
+        let bad = dir.join("bad.json");
+        fs::write(&bad, "{ not json").unwrap();
+        let k = ApiKeys::load_from_file(&bad, 0, false);
+        assert!(k.enabled());
+        assert_eq!(
+            k.authorize(Some("anything"), Scope::Read, 0),
+            Err(Rejection::Unauthorized)
+        );
+
+        let log = AuditLog::new(dir.join("audit/admin.jsonl"));
+        log.record(&AuditRecord {
+            ts_unix: 5,
+            key: Some("ops".into()),
+            method: "POST".into(),
+            path: "/admin/reload-source-weights".into(),
+            status: 200,
+        });
+        log.flush();
+        let raw = fs::read_to_string(log.path()).unwrap();
+        let rec: AuditRecord = serde_json::from_str(raw.trim()).unwrap();
+        assert_eq!(rec.key.as_deref(), Some("ops"));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,616 @@
+//! # Backtest
+//! Offline replay of time-stamped statements through the `/decide` pipeline
+//! (`decide::Scorer` / `decide::Finisher`, the same stages the handler runs) on a
+//! simulated clock, joined against OHLC price bars.
+//!
+//! Inputs:
+//! - statements JSONL, one object per line:
+//!   `{"ts_unix": 1757000000, "source": "Fed", "text": "..."}`
+//!   (`ts` as RFC3339 string is accepted instead of `ts_unix`)
+//! - OHLC CSV: `ts,open,high,low,close[,volume]` with a header row; `ts` is unix
+//!   seconds or RFC3339 / `YYYY-MM-DD HH:MM:SS` (UTC).
+//!
+//! The AI hint is never called during replay so runs stay deterministic. Each
+//! decision posts the lookback window as one batch, like a client re-sending its
+//! recent feed, so anti-spam works per batch exactly as in `/decide`
+//! (across batches only with `PIPELINE_ANTISPAM_ACROSS_REQUESTS=1`).
+//!
+//! Fills use the open of the first bar starting at or after a timestamp, so a
+//! decision never sees the bar it is made in (no look-ahead).
+//!
+//! Metrics (`BacktestReport`):
+//! - hit rate and mean forward return per horizon (directional verdicts only),
+//! - turnover (sum of |Δposition|, position = +1 BUY / 0 HOLD / −1 SELL),
+//! - equity curve over bar opens (position held until the next decision),
+//!   total return and max drawdown.
+
+use std::{fs, path::Path};
+
+use anyhow::{anyhow, bail, Context};
+use chrono::{DateTime, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::analyze;
+use crate::analyze::antispam::{AntiSpam, AntiSpamParams};
+use crate::analyze::pipeline::PipelineToggles;
+use crate::analyze::rules::RuleSet;
+use crate::analyze::weights::{HotReloadWeights, Weights};
+use crate::calendar;
+use crate::calibration::{Calibration, LabeledOutcome};
+use crate::decide::{DecideInput, Finisher, Scorer};
+use crate::decision::Verdict;
+use crate::disruption::TRIGGER_MAX_AGE_SECS;
+use crate::events::EventCalendar;
+use crate::grade::{GradeScale, SignalGrade};
+use crate::history::History;
+use crate::relevance::RelevanceHandle;
+use crate::sentiment::SentimentAnalyzer;
+use crate::source_weights::SourceWeightsConfig;
+use crate::stabilizer::{StabilizerParams, VerdictStabilizer};
+
+/// Default forward-return horizons: 5 min, 15 min, 1 h.
+pub const DEFAULT_HORIZONS_SECS: [u64; 3] = [300, 900, 3600];
+
+/// One historical statement to replay.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReplayStatement {
+    pub ts_unix: u64,
+    pub source: String,
+    pub text: String,
+}
+
+#[derive(Deserialize)]
+struct RawStatement {
+    #[serde(default)]
+    ts_unix: Option<u64>,
+    #[serde(default)]
+    ts: Option<String>,
+    source: String,
+    text: String,
+}
+
+/// One OHLC bar (timestamp = bar open, UTC seconds).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct PriceBar {
+    pub ts_unix: u64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+}
+
+/// Decision produced at a simulated point in time.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayDecision {
+    pub ts_unix: u64,
+    pub verdict: Verdict,
+    pub confidence: f32,
+    pub signal: SignalGrade,
+    /// Raw verdict when the stabilizer held it back.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_verdict: Option<Verdict>,
+    /// Uncalibrated confidence when a calibration map changed it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_confidence: Option<f32>,
+}
+
+/// Forward-return statistics for one horizon.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HorizonStats {
+    pub horizon_secs: u64,
+    /// Directional decisions with price coverage at `t + horizon`.
+    pub samples: usize,
+    pub hit_rate: Option<f64>,
+    /// Mean signed return (fraction, position-adjusted).
+    pub mean_return: Option<f64>,
+    /// Mean signed move in index points.
+    pub mean_points: Option<f64>,
+}
+
+/// Summary of a backtest run.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BacktestReport {
+    pub decisions: usize,
+    pub directional: usize,
+    /// Hit rate at the first (shortest) horizon.
+    pub hit_rate: Option<f64>,
+    pub horizons: Vec<HorizonStats>,
+    pub position_changes: usize,
+    pub turnover: f64,
+    pub total_return: f64,
+    pub max_drawdown: f64,
+}
+
+/// Load statements from JSONL (blank lines and `#` comments are skipped).
+/// The result is sorted by timestamp.
+pub fn load_statements<P: AsRef<Path>>(path: P) -> anyhow::Result<Vec<ReplayStatement>> {
+    let path = path.as_ref();
+    let raw = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+    parse_statements(&raw)
+}
+
+/// Parse statements from JSONL text. See [`load_statements`].
+pub fn parse_statements(raw: &str) -> anyhow::Result<Vec<ReplayStatement>> {
+    let mut out = Vec::new();
+    for (i, line) in raw.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let r: RawStatement =
+            serde_json::from_str(line).with_context(|| format!("statements line {}", i + 1))?;
+        let ts_unix = match (r.ts_unix, r.ts.as_deref()) {
+            (Some(ts), _) => ts,
+            (None, Some(s)) => parse_ts(s).with_context(|| format!("statements line {}", i + 1))?,
+            (None, None) => bail!("statements line {}: missing ts_unix/ts", i + 1),
+        };
+        out.push(ReplayStatement {
+            ts_unix,
+            source: r.source,
+            text: r.text,
+        });
+    }
+    out.sort_by_key(|s| s.ts_unix);
+    Ok(out)
+}
+
+/// Load OHLC bars from CSV. The result is sorted by timestamp.
+pub fn load_bars<P: AsRef<Path>>(path: P) -> anyhow::Result<Vec<PriceBar>> {
+    let path = path.as_ref();
+    let raw = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+    parse_bars(&raw)
+}
+
+/// Parse OHLC bars from CSV text. See [`load_bars`].
+pub fn parse_bars(raw: &str) -> anyhow::Result<Vec<PriceBar>> {
+    let mut out = Vec::new();
+    for (i, line) in raw.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let cols: Vec<&str> = line.split(',').map(str::trim).collect();
+        if cols.len() < 5 {
+            bail!("prices line {}: expected ts,open,high,low,close", i + 1);
+        }
+        // Header row: first field is not a timestamp.
+        if out.is_empty() && cols[1].parse::<f64>().is_err() {
+            continue;
+        }
+        let num = |j: usize| -> anyhow::Result<f64> {
+            cols[j]
+                .parse::<f64>()
+                .with_context(|| format!("prices line {} column {}", i + 1, j + 1))
+        };
+        out.push(PriceBar {
+            ts_unix: parse_ts(cols[0]).with_context(|| format!("prices line {}", i + 1))?,
+            open: num(1)?,
+            high: num(2)?,
+            low: num(3)?,
+            close: num(4)?,
+        });
+    }
+    out.sort_by_key(|b| b.ts_unix);
+    Ok(out)
+}
+
+/// Unix seconds, RFC3339 or `YYYY-MM-DD HH:MM:SS` (UTC).
+fn parse_ts(s: &str) -> anyhow::Result<u64> {
+    let s = s.trim();
+    if let Ok(n) = s.parse::<u64>() {
+        return Ok(n);
+    }
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return u64::try_from(dt.timestamp()).map_err(|_| anyhow!("timestamp before epoch: {s}"));
+    }
+    let naive = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S")
+        .map_err(|_| anyhow!("unrecognized timestamp: {s}"))?;
+    u64::try_from(naive.and_utc().timestamp()).map_err(|_| anyhow!("timestamp before epoch: {s}"))
+}
+
+/// Replays statements through the decision pipeline with a simulated clock.
+///
+/// At each statement timestamp `t`, all statements in `(t - lookback, t]` form
+/// the `/decide` batch, i.e. the same view a poller posting the recent feed
+/// would have had at that moment.
+pub struct Backtester {
+    analyzer: SentimentAnalyzer,
+    relevance: RelevanceHandle,
+    source_weights: SourceWeightsConfig,
+    stabilizer: StabilizerParams,
+    calibration: Calibration,
+    grades: GradeScale,
+    events: EventCalendar,
+    pipeline: PipelineToggles,
+    rules: RuleSet,
+    weights: Weights,
+    carry_overnight: bool,
+    lookback_secs: u64,
+}
+
+impl Backtester {
+    /// Defaults match an API started without config files: all pipeline stages on,
+    /// no events, rules or calibrated weights, overnight carry-over on.
+    pub fn new(relevance: RelevanceHandle, source_weights: SourceWeightsConfig) -> Self {
+        Self {
+            analyzer: SentimentAnalyzer::new(),
+            relevance,
+            source_weights,
+            stabilizer: StabilizerParams::default(),
+            calibration: Calibration::identity(),
+            grades: GradeScale::default(),
+            events: EventCalendar::default(),
+            pipeline: PipelineToggles::default(),
+            rules: RuleSet::default(),
+            weights: Weights::default(),
+            carry_overnight: true,
+            lookback_secs: TRIGGER_MAX_AGE_SECS,
+        }
+    }
+
+    /// Every stage configured from the same env vars and config files as the API.
+    pub fn from_env(relevance: RelevanceHandle, source_weights: SourceWeightsConfig) -> Self {
+        Self::new(relevance, source_weights)
+            .stabilizer(StabilizerParams::from_env())
+            .calibration(Calibration::from_env())
+            .grades(GradeScale::from_env())
+            .events(EventCalendar::from_env())
+            .pipeline(PipelineToggles::from_env())
+            .rules(analyze::current_rules().as_ref().clone())
+            .weights(HotReloadWeights::from_env().current())
+            .carry_overnight(calendar::carry_overnight_from_env())
+    }
+
+    pub fn stabilizer(mut self, params: StabilizerParams) -> Self {
+        self.stabilizer = params;
+        self
+    }
+
+    pub fn calibration(mut self, calibration: Calibration) -> Self {
+        self.calibration = calibration;
+        self
+    }
+
+    pub fn grades(mut self, grades: GradeScale) -> Self {
+        self.grades = grades;
+        self
+    }
+
+    pub fn events(mut self, events: EventCalendar) -> Self {
+        self.events = events;
+        self
+    }
+
+    pub fn pipeline(mut self, toggles: PipelineToggles) -> Self {
+        self.pipeline = toggles;
+        self
+    }
+
+    pub fn rules(mut self, rules: RuleSet) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    pub fn weights(mut self, weights: Weights) -> Self {
+        self.weights = weights;
+        self
+    }
+
+    pub fn carry_overnight(mut self, on: bool) -> Self {
+        self.carry_overnight = on;
+        self
+    }
+
+    pub fn lookback_secs(mut self, secs: u64) -> Self {
+        self.lookback_secs = secs.max(1);
+        self
+    }
+
+    /// Run the replay; `statements` must be sorted by `ts_unix`.
+    pub fn replay(&self, statements: &[ReplayStatement]) -> Vec<ReplayDecision> {
+        let history = History::with_capacity(2000);
+        let stabilizer = VerdictStabilizer::new(self.stabilizer);
+        let scorer = Scorer {
+            analyzer: &self.analyzer,
+            relevance: &self.relevance,
+            source_weights: &self.source_weights,
+            events: &self.events,
+            carry_overnight: self.carry_overnight,
+            toggles: self.pipeline,
+            rules: &self.rules,
+        };
+        let finisher = Finisher {
+            history: &history,
+            stabilizer: &stabilizer,
+            calibration: &self.calibration,
+            grades: &self.grades,
+            weights: &self.weights,
+            toggles: self.pipeline,
+        };
+        // Only consulted with `antispam_across_requests`, like the router's window.
+        let mut antispam = AntiSpam::new(AntiSpamParams::default());
+        let mut out = Vec::with_capacity(statements.len());
+        let mut start = 0usize;
+
+        for (end, st) in statements.iter().enumerate() {
+            let now = st.ts_unix;
+            // Several statements with the same timestamp are decided once.
+            if statements.get(end + 1).is_some_and(|n| n.ts_unix == now) {
+                continue;
+            }
+            while statements[start].ts_unix + self.lookback_secs <= now {
+                start += 1;
+            }
+
+            let inputs = statements[start..=end]
+                .iter()
+                .map(|it| DecideInput {
+                    source: it.source.clone(),
+                    text: it.text.clone(),
+                    ts_unix: Some(it.ts_unix),
+                    url: None,
+                })
+                .collect();
+            let window = self
+                .pipeline
+                .antispam_across_requests
+                .then_some(&mut antispam);
+            let scored = scorer.score(inputs, window, now);
+            let (decision, _report) = finisher.finish(scored, None, now);
+            history.push_at(&decision, now);
+
+            out.push(ReplayDecision {
+                ts_unix: now,
+                verdict: decision.decision,
+                confidence: decision.confidence,
+                signal: decision
+                    .signal
+                    .unwrap_or_else(|| self.grades.grade(decision.decision, decision.confidence)),
+                raw_verdict: decision.raw_decision,
+                raw_confidence: decision.raw_confidence,
+            });
+        }
+        out
+    }
+}
+
+fn position(v: Verdict) -> f64 {
+    match v {
+        Verdict::Buy => 1.0,
+        Verdict::Sell => -1.0,
+        Verdict::Hold => 0.0,
+    }
+}
+
+/// Open of the first bar starting at or after `ts`.
+fn open_at_or_after(bars: &[PriceBar], ts: u64) -> Option<f64> {
+    let idx = bars.partition_point(|b| b.ts_unix < ts);
+    bars.get(idx).map(|b| b.open)
+}
+
+/// Join decisions against bars. Both slices must be sorted by timestamp.
+///
+/// A forward return at horizon `h` is only counted when some bar starts at or
+/// after `t + h`; otherwise the sample is skipped.
+pub fn evaluate(
+    decisions: &[ReplayDecision],
+    bars: &[PriceBar],
+    horizons_secs: &[u64],
+) -> BacktestReport {
+    let horizons: Vec<HorizonStats> = horizons_secs
+        .iter()
+        .map(|&h| {
+            let (mut n, mut hits, mut sum_ret, mut sum_pts) = (0usize, 0usize, 0.0f64, 0.0f64);
+            for d in decisions.iter().filter(|d| d.verdict != Verdict::Hold) {
+                let (Some(entry), Some(exit)) = (
+                    open_at_or_after(bars, d.ts_unix),
+                    open_at_or_after(bars, d.ts_unix + h),
+                ) else {
+                    continue;
+                };
+                if entry <= 0.0 {
+                    continue;
+                }
+                let pos = position(d.verdict);
+                let ret = pos * (exit / entry - 1.0);
+                n += 1;
+                if ret > 0.0 {
+                    hits += 1;
+                }
+                sum_ret += ret;
+                sum_pts += pos * (exit - entry);
+            }
+            let avg = |x: f64| (n > 0).then(|| x / n as f64);
+            HorizonStats {
+                horizon_secs: h,
+                samples: n,
+                hit_rate: avg(hits as f64),
+                mean_return: avg(sum_ret),
+                mean_points: avg(sum_pts),
+            }
+        })
+        .collect();
+
+    // Position path: each decision sets the position until the next one.
+    let mut position_changes = 0usize;
+    let mut turnover = 0.0f64;
+    let mut prev = 0.0f64;
+    for d in decisions {
+        let p = position(d.verdict);
+        if p != prev {
+            position_changes += 1;
+            turnover += (p - prev).abs();
+            prev = p;
+        }
+    }
+
+    // Equity curve over bar opens; a decision applies from the next bar open.
+    let mut equity = 1.0f64;
+    let mut peak = 1.0f64;
+    let mut max_drawdown = 0.0f64;
+    let mut di = 0usize;
+    let mut pos = 0.0f64;
+    for w in bars.windows(2) {
+        let (a, b) = (w[0], w[1]);
+        while di < decisions.len() && decisions[di].ts_unix <= a.ts_unix {
+            pos = position(decisions[di].verdict);
+            di += 1;
+        }
+        if a.open > 0.0 {
+            equity *= 1.0 + pos * (b.open / a.open - 1.0);
+        }
+        peak = peak.max(equity);
+        if peak > 0.0 {
+            max_drawdown = max_drawdown.max((peak - equity) / peak);
+        }
+    }
+
+    BacktestReport {
+        decisions: decisions.len(),
+        directional: decisions
+            .iter()
+            .filter(|d| d.verdict != Verdict::Hold)
+            .count(),
+        hit_rate: horizons.first().and_then(|h| h.hit_rate),
+        horizons,
+        position_changes,
+        turnover,
+        total_return: equity - 1.0,
+        max_drawdown,
+    }
+}
+
+/// Label directional decisions for calibration: a decision is correct when its
+/// forward return at `horizon_secs` is positive (same fills as `evaluate`).
+/// Uses the uncalibrated confidence, so a map can be refitted from a replay
+/// that already applied one.
+pub fn label_outcomes(
+    decisions: &[ReplayDecision],
+    bars: &[PriceBar],
+    horizon_secs: u64,
+) -> Vec<LabeledOutcome> {
+    decisions
+        .iter()
+        .filter(|d| d.verdict != Verdict::Hold)
+        .filter_map(|d| {
+            let entry = open_at_or_after(bars, d.ts_unix)?;
+            let exit = open_at_or_after(bars, d.ts_unix + horizon_secs)?;
+            (entry > 0.0).then(|| LabeledOutcome {
+                confidence: d.raw_confidence.unwrap_or(d.confidence),
+                correct: position(d.verdict) * (exit - entry) > 0.0,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar(ts: u64, close: f64) -> PriceBar {
+        PriceBar {
+            ts_unix: ts,
+            open: close,
+            high: close,
+            low: close,
+            close,
+        }
+    }
+
+    fn dec(ts: u64, verdict: Verdict) -> ReplayDecision {
+        ReplayDecision {
+            ts_unix: ts,
+            verdict,
+            confidence: 0.8,
+            signal: GradeScale::default().grade(verdict, 0.8),
+            raw_verdict: None,
+            raw_confidence: None,
+        }
+    }
+
+    #[test]
+    fn parses_bars_with_header_and_mixed_timestamps() {
+        let csv = "ts,open,high,low,close,volume\n\
+                   2025-09-01T13:31:00Z,45001,45010,44990,45005,10\n\
+                   1756733400,45000,45010,44990,45002,12\n";
+        let bars = parse_bars(csv).unwrap();
+        assert_eq!(bars.len(), 2);
+        assert_eq!(bars[0].ts_unix, 1_756_733_400);
+        assert_eq!(bars[1].ts_unix, 1_756_733_460);
+        assert_eq!(bars[1].close, 45005.0);
+    }
+
+    #[test]
+    fn parses_statements_with_rfc3339_ts() {
+        let jsonl = "{\"ts\":\"2025-09-01T13:30:00Z\",\"source\":\"Fed\",\"text\":\"a\"}\n\n\
+                     {\"ts_unix\":1,\"source\":\"Trump\",\"text\":\"b\"}\n";
+        let st = parse_statements(jsonl).unwrap();
+        assert_eq!(st[0].ts_unix, 1);
+        assert_eq!(st[1].ts_unix, 1_756_733_400);
+        assert!(parse_statements("{\"source\":\"x\",\"text\":\"y\"}").is_err());
+    }
+
+    #[test]
+    fn forward_returns_hit_rate_and_drawdown() {
+        let bars = vec![
+            bar(0, 100.0),
+            bar(60, 101.0),
+            bar(120, 102.0),
+            bar(180, 99.0),
+            bar(240, 98.0),
+        ];
+        let decisions = vec![dec(0, Verdict::Buy), dec(120, Verdict::Sell)];
+        let r = evaluate(&decisions, &bars, &[60, 600]);
+
+        let h1 = &r.horizons[0];
+        assert_eq!(h1.samples, 2);
+        assert_eq!(h1.hit_rate, Some(1.0));
+        assert!((h1.mean_points.unwrap() - 2.0).abs() < 1e-9);
+        // 600s horizon is beyond the last bar → no samples.
+        assert_eq!(r.horizons[1].samples, 0);
+        assert_eq!(r.horizons[1].hit_rate, None);
+
+        assert_eq!(r.position_changes, 2);
+        assert!((r.turnover - 3.0).abs() < 1e-9);
+        // Long 100→102, then short 102→99→98 (compounded per bar).
+        let expected = 1.02 * (1.0 + 3.0 / 102.0) * (1.0 + 1.0 / 99.0) - 1.0;
+        assert!((r.total_return - expected).abs() < 1e-9);
+        assert!(r.total_return > 0.0);
+    }
+
+    #[test]
+    fn labels_outcomes_with_raw_confidence() {
+        let bars = vec![bar(0, 100.0), bar(60, 101.0), bar(120, 102.0)];
+        let mut buy = dec(0, Verdict::Buy);
+        buy.confidence = 0.6;
+        buy.raw_confidence = Some(0.9);
+        let decisions = vec![buy, dec(60, Verdict::Sell), dec(120, Verdict::Hold)];
+
+        let labeled = label_outcomes(&decisions, &bars, 60);
+        assert_eq!(
+            labeled,
+            vec![
+                LabeledOutcome {
+                    confidence: 0.9,
+                    correct: true
+                },
+                LabeledOutcome {
+                    confidence: 0.8,
+                    correct: false
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn drawdown_tracks_peak_to_trough() {
+        let bars = vec![
+            bar(0, 100.0),
+            bar(60, 110.0),
+            bar(120, 99.0),
+            bar(180, 104.5),
+        ];
+        let r = evaluate(&[dec(0, Verdict::Buy)], &bars, &[60]);
+        assert!((r.max_drawdown - 0.1).abs() < 1e-9);
+    }
+}
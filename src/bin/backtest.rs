@@ -0,0 +1,104 @@
+//! Offline backtest: replay a statements JSONL through the decision pipeline and
+//! score the verdicts against an OHLC CSV (e.g. YM futures).
+//!
+//! ```text
+//! cargo run --bin backtest -- --statements data/statements.jsonl --prices data/ym_1m.csv \
+//!     [--horizons 300,900,3600] [--lookback 1800] [--weights source_weights.json] \
+//!     [--decisions-out state/backtest_decisions.jsonl]
+//! ```
+//! Relevance config comes from `RELEVANCE_CONFIG_PATH`; stabilizer, calibration,
+//! grades, event calendar, pipeline stages, rules and calibrated weights from the
+//! same env vars and files as the API. The report is printed as JSON.
+
+use std::io::Write;
+
+use anyhow::{bail, Context};
+use dow_sentiment_analyzer::backtest::{self, Backtester, DEFAULT_HORIZONS_SECS};
+use dow_sentiment_analyzer::relevance::{RelevanceEngine, RelevanceHandle};
+use dow_sentiment_analyzer::source_weights::SourceWeightsConfig;
+
+struct Args {
+    statements: String,
+    prices: String,
+    horizons: Vec<u64>,
+    lookback: Option<u64>,
+    weights: String,
+    decisions_out: Option<String>,
+}
+
+fn usage() -> &'static str {
+    "usage: backtest --statements <jsonl> --prices <csv> [--horizons 300,900,3600] \
+     [--lookback <secs>] [--weights <json>] [--decisions-out <jsonl>]"
+}
+
+fn parse_args() -> anyhow::Result<Args> {
+    let mut statements = None;
+    let mut prices = None;
+    let mut horizons = DEFAULT_HORIZONS_SECS.to_vec();
+    let mut lookback = None;
+    let mut weights = "source_weights.json".to_string();
+    let mut decisions_out = None;
+
+    let mut it = std::env::args().skip(1);
+    while let Some(flag) = it.next() {
+        let mut value = || it.next().with_context(|| format!("{flag} needs a value"));
+        match flag.as_str() {
+            "--statements" => statements = Some(value()?),
+            "--prices" => prices = Some(value()?),
+            "--horizons" => {
+                horizons = value()?
+                    .split(',')
+                    .map(|h| h.trim().parse::<u64>())
+                    .collect::<Result<_, _>>()
+                    .context("--horizons expects comma-separated seconds")?;
+            }
+            "--lookback" => {
+                lookback = Some(value()?.parse().context("--lookback expects seconds")?)
+            }
+            "--weights" => weights = value()?,
+            "--decisions-out" => decisions_out = Some(value()?),
+            "-h" | "--help" => {
+                println!("{}", usage());
+                std::process::exit(0);
+            }
+            other => bail!("unknown argument {other}\n{}", usage()),
+        }
+    }
+
+    Ok(Args {
+        statements: statements.with_context(usage)?,
+        prices: prices.with_context(usage)?,
+        horizons,
+        lookback,
+        weights,
+        decisions_out,
+    })
+}
+
+fn main() -> anyhow::Result<()> {
+    let _ = dotenvy::dotenv();
+    tracing_subscriber::fmt().with_target(false).init();
+    let args = parse_args()?;
+
+    let statements = backtest::load_statements(&args.statements)?;
+    let bars = backtest::load_bars(&args.prices)?;
+    let relevance = RelevanceHandle::new(RelevanceEngine::from_toml()?);
+    let weights = SourceWeightsConfig::load_from_file(&args.weights);
+
+    let mut bt = Backtester::from_env(relevance, weights);
+    if let Some(secs) = args.lookback {
+        bt = bt.lookback_secs(secs);
+    }
+
+    let decisions = bt.replay(&statements);
+    if let Some(path) = &args.decisions_out {
+        let mut f = std::fs::File::create(path).with_context(|| format!("create {path}"))?;
+        for d in &decisions {
+            writeln!(f, "{}", serde_json::to_string(d)?)?;
+        }
+    }
+
+    let report = backtest::evaluate(&decisions, &bars, &args.horizons);
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
@@ -0,0 +1,139 @@
+//! Fit a confidence calibration map from labeled outcomes and export
+//! reliability-diagram data.
+//!
+//! ```text
+//! cargo run --bin calibrate -- --outcomes data/outcomes.jsonl \
+//!     [--method isotonic|platt] [--bins 10] [--out config/calibration.json] \
+//!     [--reliability-out state/reliability.csv]
+//!
+//! # or label a backtest replay (`backtest --decisions-out`) against prices:
+//! cargo run --bin calibrate -- --decisions state/backtest_decisions.jsonl \
+//!     --prices data/ym_1m.csv [--horizon 900] ...
+//! ```
+//! Outcomes JSONL: `{"confidence": 0.75, "correct": true}` per line. The
+//! report (reliability before/after, ECE, Brier) is printed as JSON;
+//! `--reliability-out` writes the same bins as CSV, or JSON for `*.json`.
+
+use std::path::Path;
+
+use anyhow::{bail, Context};
+use dow_sentiment_analyzer::backtest::{self, ReplayDecision};
+use dow_sentiment_analyzer::calibration::{
+    self, Calibration, LabeledOutcome, Method, DEFAULT_CALIBRATION_PATH,
+};
+use serde_json::json;
+
+struct Args {
+    outcomes: Option<String>,
+    decisions: Option<String>,
+    prices: Option<String>,
+    horizon: u64,
+    method: Method,
+    bins: usize,
+    out: String,
+    reliability_out: Option<String>,
+}
+
+fn usage() -> &'static str {
+    "usage: calibrate (--outcomes <jsonl> | --decisions <jsonl> --prices <csv> [--horizon <secs>]) \
+     [--method isotonic|platt] [--bins 10] [--out <json>] [--reliability-out <csv|json>]"
+}
+
+fn parse_args() -> anyhow::Result<Args> {
+    let mut args = Args {
+        outcomes: None,
+        decisions: None,
+        prices: None,
+        horizon: 900,
+        method: Method::Isotonic,
+        bins: 10,
+        out: DEFAULT_CALIBRATION_PATH.to_string(),
+        reliability_out: None,
+    };
+
+    let mut it = std::env::args().skip(1);
+    while let Some(flag) = it.next() {
+        let mut value = || it.next().with_context(|| format!("{flag} needs a value"));
+        match flag.as_str() {
+            "--outcomes" => args.outcomes = Some(value()?),
+            "--decisions" => args.decisions = Some(value()?),
+            "--prices" => args.prices = Some(value()?),
+            "--horizon" => args.horizon = value()?.parse().context("--horizon expects seconds")?,
+            "--method" => {
+                let v = value()?;
+                args.method = Method::parse(&v)
+                    .with_context(|| format!("--method expects isotonic|platt, got {v}"))?;
+            }
+            "--bins" => args.bins = value()?.parse().context("--bins expects a count")?,
+            "--out" => args.out = value()?,
+            "--reliability-out" => args.reliability_out = Some(value()?),
+            "-h" | "--help" => {
+                println!("{}", usage());
+                std::process::exit(0);
+            }
+            other => bail!("unknown argument {other}\n{}", usage()),
+        }
+    }
+    Ok(args)
+}
+
+fn load_decisions(path: &str) -> anyhow::Result<Vec<ReplayDecision>> {
+    let raw = std::fs::read_to_string(path).with_context(|| format!("read {path}"))?;
+    raw.lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty())
+        .map(|(i, l)| serde_json::from_str(l).with_context(|| format!("{path}:{}", i + 1)))
+        .collect()
+}
+
+fn load_outcomes(args: &Args) -> anyhow::Result<Vec<LabeledOutcome>> {
+    match (&args.outcomes, &args.decisions, &args.prices) {
+        (Some(path), None, _) => calibration::load_outcomes(path),
+        (None, Some(decisions), Some(prices)) => {
+            let decisions = load_decisions(decisions)?;
+            let bars = backtest::load_bars(prices)?;
+            Ok(backtest::label_outcomes(&decisions, &bars, args.horizon))
+        }
+        _ => bail!("{}", usage()),
+    }
+}
+
+fn main() -> anyhow::Result<()> {
+    let _ = dotenvy::dotenv();
+    tracing_subscriber::fmt().with_target(false).init();
+    let args = parse_args()?;
+
+    let outcomes = load_outcomes(&args)?;
+    if outcomes.is_empty() {
+        bail!("no labeled outcomes");
+    }
+
+    let cal = Calibration::fit(args.method, &outcomes);
+    let before = calibration::reliability(&outcomes, args.bins, None);
+    let after = calibration::reliability(&outcomes, args.bins, Some(&cal));
+
+    std::fs::write(&args.out, serde_json::to_string_pretty(&cal)? + "\n")
+        .with_context(|| format!("write {}", args.out))?;
+
+    if let Some(path) = &args.reliability_out {
+        let is_json = Path::new(path)
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("json"));
+        let body = if is_json {
+            serde_json::to_string_pretty(&json!({ "before": before, "after": after }))?
+        } else {
+            calibration::reliability_csv(&[("raw", &before), ("calibrated", &after)])
+        };
+        std::fs::write(path, body).with_context(|| format!("write {path}"))?;
+    }
+
+    let report = json!({
+        "method": args.method.as_str(),
+        "samples": outcomes.len(),
+        "out": args.out,
+        "before": before,
+        "after": after,
+    });
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
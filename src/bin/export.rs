@@ -0,0 +1,108 @@
+//! Export the durable decision history as CSV or NDJSON (same schema as `/api/export`).
+//!
+//! ```text
+//! cargo run --bin export -- [--store state/history] [--kind decisions|contributors] \
+//!     [--format csv|ndjson] [--from <unix|rfc3339>] [--to <unix|rfc3339>] \
+//!     [--instrument YM] [--gzip] [--out decisions.csv.gz]
+//! ```
+//! Reads the segment log in `--store` (default `HISTORY_STORE_DIR`) without writing to
+//! it, so it is safe next to a running service. Writes to stdout unless `--out` is
+//! given; an `--out` ending in `.gz` implies `--gzip`. Row count goes to stderr.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use anyhow::{bail, Context};
+use dow_sentiment_analyzer::clock::{Clock, SystemClock};
+use dow_sentiment_analyzer::export::{self, ExportFormat, ExportKind, ExportQuery};
+use dow_sentiment_analyzer::history::parse_time;
+use dow_sentiment_analyzer::store::{self, StoreConfig};
+
+struct Args {
+    store: Option<String>,
+    query: ExportQuery,
+    out: Option<String>,
+}
+
+fn usage() -> &'static str {
+    "usage: export [--store <dir>] [--kind decisions|contributors] [--format csv|ndjson] \
+     [--from <unix|rfc3339>] [--to <unix|rfc3339>] [--instrument YM] [--gzip] [--out <file>]"
+}
+
+fn parse_args() -> anyhow::Result<Args> {
+    let mut args = Args {
+        store: None,
+        query: ExportQuery::default(),
+        out: None,
+    };
+
+    let mut it = std::env::args().skip(1);
+    while let Some(flag) = it.next() {
+        let mut value = || it.next().with_context(|| format!("{flag} needs a value"));
+        match flag.as_str() {
+            "--store" => args.store = Some(value()?),
+            "--kind" => {
+                let v = value()?;
+                args.query.kind = ExportKind::parse(&v)
+                    .with_context(|| format!("--kind expects decisions|contributors, got {v}"))?;
+            }
+            "--format" => {
+                let v = value()?;
+                args.query.format = ExportFormat::parse(&v)
+                    .with_context(|| format!("--format expects csv|ndjson, got {v}"))?;
+            }
+            "--from" => {
+                args.query.from = Some(parse_time("--from", &value()?).map_err(anyhow::Error::msg)?)
+            }
+            "--to" => {
+                args.query.to = Some(parse_time("--to", &value()?).map_err(anyhow::Error::msg)?)
+            }
+            "--instrument" => args.query.instrument = Some(value()?),
+            "--gzip" => args.query.gzip = true,
+            "--out" => args.out = Some(value()?),
+            "-h" | "--help" => {
+                println!("{}", usage());
+                std::process::exit(0);
+            }
+            other => bail!("unknown argument {other}\n{}", usage()),
+        }
+    }
+    if args.out.as_deref().is_some_and(|p| p.ends_with(".gz")) {
+        args.query.gzip = true;
+    }
+    Ok(args)
+}
+
+fn main() -> anyhow::Result<()> {
+    let _ = dotenvy::dotenv();
+    tracing_subscriber::fmt()
+        .with_target(false)
+        .with_writer(io::stderr)
+        .init();
+    let args = parse_args()?;
+
+    let cfg = match &args.store {
+        Some(dir) => StoreConfig::new(dir),
+        None => StoreConfig::from_env().with_context(|| {
+            format!(
+                "no --store given and HISTORY_STORE_DIR is not set\n{}",
+                usage()
+            )
+        })?,
+    };
+    let instrument = export::instrument_from_env();
+    args.query
+        .check_instrument(&instrument)
+        .map_err(anyhow::Error::msg)?;
+
+    let entries = store::read_history(&cfg, SystemClock.now_unix())
+        .with_context(|| format!("read {}", cfg.dir.display()))?;
+
+    let out: Box<dyn Write> = match &args.out {
+        Some(path) => Box::new(File::create(path).with_context(|| format!("create {path}"))?),
+        None => Box::new(io::stdout().lock()),
+    };
+    let rows = export::write_export(&entries, &args.query, &instrument, BufWriter::new(out))?;
+    eprintln!("exported {rows} {} row(s)", args.query.kind.as_str());
+    Ok(())
+}
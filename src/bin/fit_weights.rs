@@ -0,0 +1,94 @@
+//! Fit `config/weights.json` (`w_source`, `w_strength`, `w_recency`) from
+//! historical `ScoreInputs` and outcome labels.
+//!
+//! ```text
+//! cargo run --bin fit_weights -- --inputs data/score_inputs.jsonl \
+//!     [--loss log-loss|brier] [--out config/weights.json] [--report-out state/weights_fit.json]
+//! ```
+//! Inputs JSONL: `{"source_score": 0.9, "strength_score": 0.6, "recency_score": 1.0,
+//! "outcome": true}` per line. The weights currently in `--out` are the starting
+//! point and are kept unless the fit is strictly better. The report (losses before
+//! and after) is printed as JSON; `HotReloadWeights` picks up the new file.
+
+use anyhow::{bail, Context};
+use dow_sentiment_analyzer::analyze::weights::{self, Loss, WeightsFit};
+use serde::Serialize;
+
+const DEFAULT_WEIGHTS_PATH: &str = "config/weights.json";
+
+#[derive(Serialize)]
+struct Report<'a> {
+    out: &'a str,
+    #[serde(flatten)]
+    fit: &'a WeightsFit,
+}
+
+struct Args {
+    inputs: Option<String>,
+    loss: Loss,
+    out: String,
+    report_out: Option<String>,
+}
+
+fn usage() -> &'static str {
+    "usage: fit_weights --inputs <jsonl> [--loss log-loss|brier] [--out <json>] [--report-out <json>]"
+}
+
+fn parse_args() -> anyhow::Result<Args> {
+    let mut args = Args {
+        inputs: None,
+        loss: Loss::LogLoss,
+        out: DEFAULT_WEIGHTS_PATH.to_string(),
+        report_out: None,
+    };
+
+    let mut it = std::env::args().skip(1);
+    while let Some(flag) = it.next() {
+        let mut value = || it.next().with_context(|| format!("{flag} needs a value"));
+        match flag.as_str() {
+            "--inputs" => args.inputs = Some(value()?),
+            "--loss" => {
+                let v = value()?;
+                args.loss = Loss::parse(&v)
+                    .with_context(|| format!("--loss expects log-loss|brier, got {v}"))?;
+            }
+            "--out" => args.out = value()?,
+            "--report-out" => args.report_out = Some(value()?),
+            "-h" | "--help" => {
+                println!("{}", usage());
+                std::process::exit(0);
+            }
+            other => bail!("unknown argument {other}\n{}", usage()),
+        }
+    }
+    Ok(args)
+}
+
+fn main() -> anyhow::Result<()> {
+    let _ = dotenvy::dotenv();
+    tracing_subscriber::fmt().with_target(false).init();
+    let args = parse_args()?;
+
+    let Some(inputs) = &args.inputs else {
+        bail!("{}", usage());
+    };
+    let samples = weights::load_labeled_inputs(inputs)?;
+    if samples.is_empty() {
+        bail!("no labeled inputs");
+    }
+
+    let initial = weights::load_weights_file(std::path::Path::new(&args.out)).unwrap_or_default();
+    let fit = weights::fit_weights(&samples, args.loss, initial);
+    weights::save_weights_file(std::path::Path::new(&args.out), &fit.fitted)
+        .with_context(|| format!("write {}", args.out))?;
+
+    let report = serde_json::to_string_pretty(&Report {
+        out: &args.out,
+        fit: &fit,
+    })?;
+    if let Some(path) = &args.report_out {
+        std::fs::write(path, &report).with_context(|| format!("write {path}"))?;
+    }
+    println!("{report}");
+    Ok(())
+}
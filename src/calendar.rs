@@ -0,0 +1,395 @@
+//! # Market Calendar
+//! US trading sessions for YM: NYSE/CBOT regular hours, exchange holidays,
+//! early closes and the CME Globex futures schedule (all in America/New_York).
+//!
+//! Session states:
+//! - `REGULAR`  — cash session 09:30–16:00 ET (13:00 on early-close days),
+//! - `EXTENDED` — Globex open outside the cash session,
+//! - `CLOSED`   — Globex closed: daily halt 17:00–18:00 ET, weekend
+//!   (Fri 17:00 → Sun 18:00), holiday halts.
+//!
+//! Holiday rules (NYSE): New Year's Day, MLK Day, Presidents' Day, Good Friday,
+//! Memorial Day, Juneteenth (from 2022), Independence Day, Labor Day,
+//! Thanksgiving and Christmas; Saturday holidays are observed on Friday
+//! (except New Year's Day), Sunday holidays on Monday. Early closes (13:00 ET):
+//! July 3, the day after Thanksgiving and Christmas Eve, when they are trading days.
+//!
+//! Globex on holidays is approximated: closed until 18:00 ET on New Year's Day,
+//! Good Friday and Christmas; halted from 13:00 ET on the other holidays and
+//! from 13:15 ET on early-close days.
+
+use chrono::{
+    DateTime, Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Timelike, Utc, Weekday,
+};
+use chrono_tz::America::New_York;
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+/// Trading-session state at a point in time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SessionState {
+    Regular,
+    Extended,
+    Closed,
+}
+
+impl SessionState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SessionState::Regular => "REGULAR",
+            SessionState::Extended => "EXTENDED",
+            SessionState::Closed => "CLOSED",
+        }
+    }
+}
+
+const fn hm(h: u32, m: u32) -> u32 {
+    h * 60 + m
+}
+
+const RTH_OPEN: u32 = hm(9, 30);
+const RTH_CLOSE: u32 = hm(16, 0);
+const EARLY_CLOSE: u32 = hm(13, 0);
+const GLOBEX_EARLY_HALT: u32 = hm(13, 15);
+const GLOBEX_HOLIDAY_HALT: u32 = hm(13, 0);
+const GLOBEX_HALT: u32 = hm(17, 0);
+const GLOBEX_REOPEN: u32 = hm(18, 0);
+
+fn nth_weekday(year: i32, month: u32, wd: Weekday, n: u8) -> NaiveDate {
+    NaiveDate::from_weekday_of_month_opt(year, month, wd, n).expect("valid nth weekday")
+}
+
+fn last_weekday(year: i32, month: u32, wd: Weekday) -> NaiveDate {
+    NaiveDate::from_weekday_of_month_opt(year, month, wd, 5)
+        .unwrap_or_else(|| nth_weekday(year, month, wd, 4))
+}
+
+/// Gregorian Easter Sunday (anonymous Gregorian algorithm).
+fn easter(year: i32) -> NaiveDate {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32).expect("valid easter date")
+}
+
+/// Saturday → Friday, Sunday → Monday.
+fn observed(d: NaiveDate) -> NaiveDate {
+    match d.weekday() {
+        Weekday::Sat => d - Duration::days(1),
+        Weekday::Sun => d + Duration::days(1),
+        _ => d,
+    }
+}
+
+/// NYSE full-day holiday name for `date`, if any.
+pub fn holiday(date: NaiveDate) -> Option<&'static str> {
+    let y = date.year();
+    let ymd = |m, d| NaiveDate::from_ymd_opt(y, m, d).expect("valid date");
+
+    // New Year's Day: Sunday → Monday; a Saturday is not observed on Dec 31.
+    let new_year = ymd(1, 1);
+    if new_year.weekday() != Weekday::Sat && observed(new_year) == date {
+        return Some("New Year's Day");
+    }
+    if y >= 2022 && observed(ymd(6, 19)) == date {
+        return Some("Juneteenth");
+    }
+    if observed(ymd(7, 4)) == date {
+        return Some("Independence Day");
+    }
+    if observed(ymd(12, 25)) == date {
+        return Some("Christmas Day");
+    }
+
+    let floating = [
+        (
+            "Martin Luther King Jr. Day",
+            nth_weekday(y, 1, Weekday::Mon, 3),
+        ),
+        ("Presidents' Day", nth_weekday(y, 2, Weekday::Mon, 3)),
+        ("Good Friday", easter(y) - Duration::days(2)),
+        ("Memorial Day", last_weekday(y, 5, Weekday::Mon)),
+        ("Labor Day", nth_weekday(y, 9, Weekday::Mon, 1)),
+        ("Thanksgiving Day", nth_weekday(y, 11, Weekday::Thu, 4)),
+    ];
+    floating
+        .into_iter()
+        .find(|(_, d)| *d == date)
+        .map(|(name, _)| name)
+}
+
+/// Weekday that is not an NYSE holiday.
+pub fn is_trading_day(date: NaiveDate) -> bool {
+    !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) && holiday(date).is_none()
+}
+
+/// NYSE 13:00 ET early close on `date`.
+pub fn is_early_close(date: NaiveDate) -> bool {
+    if !is_trading_day(date) {
+        return false;
+    }
+    let y = date.year();
+    let jul3 = NaiveDate::from_ymd_opt(y, 7, 3).expect("valid date");
+    let black_friday = nth_weekday(y, 11, Weekday::Thu, 4) + Duration::days(1);
+    let xmas_eve = NaiveDate::from_ymd_opt(y, 12, 24).expect("valid date");
+    date == jul3 || date == black_friday || date == xmas_eve
+}
+
+fn full_globex_closure(name: &str) -> bool {
+    matches!(name, "New Year's Day" | "Good Friday" | "Christmas Day")
+}
+
+fn local(unix: u64) -> DateTime<Tz> {
+    New_York.from_utc_datetime(
+        &DateTime::from_timestamp(unix as i64, 0)
+            .unwrap_or_default()
+            .naive_utc(),
+    )
+}
+
+/// Minutes since local midnight.
+fn minutes(t: &DateTime<Tz>) -> u32 {
+    hm(t.hour(), t.minute())
+}
+
+/// Session state at `unix` (seconds, UTC).
+pub fn session_at(unix: u64) -> SessionState {
+    let t = local(unix);
+    let date = t.date_naive();
+    let m = minutes(&t);
+
+    // Weekly Globex schedule.
+    match date.weekday() {
+        Weekday::Sat => return SessionState::Closed,
+        Weekday::Sun if m < GLOBEX_REOPEN => return SessionState::Closed,
+        Weekday::Fri if m >= GLOBEX_HALT => return SessionState::Closed,
+        _ if (GLOBEX_HALT..GLOBEX_REOPEN).contains(&m) => return SessionState::Closed,
+        _ => {}
+    }
+    if date.weekday() == Weekday::Sun {
+        return SessionState::Extended;
+    }
+
+    if let Some(name) = holiday(date) {
+        let halt = if full_globex_closure(name) {
+            0
+        } else {
+            GLOBEX_HOLIDAY_HALT
+        };
+        return if (halt..GLOBEX_REOPEN).contains(&m) {
+            SessionState::Closed
+        } else {
+            SessionState::Extended
+        };
+    }
+
+    let (close, globex_halt) = if is_early_close(date) {
+        (EARLY_CLOSE, GLOBEX_EARLY_HALT)
+    } else {
+        (RTH_CLOSE, GLOBEX_HALT)
+    };
+    if (RTH_OPEN..close).contains(&m) {
+        SessionState::Regular
+    } else if (globex_halt..GLOBEX_REOPEN).contains(&m) {
+        SessionState::Closed
+    } else {
+        SessionState::Extended
+    }
+}
+
+/// Which sessions count as "tradable" for alerting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TradableHours {
+    /// Cash session only.
+    Regular,
+    /// Regular + extended (Globex open).
+    Globex,
+}
+
+impl TradableHours {
+    pub fn allows(self, s: SessionState) -> bool {
+        match self {
+            TradableHours::Regular => s == SessionState::Regular,
+            TradableHours::Globex => s != SessionState::Closed,
+        }
+    }
+}
+
+fn at_et(date: NaiveDate, minutes: u32) -> u64 {
+    let t = NaiveTime::from_hms_opt(minutes / 60, minutes % 60, 0).expect("valid time");
+    New_York
+        .from_local_datetime(&date.and_time(t))
+        .earliest()
+        .map(|dt| dt.with_timezone(&Utc).timestamp().max(0) as u64)
+        .unwrap_or(0)
+}
+
+/// Regular-session `(open, close)` in UNIX seconds, if `date` is a trading day.
+pub fn regular_hours(date: NaiveDate) -> Option<(u64, u64)> {
+    if !is_trading_day(date) {
+        return None;
+    }
+    let close = if is_early_close(date) {
+        EARLY_CLOSE
+    } else {
+        RTH_CLOSE
+    };
+    Some((at_et(date, RTH_OPEN), at_et(date, close)))
+}
+
+/// First regular-session open strictly after `unix`.
+pub fn next_regular_open(unix: u64) -> u64 {
+    let mut date = local(unix).date_naive();
+    // At most ~4 non-trading days in a row; 14 is a safe bound.
+    for _ in 0..14 {
+        if let Some((open, _)) = regular_hours(date) {
+            if open > unix {
+                return open;
+            }
+        }
+        date += Duration::days(1);
+    }
+    unix
+}
+
+/// Last regular-session close at or before `unix`.
+pub fn previous_regular_close(unix: u64) -> Option<u64> {
+    let mut date = local(unix).date_naive();
+    for _ in 0..14 {
+        if let Some((_, close)) = regular_hours(date) {
+            if close <= unix {
+                return Some(close);
+            }
+        }
+        date -= Duration::days(1);
+    }
+    None
+}
+
+/// Effective timestamp for weighing a statement made at `ts` when deciding at `now`.
+///
+/// During the regular session, statements made since the previous close (i.e.
+/// overnight, over a weekend or holiday) count as made at today's open, so they
+/// are weighed at the open instead of having decayed overnight.
+pub fn weigh_at_open(ts: u64, now: u64) -> u64 {
+    if session_at(now) != SessionState::Regular {
+        return ts;
+    }
+    let Some((open, _)) = regular_hours(local(now).date_naive()) else {
+        return ts;
+    };
+    match previous_regular_close(open) {
+        Some(prev_close) if ts >= prev_close && ts < open => open,
+        _ => ts,
+    }
+}
+
+/// `SESSION_CARRY_OVERNIGHT` (default on; `0` disables `weigh_at_open` in `/decide`).
+pub fn carry_overnight_from_env() -> bool {
+    std::env::var("SESSION_CARRY_OVERNIGHT")
+        .map(|v| v.trim() != "0")
+        .unwrap_or(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(y: i32, m: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, day).unwrap()
+    }
+
+    /// Unix seconds for an ET wall-clock time.
+    fn et(y: i32, m: u32, day: u32, h: u32, min: u32) -> u64 {
+        at_et(d(y, m, day), hm(h, min))
+    }
+
+    #[test]
+    fn nyse_holidays_2025_and_observance() {
+        assert_eq!(holiday(d(2025, 1, 1)), Some("New Year's Day"));
+        assert_eq!(holiday(d(2025, 1, 20)), Some("Martin Luther King Jr. Day"));
+        assert_eq!(holiday(d(2025, 4, 18)), Some("Good Friday"));
+        assert_eq!(holiday(d(2025, 5, 26)), Some("Memorial Day"));
+        assert_eq!(holiday(d(2025, 6, 19)), Some("Juneteenth"));
+        assert_eq!(holiday(d(2025, 11, 27)), Some("Thanksgiving Day"));
+        // July 4th 2026 is a Saturday → observed Friday July 3rd.
+        assert_eq!(holiday(d(2026, 7, 3)), Some("Independence Day"));
+        // New Year's Day 2022 was a Saturday → no Friday observance.
+        assert_eq!(holiday(d(2021, 12, 31)), None);
+        assert_eq!(holiday(d(2021, 6, 18)), None);
+        assert!(is_trading_day(d(2025, 9, 2)));
+    }
+
+    #[test]
+    fn early_closes() {
+        assert!(is_early_close(d(2025, 7, 3)));
+        assert!(is_early_close(d(2025, 11, 28)));
+        assert!(is_early_close(d(2025, 12, 24)));
+        // 2026-07-03 is the observed holiday, not an early close.
+        assert!(!is_early_close(d(2026, 7, 3)));
+        assert_eq!(session_at(et(2025, 11, 28, 12, 59)), SessionState::Regular);
+        assert_eq!(session_at(et(2025, 11, 28, 13, 5)), SessionState::Extended);
+        assert_eq!(session_at(et(2025, 11, 28, 13, 30)), SessionState::Closed);
+    }
+
+    #[test]
+    fn sessions_across_dst_and_weekends() {
+        // Winter (EST) and summer (EDT) opens.
+        assert_eq!(session_at(et(2025, 1, 6, 9, 30)), SessionState::Regular);
+        assert_eq!(session_at(et(2025, 7, 7, 9, 29)), SessionState::Extended);
+        assert_eq!(session_at(et(2025, 7, 7, 15, 59)), SessionState::Regular);
+        // Daily Globex halt and weekend.
+        assert_eq!(session_at(et(2025, 7, 8, 17, 30)), SessionState::Closed);
+        assert_eq!(session_at(et(2025, 7, 8, 18, 0)), SessionState::Extended);
+        assert_eq!(session_at(et(2025, 7, 11, 17, 0)), SessionState::Closed);
+        assert_eq!(session_at(et(2025, 7, 13, 3, 0)), SessionState::Closed);
+        assert_eq!(session_at(et(2025, 7, 13, 18, 30)), SessionState::Extended);
+        // Labor Day: Globex until 13:00, no cash session.
+        assert_eq!(session_at(et(2025, 9, 1, 10, 0)), SessionState::Extended);
+        assert_eq!(session_at(et(2025, 9, 1, 14, 0)), SessionState::Closed);
+        // Christmas: closed until the evening reopen.
+        assert_eq!(session_at(et(2025, 12, 25, 10, 0)), SessionState::Closed);
+        assert_eq!(session_at(et(2025, 12, 25, 18, 0)), SessionState::Extended);
+    }
+
+    #[test]
+    fn next_open_skips_weekend_and_holiday() {
+        // Friday after close → Monday open; Labor Day weekend → Tuesday.
+        assert_eq!(
+            next_regular_open(et(2025, 7, 11, 16, 30)),
+            et(2025, 7, 14, 9, 30)
+        );
+        assert_eq!(
+            next_regular_open(et(2025, 8, 29, 16, 30)),
+            et(2025, 9, 2, 9, 30)
+        );
+    }
+
+    #[test]
+    fn overnight_statements_are_weighed_at_the_open() {
+        let open = et(2025, 9, 2, 9, 30);
+        let now = open + 600;
+        // Made during the long weekend.
+        assert_eq!(weigh_at_open(et(2025, 8, 31, 20, 0), now), open);
+        // Made during today's session: unchanged.
+        assert_eq!(weigh_at_open(open + 60, now), open + 60);
+        // Made before the previous close: unchanged.
+        let old = et(2025, 8, 29, 15, 0);
+        assert_eq!(weigh_at_open(old, now), old);
+        // Outside the regular session nothing is shifted.
+        let ts = et(2025, 9, 1, 20, 0);
+        assert_eq!(weigh_at_open(ts, ts + 60), ts);
+    }
+}
@@ -0,0 +1,499 @@
+//! # Confidence Calibration
+//! Maps raw engine confidence (hand-tuned constants in `engine.rs` and the
+//! volume context) to the empirical probability that a directional verdict is
+//! correct.
+//!
+//! - Fitted offline from labeled outcomes `(raw confidence, correct?)` with
+//!   Platt scaling (logistic fit) or isotonic regression (pool adjacent
+//!   violators); see the `calibrate` binary.
+//! - Stored in `config/calibration.json` (`CALIBRATION_PATH`); a missing file
+//!   means identity.
+//! - Applied to BUY/SELL decisions after the stabilizer and before grading and
+//!   `History::push`; the original value stays visible as `raw_confidence`.
+//! - `reliability` bins outcomes into reliability-diagram data (mean confidence
+//!   vs. observed accuracy per bin) with ECE and Brier score.
+
+use std::{fs, path::Path};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::decision::{Decision, Reason, ReasonKind, Verdict};
+
+pub const DEFAULT_CALIBRATION_PATH: &str = "config/calibration.json";
+pub const ENV_CALIBRATION_PATH: &str = "CALIBRATION_PATH";
+
+/// One labeled outcome.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LabeledOutcome {
+    /// Raw (uncalibrated) confidence in `<0.0, 1.0>`.
+    pub confidence: f32,
+    /// Whether the verdict turned out right.
+    #[serde(alias = "hit")]
+    pub correct: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Method {
+    #[default]
+    Identity,
+    Platt,
+    Isotonic,
+}
+
+impl Method {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Method::Identity => "identity",
+            Method::Platt => "platt",
+            Method::Isotonic => "isotonic",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "identity" | "none" => Some(Method::Identity),
+            "platt" => Some(Method::Platt),
+            "isotonic" => Some(Method::Isotonic),
+            _ => None,
+        }
+    }
+}
+
+/// Knot of the isotonic map; values between knots are interpolated linearly.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CalPoint {
+    pub raw: f32,
+    pub calibrated: f32,
+}
+
+/// Calibration map (the `config/calibration.json` file).
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct Calibration {
+    #[serde(default)]
+    pub method: Method,
+    /// Platt: `p = 1 / (1 + exp(-(a * raw + b)))`.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub a: f64,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub b: f64,
+    /// Isotonic: non-decreasing knots sorted by `raw`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub points: Vec<CalPoint>,
+    /// Number of outcomes the map was fitted on.
+    #[serde(default)]
+    pub samples: usize,
+}
+
+fn is_zero(x: &f64) -> bool {
+    *x == 0.0
+}
+
+impl Calibration {
+    pub fn identity() -> Self {
+        Self::default()
+    }
+
+    /// Load from JSON; falls back to identity if the file is missing or invalid.
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Self {
+        let path = path.as_ref();
+        let parsed = fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|s| serde_json::from_str::<Calibration>(&s).map_err(|e| e.to_string()));
+        match parsed {
+            Ok(cal) => cal.sanitized(),
+            Err(e) => {
+                tracing::debug!(
+                    "calibration {} not used ({e}); using identity",
+                    path.display()
+                );
+                Self::identity()
+            }
+        }
+    }
+
+    /// Load from `CALIBRATION_PATH` (default `config/calibration.json`).
+    pub fn from_env() -> Self {
+        let path = std::env::var(ENV_CALIBRATION_PATH)
+            .unwrap_or_else(|_| DEFAULT_CALIBRATION_PATH.to_string());
+        Self::load_from_file(path)
+    }
+
+    pub fn is_identity(&self) -> bool {
+        match self.method {
+            Method::Identity => true,
+            Method::Platt => false,
+            Method::Isotonic => self.points.is_empty(),
+        }
+    }
+
+    /// Fit with the given method.
+    pub fn fit(method: Method, outcomes: &[LabeledOutcome]) -> Self {
+        match method {
+            Method::Identity => Self {
+                samples: outcomes.len(),
+                ..Self::identity()
+            },
+            Method::Platt => fit_platt(outcomes),
+            Method::Isotonic => fit_isotonic(outcomes),
+        }
+    }
+
+    /// Calibrated value of a raw confidence.
+    pub fn apply(&self, raw: f32) -> f32 {
+        let raw = raw.clamp(0.0, 1.0);
+        let out = match self.method {
+            Method::Identity => raw,
+            Method::Platt => sigmoid(self.a * raw as f64 + self.b) as f32,
+            Method::Isotonic => interpolate(&self.points, raw),
+        };
+        out.clamp(0.0, 1.0)
+    }
+
+    /// Calibrate a BUY/SELL decision in place (HOLD has no outcome to calibrate
+    /// against and is left alone). Keeps the raw value in `raw_confidence` and
+    /// adds a reason when the value changes.
+    pub fn apply_to(&self, d: &mut Decision) {
+        if self.is_identity() || d.decision == Verdict::Hold {
+            return;
+        }
+        let raw = d.confidence;
+        let calibrated = self.apply(raw);
+        if (calibrated - raw).abs() < 1e-4 {
+            return;
+        }
+        d.raw_confidence = Some(raw);
+        d.confidence = calibrated;
+        d.reasons.push(
+            Reason::new(format!(
+                "Calibrated confidence {raw:.3}->{calibrated:.3} ({}, n={})",
+                self.method.as_str(),
+                self.samples
+            ))
+            .kind(ReasonKind::Threshold)
+            .weighted(0.1),
+        );
+    }
+
+    fn sanitized(mut self) -> Self {
+        self.points
+            .retain(|p| p.raw.is_finite() && p.calibrated.is_finite());
+        self.points.sort_by(|x, y| x.raw.total_cmp(&y.raw));
+        // Enforce monotonicity on hand-edited files.
+        let mut floor = 0.0f32;
+        for p in &mut self.points {
+            p.calibrated = p.calibrated.clamp(floor, 1.0);
+            floor = p.calibrated;
+        }
+        if !(self.a.is_finite() && self.b.is_finite()) {
+            self.method = Method::Identity;
+        }
+        self
+    }
+}
+
+fn sigmoid(z: f64) -> f64 {
+    1.0 / (1.0 + (-z).exp())
+}
+
+fn interpolate(points: &[CalPoint], raw: f32) -> f32 {
+    let (Some(first), Some(last)) = (points.first(), points.last()) else {
+        return raw;
+    };
+    if raw <= first.raw {
+        return first.calibrated;
+    }
+    if raw >= last.raw {
+        return last.calibrated;
+    }
+    let i = points.partition_point(|p| p.raw <= raw);
+    let (lo, hi) = (points[i - 1], points[i]);
+    let span = hi.raw - lo.raw;
+    if span <= f32::EPSILON {
+        return hi.calibrated;
+    }
+    lo.calibrated + (hi.calibrated - lo.calibrated) * (raw - lo.raw) / span
+}
+
+/// Platt scaling via Newton's method, with Platt's smoothed targets so a
+/// perfectly separable sample does not diverge.
+pub fn fit_platt(outcomes: &[LabeledOutcome]) -> Calibration {
+    let n_pos = outcomes.iter().filter(|o| o.correct).count() as f64;
+    let n_neg = outcomes.len() as f64 - n_pos;
+    let t_pos = (n_pos + 1.0) / (n_pos + 2.0);
+    let t_neg = 1.0 / (n_neg + 2.0);
+
+    let (mut a, mut b) = (0.0f64, ((n_pos + 1.0) / (n_neg + 1.0)).ln());
+    for _ in 0..100 {
+        let (mut g_a, mut g_b, mut h_aa, mut h_ab, mut h_bb) = (0.0, 0.0, 1e-9, 0.0, 1e-9);
+        for o in outcomes {
+            let x = o.confidence.clamp(0.0, 1.0) as f64;
+            let t = if o.correct { t_pos } else { t_neg };
+            let p = sigmoid(a * x + b);
+            let w = p * (1.0 - p);
+            g_a += (p - t) * x;
+            g_b += p - t;
+            h_aa += w * x * x;
+            h_ab += w * x;
+            h_bb += w;
+        }
+        let det = h_aa * h_bb - h_ab * h_ab;
+        if det.abs() < 1e-12 {
+            break;
+        }
+        let da = (h_bb * g_a - h_ab * g_b) / det;
+        let db = (h_aa * g_b - h_ab * g_a) / det;
+        a -= da;
+        b -= db;
+        if da.abs() < 1e-9 && db.abs() < 1e-9 {
+            break;
+        }
+    }
+
+    Calibration {
+        method: Method::Platt,
+        a,
+        b,
+        points: Vec::new(),
+        samples: outcomes.len(),
+    }
+}
+
+/// Isotonic regression (pool adjacent violators). Knots sit at the mean raw
+/// confidence of each pooled block.
+pub fn fit_isotonic(outcomes: &[LabeledOutcome]) -> Calibration {
+    let mut sorted: Vec<(f64, f64)> = outcomes
+        .iter()
+        .map(|o| {
+            (
+                o.confidence.clamp(0.0, 1.0) as f64,
+                if o.correct { 1.0 } else { 0.0 },
+            )
+        })
+        .collect();
+    sorted.sort_by(|x, y| x.0.total_cmp(&y.0));
+
+    // Block = (sum_x, sum_y, weight); equal raw values start in one block.
+    let mut blocks: Vec<(f64, f64, f64)> = Vec::new();
+    for (i, &(x, y)) in sorted.iter().enumerate() {
+        if i > 0 && sorted[i - 1].0 == x {
+            let last = blocks.last_mut().expect("previous block");
+            *last = (last.0 + x, last.1 + y, last.2 + 1.0);
+        } else {
+            blocks.push((x, y, 1.0));
+        }
+        while blocks.len() >= 2 {
+            let (cur, prev) = (blocks[blocks.len() - 1], blocks[blocks.len() - 2]);
+            if prev.1 / prev.2 < cur.1 / cur.2 {
+                break;
+            }
+            blocks.pop();
+            let last = blocks.last_mut().expect("previous block");
+            *last = (prev.0 + cur.0, prev.1 + cur.1, prev.2 + cur.2);
+        }
+    }
+
+    Calibration {
+        method: Method::Isotonic,
+        a: 0.0,
+        b: 0.0,
+        points: blocks
+            .into_iter()
+            .map(|(sx, sy, w)| CalPoint {
+                raw: (sx / w) as f32,
+                calibrated: (sy / w) as f32,
+            })
+            .collect(),
+        samples: outcomes.len(),
+    }
+}
+
+/// One bin of a reliability diagram.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReliabilityBin {
+    pub lo: f32,
+    pub hi: f32,
+    pub count: usize,
+    pub mean_confidence: Option<f32>,
+    pub accuracy: Option<f32>,
+}
+
+/// Reliability-diagram data plus summary scores.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReliabilityReport {
+    pub samples: usize,
+    /// Expected calibration error (count-weighted |accuracy − confidence|).
+    pub ece: f32,
+    /// Mean squared error of confidence vs. outcome.
+    pub brier: f32,
+    pub bins: Vec<ReliabilityBin>,
+}
+
+/// CSV for plotting, header `stage,lo,hi,count,mean_confidence,accuracy`;
+/// one block of rows per `(stage, report)` (empty bins have blank values).
+pub fn reliability_csv(reports: &[(&str, &ReliabilityReport)]) -> String {
+    let opt = |v: Option<f32>| v.map(|x| format!("{x:.4}")).unwrap_or_default();
+    let mut out = String::from("stage,lo,hi,count,mean_confidence,accuracy\n");
+    for (stage, r) in reports {
+        for b in &r.bins {
+            out.push_str(&format!(
+                "{stage},{:.2},{:.2},{},{},{}\n",
+                b.lo,
+                b.hi,
+                b.count,
+                opt(b.mean_confidence),
+                opt(b.accuracy)
+            ));
+        }
+    }
+    out
+}
+
+/// Bin outcomes into `bins` equal-width confidence bins, after mapping each
+/// confidence through `calibration` (identity if `None`).
+pub fn reliability(
+    outcomes: &[LabeledOutcome],
+    bins: usize,
+    calibration: Option<&Calibration>,
+) -> ReliabilityReport {
+    let bins = bins.max(1);
+    let mut acc: Vec<(usize, f64, f64)> = vec![(0, 0.0, 0.0); bins];
+    let mut brier = 0.0f64;
+    for o in outcomes {
+        let c = calibration.map_or(o.confidence.clamp(0.0, 1.0), |cal| cal.apply(o.confidence));
+        let y = if o.correct { 1.0 } else { 0.0 };
+        let idx = ((c * bins as f32) as usize).min(bins - 1);
+        acc[idx].0 += 1;
+        acc[idx].1 += c as f64;
+        acc[idx].2 += y;
+        brier += (c as f64 - y).powi(2);
+    }
+
+    let n = outcomes.len();
+    let mut ece = 0.0f64;
+    let out_bins = acc
+        .into_iter()
+        .enumerate()
+        .map(|(i, (count, sum_c, sum_y))| {
+            let (mean_confidence, accuracy) = if count > 0 {
+                let (mc, ac) = (sum_c / count as f64, sum_y / count as f64);
+                ece += (count as f64 / n as f64) * (ac - mc).abs();
+                (Some(mc as f32), Some(ac as f32))
+            } else {
+                (None, None)
+            };
+            ReliabilityBin {
+                lo: i as f32 / bins as f32,
+                hi: (i + 1) as f32 / bins as f32,
+                count,
+                mean_confidence,
+                accuracy,
+            }
+        })
+        .collect();
+
+    ReliabilityReport {
+        samples: n,
+        ece: ece as f32,
+        brier: if n > 0 {
+            (brier / n as f64) as f32
+        } else {
+            0.0
+        },
+        bins: out_bins,
+    }
+}
+
+/// Load labeled outcomes from JSONL (`{"confidence": 0.75, "correct": true}`;
+/// blank lines and `#` comments are skipped).
+pub fn load_outcomes<P: AsRef<Path>>(path: P) -> anyhow::Result<Vec<LabeledOutcome>> {
+    let path = path.as_ref();
+    let raw = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+    raw.lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty() && !l.trim_start().starts_with('#'))
+        .map(|(i, l)| {
+            serde_json::from_str::<LabeledOutcome>(l)
+                .with_context(|| format!("{}:{}", path.display(), i + 1))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Over-confident engine: raw 0.9 is right 60% of the time, raw 0.6 only 40%.
+    fn overconfident() -> Vec<LabeledOutcome> {
+        let mut v = Vec::new();
+        for i in 0..10 {
+            v.push(LabeledOutcome {
+                confidence: 0.9,
+                correct: i < 6,
+            });
+            v.push(LabeledOutcome {
+                confidence: 0.6,
+                correct: i < 4,
+            });
+        }
+        v
+    }
+
+    #[test]
+    fn isotonic_pools_violators_and_interpolates() {
+        let data =
+            [(0.5, true), (0.6, false), (0.7, true), (0.8, true)].map(|(c, y)| LabeledOutcome {
+                confidence: c,
+                correct: y,
+            });
+        let cal = fit_isotonic(&data);
+        // 0.5 (1.0) and 0.6 (0.0) violate monotonicity and pool to 0.5 at 0.55.
+        assert_eq!(cal.points.len(), 2);
+        assert!((cal.points[0].raw - 0.55).abs() < 1e-6);
+        assert!((cal.points[0].calibrated - 0.5).abs() < 1e-6);
+        assert_eq!(cal.points[1].calibrated, 1.0);
+        assert!((cal.apply(0.65) - 0.75).abs() < 1e-6);
+        assert_eq!(cal.apply(0.1), 0.5);
+        assert_eq!(cal.apply(0.99), 1.0);
+    }
+
+    #[test]
+    fn platt_and_isotonic_reduce_calibration_error() {
+        let data = overconfident();
+        let before = reliability(&data, 10, None);
+        for method in [Method::Platt, Method::Isotonic] {
+            let cal = Calibration::fit(method, &data);
+            assert!(cal.apply(0.9) > cal.apply(0.6), "{method:?} not monotone");
+            assert!((cal.apply(0.9) - 0.6).abs() < 0.05, "{method:?}: {cal:?}");
+            let after = reliability(&data, 10, Some(&cal));
+            assert!(after.ece < before.ece, "{method:?}: {after:?}");
+            assert!(after.brier < before.brier);
+        }
+        assert_eq!(before.bins[9].count, 10);
+        assert_eq!(before.bins[9].accuracy, Some(0.6));
+
+        let csv = reliability_csv(&[("raw", &before)]);
+        assert_eq!(csv.lines().count(), 11);
+        assert_eq!(csv.lines().nth(1), Some("raw,0.00,0.10,0,,"));
+        assert_eq!(csv.lines().nth(10), Some("raw,0.90,1.00,10,0.9000,0.6000"));
+    }
+
+    #[test]
+    fn apply_to_keeps_raw_and_skips_hold() {
+        let cal = Calibration::fit(Method::Isotonic, &overconfident());
+        let mut buy = Decision::buy(0.9);
+        cal.apply_to(&mut buy);
+        assert_eq!(buy.raw_confidence, Some(0.9));
+        assert!((buy.confidence - 0.6).abs() < 1e-6);
+        assert!(buy.reasons[0].message.starts_with("Calibrated confidence"));
+
+        let mut hold = Decision::hold(0.9);
+        cal.apply_to(&mut hold);
+        assert_eq!(hold.raw_confidence, None);
+        assert_eq!(hold.confidence, 0.9);
+
+        let json = serde_json::to_string(&cal).unwrap();
+        assert_eq!(serde_json::from_str::<Calibration>(&json).unwrap(), cal);
+    }
+}
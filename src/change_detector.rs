@@ -3,6 +3,9 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use tokio::{fs, time};
 
+use crate::calendar::{self, TradableHours};
+use crate::clock::{self, SharedClock};
+use crate::grade::SignalGrade;
 use crate::notify::antiflutter::AntiFlutter;
 use crate::notify::{DecisionKind, NotificationEvent, NotifierMux};
 
@@ -23,6 +26,9 @@ struct DecideResponseFlat {
     confidence: f32,
     #[serde(default)]
     reasons: Vec<String>,
+    /// Optional graded signal (`STRONG_BUY` … `STRONG_SELL`); preferred over `decision`.
+    #[serde(default)]
+    signal: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -42,12 +48,9 @@ enum DecideAny {
 }
 
 fn map_decision(s: &str) -> DecisionKind {
-    match s.to_ascii_uppercase().as_str() {
-        "BUY" => DecisionKind::BUY,
-        "SELL" => DecisionKind::SELL,
-        "HOLD" => DecisionKind::HOLD,
-        _ => DecisionKind::HOLD,
-    }
+    SignalGrade::parse(s)
+        .map(DecisionKind::from)
+        .unwrap_or(DecisionKind::HOLD)
 }
 
 fn map_any(any: DecideAny) -> (DecisionKind, f32, Vec<String>) {
@@ -56,7 +59,12 @@ fn map_any(any: DecideAny) -> (DecisionKind, f32, Vec<String>) {
             decision,
             confidence,
             reasons,
-        }) => (map_decision(&decision), confidence, reasons),
+            signal,
+        }) => (
+            map_decision(signal.as_deref().unwrap_or(&decision)),
+            confidence,
+            reasons,
+        ),
         DecideAny::Alt(DecideResponseAlt {
             verdict,
             score,
@@ -102,7 +110,141 @@ async fn write_state(s: &LastState) {
     }
 }
 
+/// Apply one fetched decision at `now`: returns the event to send, if any.
+/// Updates `state` on every change; the caller persists it when `changed`.
+fn observe(
+    state: &mut LastState,
+    af: &mut AntiFlutter,
+    kind: DecisionKind,
+    conf: f32,
+    reasons: Vec<String>,
+    now: DateTime<Utc>,
+) -> (bool, Option<NotificationEvent>) {
+    if state.decision == Some(kind) {
+        tracing::trace!("no change: {:?}", kind);
+        return (false, None);
+    }
+    let ev = if af.should_alert(kind, now) {
+        af.record_alert(kind, now);
+        Some(NotificationEvent {
+            decision: kind,
+            confidence: conf,
+            reasons,
+            ts: now,
+        })
+    } else {
+        tracing::debug!("suppressed by antiflutter: {:?}", kind);
+        None
+    };
+    state.decision = Some(kind);
+    state.confidence = Some(conf);
+    state.ts = Some(now);
+    (true, ev)
+}
+
+/// What happens to alerts raised outside tradable hours.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OffHoursMode {
+    /// Send immediately (default).
+    Send,
+    /// Keep only the latest alert and send it when trading resumes.
+    Defer,
+    /// Collect all alerts and send one summary when trading resumes.
+    Batch,
+}
+
+/// Holds alerts back outside tradable hours (see `calendar::session_at`).
+#[derive(Debug)]
+struct SessionGate {
+    mode: OffHoursMode,
+    hours: TradableHours,
+    pending: Vec<NotificationEvent>,
+}
+
+impl SessionGate {
+    fn new(mode: OffHoursMode, hours: TradableHours) -> Self {
+        Self {
+            mode,
+            hours,
+            pending: Vec::new(),
+        }
+    }
+
+    /// - `ALERT_OFF_HOURS`: `send` (default) | `defer` | `batch`
+    /// - `ALERT_TRADABLE_HOURS`: `regular` (default, cash session) | `globex`
+    fn from_env() -> Self {
+        let mode = match std::env::var("ALERT_OFF_HOURS")
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase()
+            .as_str()
+        {
+            "defer" => OffHoursMode::Defer,
+            "batch" => OffHoursMode::Batch,
+            _ => OffHoursMode::Send,
+        };
+        let hours = match std::env::var("ALERT_TRADABLE_HOURS")
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase()
+            .as_str()
+        {
+            "globex" => TradableHours::Globex,
+            _ => TradableHours::Regular,
+        };
+        Self::new(mode, hours)
+    }
+
+    fn tradable(&self, now: DateTime<Utc>) -> bool {
+        self.hours
+            .allows(calendar::session_at(now.timestamp().max(0) as u64))
+    }
+
+    /// Event to send now, or `None` when it was held back.
+    fn offer(&mut self, ev: NotificationEvent, now: DateTime<Utc>) -> Option<NotificationEvent> {
+        if self.mode == OffHoursMode::Send || self.tradable(now) {
+            return Some(ev);
+        }
+        tracing::debug!("alert held outside tradable hours: {:?}", ev.decision);
+        if self.mode == OffHoursMode::Defer {
+            self.pending.clear();
+        }
+        self.pending.push(ev);
+        None
+    }
+
+    /// Release held alerts once trading resumes (one event, stamped `now`).
+    fn flush(&mut self, now: DateTime<Utc>) -> Option<NotificationEvent> {
+        if self.pending.is_empty() || !self.tradable(now) {
+            return None;
+        }
+        let held = std::mem::take(&mut self.pending);
+        let mut ev = held.last().cloned()?;
+        let summary = if held.len() == 1 {
+            format!("Deferred alert from {}", ev.ts.format("%Y-%m-%d %H:%M UTC"))
+        } else {
+            let seq: Vec<String> = held
+                .iter()
+                .map(|e| format!("{:?}@{}", e.decision, e.ts.format("%H:%M")))
+                .collect();
+            format!(
+                "Batched {} alerts outside tradable hours: {}",
+                held.len(),
+                seq.join(", ")
+            )
+        };
+        ev.reasons.insert(0, summary);
+        ev.ts = now;
+        Some(ev)
+    }
+}
+
 pub async fn run_change_detector() -> Result<()> {
+    run_change_detector_with_clock(clock::system()).await
+}
+
+/// Same as `run_change_detector`, timestamping events with `clock`.
+pub async fn run_change_detector_with_clock(clock: SharedClock) -> Result<()> {
     let interval_secs: u64 = std::env::var("CHECK_INTERVAL_SECS")
         .ok()
         .and_then(|v| v.parse().ok())
@@ -121,32 +263,23 @@ pub async fn run_change_detector() -> Result<()> {
             .unwrap_or(10_800); // 3h
         AntiFlutter::new(cd_secs)
     };
+    let mut gate = SessionGate::from_env();
 
     loop {
         ticker.tick().await;
 
-        let now = Utc::now();
+        let now = clock.now_utc();
+        if let Some(ev) = gate.flush(now) {
+            mux.notify(&ev).await;
+        }
         match fetch_decision(&endpoint).await {
             Ok((kind, conf, reasons)) => {
-                if state.decision != Some(kind) {
-                    if af.should_alert(kind, now) {
-                        let ev = NotificationEvent {
-                            decision: kind,
-                            confidence: conf,
-                            reasons: reasons.clone(),
-                            ts: now,
-                        };
-                        mux.notify(&ev).await;
-                        af.record_alert(kind, now);
-                    } else {
-                        tracing::debug!("suppressed by antiflutter: {:?}", kind);
-                    }
-                    state.decision = Some(kind);
-                    state.confidence = Some(conf);
-                    state.ts = Some(now);
+                let (changed, ev) = observe(&mut state, &mut af, kind, conf, reasons, now);
+                if let Some(ev) = ev.and_then(|ev| gate.offer(ev, now)) {
+                    mux.notify(&ev).await;
+                }
+                if changed {
                     write_state(&state).await;
-                } else {
-                    tracing::trace!("no change: {:?}", kind);
                 }
             }
             Err(e) => {
@@ -155,3 +288,135 @@ pub async fn run_change_detector() -> Result<()> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn graded_signal_is_preferred_over_base_decision() {
+        let any: DecideAny = serde_json::from_str(
+            r#"{"decision":"BUY","confidence":0.91,"reasons":[],"signal":"STRONG_BUY"}"#,
+        )
+        .unwrap();
+        let (kind, conf, _) = map_any(any);
+        assert_eq!(kind, DecisionKind::STRONG_BUY);
+        assert!((conf - 0.91).abs() < 1e-6);
+    }
+
+    #[test]
+    fn legacy_three_value_payloads_still_map() {
+        let flat: DecideAny =
+            serde_json::from_str(r#"{"decision":"sell","confidence":0.7}"#).unwrap();
+        assert_eq!(map_any(flat).0, DecisionKind::SELL);
+
+        let alt: DecideAny = serde_json::from_str(r#"{"verdict":"HOLD","score":0.5}"#).unwrap();
+        assert_eq!(map_any(alt).0, DecisionKind::HOLD);
+
+        assert_eq!(map_decision("garbage"), DecisionKind::HOLD);
+    }
+
+    #[test]
+    fn cooldown_follows_injected_clock() {
+        use crate::clock::{Clock, ManualClock};
+
+        let clock = ManualClock::new(1_757_000_000);
+        let mut state = LastState::default();
+        let mut af = AntiFlutter::new(600);
+
+        let (changed, ev) = observe(
+            &mut state,
+            &mut af,
+            DecisionKind::BUY,
+            0.8,
+            vec![],
+            clock.now_utc(),
+        );
+        assert!(changed);
+        assert_eq!(ev.unwrap().ts.timestamp(), 1_757_000_000);
+
+        // Same verdict: nothing to do.
+        assert!(
+            !observe(
+                &mut state,
+                &mut af,
+                DecisionKind::BUY,
+                0.8,
+                vec![],
+                clock.now_utc()
+            )
+            .0
+        );
+
+        // Change inside the cooldown is recorded but not alerted.
+        clock.advance(300);
+        let (changed, ev) = observe(
+            &mut state,
+            &mut af,
+            DecisionKind::SELL,
+            0.7,
+            vec![],
+            clock.now_utc(),
+        );
+        assert!(changed && ev.is_none());
+        assert_eq!(state.decision, Some(DecisionKind::SELL));
+
+        clock.advance(300);
+        let (_, ev) = observe(
+            &mut state,
+            &mut af,
+            DecisionKind::HOLD,
+            0.55,
+            vec![],
+            clock.now_utc(),
+        );
+        assert_eq!(ev.unwrap().decision, DecisionKind::HOLD);
+    }
+
+    fn ev(kind: DecisionKind, ts: DateTime<Utc>) -> NotificationEvent {
+        NotificationEvent {
+            decision: kind,
+            confidence: 0.8,
+            reasons: vec!["r".into()],
+            ts,
+        }
+    }
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn session_gate_defers_latest_alert_to_the_open() {
+        let mut g = SessionGate::new(OffHoursMode::Defer, TradableHours::Regular);
+        // Saturday: held; only the latest one survives.
+        let sat = utc("2025-09-06T15:00:00Z");
+        assert!(g.offer(ev(DecisionKind::BUY, sat), sat).is_none());
+        assert!(g.offer(ev(DecisionKind::SELL, sat), sat).is_none());
+        assert!(g.flush(utc("2025-09-07T23:00:00Z")).is_none()); // Globex, not RTH
+
+        let open = utc("2025-09-08T13:30:00Z"); // Mon 09:30 EDT
+        let out = g.flush(open).unwrap();
+        assert_eq!(out.decision, DecisionKind::SELL);
+        assert_eq!(out.ts, open);
+        assert!(out.reasons[0].starts_with("Deferred alert"));
+        assert!(g.flush(open).is_none());
+
+        // During the session alerts pass straight through.
+        assert!(g.offer(ev(DecisionKind::HOLD, open), open).is_some());
+    }
+
+    #[test]
+    fn session_gate_batches_off_hours_alerts() {
+        let mut g = SessionGate::new(OffHoursMode::Batch, TradableHours::Globex);
+        let halt = utc("2025-09-09T21:30:00Z"); // Tue 17:30 EDT, Globex halt
+        g.offer(ev(DecisionKind::BUY, halt), halt);
+        g.offer(ev(DecisionKind::STRONG_SELL, halt), halt);
+        let out = g.flush(utc("2025-09-09T22:00:00Z")).unwrap();
+        assert_eq!(out.decision, DecisionKind::STRONG_SELL);
+        assert!(out.reasons[0].starts_with("Batched 2 alerts"));
+
+        let mut send = SessionGate::new(OffHoursMode::Send, TradableHours::Regular);
+        assert!(send.offer(ev(DecisionKind::BUY, halt), halt).is_some());
+    }
+}
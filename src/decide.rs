@@ -0,0 +1,348 @@
+//! # Decide
+//! The `/decide` pipeline as plain functions, shared by the HTTP handler and the
+//! backtester so a replayed batch is decided exactly like a live one:
+//!
+//! 1. `score`: antispam → relevance (relaxed in event windows) → sentiment →
+//!    disruption (session carry-over, event boosts) → rerank / NER / rules.
+//! 2. `finish`: `make_decision` → volume context → relevance / session / event
+//!    reasons → AI hint → calibrated weights and rule outcome → stabilizer →
+//!    calibration → grade.
+//!
+//! The handler awaits the AI hint between the two steps; the backtester passes
+//! none, so replays stay deterministic. Recording the decision (history, rolling
+//! window, paper trading, metrics) is left to the caller.
+
+use crate::analyze::antispam::{AntiSpam, AntiSpamParams};
+use crate::analyze::pipeline::{self, ItemMeta, PipelineReport, PipelineToggles};
+use crate::analyze::rules::{RuleContext, RuleSet};
+use crate::analyze::weights::Weights;
+use crate::calibration::Calibration;
+use crate::decision::{Decision, Reason, ReasonKind};
+use crate::disruption::{
+    evaluate_boosted_at, evaluate_with_weights_at, DisruptionInput, DisruptionResult,
+};
+use crate::engine;
+use crate::events::{self, EventCalendar};
+use crate::grade::GradeScale;
+use crate::history::History;
+use crate::relevance::{
+    ai_gate_should_call, anon_hash, dev_logging_enabled, truncate_vec, RelevanceHandle,
+};
+use crate::sentiment::{BatchItem, SentimentAnalyzer};
+use crate::source_weights::SourceWeightsConfig;
+use crate::stabilizer::VerdictStabilizer;
+
+/// One statement entering the pipeline.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecideInput {
+    pub source: String,
+    pub text: String,
+    /// Statement time; `None` = now.
+    pub ts_unix: Option<u64>,
+    /// Link to the original, kept on contributors as evidence.
+    pub url: Option<String>,
+}
+
+/// Per-statement stages (everything before the AI hint).
+pub struct Scorer<'a> {
+    pub analyzer: &'a SentimentAnalyzer,
+    pub relevance: &'a RelevanceHandle,
+    pub source_weights: &'a SourceWeightsConfig,
+    pub events: &'a EventCalendar,
+    /// Weigh statements from outside regular hours at the open (`SESSION_CARRY_OVERNIGHT`).
+    pub carry_overnight: bool,
+    pub toggles: PipelineToggles,
+    pub rules: &'a RuleSet,
+}
+
+/// Decision stages (everything after the AI hint).
+pub struct Finisher<'a> {
+    /// Recent decisions for the volume context.
+    pub history: &'a History,
+    pub stabilizer: &'a VerdictStabilizer,
+    pub calibration: &'a Calibration,
+    pub grades: &'a GradeScale,
+    /// Calibrated analyze weights (`WEIGHTS_CONFIG_PATH`).
+    pub weights: &'a Weights,
+    pub toggles: PipelineToggles,
+}
+
+/// A scored batch, ready for `finish`.
+#[derive(Debug, Clone)]
+pub struct Scored {
+    pub items: Vec<(BatchItem, i32, DisruptionResult)>,
+    /// `urls[i]` belongs to `items[i]`.
+    pub urls: Vec<Option<String>>,
+    /// Statements passed in, before antispam.
+    pub total: usize,
+    /// Items whose sentiment the relevance gate zeroed.
+    pub neutralized: usize,
+    /// Items from outside regular hours weighed at the open.
+    pub carried: usize,
+    /// One reason per active event window that boosted an item.
+    pub event_windows: Vec<String>,
+    /// Texts the AI gate would send for a hint (`ai_gate_should_call`).
+    pub ai_texts: Vec<String>,
+    pub report: PipelineReport,
+}
+
+impl Scorer<'_> {
+    /// Score `inputs` at `now`. With the antispam stage on, near-duplicates are
+    /// dropped first: within this batch, or against everything `window` saw before
+    /// when the caller keeps one across calls (`antispam_across_requests`).
+    pub fn score(
+        &self,
+        inputs: Vec<DecideInput>,
+        window: Option<&mut AntiSpam>,
+        now: u64,
+    ) -> Scored {
+        let total = inputs.len();
+        let mut report = PipelineReport::new(self.toggles);
+        let inputs = if self.toggles.antispam {
+            let mut batch = AntiSpam::new(AntiSpamParams::default());
+            let anti = window.unwrap_or(&mut batch);
+            let (kept, dropped) = pipeline::antispam_filter(anti, inputs, |it| {
+                (it.ts_unix.unwrap_or(now), it.text.as_str())
+            });
+            report.antispam_dropped = dropped;
+            kept
+        } else {
+            inputs
+        };
+
+        let events_active = self.events.active_at(now).next().is_some();
+        let mut items = Vec::with_capacity(inputs.len());
+        let mut urls = Vec::with_capacity(inputs.len());
+        let mut item_meta: Vec<ItemMeta> = Vec::with_capacity(inputs.len());
+        let mut neutralized = 0usize;
+        let mut carried = 0usize;
+        let mut event_windows: Vec<String> = Vec::new();
+        let mut ai_texts: Vec<String> = Vec::new();
+
+        for it in inputs {
+            let (raw_score, _tokens) = self.analyzer.score_text(&it.text);
+            let boost = if events_active {
+                self.events
+                    .boost_for(&events::ner_categories(&it.text), now)
+            } else {
+                None
+            };
+            let rel = match &boost {
+                Some(b) => self.relevance.score_relaxed(&it.text, b.boost.relevance),
+                None => self.relevance.score(&it.text),
+            };
+            let gated_score = if rel.score > 0.0 { raw_score } else { 0 };
+
+            if ai_gate_should_call(&it.source, &rel) {
+                ai_texts.push(it.text.clone());
+            }
+
+            if dev_logging_enabled() {
+                let event = if rel.score > 0.0 {
+                    "api_pass"
+                } else {
+                    "api_neutralized"
+                };
+                tracing::info!(
+                    target: "relevance",
+                    event,
+                    id = %anon_hash(&it.text),
+                    matched = ?truncate_vec(&rel.matched, 5),
+                    reasons = ?truncate_vec(&rel.reasons, 5),
+                    rel_score = rel.score,
+                    raw = raw_score,
+                    gated = gated_score
+                );
+            }
+
+            if gated_score == 0 && raw_score != 0 {
+                neutralized += 1;
+            }
+
+            let raw_ts = it.ts_unix.unwrap_or(now);
+            let ts = if self.carry_overnight {
+                crate::calendar::weigh_at_open(raw_ts, now)
+            } else {
+                raw_ts
+            };
+            if ts != raw_ts {
+                carried += 1;
+            }
+            item_meta.push(ItemMeta {
+                ts_unix: raw_ts,
+                score: raw_score,
+                relevance: rel.score,
+            });
+
+            let di = DisruptionInput {
+                source: it.source.clone(),
+                text: it.text.clone(),
+                score: gated_score,
+                ts_unix: ts,
+            };
+            let res = match &boost {
+                Some(b) => evaluate_boosted_at(
+                    &di,
+                    self.source_weights,
+                    now,
+                    b.boost.source,
+                    b.boost.strength,
+                ),
+                None => evaluate_with_weights_at(&di, self.source_weights, now),
+            };
+            if let Some(b) = boost {
+                let reason = b.describe();
+                if !event_windows.contains(&reason) {
+                    event_windows.push(reason);
+                }
+            }
+
+            items.push((
+                BatchItem {
+                    source: it.source,
+                    text: it.text,
+                },
+                gated_score,
+                res,
+            ));
+            urls.push(it.url);
+        }
+
+        if self.toggles.rerank {
+            report.rerank_decayed = pipeline::rerank_decay(&mut items, &item_meta);
+        }
+        if self.toggles.ner {
+            report.ner = pipeline::ner_reasons(items.iter().map(|(bi, _, _)| bi.text.as_str()));
+        }
+        if self.toggles.rules {
+            let contexts = items
+                .iter()
+                .zip(&item_meta)
+                .map(|((bi, _, _), m)| RuleContext {
+                    text: &bi.text,
+                    source: Some(&bi.source),
+                    score: Some(m.score),
+                    relevance: Some(m.relevance),
+                    ts_unix: Some(m.ts_unix),
+                    sources: Some(self.source_weights),
+                });
+            pipeline::apply_rules(&mut report, contexts, self.rules);
+        }
+
+        Scored {
+            items,
+            urls,
+            total,
+            neutralized,
+            carried,
+            event_windows,
+            ai_texts,
+            report,
+        }
+    }
+}
+
+impl Finisher<'_> {
+    /// Decide `scored` at `now`, with the AI hint if there is one. Returns the graded
+    /// decision and the stage report; nothing is recorded.
+    pub fn finish(
+        &self,
+        scored: Scored,
+        ai_hint: Option<&str>,
+        now: u64,
+    ) -> (Decision, PipelineReport) {
+        let Scored {
+            items,
+            urls,
+            total,
+            neutralized,
+            carried,
+            event_windows,
+            mut report,
+            ..
+        } = scored;
+
+        let mut decision = engine::make_decision_at(&items, now);
+        attach_urls(&mut decision, &items, &urls);
+
+        engine::apply_volume_context(&mut decision, self.history, now);
+
+        if neutralized > 0 && total > 0 {
+            let frac = neutralized as f32 / total as f32;
+            decision.reasons.push(
+                Reason::new(format!(
+                    "Relevance gate neutralized {}/{} items before decision",
+                    neutralized, total
+                ))
+                .kind(ReasonKind::Threshold)
+                .weighted(frac.clamp(0.0, 1.0)),
+            );
+        }
+
+        if carried > 0 {
+            decision.reasons.push(
+                Reason::new(format!(
+                    "Session: {} statement(s) from outside regular hours weighed at the open",
+                    carried
+                ))
+                .kind(ReasonKind::Recency)
+                .weighted(0.3),
+            );
+        }
+
+        for reason in event_windows {
+            decision
+                .reasons
+                .push(Reason::new(reason).kind(ReasonKind::Other).weighted(0.4));
+        }
+
+        if let Some(r) = ai_hint {
+            decision.reasons.push(
+                Reason::new(format!("AI hint: {}", r))
+                    .kind(ReasonKind::Threshold)
+                    .weighted(0.5),
+            );
+            let before = decision.confidence;
+            let after = (before + 0.02).clamp(0.0, 0.99);
+            if after != before {
+                decision.confidence = after;
+                decision.reasons.push(
+                    Reason::new(format!(
+                        "AI hint nudged confidence +0.02 ({before:.3}->{after:.3})"
+                    ))
+                    .kind(ReasonKind::Threshold)
+                    .weighted(0.1),
+                );
+            }
+        }
+
+        if self.toggles.weights {
+            report.weights_delta = pipeline::weights_delta(&items, decision.decision, self.weights);
+        }
+        // Phase-3 stage reasons and rule action/confidence, before hysteresis.
+        report.annotate(&mut decision, total);
+
+        // Damp verdict flips (raw verdict stays visible as `raw_decision`).
+        self.stabilizer.apply(&mut decision, now);
+        self.calibration.apply_to(&mut decision);
+        decision.signal = Some(self.grades.grade(decision.decision, decision.confidence));
+
+        (decision, report)
+    }
+}
+
+/// Copy input URLs onto the decision's contributors (matched by source and text).
+fn attach_urls(
+    decision: &mut Decision,
+    scored: &[(BatchItem, i32, DisruptionResult)],
+    urls: &[Option<String>],
+) {
+    for c in decision.top_contributors.iter_mut() {
+        if let Some(i) = scored
+            .iter()
+            .position(|(bi, _, _)| bi.source == c.source && bi.text == c.text)
+        {
+            c.url = urls.get(i).cloned().flatten();
+        }
+    }
+}
@@ -13,11 +13,13 @@
 use serde::{Deserialize, Serialize};
 
 // ----- Relevance gate hook (light coupling) -----
+use crate::calendar::SessionState;
+use crate::grade::SignalGrade;
 use crate::relevance::RelevanceHandle;
 use sha2::{Digest, Sha256};
 
 /// Final verdict of the decision.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum Verdict {
     Buy,
@@ -27,7 +29,7 @@ pub enum Verdict {
 
 /// A concise reason shown to users (explainability).
 /// Keep it readable; we can refine categories as the system evolves.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct Reason {
     /// Human-readable explanation (e.g., "Trump said economy is strong (+2)").
     pub message: String,
@@ -40,7 +42,7 @@ pub struct Reason {
 }
 
 /// Coarse-grained reason kinds (for UI/test cohesion).
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ReasonKind {
     SourceStrength,
@@ -54,7 +56,7 @@ pub enum ReasonKind {
 
 /// Top contributors to the current verdict.
 /// Lets us show "evidence": who said what, with what score, and when.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct Contributor {
     /// E.g., "Trump", "Fed", "Yellen", "Analyst", ...
     pub source: String,
@@ -75,11 +77,15 @@ pub struct Contributor {
     pub w_strength: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub w_recency: Option<f32>,
+
+    /// Link to the original statement/article, when the input carried one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
 }
 
 /// Complete decision including explainability.
 /// This is the shape returned by the API.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct Decision {
     pub decision: Verdict,
     /// Confidence in `<0.0, 1.0>`.
@@ -90,6 +96,18 @@ pub struct Decision {
     /// Top N contributors (typically 1–3).
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub top_contributors: Vec<Contributor>,
+    /// Raw engine verdict when the stabilizer held back a flip (see `stabilizer`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_decision: Option<Verdict>,
+    /// Uncalibrated engine confidence when a calibration map changed it (see `calibration`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_confidence: Option<f32>,
+    /// Five-level grade of the final verdict/confidence (see `grade::GradeScale`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signal: Option<SignalGrade>,
+    /// Trading session at decision time (see `calendar::session_at`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session: Option<SessionState>,
 }
 
 #[allow(dead_code)]
@@ -101,6 +119,10 @@ impl Decision {
             confidence: clamp01(confidence),
             reasons: Vec::new(),
             top_contributors: Vec::new(),
+            raw_decision: None,
+            raw_confidence: None,
+            signal: None,
+            session: None,
         }
     }
 
@@ -228,9 +250,15 @@ impl Contributor {
             w_source: None,
             w_strength: None,
             w_recency: None,
+            url: None,
         }
     }
 
+    pub fn url(mut self, url: impl Into<String>) -> Self {
+        self.url = Some(url.into());
+        self
+    }
+
     pub fn weights(mut self, w_source: f32, w_strength: f32, w_recency: f32) -> Self {
         self.w_source = Some(clamp01(w_source));
         self.w_strength = Some(clamp01(w_strength));
@@ -8,9 +8,9 @@
 //!
 //! Pure business logic with no side effects.
 
+use crate::clock::{Clock, SystemClock};
 use crate::source_weights::SourceWeightsConfig;
 use serde::{Deserialize, Serialize};
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 /// Simple, readable thresholds for easy tuning.
 const TRIGGER_W_SOURCE_MIN: f32 = 0.80;
@@ -57,6 +57,21 @@ impl DisruptionResult {
             age_secs,
         }
     }
+
+    /// Scale source/strength by `factor` (e.g. rerank duplicate decay) and
+    /// re-check the trigger thresholds; a decayed item never starts triggering.
+    pub fn decayed(&self, factor: f32) -> Self {
+        let w_source = clamp01(self.w_source * factor);
+        let w_strength = clamp01(self.w_strength * factor);
+        Self {
+            triggered: self.triggered
+                && w_source >= TRIGGER_W_SOURCE_MIN
+                && w_strength >= TRIGGER_W_STRENGTH_MIN,
+            w_source,
+            w_strength,
+            age_secs: self.age_secs,
+        }
+    }
 }
 
 /// Soft recency weight: 1.0 up to 15 min; linearly decays to 0.0 by 30 min; 0.0 afterwards.
@@ -74,7 +89,11 @@ fn recency_weight(age_secs: u64) -> f32 {
 
 /// Main path: evaluate whether the input is "disruptive" (no external weights).
 pub fn evaluate(input: &DisruptionInput) -> DisruptionResult {
-    let now = now_unix();
+    evaluate_at(input, now_unix())
+}
+
+/// Same as `evaluate`, aged against an explicit `now` (see `crate::clock`).
+pub fn evaluate_at(input: &DisruptionInput, now: u64) -> DisruptionResult {
     let age_secs = now.saturating_sub(input.ts_unix);
 
     // 1) Intensity by absolute score.
@@ -115,11 +134,9 @@ pub fn source_weight(source: &str) -> f32 {
     }
 }
 
+/// Wall-clock UNIX seconds; injectable callers use `Clock::now_unix` instead.
 pub fn now_unix() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or(Duration::from_secs(0))
-        .as_secs()
+    SystemClock.now_unix()
 }
 
 fn clamp01(x: f32) -> f32 {
@@ -131,11 +148,37 @@ pub fn evaluate_with_weights(
     input: &DisruptionInput,
     sw: &SourceWeightsConfig,
 ) -> DisruptionResult {
-    let now = now_unix();
+    evaluate_with_weights_at(input, sw, now_unix())
+}
+
+/// Same as `evaluate_with_weights`, aged against an explicit `now` (see `crate::clock`).
+pub fn evaluate_with_weights_at(
+    input: &DisruptionInput,
+    sw: &SourceWeightsConfig,
+    now: u64,
+) -> DisruptionResult {
+    evaluate_boosted_at(input, sw, now, 0.0, 0.0)
+}
+
+/// Same as `evaluate_with_weights_at`, with additive boosts to `w_source` and
+/// `w_strength` (e.g. inside an economic-event window, see `crate::events`).
+/// The strength boost only applies to non-neutral scores.
+pub fn evaluate_boosted_at(
+    input: &DisruptionInput,
+    sw: &SourceWeightsConfig,
+    now: u64,
+    source_boost: f32,
+    strength_boost: f32,
+) -> DisruptionResult {
     let age_secs = now.saturating_sub(input.ts_unix);
 
-    let w_strength = strength_weight(input.score);
-    let w_source = clamp01(sw.weight_for(&input.source));
+    let strength_boost = if input.score != 0 {
+        strength_boost
+    } else {
+        0.0
+    };
+    let w_strength = clamp01(strength_weight(input.score) + strength_boost);
+    let w_source = clamp01(sw.weight_for(&input.source) + source_boost);
     let w_recency = recency_weight(age_secs);
 
     let passes =
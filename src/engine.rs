@@ -6,12 +6,26 @@
 //! or lack of triggers yield HOLD. Confidence blends trigger count, average
 //! component quality, and source independence.
 
+use std::collections::HashSet;
+
+use crate::calendar;
+use crate::clock::{Clock, SystemClock};
 use crate::decision::{Contributor, Decision, Reason, ReasonKind, Verdict};
 use crate::disruption::DisruptionResult;
+use crate::history::History;
 use crate::sentiment::BatchItem;
 
+/// Lookback for the volume context applied after `make_decision` (10 min).
+pub const VOLUME_WINDOW_SECS: u64 = 600;
+
 /// Same logic as the `/decide` handler but purely functional for testing.
 pub fn make_decision(scored: &[(BatchItem, i32, DisruptionResult)]) -> Decision {
+    make_decision_at(scored, SystemClock.now_unix())
+}
+
+/// Same as `make_decision` at an explicit `now` (contributor timestamps and
+/// the session tag).
+pub fn make_decision_at(scored: &[(BatchItem, i32, DisruptionResult)], now: u64) -> Decision {
     // 1) Split triggered items into positive/negative
     let mut triggers_pos = Vec::new();
     let mut triggers_neg = Vec::new();
@@ -121,7 +135,7 @@ pub fn make_decision(scored: &[(BatchItem, i32, DisruptionResult)]) -> Decision
     let mut contributors = Vec::new();
     for (it, score, res) in all.into_iter().take(3) {
         contributors.push(
-            Contributor::new(&it.source, &it.text, score, iso_now()).weights(
+            Contributor::new(&it.source, &it.text, score, iso_at(now)).weights(
                 res.w_source,
                 res.w_strength,
                 recency_weight(res.age_secs),
@@ -134,11 +148,58 @@ pub fn make_decision(scored: &[(BatchItem, i32, DisruptionResult)]) -> Decision
         confidence,
         reasons,
         top_contributors: contributors,
+        raw_decision: None,
+        raw_confidence: None,
+        signal: None,
+        session: Some(calendar::session_at(now)),
+    }
+}
+
+/// Volume factor from recent history: more recent BUY/SELL decisions from more
+/// distinct sources → slightly higher confidence. Returns
+/// `(factor in [0.90, 1.05], recent_triggers, unique_sources)`.
+pub fn volume_factor_from_history(hist: &History, now: u64) -> (f32, usize, usize) {
+    let rows = hist.snapshot_last_n(200);
+    let mut recent_triggers = 0usize;
+    let mut uniq: HashSet<String> = HashSet::new();
+
+    for h in rows {
+        if now.saturating_sub(h.ts_unix) <= VOLUME_WINDOW_SECS
+            && matches!(h.verdict, Verdict::Buy | Verdict::Sell)
+        {
+            recent_triggers += 1;
+            for s in h.top_sources.iter().take(5) {
+                uniq.insert(s.clone());
+            }
+        }
     }
+
+    let rt = recent_triggers.min(5) as f32;
+    let us = uniq.len().min(5) as f32;
+    let mut vf = 0.90 + 0.02 * rt + 0.01 * us;
+    vf = vf.clamp(0.90, 1.05);
+
+    (vf, recent_triggers, uniq.len())
+}
+
+/// Scale `decision.confidence` by the volume factor and explain it in a reason.
+pub fn apply_volume_context(decision: &mut Decision, hist: &History, now: u64) {
+    let (vf, recent_triggers, uniq_sources) = volume_factor_from_history(hist, now);
+    let old_conf = decision.confidence;
+    let new_conf = (old_conf * vf).clamp(0.0, 0.99);
+    decision.confidence = new_conf;
+    decision.reasons.push(
+        Reason::new(format!(
+            "Volume context (last {window}s): {rt} triggers from {us} sources -> confidence x{vf:.3} ({old:.3}->{new:.3})",
+            window = VOLUME_WINDOW_SECS, rt = recent_triggers, us = uniq_sources, vf = vf, old = old_conf, new = new_conf,
+        ))
+        .kind(ReasonKind::Threshold)
+        .weighted(((vf - 0.90) / (1.05 - 0.90)).clamp(0.0, 1.0)),
+    );
 }
 
 /// Soft, linear decay from 0..1800s (inclusive).
-fn recency_weight(age_secs: u64) -> f32 {
+pub(crate) fn recency_weight(age_secs: u64) -> f32 {
     if age_secs == 0 {
         1.0
     } else {
@@ -147,13 +208,8 @@ fn recency_weight(age_secs: u64) -> f32 {
 }
 
 /// Minimal ISO-like timestamp as `String` (keep dependencies at zero).
-fn iso_now() -> String {
-    use std::time::{SystemTime, UNIX_EPOCH};
-    let s = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_secs();
-    format!("{}Z", s)
+fn iso_at(unix: u64) -> String {
+    format!("{}Z", unix)
 }
 
 #[cfg(test)]
@@ -221,4 +277,15 @@ mod tests {
         let d = make_decision(&items);
         assert_eq!(d.decision, Verdict::Hold);
     }
+
+    #[test]
+    fn decision_is_tagged_with_session() {
+        use crate::calendar::SessionState;
+        let items = vec![(mk_item("Analyst", "meh"), 0, notrig(0.6, 0.0, 300))];
+        // Tue 2025-09-02 14:00 UTC = 10:00 EDT; Sat 2025-09-06 14:00 UTC.
+        let d = make_decision_at(&items, 1_756_821_600);
+        assert_eq!(d.session, Some(SessionState::Regular));
+        let d = make_decision_at(&items, 1_757_167_200);
+        assert_eq!(d.session, Some(SessionState::Closed));
+    }
 }
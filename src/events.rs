@@ -0,0 +1,558 @@
+//! # Economic Event Calendar
+//! Scheduled releases (CPI/NFP at 08:30 ET, FOMC at 14:00 ET, ...) that make
+//! the pipeline more sensitive around them.
+//!
+//! Inside an event window (`before_secs` .. `after_secs` around the event
+//! time), statements whose NER categories (file stems of `config/ner/*.json`,
+//! e.g. `inflation`, `rates`) match the event's categories get:
+//! - a relaxed relevance threshold (`relevance` delta),
+//! - additive boosts to `w_source` / `w_strength` in the disruption check,
+//! - an `Event window: ...` reason (`ReasonKind::Other`) on the decision.
+//!
+//! Sources: JSON (`config/events.json`) or ICS (`*.ics`), selected by file
+//! extension of `EVENT_CALENDAR_PATH`. A missing file means no events.
+//!
+//! JSON shape:
+//! ```json
+//! {
+//!   "window": { "before_secs": 900, "after_secs": 3600 },
+//!   "boosts": { "high": { "source": 0.10, "strength": 0.40, "relevance": 0.10 } },
+//!   "events": [
+//!     { "name": "CPI (Sep)", "kind": "CPI", "time": "2025-10-15T08:30:00-04:00",
+//!       "importance": "high", "categories": ["inflation"] }
+//!   ]
+//! }
+//! ```
+//! ICS: `SUMMARY`, `DTSTART` (UTC, `TZID=...` or floating = New York),
+//! `CATEGORIES`, `PRIORITY` (1–4 high, 5 medium, 6–9 low) or `X-IMPORTANCE`.
+
+use std::{fs, path::Path};
+
+use anyhow::{anyhow, Context};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone};
+use chrono_tz::America::New_York;
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_EVENTS_PATH: &str = "config/events.json";
+pub const ENV_EVENTS_PATH: &str = "EVENT_CALENDAR_PATH";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Importance {
+    Low,
+    Medium,
+    High,
+}
+
+impl Importance {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Importance::Low => "low",
+            Importance::Medium => "medium",
+            Importance::High => "high",
+        }
+    }
+}
+
+/// One scheduled release.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EconomicEvent {
+    pub name: String,
+    /// Short type label (`CPI`, `NFP`, `FOMC`, ...).
+    pub kind: String,
+    pub ts_unix: u64,
+    pub importance: Importance,
+    /// NER categories this event amplifies (lower-case file stems).
+    pub categories: Vec<String>,
+    /// Per-event window overrides.
+    pub before_secs: Option<u64>,
+    pub after_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WindowConfig {
+    #[serde(default = "default_before")]
+    pub before_secs: u64,
+    #[serde(default = "default_after")]
+    pub after_secs: u64,
+}
+
+fn default_before() -> u64 {
+    900
+}
+
+fn default_after() -> u64 {
+    3600
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        Self {
+            before_secs: default_before(),
+            after_secs: default_after(),
+        }
+    }
+}
+
+/// Additive boosts for one importance level.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct Boost {
+    /// Added to `w_source`.
+    #[serde(default)]
+    pub source: f32,
+    /// Added to `w_strength` (non-neutral scores only).
+    #[serde(default)]
+    pub strength: f32,
+    /// Subtracted from the relevance threshold.
+    #[serde(default)]
+    pub relevance: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Boosts {
+    #[serde(default = "default_high")]
+    pub high: Boost,
+    #[serde(default = "default_medium")]
+    pub medium: Boost,
+    #[serde(default = "default_low")]
+    pub low: Boost,
+}
+
+fn default_high() -> Boost {
+    Boost {
+        source: 0.10,
+        strength: 0.40,
+        relevance: 0.10,
+    }
+}
+
+fn default_medium() -> Boost {
+    Boost {
+        source: 0.05,
+        strength: 0.25,
+        relevance: 0.05,
+    }
+}
+
+fn default_low() -> Boost {
+    Boost {
+        source: 0.0,
+        strength: 0.10,
+        relevance: 0.0,
+    }
+}
+
+impl Default for Boosts {
+    fn default() -> Self {
+        Self {
+            high: default_high(),
+            medium: default_medium(),
+            low: default_low(),
+        }
+    }
+}
+
+impl Boosts {
+    pub fn for_importance(&self, i: Importance) -> Boost {
+        match i {
+            Importance::High => self.high,
+            Importance::Medium => self.medium,
+            Importance::Low => self.low,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct RawEvent {
+    name: String,
+    #[serde(default)]
+    kind: Option<String>,
+    #[serde(default)]
+    time: Option<String>,
+    #[serde(default)]
+    ts_unix: Option<u64>,
+    #[serde(default = "default_importance")]
+    importance: Importance,
+    #[serde(default)]
+    categories: Vec<String>,
+    #[serde(default)]
+    before_secs: Option<u64>,
+    #[serde(default)]
+    after_secs: Option<u64>,
+}
+
+fn default_importance() -> Importance {
+    Importance::Medium
+}
+
+#[derive(Deserialize, Default)]
+struct RawCalendar {
+    #[serde(default)]
+    window: WindowConfig,
+    #[serde(default)]
+    boosts: Boosts,
+    #[serde(default)]
+    events: Vec<RawEvent>,
+}
+
+/// Boost that applies to one statement.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventBoost {
+    pub event: String,
+    pub kind: String,
+    pub importance: Importance,
+    /// Matching category that activated the boost.
+    pub category: String,
+    pub boost: Boost,
+}
+
+impl EventBoost {
+    /// Human-readable reason text.
+    pub fn describe(&self) -> String {
+        format!(
+            "Event window: {} [{}] for {} (w_source +{:.2}, w_strength +{:.2}, relevance -{:.2})",
+            self.event,
+            self.importance.as_str(),
+            self.category,
+            self.boost.source,
+            self.boost.strength,
+            self.boost.relevance
+        )
+    }
+}
+
+/// Loaded events plus window/boost settings.
+#[derive(Debug, Clone, Default)]
+pub struct EventCalendar {
+    events: Vec<EconomicEvent>,
+    window: WindowConfig,
+    boosts: Boosts,
+}
+
+impl EventCalendar {
+    pub fn new(mut events: Vec<EconomicEvent>, window: WindowConfig, boosts: Boosts) -> Self {
+        events.sort_by_key(|e| e.ts_unix);
+        Self {
+            events,
+            window,
+            boosts,
+        }
+    }
+
+    /// Load from `EVENT_CALENDAR_PATH` (default `config/events.json`); empty on
+    /// missing file, logged and empty on parse errors.
+    pub fn from_env() -> Self {
+        let path = std::env::var(ENV_EVENTS_PATH).unwrap_or_else(|_| DEFAULT_EVENTS_PATH.into());
+        if !Path::new(&path).exists() {
+            return Self::default();
+        }
+        Self::load(&path).unwrap_or_else(|e| {
+            tracing::warn!("event calendar {path} not loaded: {e:#}");
+            Self::default()
+        })
+    }
+
+    /// Load JSON or ICS depending on the file extension.
+    pub fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let raw = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+        let is_ics = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case("ics"));
+        if is_ics {
+            Ok(Self::new(
+                parse_ics(&raw)?,
+                WindowConfig::default(),
+                Boosts::default(),
+            ))
+        } else {
+            Self::from_json_str(&raw)
+        }
+    }
+
+    pub fn from_json_str(raw: &str) -> anyhow::Result<Self> {
+        let cal: RawCalendar = serde_json::from_str(raw)?;
+        let events = cal
+            .events
+            .into_iter()
+            .map(|e| {
+                let ts_unix = match (e.ts_unix, e.time.as_deref()) {
+                    (Some(ts), _) => ts,
+                    (None, Some(t)) => {
+                        parse_time(t).with_context(|| format!("event {}", e.name))?
+                    }
+                    (None, None) => return Err(anyhow!("event {}: missing time", e.name)),
+                };
+                Ok(EconomicEvent {
+                    kind: e.kind.unwrap_or_else(|| kind_from_name(&e.name)),
+                    name: e.name,
+                    ts_unix,
+                    importance: e.importance,
+                    categories: lower(e.categories),
+                    before_secs: e.before_secs,
+                    after_secs: e.after_secs,
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(Self::new(events, cal.window, cal.boosts))
+    }
+
+    pub fn events(&self) -> &[EconomicEvent] {
+        &self.events
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Events whose window contains `now`.
+    pub fn active_at(&self, now: u64) -> impl Iterator<Item = &EconomicEvent> {
+        let w = self.window;
+        self.events.iter().filter(move |e| {
+            let before = e.before_secs.unwrap_or(w.before_secs);
+            let after = e.after_secs.unwrap_or(w.after_secs);
+            now + before >= e.ts_unix && now <= e.ts_unix + after
+        })
+    }
+
+    /// Strongest boost for a statement with NER `categories` at `now`.
+    pub fn boost_for(&self, categories: &[String], now: u64) -> Option<EventBoost> {
+        self.active_at(now)
+            .filter_map(|e| {
+                let cat = e
+                    .categories
+                    .iter()
+                    .find(|c| categories.iter().any(|x| x.eq_ignore_ascii_case(c)))?;
+                Some((e, cat))
+            })
+            .max_by_key(|(e, _)| e.importance)
+            .map(|(e, cat)| EventBoost {
+                event: e.name.clone(),
+                kind: e.kind.clone(),
+                importance: e.importance,
+                category: cat.clone(),
+                boost: self.boosts.for_importance(e.importance),
+            })
+    }
+}
+
+/// NER categories (`"inflation: CPI"` → `inflation`) of a text.
+pub fn ner_categories(text: &str) -> Vec<String> {
+    let mut cats: Vec<String> = crate::analyze::ner::extract_entities(text)
+        .into_iter()
+        .map(|e| e.category.to_ascii_lowercase())
+        .collect();
+    cats.sort();
+    cats.dedup();
+    cats
+}
+
+fn lower(v: Vec<String>) -> Vec<String> {
+    v.into_iter()
+        .map(|c| c.trim().to_ascii_lowercase())
+        .filter(|c| !c.is_empty())
+        .collect()
+}
+
+fn kind_from_name(name: &str) -> String {
+    name.split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_ascii_uppercase()
+}
+
+/// RFC3339, or `YYYY-MM-DD HH:MM[:SS]` in New York time.
+fn parse_time(s: &str) -> anyhow::Result<u64> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s.trim()) {
+        return Ok(dt.timestamp().max(0) as u64);
+    }
+    let naive = NaiveDateTime::parse_from_str(s.trim(), "%Y-%m-%d %H:%M:%S")
+        .or_else(|_| NaiveDateTime::parse_from_str(s.trim(), "%Y-%m-%d %H:%M"))
+        .map_err(|_| anyhow!("unrecognized time: {s}"))?;
+    local_to_unix(New_York, naive)
+}
+
+fn local_to_unix(tz: Tz, naive: NaiveDateTime) -> anyhow::Result<u64> {
+    tz.from_local_datetime(&naive)
+        .earliest()
+        .map(|dt| dt.timestamp().max(0) as u64)
+        .ok_or_else(|| anyhow!("nonexistent local time {naive}"))
+}
+
+/// `DTSTART[;TZID=..][;VALUE=DATE]:value`
+fn parse_ics_dtstart(params: &str, value: &str) -> anyhow::Result<u64> {
+    let value = value.trim();
+    if let Some(utc) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S")?;
+        return Ok(naive.and_utc().timestamp().max(0) as u64);
+    }
+    let tz: Tz = params
+        .split(';')
+        .find_map(|p| p.strip_prefix("TZID="))
+        .map(|id| id.parse::<Tz>().map_err(|e| anyhow!("TZID {id}: {e}")))
+        .transpose()?
+        .unwrap_or(New_York);
+    let naive = match NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S") {
+        Ok(n) => n,
+        Err(_) => NaiveDate::parse_from_str(value, "%Y%m%d")?
+            .and_hms_opt(0, 0, 0)
+            .expect("midnight"),
+    };
+    local_to_unix(tz, naive)
+}
+
+fn importance_from_ics(priority: Option<u8>, explicit: Option<&str>) -> Importance {
+    match explicit.map(|s| s.trim().to_ascii_lowercase()).as_deref() {
+        Some("high") => return Importance::High,
+        Some("medium") => return Importance::Medium,
+        Some("low") => return Importance::Low,
+        _ => {}
+    }
+    match priority {
+        Some(1..=4) => Importance::High,
+        Some(6..=9) => Importance::Low,
+        _ => Importance::Medium,
+    }
+}
+
+/// VEVENT properties collected until `END:VEVENT`.
+#[derive(Default)]
+struct VEventDraft {
+    summary: Option<String>,
+    ts_unix: Option<u64>,
+    categories: Vec<String>,
+    priority: Option<u8>,
+    importance: Option<String>,
+}
+
+/// Minimal VEVENT parser (line unfolding, the properties listed in the module docs).
+pub fn parse_ics(raw: &str) -> anyhow::Result<Vec<EconomicEvent>> {
+    // RFC 5545 line unfolding: continuation lines start with a space or tab.
+    let mut lines: Vec<String> = Vec::new();
+    for line in raw.lines() {
+        let line = line.trim_end_matches('\r');
+        if let Some(cont) = line.strip_prefix([' ', '\t']) {
+            if let Some(last) = lines.last_mut() {
+                last.push_str(cont);
+                continue;
+            }
+        }
+        lines.push(line.to_string());
+    }
+
+    let mut out = Vec::new();
+    let mut cur: Option<VEventDraft> = None;
+    for line in &lines {
+        match line.as_str() {
+            "BEGIN:VEVENT" => cur = Some(VEventDraft::default()),
+            "END:VEVENT" => {
+                let Some(ev) = cur.take() else { continue };
+                let name = ev
+                    .summary
+                    .ok_or_else(|| anyhow!("VEVENT without SUMMARY"))?;
+                let ts_unix = ev
+                    .ts_unix
+                    .ok_or_else(|| anyhow!("VEVENT {name} without DTSTART"))?;
+                out.push(EconomicEvent {
+                    kind: kind_from_name(&name),
+                    importance: importance_from_ics(ev.priority, ev.importance.as_deref()),
+                    name,
+                    ts_unix,
+                    categories: lower(ev.categories),
+                    before_secs: None,
+                    after_secs: None,
+                });
+            }
+            _ => {
+                let Some(ev) = cur.as_mut() else { continue };
+                let Some((key, value)) = line.split_once(':') else {
+                    continue;
+                };
+                let (name, params) = key.split_once(';').unwrap_or((key, ""));
+                match name.to_ascii_uppercase().as_str() {
+                    "SUMMARY" => ev.summary = Some(value.trim().replace("\\,", ",")),
+                    "DTSTART" => ev.ts_unix = Some(parse_ics_dtstart(params, value)?),
+                    "CATEGORIES" => ev
+                        .categories
+                        .extend(value.split(',').map(|c| c.trim().to_string())),
+                    "PRIORITY" => ev.priority = value.trim().parse().ok(),
+                    "X-IMPORTANCE" => ev.importance = Some(value.to_string()),
+                    _ => {}
+                }
+            }
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CPI_TS: u64 = 1_760_531_400; // 2025-10-15T08:30:00-04:00
+
+    fn cal() -> EventCalendar {
+        EventCalendar::from_json_str(
+            r#"{
+              "window": { "before_secs": 600, "after_secs": 1800 },
+              "events": [
+                { "name": "CPI (Sep)", "time": "2025-10-15T08:30:00-04:00",
+                  "importance": "high", "categories": ["Inflation"] },
+                { "name": "Fed speaker", "time": "2025-10-15 09:00",
+                  "importance": "low", "categories": ["rates"], "after_secs": 60 }
+              ]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn json_events_and_windows() {
+        let c = cal();
+        assert_eq!(c.events()[0].ts_unix, CPI_TS);
+        assert_eq!(c.events()[0].kind, "CPI");
+        assert_eq!(c.events()[1].ts_unix, CPI_TS + 1800);
+
+        let infl = vec!["inflation".to_string()];
+        assert!(c.boost_for(&infl, CPI_TS - 601).is_none());
+        let b = c.boost_for(&infl, CPI_TS - 600).unwrap();
+        assert_eq!(b.importance, Importance::High);
+        assert_eq!(b.boost, Boosts::default().high);
+        assert!(b.describe().starts_with("Event window: CPI (Sep) [high]"));
+        assert!(c.boost_for(&infl, CPI_TS + 1801).is_none());
+
+        // Category must match; per-event window override applies.
+        let rates = vec!["rates".to_string()];
+        assert!(c.boost_for(&rates, CPI_TS).is_none());
+        assert!(c.boost_for(&rates, CPI_TS + 1200).is_some());
+        assert!(c.boost_for(&rates, CPI_TS + 1800 + 61).is_none());
+        assert!(c.boost_for(&["earnings".to_string()], CPI_TS).is_none());
+    }
+
+    #[test]
+    fn ics_events_with_tz_and_priority() {
+        let ics = "BEGIN:VCALENDAR\r\n\
+BEGIN:VEVENT\r\n\
+SUMMARY:FOMC rate decision\r\n\
+DTSTART;TZID=America/New_York:20251029T140000\r\n\
+CATEGORIES:rates\r\n\
+PRIORITY:1\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+SUMMARY:NFP\r\n\
+DTSTART:20251107T133000Z\r\n\
+CATEGORIES:inflation,\r\n rates\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+        let ev = parse_ics(ics).unwrap();
+        assert_eq!(ev.len(), 2);
+        assert_eq!(ev[0].kind, "FOMC");
+        assert_eq!(ev[0].ts_unix, 1_761_760_800); // 18:00Z (EDT)
+        assert_eq!(ev[0].importance, Importance::High);
+        assert_eq!(ev[1].ts_unix, 1_762_522_200);
+        assert_eq!(ev[1].categories, vec!["inflation", "rates"]);
+        assert_eq!(ev[1].importance, Importance::Medium);
+    }
+}
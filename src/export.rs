@@ -0,0 +1,491 @@
+//! # Export (CSV / NDJSON)
+//! Flat dumps of `History` for notebooks, served by `/api/export` and written by
+//! the `export` binary (which reads a `HISTORY_STORE_DIR` directly).
+//!
+//! - `kind=decisions`: one row per decision, columns `DECISION_COLUMNS`.
+//! - `kind=contributors`: one row per contributor of each decision, `CONTRIBUTOR_COLUMNS`.
+//! - CSV joins lists with `;` (reasons with ` | `) and leaves missing values empty;
+//!   NDJSON uses the same keys in the same order, with arrays and `null`s.
+//! - The schema is stable: columns are only ever appended, never renamed or reordered.
+//! - The analyzer tracks one instrument (`EXPORT_INSTRUMENT`, default `YM`); every row
+//!   carries it, and asking for another one is an error rather than an empty file.
+//! - Optional gzip (`flate2`), e.g. `decisions-YM.csv.gz` for `pandas.read_csv`.
+
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde_json::{json, Value};
+
+use crate::history::{parse_time, HistoryEntry};
+
+/// Instrument the decisions are about (Dow futures).
+pub const DEFAULT_INSTRUMENT: &str = "YM";
+
+/// Columns of `kind=decisions`, in order.
+pub const DECISION_COLUMNS: [&str; 11] = [
+    "id",
+    "ts_unix",
+    "ts",
+    "instrument",
+    "verdict",
+    "signal",
+    "confidence",
+    "top_sources",
+    "top_scores",
+    "reasons",
+    "contributor_count",
+];
+
+/// Columns of `kind=contributors`, in order.
+pub const CONTRIBUTOR_COLUMNS: [&str; 13] = [
+    "decision_id",
+    "decision_ts_unix",
+    "instrument",
+    "verdict",
+    "rank",
+    "source",
+    "score",
+    "text",
+    "ts",
+    "url",
+    "w_source",
+    "w_strength",
+    "w_recency",
+];
+
+/// `EXPORT_INSTRUMENT`, or `YM`.
+pub fn instrument_from_env() -> String {
+    std::env::var("EXPORT_INSTRUMENT")
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| DEFAULT_INSTRUMENT.to_string())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Ndjson,
+}
+
+impl ExportFormat {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "csv" => Some(Self::Csv),
+            "ndjson" | "jsonl" => Some(Self::Ndjson),
+            _ => None,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Ndjson => "ndjson",
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Ndjson => "application/x-ndjson",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportKind {
+    Decisions,
+    Contributors,
+}
+
+impl ExportKind {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "decisions" => Some(Self::Decisions),
+            "contributors" => Some(Self::Contributors),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Decisions => "decisions",
+            Self::Contributors => "contributors",
+        }
+    }
+
+    pub fn columns(self) -> &'static [&'static str] {
+        match self {
+            Self::Decisions => &DECISION_COLUMNS,
+            Self::Contributors => &CONTRIBUTOR_COLUMNS,
+        }
+    }
+}
+
+/// What to export and how.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportQuery {
+    /// Inclusive lower bound on the decision's `ts_unix`.
+    pub from: Option<u64>,
+    /// Exclusive upper bound on the decision's `ts_unix`.
+    pub to: Option<u64>,
+    /// Requested instrument; must be the tracked one when set.
+    pub instrument: Option<String>,
+    pub kind: ExportKind,
+    pub format: ExportFormat,
+    pub gzip: bool,
+}
+
+impl Default for ExportQuery {
+    fn default() -> Self {
+        Self {
+            from: None,
+            to: None,
+            instrument: None,
+            kind: ExportKind::Decisions,
+            format: ExportFormat::Csv,
+            gzip: false,
+        }
+    }
+}
+
+impl ExportQuery {
+    /// Build from query parameters (`from`, `to`, `instrument`, `kind`, `format`, `gzip`).
+    pub fn from_query(q: &HashMap<String, String>) -> Result<Self, String> {
+        let mut eq = Self::default();
+        if let Some(v) = q.get("from").filter(|s| !s.trim().is_empty()) {
+            eq.from = Some(parse_time("from", v)?);
+        }
+        if let Some(v) = q.get("to").filter(|s| !s.trim().is_empty()) {
+            eq.to = Some(parse_time("to", v)?);
+        }
+        if let (Some(from), Some(to)) = (eq.from, eq.to) {
+            if from >= to {
+                return Err(format!("from ({from}) must be before to ({to})"));
+            }
+        }
+        eq.instrument = q
+            .get("instrument")
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty());
+        if let Some(v) = q.get("kind") {
+            eq.kind = ExportKind::parse(v).ok_or_else(|| {
+                format!("kind: expected `decisions` or `contributors`, got `{v}`")
+            })?;
+        }
+        if let Some(v) = q.get("format") {
+            eq.format = ExportFormat::parse(v)
+                .ok_or_else(|| format!("format: expected `csv` or `ndjson`, got `{v}`"))?;
+        }
+        if let Some(v) = q.get("gzip") {
+            eq.gzip = match v.trim() {
+                "1" | "true" => true,
+                "" | "0" | "false" => false,
+                _ => {
+                    return Err(format!(
+                        "gzip: expected `1`/`true` or `0`/`false`, got `{v}`"
+                    ))
+                }
+            };
+        }
+        Ok(eq)
+    }
+
+    /// Error unless the requested instrument (if any) is `tracked`.
+    pub fn check_instrument(&self, tracked: &str) -> Result<(), String> {
+        match &self.instrument {
+            Some(i) if !i.eq_ignore_ascii_case(tracked) => Err(format!(
+                "instrument: `{i}` is not tracked (decisions are for `{tracked}`)"
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn in_range(&self, e: &HistoryEntry) -> bool {
+        self.from.is_none_or(|from| e.ts_unix >= from) && self.to.is_none_or(|to| e.ts_unix < to)
+    }
+
+    /// Suggested download name, e.g. `decisions-YM.csv.gz`.
+    pub fn file_name(&self, instrument: &str) -> String {
+        let gz = if self.gzip { ".gz" } else { "" };
+        format!(
+            "{}-{}.{}{}",
+            self.kind.as_str(),
+            instrument,
+            self.format.extension(),
+            gz
+        )
+    }
+}
+
+/// Write the entries (oldest first) in range as rows; returns the number of rows.
+/// A CSV header is written even when nothing matches.
+pub fn write_export<W: Write>(
+    entries: &[HistoryEntry],
+    q: &ExportQuery,
+    instrument: &str,
+    out: W,
+) -> io::Result<usize> {
+    if q.gzip {
+        let mut gz = GzEncoder::new(out, Compression::default());
+        let rows = write_rows(entries, q, instrument, &mut gz)?;
+        gz.finish()?.flush()?;
+        Ok(rows)
+    } else {
+        let mut out = out;
+        let rows = write_rows(entries, q, instrument, &mut out)?;
+        out.flush()?;
+        Ok(rows)
+    }
+}
+
+fn write_rows<W: Write>(
+    entries: &[HistoryEntry],
+    q: &ExportQuery,
+    instrument: &str,
+    out: &mut W,
+) -> io::Result<usize> {
+    let columns = q.kind.columns();
+    if q.format == ExportFormat::Csv {
+        writeln!(out, "{}", columns.join(","))?;
+    }
+    let mut rows = 0;
+    for e in entries.iter().filter(|e| q.in_range(e)) {
+        let values = match q.kind {
+            ExportKind::Decisions => vec![decision_row(e, instrument)],
+            ExportKind::Contributors => contributor_rows(e, instrument),
+        };
+        for row in values {
+            match q.format {
+                ExportFormat::Csv => write_csv_row(out, columns, &row)?,
+                ExportFormat::Ndjson => write_ndjson_row(out, columns, &row)?,
+            }
+            rows += 1;
+        }
+    }
+    Ok(rows)
+}
+
+/// `f32` as written by serde (`0.7`, not the widened `0.699999988079071`).
+fn num(x: f32) -> Value {
+    x.to_string()
+        .parse::<f64>()
+        .ok()
+        .and_then(serde_json::Number::from_f64)
+        .map_or(Value::Null, Value::Number)
+}
+
+fn iso(ts_unix: u64) -> Value {
+    chrono::DateTime::from_timestamp(ts_unix as i64, 0)
+        .map(|t| Value::String(t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)))
+        .unwrap_or(Value::Null)
+}
+
+/// Values in `DECISION_COLUMNS` order.
+fn decision_row(e: &HistoryEntry, instrument: &str) -> Vec<Value> {
+    vec![
+        json!(e.id),
+        json!(e.ts_unix),
+        iso(e.ts_unix),
+        json!(instrument),
+        json!(e.verdict),
+        json!(e.signal),
+        num(e.confidence),
+        json!(e.top_sources),
+        json!(e.top_scores),
+        json!(e.reasons.iter().map(|r| &r.message).collect::<Vec<_>>()),
+        json!(e.contributors.len()),
+    ]
+}
+
+/// Values in `CONTRIBUTOR_COLUMNS` order, one row per contributor (rank from 1).
+fn contributor_rows(e: &HistoryEntry, instrument: &str) -> Vec<Vec<Value>> {
+    e.contributors
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            vec![
+                json!(e.id),
+                json!(e.ts_unix),
+                json!(instrument),
+                json!(e.verdict),
+                json!(i + 1),
+                json!(c.source),
+                json!(c.score),
+                json!(c.text),
+                json!(c.ts_iso),
+                json!(c.url),
+                c.w_source.map_or(Value::Null, num),
+                c.w_strength.map_or(Value::Null, num),
+                c.w_recency.map_or(Value::Null, num),
+            ]
+        })
+        .collect()
+}
+
+/// Quote a CSV field when it contains a separator, quote or line break.
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+fn csv_value(column: &str, v: &Value) -> String {
+    let text = match v {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Array(items) => {
+            let sep = if column == "reasons" { " | " } else { ";" };
+            items
+                .iter()
+                .map(|i| match i {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(sep)
+        }
+        other => other.to_string(),
+    };
+    csv_field(&text)
+}
+
+fn write_csv_row<W: Write>(out: &mut W, columns: &[&str], row: &[Value]) -> io::Result<()> {
+    let fields: Vec<String> = columns
+        .iter()
+        .zip(row)
+        .map(|(c, v)| csv_value(c, v))
+        .collect();
+    writeln!(out, "{}", fields.join(","))
+}
+
+/// Object with the keys in column order (not `serde_json::Map`'s sorted order).
+fn write_ndjson_row<W: Write>(out: &mut W, columns: &[&str], row: &[Value]) -> io::Result<()> {
+    let fields: Vec<String> = columns
+        .iter()
+        .zip(row)
+        .map(|(c, v)| format!("{}:{}", Value::from(*c), v))
+        .collect();
+    writeln!(out, "{{{}}}", fields.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::*;
+    use crate::decision::{Contributor, Reason, Verdict};
+    use crate::grade::SignalGrade;
+
+    fn entry(id: u64, ts_unix: u64) -> HistoryEntry {
+        HistoryEntry {
+            id,
+            ts_unix,
+            verdict: Verdict::Buy,
+            confidence: 0.7,
+            signal: Some(SignalGrade::Buy),
+            top_sources: vec!["Fed".into(), "Reuters".into()],
+            top_scores: vec![3, 1],
+            reasons: vec![
+                Reason::new("Fed said \"cuts\", markets cheered"),
+                Reason::new("Consensus"),
+            ],
+            contributors: vec![
+                Contributor::new("Fed", "Rate cuts, soon", 3, "2025-09-01T13:30:00Z")
+                    .url("https://example.com/fed"),
+                Contributor::new("Reuters", "Dow futures up", 1, "2025-09-01T13:29:00Z"),
+            ],
+        }
+    }
+
+    fn export(entries: &[HistoryEntry], q: &ExportQuery) -> String {
+        let mut buf = Vec::new();
+        write_export(entries, q, "YM", &mut buf).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn decisions_csv_has_stable_header_and_quoted_fields() {
+        let out = export(&[entry(1, 1_756_733_400)], &ExportQuery::default());
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(
+            lines[0],
+            "id,ts_unix,ts,instrument,verdict,signal,confidence,top_sources,top_scores,reasons,contributor_count"
+        );
+        assert_eq!(
+            lines[1],
+            "1,1756733400,2025-09-01T13:30:00Z,YM,BUY,BUY,0.7,Fed;Reuters,3;1,\"Fed said \"\"cuts\"\", markets cheered | Consensus\",2"
+        );
+    }
+
+    #[test]
+    fn contributors_ndjson_keeps_column_order_and_nulls() {
+        let q = ExportQuery {
+            kind: ExportKind::Contributors,
+            format: ExportFormat::Ndjson,
+            ..ExportQuery::default()
+        };
+        let out = export(&[entry(7, 100)], &q);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with(r#"{"decision_id":7,"decision_ts_unix":100,"instrument":"YM","verdict":"BUY","rank":1,"source":"Fed""#));
+        let second: Value = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(second["rank"], 2);
+        assert_eq!(second["url"], Value::Null);
+        let keys: Vec<&str> = CONTRIBUTOR_COLUMNS.to_vec();
+        assert_eq!(second.as_object().unwrap().len(), keys.len());
+    }
+
+    #[test]
+    fn range_filters_and_gzip_round_trips() {
+        let entries = [entry(1, 100), entry(2, 200), entry(3, 300)];
+        let q = ExportQuery {
+            from: Some(200),
+            to: Some(300),
+            gzip: true,
+            ..ExportQuery::default()
+        };
+        let mut buf = Vec::new();
+        assert_eq!(write_export(&entries, &q, "YM", &mut buf).unwrap(), 1);
+        let mut csv = String::new();
+        flate2::read::GzDecoder::new(&buf[..])
+            .read_to_string(&mut csv)
+            .unwrap();
+        assert_eq!(csv.lines().count(), 2);
+        assert!(csv.lines().nth(1).unwrap().starts_with("2,200,"));
+        assert_eq!(q.file_name("YM"), "decisions-YM.csv.gz");
+    }
+
+    #[test]
+    fn query_parsing_and_instrument_check() {
+        let q: HashMap<String, String> = [
+            ("kind", "contributors"),
+            ("format", "ndjson"),
+            ("gzip", "1"),
+            ("instrument", "ym"),
+            ("from", "2025-09-01T13:30:00Z"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        let eq = ExportQuery::from_query(&q).unwrap();
+        assert_eq!(eq.kind, ExportKind::Contributors);
+        assert_eq!(eq.format, ExportFormat::Ndjson);
+        assert!(eq.gzip);
+        assert_eq!(eq.from, Some(1_756_733_400));
+        assert!(eq.check_instrument("YM").is_ok());
+
+        let other = ExportQuery {
+            instrument: Some("ES".into()),
+            ..ExportQuery::default()
+        };
+        assert!(other.check_instrument("YM").is_err());
+
+        let bad: HashMap<String, String> = [("format".to_string(), "xlsx".to_string())].into();
+        assert!(ExportQuery::from_query(&bad).is_err());
+    }
+}
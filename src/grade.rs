@@ -0,0 +1,186 @@
+//! # Signal Grades
+//! Five-level signal scale (`STRONG_BUY` … `STRONG_SELL`) derived from the
+//! three-valued `Verdict` plus the final confidence.
+//!
+//! The verdict stays the primary, backward-compatible field; the grade is an
+//! additional label for execution layers that size by conviction.
+//!
+//! JSON shape (`config/grades.json`, path overridable via `SIGNAL_GRADES_PATH`):
+//! ```json
+//! { "strong_min": 0.85, "min": 0.0 }
+//! ```
+//! - directional verdict with `confidence >= strong_min` → `STRONG_*`
+//! - directional verdict with `confidence >= min`        → `BUY` / `SELL`
+//! - anything else                                        → `HOLD`
+
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+use crate::decision::Verdict;
+
+pub const DEFAULT_GRADES_PATH: &str = "config/grades.json";
+pub const ENV_GRADES_PATH: &str = "SIGNAL_GRADES_PATH";
+
+/// Graded signal derived from verdict + confidence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SignalGrade {
+    StrongBuy,
+    Buy,
+    Hold,
+    Sell,
+    StrongSell,
+}
+
+impl SignalGrade {
+    /// Collapse back to the three-valued verdict.
+    pub fn verdict(self) -> Verdict {
+        match self {
+            SignalGrade::StrongBuy | SignalGrade::Buy => Verdict::Buy,
+            SignalGrade::Hold => Verdict::Hold,
+            SignalGrade::Sell | SignalGrade::StrongSell => Verdict::Sell,
+        }
+    }
+
+    /// Wire label (same as the serde representation).
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SignalGrade::StrongBuy => "STRONG_BUY",
+            SignalGrade::Buy => "BUY",
+            SignalGrade::Hold => "HOLD",
+            SignalGrade::Sell => "SELL",
+            SignalGrade::StrongSell => "STRONG_SELL",
+        }
+    }
+
+    /// Lenient parse: accepts `STRONG_BUY`, `strong-buy`, `Strong Buy`, ...
+    pub fn parse(s: &str) -> Option<Self> {
+        let norm = s.trim().to_ascii_uppercase().replace(['-', ' '], "_");
+        match norm.as_str() {
+            "STRONG_BUY" => Some(SignalGrade::StrongBuy),
+            "BUY" => Some(SignalGrade::Buy),
+            "HOLD" => Some(SignalGrade::Hold),
+            "SELL" => Some(SignalGrade::Sell),
+            "STRONG_SELL" => Some(SignalGrade::StrongSell),
+            _ => None,
+        }
+    }
+}
+
+/// Confidence-to-grade mapping.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GradeScale {
+    /// Minimum confidence for `STRONG_BUY` / `STRONG_SELL`.
+    #[serde(default = "default_strong_min")]
+    pub strong_min: f32,
+    /// Minimum confidence for a directional grade; below it the grade is `HOLD`.
+    #[serde(default)]
+    pub min: f32,
+}
+
+fn default_strong_min() -> f32 {
+    0.85
+}
+
+impl Default for GradeScale {
+    fn default() -> Self {
+        Self {
+            strong_min: default_strong_min(),
+            min: 0.0,
+        }
+    }
+}
+
+impl GradeScale {
+    /// Load from JSON; falls back to defaults if the file is missing or invalid.
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Self {
+        let path = path.as_ref();
+        let parsed = fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|s| serde_json::from_str::<GradeScale>(&s).map_err(|e| e.to_string()));
+        match parsed {
+            Ok(scale) => scale.sanitized(),
+            Err(e) => {
+                tracing::debug!(
+                    "grades config {} not used ({e}); using defaults",
+                    path.display()
+                );
+                Self::default()
+            }
+        }
+    }
+
+    /// Load from `SIGNAL_GRADES_PATH` (default `config/grades.json`).
+    pub fn from_env() -> Self {
+        let path =
+            std::env::var(ENV_GRADES_PATH).unwrap_or_else(|_| DEFAULT_GRADES_PATH.to_string());
+        Self::load_from_file(path)
+    }
+
+    /// Map a verdict and its confidence to a grade.
+    pub fn grade(&self, verdict: Verdict, confidence: f32) -> SignalGrade {
+        let strong = confidence >= self.strong_min;
+        let directional = confidence >= self.min;
+        match verdict {
+            Verdict::Buy if strong => SignalGrade::StrongBuy,
+            Verdict::Buy if directional => SignalGrade::Buy,
+            Verdict::Sell if strong => SignalGrade::StrongSell,
+            Verdict::Sell if directional => SignalGrade::Sell,
+            _ => SignalGrade::Hold,
+        }
+    }
+
+    fn sanitized(mut self) -> Self {
+        self.strong_min = self.strong_min.clamp(0.0, 1.0);
+        self.min = self.min.clamp(0.0, 1.0);
+        if self.min > self.strong_min {
+            std::mem::swap(&mut self.min, &mut self.strong_min);
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_scale_grades_by_confidence() {
+        let s = GradeScale::default();
+        assert_eq!(s.grade(Verdict::Buy, 0.90), SignalGrade::StrongBuy);
+        assert_eq!(s.grade(Verdict::Buy, 0.70), SignalGrade::Buy);
+        assert_eq!(s.grade(Verdict::Sell, 0.85), SignalGrade::StrongSell);
+        assert_eq!(s.grade(Verdict::Sell, 0.60), SignalGrade::Sell);
+        assert_eq!(s.grade(Verdict::Hold, 0.99), SignalGrade::Hold);
+    }
+
+    #[test]
+    fn weak_directional_degrades_to_hold() {
+        let s = GradeScale {
+            strong_min: 0.9,
+            min: 0.6,
+        };
+        assert_eq!(s.grade(Verdict::Buy, 0.55), SignalGrade::Hold);
+        assert_eq!(s.grade(Verdict::Sell, 0.65), SignalGrade::Sell);
+    }
+
+    #[test]
+    fn serde_labels_and_parse_roundtrip() {
+        for g in [
+            SignalGrade::StrongBuy,
+            SignalGrade::Buy,
+            SignalGrade::Hold,
+            SignalGrade::Sell,
+            SignalGrade::StrongSell,
+        ] {
+            let json = serde_json::to_string(&g).unwrap();
+            assert_eq!(json, format!("\"{}\"", g.as_str()));
+            assert_eq!(SignalGrade::parse(g.as_str()), Some(g));
+        }
+        assert_eq!(
+            SignalGrade::parse("strong-sell"),
+            Some(SignalGrade::StrongSell)
+        );
+        assert_eq!(SignalGrade::StrongSell.verdict(), Verdict::Sell);
+    }
+}
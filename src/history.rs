@@ -3,26 +3,176 @@
 //! potential future anti-flutter/alert logic.
 //!
 //! - Capacity-limited circular buffer (max 10,000).
-//! - Stores verdict, confidence, and lightweight contributor fingerprints
-//!   (sources and scores).
-//! - Intended for quick debugging, transparency, and context checks.
+//! - Stores verdict, confidence, reasons, lightweight contributor fingerprints
+//!   (sources and scores) and the full contributor list (evidence, audits).
+//! - Every entry gets a monotonically increasing `id` (pagination cursor).
+//! - Queried by `/api/history` through `HistoryQuery` (time range, verdicts,
+//!   minimum confidence, source substring; newest first).
+//! - Optionally publishes each push (and verdict changes) to a `stream::DecisionBus`.
+//! - Optionally persists each push to a `store::DecisionStore` (replayed via `restore`);
+//!   `query_durable` then pages on into the store once the ring is exhausted.
 
-use std::sync::Mutex;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::collections::HashMap;
+use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 
-use crate::decision::{Decision, Verdict};
+use serde::{Deserialize, Serialize};
 
-/// Compact record of a past decision.
-/// Used for quick lookback (no full explainability retained).
-#[derive(Debug, Clone)]
+use crate::clock::{self, SharedClock};
+use crate::decision::{Contributor, Decision, Reason, Verdict};
+use crate::grade::SignalGrade;
+use crate::store::{self, DecisionStore, Record};
+use crate::stream::{DecisionBus, StreamEvent};
+
+/// Record of a past decision, with enough explainability to audit it later.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct HistoryEntry {
+    /// Position in the log (increasing; `0` on entries written before ids existed).
+    #[serde(default)]
+    pub id: u64,
     pub ts_unix: u64,
     pub verdict: Verdict,
     pub confidence: f32,
+    /// Graded signal, when the decision was graded.
+    pub signal: Option<SignalGrade>,
     /// Top contributor sources (e.g., `["Trump", "Fed"]`).
     pub top_sources: Vec<String>,
     /// Their corresponding scores (e.g., `[2, -3]`).
     pub top_scores: Vec<i32>,
+    /// Reasons attached to the decision when it was made.
+    #[serde(default)]
+    pub reasons: Vec<Reason>,
+    /// All contributors of the decision with text and URL (evidence for `/analyze`).
+    pub contributors: Vec<Contributor>,
+}
+
+/// Largest page `History::query` returns.
+pub const MAX_QUERY_LIMIT: usize = 500;
+
+/// Filters and paging for `History::query` (all optional; results newest first).
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryQuery {
+    /// Inclusive lower bound on `ts_unix`.
+    pub from: Option<u64>,
+    /// Exclusive upper bound on `ts_unix`.
+    pub to: Option<u64>,
+    /// Keep only these verdicts (empty = all).
+    pub verdicts: Vec<Verdict>,
+    pub min_confidence: Option<f32>,
+    /// Case-insensitive substring of any contributor source.
+    pub source: Option<String>,
+    /// Only entries with `id` below this (the previous page's `next_cursor`).
+    pub cursor: Option<u64>,
+    pub limit: usize,
+}
+
+impl Default for HistoryQuery {
+    fn default() -> Self {
+        Self {
+            from: None,
+            to: None,
+            verdicts: Vec::new(),
+            min_confidence: None,
+            source: None,
+            cursor: None,
+            limit: 50,
+        }
+    }
+}
+
+/// Unix seconds or an RFC 3339 timestamp (`key` names the parameter in errors).
+pub fn parse_time(key: &str, v: &str) -> Result<u64, String> {
+    let v = v.trim();
+    if let Ok(ts) = v.parse::<u64>() {
+        return Ok(ts);
+    }
+    chrono::DateTime::parse_from_rfc3339(v)
+        .ok()
+        .and_then(|t| u64::try_from(t.timestamp()).ok())
+        .ok_or_else(|| format!("{key}: expected unix seconds or RFC 3339, got `{v}`"))
+}
+
+impl HistoryQuery {
+    /// Build from query parameters (`from`, `to`, `verdict`, `min_confidence`,
+    /// `source`, `cursor`, `limit`); malformed values are an error.
+    pub fn from_query(q: &HashMap<String, String>) -> Result<Self, String> {
+        let mut hq = Self::default();
+        if let Some(v) = q.get("from").filter(|s| !s.trim().is_empty()) {
+            hq.from = Some(parse_time("from", v)?);
+        }
+        if let Some(v) = q.get("to").filter(|s| !s.trim().is_empty()) {
+            hq.to = Some(parse_time("to", v)?);
+        }
+        if let (Some(from), Some(to)) = (hq.from, hq.to) {
+            if from >= to {
+                return Err(format!("from ({from}) must be before to ({to})"));
+            }
+        }
+        if let Some(vs) = q.get("verdict").filter(|s| !s.trim().is_empty()) {
+            for v in vs.split(',').map(str::trim).filter(|v| !v.is_empty()) {
+                hq.verdicts.push(match v.to_ascii_uppercase().as_str() {
+                    "BUY" => Verdict::Buy,
+                    "SELL" => Verdict::Sell,
+                    "HOLD" => Verdict::Hold,
+                    _ => return Err(format!("verdict: unknown verdict `{v}`")),
+                });
+            }
+        }
+        if let Some(v) = q.get("min_confidence") {
+            let c = v
+                .trim()
+                .parse::<f32>()
+                .ok()
+                .filter(|c| (0.0..=1.0).contains(c))
+                .ok_or_else(|| format!("min_confidence: expected 0..1, got `{v}`"))?;
+            hq.min_confidence = Some(c);
+        }
+        hq.source = q
+            .get("source")
+            .map(|s| s.trim().to_lowercase())
+            .filter(|s| !s.is_empty());
+        if let Some(v) = q.get("cursor").filter(|s| !s.trim().is_empty()) {
+            let c = v
+                .trim()
+                .parse::<u64>()
+                .map_err(|_| format!("cursor: expected a `next_cursor` value, got `{v}`"))?;
+            hq.cursor = Some(c);
+        }
+        if let Some(v) = q.get("limit") {
+            hq.limit = v
+                .trim()
+                .parse::<usize>()
+                .ok()
+                .filter(|n| (1..=MAX_QUERY_LIMIT).contains(n))
+                .ok_or_else(|| format!("limit: expected 1..={MAX_QUERY_LIMIT}, got `{v}`"))?;
+        }
+        Ok(hq)
+    }
+
+    /// Whether `e` passes the filters (paging aside).
+    pub fn matches(&self, e: &HistoryEntry) -> bool {
+        self.from.is_none_or(|from| e.ts_unix >= from)
+            && self.to.is_none_or(|to| e.ts_unix < to)
+            && (self.verdicts.is_empty() || self.verdicts.contains(&e.verdict))
+            && self.min_confidence.is_none_or(|c| e.confidence >= c)
+            && self.source.as_deref().is_none_or(|needle| {
+                e.contributors
+                    .iter()
+                    .map(|c| &c.source)
+                    .chain(&e.top_sources)
+                    .any(|s| s.to_lowercase().contains(needle))
+            })
+    }
+}
+
+/// One page of `History::query`.
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct HistoryPage {
+    /// Matching entries, newest first.
+    pub entries: Vec<HistoryEntry>,
+    /// Pass as `cursor` for the next page; absent on the last page.
+    pub next_cursor: Option<u64>,
 }
 
 /// Fixed-capacity in-memory buffer of past decisions.
@@ -31,6 +181,10 @@ pub struct HistoryEntry {
 pub struct History {
     inner: Mutex<Vec<HistoryEntry>>,
     cap: usize,
+    clock: SharedClock,
+    bus: Option<DecisionBus>,
+    store: Option<Arc<DecisionStore>>,
+    next_id: AtomicU64,
 }
 
 impl History {
@@ -39,14 +193,64 @@ impl History {
         Self {
             inner: Mutex::new(Vec::with_capacity(cap.min(10_000))),
             cap: cap.min(10_000),
+            clock: clock::system(),
+            bus: None,
+            store: None,
+            next_id: AtomicU64::new(1),
         }
     }
 
+    /// Use `clock` for the timestamps of `push`.
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Publish every pushed decision, and verdict changes, to `bus` (`/api/stream`).
+    pub fn with_bus(mut self, bus: DecisionBus) -> Self {
+        self.bus = Some(bus);
+        self
+    }
+
+    /// Append every pushed entry to `store` (durable history).
+    pub fn with_store(mut self, store: Arc<DecisionStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Load replayed entries (oldest first) without persisting or publishing them.
+    /// Entries without an id (older logs) are numbered after their predecessor.
+    pub fn restore(&self, entries: Vec<HistoryEntry>) {
+        let mut v = self.inner.lock().expect("history mutex poisoned");
+        let mut last = v.last().map_or(0, |e| e.id);
+        for mut e in entries {
+            if e.id <= last {
+                e.id = last + 1;
+            }
+            last = e.id;
+            v.push(e);
+        }
+        self.next_id.fetch_max(last + 1, Ordering::Relaxed);
+        if v.len() > self.cap {
+            let excess = v.len() - self.cap;
+            v.drain(0..excess);
+        }
+    }
+
+    /// Maximum number of entries kept.
+    pub fn capacity(&self) -> usize {
+        self.cap
+    }
+
     /// Append a decision snapshot to history.
     ///
     /// Keeps only the last `cap` entries; drains older items if needed.
     pub fn push(&self, d: &Decision) {
-        let ts = now_unix();
+        self.push_at(d, self.clock.now_unix());
+    }
+
+    /// Same as `push`, with an explicit timestamp (offline replay/backtests).
+    pub fn push_at(&self, d: &Decision, ts: u64) {
         let (sources, scores) = {
             let mut s = Vec::new();
             let mut sc = Vec::new();
@@ -57,19 +261,50 @@ impl History {
             (s, sc)
         };
 
-        let entry = HistoryEntry {
+        let mut entry = HistoryEntry {
+            id: 0, // assigned under the lock, so ids follow the log order
             ts_unix: ts,
             verdict: d.decision,
             confidence: d.confidence,
+            signal: d.signal,
             top_sources: sources,
             top_scores: scores,
+            reasons: d.reasons.clone(),
+            contributors: d.top_contributors.clone(),
         };
 
-        let mut v = self.inner.lock().expect("history mutex poisoned");
-        v.push(entry);
-        if v.len() > self.cap {
-            let excess = v.len() - self.cap;
-            v.drain(0..excess);
+        let previous = {
+            let mut v = self.inner.lock().expect("history mutex poisoned");
+            entry.id = self.next_id.fetch_add(1, Ordering::Relaxed);
+            // Queued under the lock, so the log has the same order as memory.
+            if let Some(store) = &self.store {
+                store.append(&Record::History(entry.clone()));
+            }
+            let previous = v.last().map(|e| (e.verdict, e.signal));
+            v.push(entry);
+            if v.len() > self.cap {
+                let excess = v.len() - self.cap;
+                v.drain(0..excess);
+            }
+            previous
+        };
+
+        if let Some(bus) = &self.bus {
+            bus.publish(StreamEvent::Decision {
+                ts_unix: ts,
+                decision: d.clone(),
+            });
+            // A grade move within one verdict (BUY -> STRONG_BUY) counts as a change.
+            if let Some((from, from_signal)) = previous.filter(|p| *p != (d.decision, d.signal)) {
+                bus.publish(StreamEvent::VerdictChange {
+                    ts_unix: ts,
+                    from,
+                    to: d.decision,
+                    confidence: d.confidence,
+                    from_signal,
+                    signal: d.signal,
+                });
+            }
         }
     }
 
@@ -80,12 +315,311 @@ impl History {
         let start = len.saturating_sub(n);
         v[start..].to_vec()
     }
+
+    /// Entries matching `q`, newest first, at most `q.limit` of them.
+    pub fn query(&self, q: &HistoryQuery) -> HistoryPage {
+        let v = self.inner.lock().expect("history mutex poisoned");
+        Self::page_of(&v, q)
+    }
+
+    fn page_of(v: &[HistoryEntry], q: &HistoryQuery) -> HistoryPage {
+        let mut matching = v
+            .iter()
+            .rev()
+            .filter(|e| q.cursor.is_none_or(|c| e.id < c))
+            .filter(|e| q.matches(e));
+        let entries: Vec<HistoryEntry> = matching.by_ref().take(q.limit).cloned().collect();
+        let next_cursor = match (entries.last(), matching.next()) {
+            (Some(last), Some(_)) => Some(last.id),
+            _ => None,
+        };
+        HistoryPage {
+            entries,
+            next_cursor,
+        }
+    }
+
+    /// Every recorded entry, oldest first: the durable store's history (if any) plus
+    /// ring entries it does not hold. Reads the segment files, so call it off the
+    /// async runtime.
+    pub fn durable_entries(&self) -> io::Result<Vec<HistoryEntry>> {
+        let Some(store) = &self.store else {
+            return Ok(self.snapshot_last_n(self.cap));
+        };
+        store.flush();
+        let mut all = store::read_history(store.config(), self.clock.now_unix())?;
+        let ring = self.snapshot_last_n(self.cap);
+        let (Some(first), Some(last)) = (all.first().map(|e| e.id), all.last().map(|e| e.id))
+        else {
+            return Ok(ring);
+        };
+        // The ring can outlive the store's retention, and hold pushes made after the read.
+        let older: Vec<HistoryEntry> = ring.iter().filter(|e| e.id < first).cloned().collect();
+        all.splice(0..0, older);
+        all.extend(ring.into_iter().filter(|e| e.id > last));
+        Ok(all)
+    }
+
+    /// Like `query`, but once the in-memory ring has no more matches the page continues
+    /// with older entries from the durable store (if any, and only after the ring has
+    /// dropped entries). Reads the segment files, so call it off the async runtime.
+    pub fn query_durable(&self, q: &HistoryQuery) -> io::Result<HistoryPage> {
+        let (mut page, oldest) = {
+            let v = self.inner.lock().expect("history mutex poisoned");
+            let page = Self::page_of(&v, q);
+            let evicted = v.len() >= self.cap;
+            (page, v.first().map(|e| e.id).filter(|_| evicted))
+        };
+        let (Some(store), Some(oldest), None) = (&self.store, oldest, page.next_cursor) else {
+            return Ok(page);
+        };
+        store.flush();
+        let older = store::read_history(store.config(), self.clock.now_unix())?;
+        // Everything in the ring is newer than `oldest`, so only older ids can follow.
+        let below = q.cursor.map_or(oldest, |c| c.min(oldest));
+        let mut matching = older
+            .iter()
+            .rev()
+            .filter(|e| e.id < below)
+            .filter(|e| q.matches(e));
+        let room = q.limit.saturating_sub(page.entries.len());
+        page.entries.extend(matching.by_ref().take(room).cloned());
+        page.next_cursor = match (page.entries.last(), matching.next()) {
+            (Some(last), Some(_)) => Some(last.id),
+            _ => None,
+        };
+        Ok(page)
+    }
 }
 
-/// Current UNIX timestamp in seconds.
-fn now_unix() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_secs()
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decision(verdict: Verdict, confidence: f32, source: &str) -> Decision {
+        let mut d = Decision::new(verdict, confidence);
+        d.reasons.push(Reason::new(format!("{source} said so")));
+        d.top_contributors
+            .push(Contributor::new(source, "text", 1, ""));
+        d
+    }
+
+    fn query(pairs: &[(&str, &str)]) -> Result<HistoryQuery, String> {
+        let q = pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        HistoryQuery::from_query(&q)
+    }
+
+    fn filled() -> History {
+        let h = History::with_capacity(100);
+        h.push_at(&decision(Verdict::Buy, 0.8, "Fed"), 100);
+        h.push_at(&decision(Verdict::Sell, 0.6, "Reuters"), 200);
+        h.push_at(&decision(Verdict::Hold, 0.5, "Trump"), 300);
+        h.push_at(&decision(Verdict::Buy, 0.9, "Federal Reserve"), 400);
+        h
+    }
+
+    fn ts(page: &HistoryPage) -> Vec<u64> {
+        page.entries.iter().map(|e| e.ts_unix).collect()
+    }
+
+    #[test]
+    fn filters_combine_and_results_are_newest_first() {
+        let h = filled();
+        assert_eq!(
+            ts(&h.query(&HistoryQuery::default())),
+            vec![400, 300, 200, 100]
+        );
+
+        let q = query(&[("from", "200"), ("to", "400")]).unwrap();
+        assert_eq!(ts(&h.query(&q)), vec![300, 200]);
+
+        let q = query(&[("verdict", "buy,sell"), ("min_confidence", "0.7")]).unwrap();
+        assert_eq!(ts(&h.query(&q)), vec![400, 100]);
+
+        let q = query(&[("source", "FED")]).unwrap();
+        let page = h.query(&q);
+        assert_eq!(ts(&page), vec![400, 100]);
+        assert_eq!(
+            page.entries[0].reasons[0].message,
+            "Federal Reserve said so"
+        );
+    }
+
+    #[test]
+    fn cursor_pages_do_not_overlap() {
+        let h = filled();
+        let mut q = query(&[("limit", "3")]).unwrap();
+        let first = h.query(&q);
+        assert_eq!(ts(&first), vec![400, 300, 200]);
+        q.cursor = first.next_cursor;
+        let second = h.query(&q);
+        assert_eq!(ts(&second), vec![100]);
+        assert_eq!(second.next_cursor, None);
+
+        // Exactly one page: no cursor to follow.
+        let q = query(&[("limit", "4")]).unwrap();
+        assert_eq!(h.query(&q).next_cursor, None);
+    }
+
+    #[test]
+    fn malformed_parameters_are_rejected() {
+        assert!(query(&[("verdict", "MAYBE")]).is_err());
+        assert!(query(&[("min_confidence", "1.5")]).is_err());
+        assert!(query(&[("limit", "0")]).is_err());
+        assert!(query(&[("limit", "501")]).is_err());
+        assert!(query(&[("cursor", "abc")]).is_err());
+        assert!(query(&[("from", "300"), ("to", "200")]).is_err());
+        assert_eq!(
+            query(&[("from", "2025-09-01T13:30:00Z")]).unwrap().from,
+            Some(1_756_733_400)
+        );
+    }
+
+    #[test]
+    fn restore_numbers_legacy_entries_and_push_continues_after_them() {
+        let h = History::with_capacity(10);
+        let legacy = HistoryEntry {
+            id: 0,
+            ts_unix: 10,
+            verdict: Verdict::Hold,
+            confidence: 0.5,
+            signal: None,
+            top_sources: vec!["Fed".into()],
+            top_scores: vec![0],
+            reasons: Vec::new(),
+            contributors: Vec::new(),
+        };
+        h.restore(vec![legacy.clone(), legacy]);
+        h.push_at(&decision(Verdict::Buy, 0.7, "Fed"), 20);
+        let ids: Vec<u64> = h.snapshot_last_n(3).iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![1, 2, 3]);
+        // Legacy entries match `source` through their fingerprints.
+        let q = query(&[("source", "fed"), ("to", "20")]).unwrap();
+        assert_eq!(h.query(&q).entries.len(), 2);
+    }
+
+    #[test]
+    fn store_log_follows_memory_order_under_concurrent_pushes() {
+        use crate::clock::ManualClock;
+        use crate::store::StoreConfig;
+
+        let dir = std::env::temp_dir().join(format!("history_order_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let clock: SharedClock = Arc::new(ManualClock::new(1_000));
+        let cfg = StoreConfig::new(&dir);
+        let (store, _) = DecisionStore::open(cfg.clone(), clock.clone()).unwrap();
+        let h = Arc::new(History::with_capacity(1000).with_store(Arc::new(store)));
+
+        let threads: Vec<_> = (0..8)
+            .map(|t| {
+                let h = h.clone();
+                std::thread::spawn(move || {
+                    for i in 0..50 {
+                        h.push_at(&decision(Verdict::Buy, 0.7, "Fed"), t * 100 + i);
+                    }
+                })
+            })
+            .collect();
+        for t in threads {
+            t.join().unwrap();
+        }
+        let in_memory: Vec<u64> = h.snapshot_last_n(1000).iter().map(|e| e.id).collect();
+        drop(h); // last handle on the store: drains the writer
+
+        let (_, replay) = DecisionStore::open(cfg, clock).unwrap();
+        let on_disk: Vec<u64> = replay.history.iter().map(|e| e.id).collect();
+        assert_eq!(on_disk, in_memory);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn durable_query_pages_past_the_ring_into_the_store() {
+        use crate::clock::ManualClock;
+        use crate::store::StoreConfig;
+
+        let dir = std::env::temp_dir().join(format!("history_paging_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let clock: SharedClock = Arc::new(ManualClock::new(1_000));
+        let (store, _) = DecisionStore::open(StoreConfig::new(&dir), clock.clone()).unwrap();
+        let h = History::with_capacity(3)
+            .with_clock(clock)
+            .with_store(Arc::new(store));
+        for i in 0..8 {
+            let v = if i % 2 == 0 {
+                Verdict::Buy
+            } else {
+                Verdict::Sell
+            };
+            h.push_at(&decision(v, 0.7, "Fed"), 900 + i);
+        }
+
+        // The ring alone ends at id 6; the store carries on down to id 1.
+        let q = query(&[("limit", "2")]).unwrap();
+        assert_eq!(h.query(&q).entries.len(), 2);
+        let mut ids = Vec::new();
+        let mut q = q;
+        loop {
+            let page = h.query_durable(&q).unwrap();
+            ids.extend(page.entries.iter().map(|e| e.id));
+            match page.next_cursor {
+                Some(c) => q.cursor = Some(c),
+                None => break,
+            }
+        }
+        assert_eq!(ids, vec![8, 7, 6, 5, 4, 3, 2, 1]);
+
+        // Filters apply to the stored part as well.
+        let q = query(&[("verdict", "BUY"), ("limit", "10")]).unwrap();
+        let ids: Vec<u64> = h
+            .query_durable(&q)
+            .unwrap()
+            .entries
+            .iter()
+            .map(|e| e.id)
+            .collect();
+        assert_eq!(ids, vec![7, 5, 3, 1]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn grade_moves_publish_verdict_changes() {
+        let bus = DecisionBus::new(16);
+        let mut rx = bus.subscribe();
+        let h = History::with_capacity(10).with_bus(bus);
+        let graded = |signal| {
+            let mut d = decision(Verdict::Buy, 0.9, "Fed");
+            d.signal = Some(signal);
+            d
+        };
+        h.push_at(&graded(SignalGrade::Buy), 100);
+        h.push_at(&graded(SignalGrade::Buy), 110);
+        h.push_at(&graded(SignalGrade::StrongBuy), 120);
+
+        let changes: Vec<_> = std::iter::from_fn(|| rx.try_recv().ok())
+            .filter_map(|m| match m.event {
+                StreamEvent::VerdictChange {
+                    ts_unix,
+                    from,
+                    to,
+                    from_signal,
+                    signal,
+                    ..
+                } => Some((ts_unix, from, to, from_signal, signal)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            changes,
+            vec![(
+                120,
+                Verdict::Buy,
+                Verdict::Buy,
+                Some(SignalGrade::Buy),
+                Some(SignalGrade::StrongBuy)
+            )]
+        );
+    }
 }
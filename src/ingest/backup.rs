@@ -7,10 +7,16 @@ pub trait BackupSink: Send + Sync {
     async fn store(&self, items: Vec<(String, String)>) -> Result<()>;
 }
 
-/// Reads files from `config/*.json` and passes them to the sink.
+/// Config dirs included in backups.
+const BACKUP_DIRS: [&str; 2] = ["config", "config/ner"];
+
+/// Reads files from `config/*.json` and `config/ner/*.json` and passes them to the sink.
 pub async fn backup_configs_once<S: BackupSink>(sink: &S) -> Result<()> {
     let mut items = Vec::new();
-    if let Ok(entries) = std::fs::read_dir("config") {
+    for dir in BACKUP_DIRS {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            continue;
+        };
         for e in entries.flatten() {
             let path = e.path();
             if path.extension().and_then(|s| s.to_str()) == Some("json") {
@@ -95,6 +95,18 @@ pub async fn ingest_and_decide<P: crate::ingest::types::SourceProvider>(
     now: u64,
     whitelist: &[String],
     dedup_window_secs: u64,
+) -> crate::decision::Decision {
+    ingest_and_decide_on(crate::stream::bus(), p, now, whitelist, dedup_window_secs).await
+}
+
+/// Same as `ingest_and_decide`, publishing kept events to `bus` instead of the
+/// process-wide `stream::bus()`.
+pub async fn ingest_and_decide_on<P: crate::ingest::types::SourceProvider>(
+    bus: &crate::stream::DecisionBus,
+    p: &P,
+    now: u64,
+    whitelist: &[String],
+    dedup_window_secs: u64,
 ) -> crate::decision::Decision {
     let start = std::time::Instant::now();
     let latest = match p.fetch_latest().await {
@@ -126,6 +138,14 @@ pub async fn ingest_and_decide<P: crate::ingest::types::SourceProvider>(
         counter!("ingest_dedup_total").increment(dedup_cnt as u64);
     }
 
+    // Live stream subscribers (`/api/stream?types=ingest`).
+    for ev in &events {
+        bus.publish(crate::stream::StreamEvent::Ingest {
+            ts_unix: now,
+            event: ev.clone(),
+        });
+    }
+
     // --- Adaptér do analyze/decide ---
     let joined: String = events
         .iter()
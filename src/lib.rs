@@ -2,17 +2,31 @@
 // Public library surface for integration tests (and potential reuse).
 
 pub mod api;
+pub mod auth;
+pub mod backtest;
+pub mod calendar;
+pub mod calibration;
+pub mod clock;
 pub mod config;
+pub mod decide;
 pub mod decision;
 pub mod disruption;
 pub mod engine;
+pub mod events;
+pub mod export;
+pub mod grade;
 pub mod history;
 pub mod ingest;
 pub mod metrics;
+pub mod paper;
 pub mod relevance;
 pub mod rolling;
 pub mod sentiment;
 pub mod source_weights;
+pub mod stabilizer;
+pub mod store;
+pub mod stream;
+pub mod webhooks;
 pub use relevance::Relevance;
 
 // Phase 3 analysis pipeline (NER, rerank, antispam, weights, rules, scoring, debug)
@@ -8,16 +8,34 @@ use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::grade::SignalGrade;
+
 /// High-level decision kinds used across notifications.
+/// `STRONG_*` carry the graded signal; the three base kinds stay unchanged.
+#[allow(non_camel_case_types)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DecisionKind {
     BUY,
     SELL,
     HOLD,
+    STRONG_BUY,
+    STRONG_SELL,
     #[cfg(test)] // keep TEST only for test builds to avoid dead_code warnings in CI
     TEST,
 }
 
+impl From<SignalGrade> for DecisionKind {
+    fn from(g: SignalGrade) -> Self {
+        match g {
+            SignalGrade::StrongBuy => DecisionKind::STRONG_BUY,
+            SignalGrade::Buy => DecisionKind::BUY,
+            SignalGrade::Hold => DecisionKind::HOLD,
+            SignalGrade::Sell => DecisionKind::SELL,
+            SignalGrade::StrongSell => DecisionKind::STRONG_SELL,
+        }
+    }
+}
+
 /// Payload we send to notifiers.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NotificationEvent {
@@ -0,0 +1,786 @@
+//! # Paper Trading
+//! Simulated YM execution driven by decisions; no real orders are sent.
+//!
+//! Each graded `Decision` is turned into a target position:
+//! - `STRONG_BUY` / `BUY` → long, `SELL` / `STRONG_SELL` → short, sized per grade,
+//! - `HOLD` keeps whatever is open,
+//! - the same direction keeps the open position (no pyramiding),
+//! - the opposite direction closes and reverses.
+//!
+//! Open positions are marked to a `PriceFeed` on every decision and by a
+//! background ticker (`PaperTrader::spawn_marker`), and closed there on
+//! stop-loss, take-profit or max holding time (filled at the marked price).
+//! Reads (`position`, `fills`, `pnl`) never mark. Price lookups are bounded by a
+//! deadline; a late or failed quote means no fill.
+//!
+//! JSON shape (`config/paper.json`, path overridable via `PAPER_CONFIG_PATH`):
+//! ```json
+//! {
+//!   "symbol": "YM",
+//!   "point_value": 5.0,
+//!   "size": { "STRONG_BUY": 2, "BUY": 1, "SELL": 1, "STRONG_SELL": 2 },
+//!   "stop_loss_points": 150,
+//!   "take_profit_points": 300,
+//!   "max_holding_secs": 14400
+//! }
+//! ```
+//! Enabled with `PAPER_TRADING=1` plus a feed: `PAPER_PRICE_CSV` (OHLC CSV, see
+//! `crate::backtest`) or `PAPER_PRICE_URL` (stand-in HTTP endpoint).
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use std::{fs, path::Path};
+
+use anyhow::Context;
+use async_trait::async_trait;
+use metrics::counter;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::backtest::{self, PriceBar};
+use crate::clock::SharedClock;
+use crate::decision::Decision;
+use crate::grade::SignalGrade;
+
+pub const DEFAULT_PAPER_CONFIG_PATH: &str = "config/paper.json";
+pub const ENV_PAPER_CONFIG_PATH: &str = "PAPER_CONFIG_PATH";
+
+/// Fill log capacity (oldest fills are dropped).
+const MAX_FILLS: usize = 5_000;
+
+/// Connect / total timeouts of the HTTP quote client.
+const HTTP_CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+const HTTP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Default deadline for one price lookup (`PAPER_PRICE_DEADLINE_MS`).
+const DEFAULT_PRICE_DEADLINE: Duration = Duration::from_millis(500);
+/// Default interval of the background marker (`PAPER_MARK_INTERVAL_MS`).
+const DEFAULT_MARK_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Contracts per grade.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct GradeSizes {
+    #[serde(default)]
+    pub strong_buy: u32,
+    #[serde(default)]
+    pub buy: u32,
+    #[serde(default)]
+    pub sell: u32,
+    #[serde(default)]
+    pub strong_sell: u32,
+}
+
+impl Default for GradeSizes {
+    fn default() -> Self {
+        Self {
+            strong_buy: 2,
+            buy: 1,
+            sell: 1,
+            strong_sell: 2,
+        }
+    }
+}
+
+impl GradeSizes {
+    /// Signed target quantity for a grade (`None` for HOLD or a zero size).
+    pub fn target(&self, grade: SignalGrade) -> Option<i64> {
+        let q = match grade {
+            SignalGrade::StrongBuy => self.strong_buy as i64,
+            SignalGrade::Buy => self.buy as i64,
+            SignalGrade::Hold => 0,
+            SignalGrade::Sell => -(self.sell as i64),
+            SignalGrade::StrongSell => -(self.strong_sell as i64),
+        };
+        (q != 0).then_some(q)
+    }
+}
+
+/// Paper-trading rules.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PaperConfig {
+    #[serde(default = "default_symbol")]
+    pub symbol: String,
+    /// USD per index point per contract (YM: $5).
+    #[serde(default = "default_point_value")]
+    pub point_value: f64,
+    #[serde(default)]
+    pub size: GradeSizes,
+    #[serde(default)]
+    pub stop_loss_points: Option<f64>,
+    #[serde(default)]
+    pub take_profit_points: Option<f64>,
+    #[serde(default)]
+    pub max_holding_secs: Option<u64>,
+}
+
+fn default_symbol() -> String {
+    "YM".to_string()
+}
+
+fn default_point_value() -> f64 {
+    5.0
+}
+
+impl Default for PaperConfig {
+    fn default() -> Self {
+        Self {
+            symbol: default_symbol(),
+            point_value: default_point_value(),
+            size: GradeSizes::default(),
+            stop_loss_points: None,
+            take_profit_points: None,
+            max_holding_secs: None,
+        }
+    }
+}
+
+impl PaperConfig {
+    /// Load from JSON; falls back to defaults if the file is missing or invalid.
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Self {
+        let path = path.as_ref();
+        let parsed = fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|s| serde_json::from_str::<PaperConfig>(&s).map_err(|e| e.to_string()));
+        match parsed {
+            Ok(cfg) => cfg,
+            Err(e) => {
+                tracing::debug!(
+                    "paper config {} not used ({e}); using defaults",
+                    path.display()
+                );
+                Self::default()
+            }
+        }
+    }
+
+    /// Load from `PAPER_CONFIG_PATH` (default `config/paper.json`).
+    pub fn from_env() -> Self {
+        let path = std::env::var(ENV_PAPER_CONFIG_PATH)
+            .unwrap_or_else(|_| DEFAULT_PAPER_CONFIG_PATH.to_string());
+        Self::load_from_file(path)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FillReason {
+    Open,
+    /// Closed by an opposite signal.
+    Reverse,
+    StopLoss,
+    TakeProfit,
+    MaxHolding,
+}
+
+/// One simulated execution.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct Fill {
+    pub id: u64,
+    pub ts_unix: u64,
+    pub symbol: String,
+    pub side: Side,
+    pub qty: u32,
+    pub price: f64,
+    pub reason: FillReason,
+    /// Realized PnL in points × contracts (closing fills only).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub realized_points: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Position {
+    /// Signed contracts (long > 0).
+    qty: i64,
+    entry_price: f64,
+    opened_unix: u64,
+    grade: SignalGrade,
+}
+
+/// Open position as exposed by the API.
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct PositionView {
+    pub symbol: String,
+    pub qty: i64,
+    pub entry_price: f64,
+    pub opened_unix: u64,
+    pub grade: SignalGrade,
+    pub last_price: Option<f64>,
+    pub unrealized_points: f64,
+    pub unrealized_usd: f64,
+}
+
+/// Running PnL summary.
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct PnlSummary {
+    pub symbol: String,
+    pub realized_points: f64,
+    pub realized_usd: f64,
+    pub unrealized_points: f64,
+    pub unrealized_usd: f64,
+    pub total_usd: f64,
+    pub trades: usize,
+    pub wins: usize,
+    pub win_rate: Option<f64>,
+    pub last_price: Option<f64>,
+    pub last_mark_unix: Option<u64>,
+}
+
+#[derive(Debug, Default)]
+struct Book {
+    position: Option<Position>,
+    fills: VecDeque<Fill>,
+    next_id: u64,
+    realized_points: f64,
+    trades: usize,
+    wins: usize,
+    last_price: Option<f64>,
+    last_mark_unix: Option<u64>,
+}
+
+/// Thread-safe simulated book for one instrument.
+#[derive(Debug)]
+pub struct PaperBroker {
+    cfg: PaperConfig,
+    book: Mutex<Book>,
+}
+
+impl PaperBroker {
+    pub fn new(cfg: PaperConfig) -> Self {
+        Self {
+            cfg,
+            book: Mutex::new(Book::default()),
+        }
+    }
+
+    pub fn config(&self) -> &PaperConfig {
+        &self.cfg
+    }
+
+    /// Mark to `price` at `now` and apply the decision's target position.
+    /// Returns the fills generated (exits first).
+    pub fn on_decision(&self, d: &Decision, price: f64, now: u64) -> Vec<Fill> {
+        let mut book = self.book.lock().expect("paper book poisoned");
+        let mut fills = self.mark_locked(&mut book, price, now);
+
+        let grade = d.signal.unwrap_or(SignalGrade::Hold);
+        let Some(target) = self.cfg.size.target(grade) else {
+            return fills;
+        };
+        if let Some(pos) = book.position {
+            if pos.qty.signum() == target.signum() {
+                return fills;
+            }
+            fills.push(self.close_locked(&mut book, price, now, FillReason::Reverse));
+        }
+
+        book.position = Some(Position {
+            qty: target,
+            entry_price: price,
+            opened_unix: now,
+            grade,
+        });
+        let side = if target > 0 { Side::Buy } else { Side::Sell };
+        fills.push(self.record_fill(
+            &mut book,
+            now,
+            side,
+            target.unsigned_abs() as u32,
+            price,
+            FillReason::Open,
+            None,
+        ));
+        fills
+    }
+
+    /// Mark the open position to `price`; closes it on SL/TP/max holding.
+    pub fn mark(&self, price: f64, now: u64) -> Vec<Fill> {
+        let mut book = self.book.lock().expect("paper book poisoned");
+        self.mark_locked(&mut book, price, now)
+    }
+
+    pub fn position(&self) -> Option<PositionView> {
+        let book = self.book.lock().expect("paper book poisoned");
+        book.position.map(|p| {
+            let upts = unrealized(&p, book.last_price);
+            PositionView {
+                symbol: self.cfg.symbol.clone(),
+                qty: p.qty,
+                entry_price: p.entry_price,
+                opened_unix: p.opened_unix,
+                grade: p.grade,
+                last_price: book.last_price,
+                unrealized_points: upts,
+                unrealized_usd: upts * self.cfg.point_value,
+            }
+        })
+    }
+
+    /// Most recent fills, oldest first.
+    pub fn fills(&self, limit: usize) -> Vec<Fill> {
+        let book = self.book.lock().expect("paper book poisoned");
+        let skip = book.fills.len().saturating_sub(limit);
+        book.fills.iter().skip(skip).cloned().collect()
+    }
+
+    pub fn pnl(&self) -> PnlSummary {
+        let book = self.book.lock().expect("paper book poisoned");
+        let upts = book
+            .position
+            .map(|p| unrealized(&p, book.last_price))
+            .unwrap_or(0.0);
+        let pv = self.cfg.point_value;
+        PnlSummary {
+            symbol: self.cfg.symbol.clone(),
+            realized_points: book.realized_points,
+            realized_usd: book.realized_points * pv,
+            unrealized_points: upts,
+            unrealized_usd: upts * pv,
+            total_usd: (book.realized_points + upts) * pv,
+            trades: book.trades,
+            wins: book.wins,
+            win_rate: (book.trades > 0).then(|| book.wins as f64 / book.trades as f64),
+            last_price: book.last_price,
+            last_mark_unix: book.last_mark_unix,
+        }
+    }
+
+    fn mark_locked(&self, book: &mut Book, price: f64, now: u64) -> Vec<Fill> {
+        book.last_price = Some(price);
+        book.last_mark_unix = Some(now);
+
+        let Some(pos) = book.position else {
+            return Vec::new();
+        };
+        // Favorable move in points (per contract).
+        let move_pts = (price - pos.entry_price) * pos.qty.signum() as f64;
+        let reason = if self.cfg.stop_loss_points.is_some_and(|sl| move_pts <= -sl) {
+            Some(FillReason::StopLoss)
+        } else if self.cfg.take_profit_points.is_some_and(|tp| move_pts >= tp) {
+            Some(FillReason::TakeProfit)
+        } else if self
+            .cfg
+            .max_holding_secs
+            .is_some_and(|max| now.saturating_sub(pos.opened_unix) >= max)
+        {
+            Some(FillReason::MaxHolding)
+        } else {
+            None
+        };
+        reason
+            .map(|r| vec![self.close_locked(book, price, now, r)])
+            .unwrap_or_default()
+    }
+
+    fn close_locked(&self, book: &mut Book, price: f64, now: u64, reason: FillReason) -> Fill {
+        let pos = book.position.take().expect("close without position");
+        let pts = (price - pos.entry_price) * pos.qty as f64;
+        book.realized_points += pts;
+        book.trades += 1;
+        if pts > 0.0 {
+            book.wins += 1;
+        }
+        let side = if pos.qty > 0 { Side::Sell } else { Side::Buy };
+        self.record_fill(
+            book,
+            now,
+            side,
+            pos.qty.unsigned_abs() as u32,
+            price,
+            reason,
+            Some(pts),
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn record_fill(
+        &self,
+        book: &mut Book,
+        now: u64,
+        side: Side,
+        qty: u32,
+        price: f64,
+        reason: FillReason,
+        realized_points: Option<f64>,
+    ) -> Fill {
+        book.next_id += 1;
+        let fill = Fill {
+            id: book.next_id,
+            ts_unix: now,
+            symbol: self.cfg.symbol.clone(),
+            side,
+            qty,
+            price,
+            reason,
+            realized_points,
+        };
+        book.fills.push_back(fill.clone());
+        while book.fills.len() > MAX_FILLS {
+            book.fills.pop_front();
+        }
+        fill
+    }
+}
+
+fn unrealized(p: &Position, last: Option<f64>) -> f64 {
+    last.map(|px| (px - p.entry_price) * p.qty as f64)
+        .unwrap_or(0.0)
+}
+
+/// Source of the price positions are marked to.
+#[async_trait]
+pub trait PriceFeed: Send + Sync {
+    /// Last known price at `now_unix` (`None` when the feed has nothing yet).
+    async fn price_at(&self, now_unix: u64) -> anyhow::Result<Option<f64>>;
+}
+
+/// Replays a local OHLC CSV: the open of the latest bar starting at or before
+/// `now`, or the last close once `now` is past the end of the data.
+#[derive(Debug, Clone)]
+pub struct CsvPriceFeed {
+    bars: Vec<PriceBar>,
+}
+
+impl CsvPriceFeed {
+    pub fn new(bars: Vec<PriceBar>) -> Self {
+        Self { bars }
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        Ok(Self::new(backtest::load_bars(path)?))
+    }
+
+    fn lookup(&self, now_unix: u64) -> Option<f64> {
+        let idx = self.bars.partition_point(|b| b.ts_unix <= now_unix);
+        let i = idx.checked_sub(1)?;
+        Some(if idx == self.bars.len() {
+            self.bars[i].close
+        } else {
+            self.bars[i].open
+        })
+    }
+}
+
+#[async_trait]
+impl PriceFeed for CsvPriceFeed {
+    async fn price_at(&self, now_unix: u64) -> anyhow::Result<Option<f64>> {
+        Ok(self.lookup(now_unix))
+    }
+}
+
+/// Stand-in HTTP quote endpoint. Accepts a bare number or an object with
+/// `price`, `last` or `close`.
+#[derive(Debug, Clone)]
+pub struct HttpPriceFeed {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl HttpPriceFeed {
+    pub fn new(url: impl Into<String>) -> Self {
+        let client = reqwest::Client::builder()
+            .connect_timeout(HTTP_CONNECT_TIMEOUT)
+            .timeout(HTTP_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        Self {
+            url: url.into(),
+            client,
+        }
+    }
+}
+
+fn price_from_json(v: &Value) -> Option<f64> {
+    match v {
+        Value::Number(n) => n.as_f64(),
+        Value::Object(map) => ["price", "last", "close"]
+            .iter()
+            .find_map(|k| map.get(*k).and_then(Value::as_f64)),
+        _ => None,
+    }
+}
+
+#[async_trait]
+impl PriceFeed for HttpPriceFeed {
+    async fn price_at(&self, _now_unix: u64) -> anyhow::Result<Option<f64>> {
+        let v: Value = self
+            .client
+            .get(&self.url)
+            .send()
+            .await
+            .context("fetch paper price")?
+            .error_for_status()?
+            .json()
+            .await
+            .context("parse paper price")?;
+        Ok(price_from_json(&v))
+    }
+}
+
+/// Broker + feed, as wired into the API.
+pub struct PaperTrader {
+    broker: PaperBroker,
+    feed: Arc<dyn PriceFeed>,
+    /// Longest wait for one quote; past it the lookup counts as "no price".
+    price_deadline: Duration,
+    /// Period of the background marker.
+    mark_interval: Duration,
+}
+
+impl PaperTrader {
+    pub fn new(cfg: PaperConfig, feed: Arc<dyn PriceFeed>) -> Self {
+        Self {
+            broker: PaperBroker::new(cfg),
+            feed,
+            price_deadline: DEFAULT_PRICE_DEADLINE,
+            mark_interval: DEFAULT_MARK_INTERVAL,
+        }
+    }
+
+    pub fn with_price_deadline(mut self, deadline: Duration) -> Self {
+        self.price_deadline = deadline;
+        self
+    }
+
+    pub fn with_mark_interval(mut self, interval: Duration) -> Self {
+        self.mark_interval = interval;
+        self
+    }
+
+    /// Build from env; `None` unless `PAPER_TRADING=1` and a feed is configured.
+    ///
+    /// - `PAPER_PRICE_CSV`: OHLC CSV replayed against the API clock
+    /// - `PAPER_PRICE_URL`: HTTP quote endpoint (used if no CSV is set)
+    /// - `PAPER_PRICE_DEADLINE_MS`: max wait per quote (default 500)
+    /// - `PAPER_MARK_INTERVAL_MS`: background marking period (default 15000)
+    pub fn from_env() -> Option<Self> {
+        let enabled = std::env::var("PAPER_TRADING")
+            .map(|v| v == "1")
+            .unwrap_or(false);
+        if !enabled {
+            return None;
+        }
+        let feed: Arc<dyn PriceFeed> = if let Ok(path) = std::env::var("PAPER_PRICE_CSV") {
+            match CsvPriceFeed::from_file(&path) {
+                Ok(f) => Arc::new(f),
+                Err(e) => {
+                    tracing::warn!("paper trading disabled: price CSV {path}: {e:#}");
+                    return None;
+                }
+            }
+        } else if let Ok(url) = std::env::var("PAPER_PRICE_URL") {
+            Arc::new(HttpPriceFeed::new(url))
+        } else {
+            tracing::warn!("paper trading disabled: set PAPER_PRICE_CSV or PAPER_PRICE_URL");
+            return None;
+        };
+        let ms = |key: &str| {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.trim().parse::<u64>().ok())
+                .filter(|n| *n > 0)
+                .map(Duration::from_millis)
+        };
+        let trader = Self::new(PaperConfig::from_env(), feed)
+            .with_price_deadline(ms("PAPER_PRICE_DEADLINE_MS").unwrap_or(DEFAULT_PRICE_DEADLINE))
+            .with_mark_interval(ms("PAPER_MARK_INTERVAL_MS").unwrap_or(DEFAULT_MARK_INTERVAL));
+        Some(trader)
+    }
+
+    pub fn broker(&self) -> &PaperBroker {
+        &self.broker
+    }
+
+    /// Price the decision at `now` and apply it; feed errors are logged and skipped.
+    pub async fn on_decision(&self, d: &Decision, now: u64) -> Vec<Fill> {
+        match self.price(now).await {
+            Some(px) => self.broker.on_decision(d, px, now),
+            None => Vec::new(),
+        }
+    }
+
+    /// Mark the book to the feed at `now`.
+    pub async fn mark(&self, now: u64) -> Vec<Fill> {
+        match self.price(now).await {
+            Some(px) => self.broker.mark(px, now),
+            None => Vec::new(),
+        }
+    }
+
+    /// Mark every `mark_interval` on `clock` until the trader is dropped, so SL/TP
+    /// and max holding fire without new decisions. Needs a tokio runtime.
+    pub fn spawn_marker(trader: &Arc<Self>, clock: SharedClock) {
+        let weak = Arc::downgrade(trader);
+        let period = trader.mark_interval;
+        tokio::spawn(async move {
+            let mut tick = tokio::time::interval(period);
+            tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            tick.tick().await;
+            loop {
+                tick.tick().await;
+                let Some(trader) = weak.upgrade() else {
+                    break;
+                };
+                let fills = trader.mark(clock.now_unix()).await;
+                if !fills.is_empty() {
+                    counter!("paper_fills_total").increment(fills.len() as u64);
+                }
+            }
+        });
+    }
+
+    async fn price(&self, now: u64) -> Option<f64> {
+        match tokio::time::timeout(self.price_deadline, self.feed.price_at(now)).await {
+            Ok(Ok(Some(px))) if px.is_finite() && px > 0.0 => Some(px),
+            Ok(Ok(_)) => {
+                tracing::debug!("paper: no price at {now}");
+                None
+            }
+            Ok(Err(e)) => {
+                tracing::warn!("paper: price feed failed: {e:#}");
+                None
+            }
+            Err(_) => {
+                tracing::warn!("paper: no quote within {:?}; skipping", self.price_deadline);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decision::Verdict;
+
+    fn cfg() -> PaperConfig {
+        PaperConfig {
+            stop_loss_points: Some(100.0),
+            take_profit_points: Some(200.0),
+            max_holding_secs: Some(3_600),
+            ..PaperConfig::default()
+        }
+    }
+
+    fn graded(grade: SignalGrade) -> Decision {
+        let mut d = Decision::new(grade.verdict(), 0.8);
+        d.signal = Some(grade);
+        d
+    }
+
+    #[test]
+    fn opens_sized_by_grade_and_reverses() {
+        let b = PaperBroker::new(cfg());
+        let f = b.on_decision(&graded(SignalGrade::StrongBuy), 45_000.0, 0);
+        assert_eq!(f.len(), 1);
+        assert_eq!((f[0].side, f[0].qty), (Side::Buy, 2));
+
+        // Same direction and HOLD keep the position.
+        assert!(b
+            .on_decision(&graded(SignalGrade::Buy), 45_010.0, 10)
+            .is_empty());
+        assert!(b
+            .on_decision(&graded(SignalGrade::Hold), 45_020.0, 20)
+            .is_empty());
+        assert_eq!(b.position().unwrap().unrealized_points, 40.0);
+
+        let f = b.on_decision(&graded(SignalGrade::Sell), 45_050.0, 30);
+        assert_eq!(f[0].reason, FillReason::Reverse);
+        assert_eq!(f[0].realized_points, Some(100.0));
+        assert_eq!((f[1].side, f[1].qty), (Side::Sell, 1));
+        assert_eq!(b.position().unwrap().qty, -1);
+
+        let pnl = b.pnl();
+        assert_eq!(pnl.trades, 1);
+        assert_eq!(pnl.realized_usd, 500.0);
+    }
+
+    #[test]
+    fn stop_loss_take_profit_and_max_holding() {
+        let b = PaperBroker::new(cfg());
+        b.on_decision(&graded(SignalGrade::Sell), 45_000.0, 0);
+        let f = b.mark(45_100.0, 60);
+        assert_eq!(f[0].reason, FillReason::StopLoss);
+        assert_eq!(f[0].side, Side::Buy);
+        assert!(b.position().is_none());
+
+        b.on_decision(&graded(SignalGrade::Buy), 45_000.0, 100);
+        assert_eq!(b.mark(45_200.0, 160)[0].reason, FillReason::TakeProfit);
+
+        b.on_decision(&graded(SignalGrade::Buy), 45_000.0, 200);
+        assert!(b.mark(45_010.0, 3_000).is_empty());
+        assert_eq!(b.mark(45_010.0, 3_800)[0].reason, FillReason::MaxHolding);
+
+        let pnl = b.pnl();
+        assert_eq!(pnl.trades, 3);
+        assert_eq!(pnl.wins, 2);
+        assert_eq!(pnl.realized_points, -100.0 + 200.0 + 10.0);
+    }
+
+    #[test]
+    fn ungraded_decision_is_treated_as_hold() {
+        let b = PaperBroker::new(cfg());
+        assert!(b
+            .on_decision(&Decision::new(Verdict::Buy, 0.9), 45_000.0, 0)
+            .is_empty());
+        assert_eq!(b.pnl().last_price, Some(45_000.0));
+    }
+
+    struct SlowFeed;
+
+    #[async_trait]
+    impl PriceFeed for SlowFeed {
+        async fn price_at(&self, _now_unix: u64) -> anyhow::Result<Option<f64>> {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok(Some(45_000.0))
+        }
+    }
+
+    #[tokio::test]
+    async fn late_quote_means_no_fill() {
+        let trader = PaperTrader::new(cfg(), Arc::new(SlowFeed))
+            .with_price_deadline(Duration::from_millis(10));
+        let fills = trader.on_decision(&graded(SignalGrade::Buy), 0).await;
+        assert!(fills.is_empty());
+        assert!(trader.broker().position().is_none());
+        assert_eq!(trader.broker().pnl().last_price, None);
+    }
+
+    #[test]
+    fn csv_feed_and_json_quotes() {
+        let feed = CsvPriceFeed::new(vec![
+            PriceBar {
+                ts_unix: 100,
+                open: 1.0,
+                high: 2.0,
+                low: 0.5,
+                close: 1.5,
+            },
+            PriceBar {
+                ts_unix: 200,
+                open: 1.6,
+                high: 2.0,
+                low: 1.0,
+                close: 1.8,
+            },
+        ]);
+        assert_eq!(feed.lookup(50), None);
+        assert_eq!(feed.lookup(150), Some(1.0));
+        assert_eq!(feed.lookup(500), Some(1.8));
+
+        assert_eq!(price_from_json(&serde_json::json!(45000.5)), Some(45000.5));
+        assert_eq!(
+            price_from_json(&serde_json::json!({"last": 45001})),
+            Some(45001.0)
+        );
+        assert_eq!(price_from_json(&serde_json::json!({"bid": 1})), None);
+    }
+}
@@ -763,20 +763,18 @@ impl RelevanceEngine {
 
     /// Public scoring API: blockers → anchors → combos/threshold. Returns {score, matched, reasons}.
     pub fn score(&self, text: &str) -> Relevance {
+        self.score_with_threshold(text, self.cfg.relevance.threshold)
+    }
+
+    /// Same as `score`, against an explicit threshold (e.g. relaxed in event windows).
+    pub fn score_with_threshold(&self, text: &str, threshold: f32) -> Relevance {
         let mut rel = Relevance::default();
 
         // 1) Hard blockers first
         let blockers = self.find_blockers(text);
         if !blockers.is_empty() {
             rel.reasons.extend(blockers.clone());
-            dev_log_relevance(
-                "blocked",
-                text,
-                &[],
-                &rel.reasons,
-                0.0,
-                self.cfg.relevance.threshold,
-            );
+            dev_log_relevance("blocked", text, &[], &rel.reasons, 0.0, threshold);
             return rel; // score 0.0
         }
 
@@ -798,7 +796,7 @@ impl RelevanceEngine {
                     &rel.matched,
                     &rel.reasons,
                     0.0,
-                    self.cfg.relevance.threshold,
+                    threshold,
                 );
                 return rel;
             }
@@ -810,7 +808,7 @@ impl RelevanceEngine {
 
         // 4) Weighted score + threshold
         let score = self.weighted_score(&cat_counts);
-        let passed_threshold = score >= self.cfg.relevance.threshold;
+        let passed_threshold = score >= threshold;
 
         // 5) Result aggregation
         rel.matched = matched_ids;
@@ -820,12 +818,9 @@ impl RelevanceEngine {
             reasons.push("combos_fail".into());
         }
         if passed_threshold {
-            reasons.push(format!("threshold_ok:{:.2}", self.cfg.relevance.threshold));
+            reasons.push(format!("threshold_ok:{:.2}", threshold));
         } else {
-            reasons.push(format!(
-                "threshold_fail:{:.2}",
-                self.cfg.relevance.threshold
-            ));
+            reasons.push(format!("threshold_fail:{:.2}", threshold));
         }
 
         if combos_ok && passed_threshold {
@@ -843,7 +838,7 @@ impl RelevanceEngine {
                 &rel.matched,
                 &rel.reasons,
                 rel.score,
-                self.cfg.relevance.threshold,
+                threshold,
             );
         } else if combos_ok {
             dev_log_relevance(
@@ -852,7 +847,7 @@ impl RelevanceEngine {
                 &rel.matched,
                 &rel.reasons,
                 score,
-                self.cfg.relevance.threshold,
+                threshold,
             );
         } else {
             dev_log_relevance(
@@ -861,7 +856,7 @@ impl RelevanceEngine {
                 &rel.matched,
                 &rel.reasons,
                 score,
-                self.cfg.relevance.threshold,
+                threshold,
             );
         }
 
@@ -902,6 +897,16 @@ impl RelevanceHandle {
         }
     }
 
+    /// Score with the configured threshold lowered by `delta` (floored at 0).
+    pub fn score_relaxed(&self, text: &str, delta: f32) -> Relevance {
+        if let Ok(eng) = self.inner.read() {
+            let t = (eng.cfg.relevance.threshold - delta.max(0.0)).max(0.0);
+            eng.score_with_threshold(text, t)
+        } else {
+            Relevance::default()
+        }
+    }
+
     /// Backward-compatible alias — calls `score`.
     #[allow(dead_code)]
     pub fn evaluate(&self, text: &str) -> Relevance {
@@ -3,19 +3,25 @@
 //!
 //! Collects `(score, timestamp)` pairs and computes average/count over
 //! the last window. This is informational only; notifications are handled
-//! in the disruption detector.
+//! in the disruption detector. Samples can be persisted to a
+//! `store::DecisionStore` and loaded back with `restore`.
 
 use std::{
     collections::VecDeque,
-    sync::Mutex,
-    time::{Duration, SystemTime, UNIX_EPOCH},
+    sync::{Arc, Mutex},
+    time::Duration,
 };
 
+use crate::clock::{self, SharedClock};
+use crate::store::{DecisionStore, Record};
+
 /// Thread-safe rolling time window over integer sentiment scores.
 #[derive(Debug)]
 pub struct RollingWindow {
     inner: Mutex<Inner>,
     window: Duration,
+    clock: SharedClock,
+    store: Option<Arc<DecisionStore>>,
 }
 
 #[derive(Debug)]
@@ -32,9 +38,29 @@ impl RollingWindow {
                 buf: VecDeque::new(),
             }),
             window,
+            clock: clock::system(),
+            store: None,
         }
     }
 
+    /// Use `clock` for "now" (defaults to the system clock).
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Append every recorded sample to `store`.
+    pub fn with_store(mut self, store: Arc<DecisionStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Load replayed `(unix_seconds, score)` samples (oldest first) without persisting them.
+    pub fn restore(&self, samples: impl IntoIterator<Item = (u64, i32)>) {
+        let mut inner = self.inner.lock().expect("rolling window mutex poisoned");
+        inner.buf.extend(samples);
+    }
+
     /// Convenience constructor for 48h window.
     pub fn new_48h() -> Self {
         Self::with_window(Duration::from_secs(48 * 3600))
@@ -44,11 +70,14 @@ impl RollingWindow {
     ///
     /// Automatically discards entries older than the window.
     pub fn record(&self, score: i32, ts_unix: Option<u64>) {
-        let now = now_unix();
+        let now = self.clock.now_unix();
         let ts = ts_unix.unwrap_or(now);
         let cutoff = now.saturating_sub(self.window.as_secs());
 
         let mut inner = self.inner.lock().expect("rolling window mutex poisoned");
+        if let Some(store) = &self.store {
+            store.append(&Record::Rolling { ts_unix: ts, score });
+        }
 
         inner.buf.push_back((ts, score));
         while let Some(&(t, _)) = inner.buf.front() {
@@ -62,7 +91,7 @@ impl RollingWindow {
 
     /// Return the average score and number of samples within the window.
     pub fn average_and_count(&self) -> (f32, usize) {
-        let now = now_unix();
+        let now = self.clock.now_unix();
         let cutoff = now.saturating_sub(self.window.as_secs());
 
         let inner = self.inner.lock().expect("rolling window mutex poisoned");
@@ -87,10 +116,22 @@ impl RollingWindow {
     }
 }
 
-/// Current UNIX time in seconds.
-fn now_unix() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_else(|_| Duration::from_secs(0))
-        .as_secs()
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use std::sync::Arc;
+
+    #[test]
+    fn window_slides_with_injected_clock() {
+        let clock = Arc::new(ManualClock::new(10_000));
+        let rw = RollingWindow::with_window(Duration::from_secs(60)).with_clock(clock.clone());
+        rw.record(2, None);
+        clock.advance(30);
+        rw.record(-4, None);
+        assert_eq!(rw.average_and_count(), (-1.0, 2));
+
+        clock.advance(45);
+        assert_eq!(rw.average_and_count(), (-4.0, 1));
+    }
 }
@@ -97,7 +97,7 @@ fn is_negator(tok: &str) -> bool {
 }
 
 /// Batch input item: source + text.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct BatchItem {
     pub source: String,
     pub text: String,
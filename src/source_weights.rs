@@ -9,37 +9,225 @@
 //! - Aliases can map alternative spellings/usernames to canonical sources.
 //! - Fallback order: aliases → exact match → substring match → default.
 //! - Includes a built-in `default_seed()` with common sources.
+//! - Edits (admin API) are validated, diffed and saved atomically (`save_atomic`).
+//!   Names that only differ in case or punctuation (`"Fed"` / `"fed"`) are rejected
+//!   rather than merged.
 //!
 //! Designed to be simple, testable, and resilient to noisy input.
 
-use serde::Deserialize;
-use std::{collections::HashMap, fs, path::Path};
+use serde::{Deserialize, Serialize, Serializer};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+pub const DEFAULT_SOURCE_WEIGHTS_PATH: &str = "source_weights.json";
+pub const ENV_SOURCE_WEIGHTS_PATH: &str = "SOURCE_WEIGHTS_PATH";
+pub const DEFAULT_CHANGE_LOG_PATH: &str = "state/source_weights_changes.jsonl";
+pub const ENV_CHANGE_LOG_PATH: &str = "SOURCE_WEIGHTS_CHANGE_LOG";
+
+/// `SOURCE_WEIGHTS_PATH`, else `source_weights.json`.
+pub fn path_from_env() -> PathBuf {
+    std::env::var(ENV_SOURCE_WEIGHTS_PATH)
+        .ok()
+        .filter(|s| !s.trim().is_empty())
+        .map(|s| PathBuf::from(s.trim()))
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_SOURCE_WEIGHTS_PATH))
+}
 
 /// Configuration for source weights, loaded from JSON or defaults.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct SourceWeightsConfig {
     /// Default weight if no match is found.
     #[serde(default = "default_default_weight")]
     pub default_weight: f32,
     /// Explicit weights for canonical source names.
-    #[serde(default)]
+    #[serde(default, serialize_with = "sorted")]
     pub weights: HashMap<String, f32>,
     /// Aliases mapping non-canonical names → canonical names.
-    #[serde(default)]
+    #[serde(default, serialize_with = "sorted")]
     pub aliases: HashMap<String, String>,
 }
 
+/// Why a source weights file was not loaded.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LoadError {
+    /// Missing or unreadable.
+    Read(String),
+    /// Not JSON, or rejected by validation; one `field: problem` entry each.
+    Invalid(Vec<String>),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Read(e) => write!(f, "read: {e}"),
+            Self::Invalid(errors) => f.write_str(&errors.join("; ")),
+        }
+    }
+}
+
 fn default_default_weight() -> f32 {
     0.60
 }
 
+/// Serialize a map with sorted keys (stable files and diffs).
+fn sorted<S: Serializer, V: Serialize>(map: &HashMap<String, V>, s: S) -> Result<S::Ok, S::Error> {
+    map.iter().collect::<BTreeMap<_, _>>().serialize(s)
+}
+
 impl SourceWeightsConfig {
-    /// Load configuration from a JSON file.  
-    /// Falls back to `default_seed()` on error.
+    /// Load configuration from a JSON file.
+    /// Falls back to `default_seed()` if the file is missing, unparsable or invalid
+    /// (the latter two are logged).
     pub fn load_from_file<P: AsRef<Path>>(path: P) -> Self {
-        match fs::read_to_string(path) {
-            Ok(s) => serde_json::from_str(&s).unwrap_or_else(|_| Self::default_seed()),
-            Err(_) => Self::default_seed(),
+        Self::load_or_seed(path).0
+    }
+
+    /// Like `load_from_file`, also returning why an existing file was refused, so the
+    /// caller can leave that file alone instead of overwriting it with the seed.
+    pub fn load_or_seed<P: AsRef<Path>>(path: P) -> (Self, Option<LoadError>) {
+        let path = path.as_ref();
+        if !path.exists() {
+            return (Self::default_seed(), None);
+        }
+        match Self::try_load_from_file(path) {
+            Ok(cfg) => (cfg, None),
+            Err(e) => {
+                tracing::error!(path = %path.display(), error = %e, "source weights rejected; using built-in seed");
+                (Self::default_seed(), Some(e))
+            }
+        }
+    }
+
+    /// Load, normalize and validate a JSON file; the error lists every problem.
+    pub fn try_load_from_file<P: AsRef<Path>>(path: P) -> Result<Self, LoadError> {
+        let raw = fs::read_to_string(path).map_err(|e| LoadError::Read(e.to_string()))?;
+        let raw = serde_json::from_str::<Self>(&raw)
+            .map_err(|e| LoadError::Invalid(vec![format!("parse: {e}")]))?;
+        let mut errors = raw.collisions();
+        let cfg = raw.normalized();
+        errors.extend(cfg.validate());
+        if errors.is_empty() {
+            Ok(cfg)
+        } else {
+            errors.sort();
+            Err(LoadError::Invalid(errors))
+        }
+    }
+
+    /// Names that `normalized()` would merge (`"Fed"` and `"fed"`); empty when none.
+    /// Expects the config as written, before `normalized()`.
+    pub fn collisions(&self) -> Vec<String> {
+        let mut errors = name_collisions("weights", self.weights.keys());
+        errors.extend(name_collisions("aliases", self.aliases.keys()));
+        errors
+    }
+
+    /// Keys and alias targets normalized like lookups (`"Barron's"` → `"barron s"`),
+    /// so every entry can actually match.
+    pub fn normalized(self) -> Self {
+        Self {
+            default_weight: self.default_weight,
+            weights: self
+                .weights
+                .into_iter()
+                .map(|(k, w)| (normalize(&k), w))
+                .collect(),
+            aliases: self
+                .aliases
+                .into_iter()
+                .map(|(a, c)| (normalize(&a), normalize(&c)))
+                .collect(),
+        }
+    }
+
+    /// Problems that would make weights wrong or aliases dangling; empty when valid.
+    /// Expects a `normalized()` config.
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        let check = |field: String, w: f32, errors: &mut Vec<String>| {
+            if !(0.0..=1.0).contains(&w) {
+                errors.push(format!("{field}: expected 0..1, got {w}"));
+            }
+        };
+        check("default_weight".into(), self.default_weight, &mut errors);
+        for (name, &w) in &self.weights {
+            if name.is_empty() {
+                errors.push("weights: empty source name".into());
+            }
+            check(format!("weights.{name}"), w, &mut errors);
+        }
+        for (alias, target) in &self.aliases {
+            if alias.is_empty() {
+                errors.push("aliases: empty alias".into());
+            } else if !self.weights.contains_key(target) {
+                errors.push(format!("aliases.{alias}: target `{target}` has no weight"));
+            }
+        }
+        errors.sort();
+        errors
+    }
+
+    /// Write as pretty JSON via a temp file + rename, so readers never see half a file.
+    pub fn save_atomic<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        let mut body = serde_json::to_vec_pretty(self)?;
+        body.push(b'\n');
+        fs::write(&tmp, body)?;
+        fs::rename(&tmp, path)
+    }
+
+    /// `patch` applied on top of `self` (not yet normalized or validated).
+    pub fn patched(&self, patch: &SourceWeightsPatch) -> Self {
+        let mut out = self.clone();
+        if let Some(w) = patch.default_weight {
+            out.default_weight = w;
+        }
+        for (name, w) in &patch.weights {
+            let name = normalize(name);
+            match w {
+                Some(w) => out.weights.insert(name, *w),
+                None => out.weights.remove(&name),
+            };
+        }
+        for (alias, target) in &patch.aliases {
+            let alias = normalize(alias);
+            match target {
+                Some(t) => out.aliases.insert(alias, normalize(t)),
+                None => out.aliases.remove(&alias),
+            };
+        }
+        out
+    }
+
+    /// What changes from `self` to `new`.
+    pub fn diff(&self, new: &Self) -> SourceWeightsDiff {
+        SourceWeightsDiff {
+            default_weight: (self.default_weight != new.default_weight).then_some(Change {
+                from: self.default_weight,
+                to: new.default_weight,
+            }),
+            weights: MapDiff::between(&self.weights, &new.weights),
+            aliases: MapDiff::between(&self.aliases, &new.aliases),
+        }
+    }
+
+    /// Canonical (normalized) name of a source: alias target if one exists,
+    /// otherwise the normalized input.
+    pub fn canonical(&self, source: &str) -> String {
+        let s = normalize(source);
+        match self.aliases.get(&s) {
+            Some(canon) => normalize(canon),
+            None => s,
         }
     }
 
@@ -154,6 +342,185 @@ impl SourceWeightsConfig {
     }
 }
 
+/// Partial update (`PATCH`): listed entries are set, `null` removes one.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SourceWeightsPatch {
+    #[serde(default)]
+    pub default_weight: Option<f32>,
+    #[serde(default)]
+    pub weights: BTreeMap<String, Option<f32>>,
+    #[serde(default)]
+    pub aliases: BTreeMap<String, Option<String>>,
+}
+
+impl SourceWeightsPatch {
+    /// Entries of this patch that name the same source twice (see
+    /// `SourceWeightsConfig::collisions`).
+    pub fn collisions(&self) -> Vec<String> {
+        let mut errors = name_collisions("weights", self.weights.keys());
+        errors.extend(name_collisions("aliases", self.aliases.keys()));
+        errors
+    }
+}
+
+/// `section.<name>: ...` for every normalized name that more than one of `names` maps to.
+fn name_collisions<'a>(section: &str, names: impl Iterator<Item = &'a String>) -> Vec<String> {
+    let mut by_name: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+    for n in names {
+        by_name.entry(normalize(n)).or_default().push(n);
+    }
+    by_name
+        .into_iter()
+        .filter(|(_, raw)| raw.len() > 1)
+        .map(|(name, mut raw)| {
+            raw.sort_unstable();
+            let raw: Vec<String> = raw.iter().map(|r| format!("`{r}`")).collect();
+            format!(
+                "{section}.{name}: {} are the same source; keep one",
+                raw.join(" and ")
+            )
+        })
+        .collect()
+}
+
+/// Old and new value of a changed entry.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Change<T> {
+    pub from: T,
+    pub to: T,
+}
+
+/// Entries added, removed (with their old value) and changed in one map.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MapDiff<T> {
+    pub added: BTreeMap<String, T>,
+    pub removed: BTreeMap<String, T>,
+    pub changed: BTreeMap<String, Change<T>>,
+}
+
+impl<T: Clone + PartialEq> MapDiff<T> {
+    fn between(old: &HashMap<String, T>, new: &HashMap<String, T>) -> Self {
+        let mut d = Self {
+            added: BTreeMap::new(),
+            removed: BTreeMap::new(),
+            changed: BTreeMap::new(),
+        };
+        for (k, v) in old {
+            match new.get(k) {
+                None => {
+                    d.removed.insert(k.clone(), v.clone());
+                }
+                Some(n) if n != v => {
+                    d.changed.insert(
+                        k.clone(),
+                        Change {
+                            from: v.clone(),
+                            to: n.clone(),
+                        },
+                    );
+                }
+                Some(_) => {}
+            }
+        }
+        for (k, v) in new {
+            if !old.contains_key(k) {
+                d.added.insert(k.clone(), v.clone());
+            }
+        }
+        d
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Difference between two configs, as returned by the admin API and kept in its history.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SourceWeightsDiff {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_weight: Option<Change<f32>>,
+    pub weights: MapDiff<f32>,
+    pub aliases: MapDiff<String>,
+}
+
+impl SourceWeightsDiff {
+    pub fn is_empty(&self) -> bool {
+        self.default_weight.is_none() && self.weights.is_empty() && self.aliases.is_empty()
+    }
+}
+
+/// One applied change (a line of the change log).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChangeRecord {
+    pub ts_unix: u64,
+    /// API key id (`null` when access control is off).
+    pub key: Option<String>,
+    /// `put`, `patch` or `reload`.
+    pub action: String,
+    pub diff: SourceWeightsDiff,
+}
+
+/// Append-only JSONL history of source-weight changes.
+#[derive(Debug)]
+pub struct ChangeLog {
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl ChangeLog {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// `SOURCE_WEIGHTS_CHANGE_LOG`, or `state/source_weights_changes.jsonl`.
+    pub fn from_env() -> Self {
+        let path = std::env::var(ENV_CHANGE_LOG_PATH)
+            .ok()
+            .filter(|s| !s.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_CHANGE_LOG_PATH.to_string());
+        Self::new(path.trim())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append `rec`; write errors are logged (the change itself is already saved).
+    pub fn append(&self, rec: &ChangeRecord) {
+        let _guard = self.lock.lock().expect("change log mutex poisoned");
+        let res = (|| -> std::io::Result<()> {
+            if let Some(dir) = self.path.parent().filter(|d| !d.as_os_str().is_empty()) {
+                fs::create_dir_all(dir)?;
+            }
+            let mut line = serde_json::to_vec(rec)?;
+            line.push(b'\n');
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?
+                .write_all(&line)
+        })();
+        if let Err(e) = res {
+            tracing::warn!(path = %self.path.display(), error = %e, "source weights change not logged");
+        }
+    }
+
+    /// Up to `limit` most recent changes, newest first (unreadable lines are skipped).
+    pub fn recent(&self, limit: usize) -> Vec<ChangeRecord> {
+        let _guard = self.lock.lock().expect("change log mutex poisoned");
+        let raw = fs::read_to_string(&self.path).unwrap_or_default();
+        raw.lines()
+            .rev()
+            .filter_map(|l| serde_json::from_str(l).ok())
+            .take(limit)
+            .collect()
+    }
+}
+
 /// Normalize input string: lowercase, replace punctuation/dashes with spaces,
 /// collapse multiple spaces into one.
 fn normalize(s: &str) -> String {
@@ -229,6 +596,104 @@ mod tests {
         assert!((c2 - 0.90).abs() < 1e-6);
     }
 
+    #[test]
+    fn validation_flags_ranges_and_dangling_aliases() {
+        assert!(cfg().validate().is_empty());
+
+        let mut bad = cfg();
+        bad.default_weight = -0.1;
+        bad.weights.insert("fed".into(), 1.5);
+        bad.aliases.insert("nobody".into(), "missing".into());
+        assert_eq!(
+            bad.validate(),
+            vec![
+                "aliases.nobody: target `missing` has no weight",
+                "default_weight: expected 0..1, got -0.1",
+                "weights.fed: expected 0..1, got 1.5",
+            ]
+        );
+    }
+
+    #[test]
+    fn patch_normalizes_and_diff_reports_changes() {
+        let old = cfg();
+        let patch: SourceWeightsPatch = serde_json::from_value(serde_json::json!({
+            "weights": { "Barron's": 0.8, "Fed": 0.9, "boe": null },
+            "aliases": { "Barrons": "barron's", "bank of england": null }
+        }))
+        .unwrap();
+        let new = old.patched(&patch);
+        assert!(new.validate().is_empty());
+        assert!((new.weight_for("Barron’s") - 0.8).abs() < 1e-6);
+        assert!((new.weight_for("barrons") - 0.8).abs() < 1e-6);
+
+        let d = old.diff(&new);
+        assert_eq!(d.weights.added.get("barron s"), Some(&0.8));
+        assert_eq!(d.weights.removed.get("boe"), Some(&0.84));
+        assert_eq!(
+            d.weights.changed.get("fed"),
+            Some(&Change {
+                from: 0.95,
+                to: 0.9
+            })
+        );
+        assert_eq!(
+            d.aliases.added.get("barrons").map(String::as_str),
+            Some("barron s")
+        );
+        assert!(d.aliases.removed.contains_key("bank of england"));
+        assert!(d.default_weight.is_none());
+        assert!(old.diff(&old).is_empty());
+    }
+
+    #[test]
+    fn save_atomic_round_trips_and_invalid_files_are_rejected() {
+        let dir = std::env::temp_dir().join(format!("source_weights_{}", std::process::id()));
+        let path = dir.join("source_weights.json");
+        cfg().save_atomic(&path).unwrap();
+        assert_eq!(
+            SourceWeightsConfig::try_load_from_file(&path).unwrap(),
+            cfg().normalized()
+        );
+
+        fs::write(&path, r#"{"weights": {"fed": 2.0}}"#).unwrap();
+        let err = SourceWeightsConfig::try_load_from_file(&path).unwrap_err();
+        assert!(err.to_string().contains("weights.fed"), "{err}");
+        fs::write(&path, "{ not json").unwrap();
+        assert!(SourceWeightsConfig::try_load_from_file(&path)
+            .unwrap_err()
+            .to_string()
+            .starts_with("parse:"));
+
+        // The seed stands in, and the caller learns why the file was refused.
+        let (seed, err) = SourceWeightsConfig::load_or_seed(&path);
+        assert_eq!(seed, SourceWeightsConfig::default_seed());
+        assert!(matches!(err, Some(LoadError::Invalid(_))));
+        assert_eq!(
+            SourceWeightsConfig::load_or_seed(dir.join("missing.json")).1,
+            None
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn names_that_normalize_alike_are_collisions() {
+        let mut c = cfg();
+        assert!(c.collisions().is_empty());
+        c.weights.insert("Fed".into(), 0.5);
+        c.aliases.insert("Federal-Reserve".into(), "fed".into());
+        assert_eq!(
+            c.collisions(),
+            vec![
+                "weights.fed: `Fed` and `fed` are the same source; keep one".to_string(),
+                "aliases.federal reserve: `Federal-Reserve` and `federal reserve` are the same source; keep one".to_string(),
+            ]
+        );
+        let patch: SourceWeightsPatch =
+            serde_json::from_str(r#"{"weights": {"Reuters": 0.8, "reuters": null}}"#).unwrap();
+        assert_eq!(patch.collisions().len(), 1);
+    }
+
     #[test]
     fn alias_overrides_to_canonical() {
         let c = cfg();
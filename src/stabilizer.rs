@@ -0,0 +1,280 @@
+//! # Verdict Stabilizer (Hysteresis)
+//! Stateful filter between `engine::make_decision` and `History::push`.
+//!
+//! `AntiFlutter` only throttles notifications; this module damps the verdict
+//! itself so successive `/decide` calls do not flip BUY→HOLD→BUY.
+//!
+//! A new direction replaces the current (stable) verdict only when:
+//! - it was seen `confirmations` times in a row **and** the stable verdict has
+//!   been held for at least `min_dwell_secs`, or
+//! - its confidence beats the stable verdict's confidence by `override_margin`.
+//!
+//! Otherwise the stable verdict is reported with the confidence it was last
+//! confirmed at, and the suppressed raw verdict is kept in
+//! `Decision::raw_decision` for transparency (its confidence goes into the reason).
+//!
+//! Defaults (`confirmations = 1`, no dwell, no margin) are a pass-through.
+
+use std::sync::Mutex;
+
+use crate::decision::{Decision, Reason, ReasonKind, Verdict};
+
+/// Tuning knobs for the stabilizer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StabilizerParams {
+    /// Consecutive observations of a new verdict required to switch (min 1).
+    pub confirmations: u32,
+    /// Minimum time (seconds) a stable verdict is held before it may change.
+    pub min_dwell_secs: u64,
+    /// Switch immediately if `raw_conf >= stable_conf + margin` (None = disabled).
+    pub override_margin: Option<f32>,
+}
+
+impl Default for StabilizerParams {
+    fn default() -> Self {
+        Self {
+            confirmations: 1,
+            min_dwell_secs: 0,
+            override_margin: None,
+        }
+    }
+}
+
+impl StabilizerParams {
+    /// Read params from env; unset or invalid values keep the pass-through defaults.
+    ///
+    /// - `VERDICT_CONFIRMATIONS`   (u32, default 1)
+    /// - `VERDICT_MIN_DWELL_SECS`  (u64, default 0)
+    /// - `VERDICT_OVERRIDE_MARGIN` (f32 in `[0,1]`, default off)
+    pub fn from_env() -> Self {
+        let d = Self::default();
+        let confirmations = std::env::var("VERDICT_CONFIRMATIONS")
+            .ok()
+            .and_then(|v| v.trim().parse::<u32>().ok())
+            .unwrap_or(d.confirmations)
+            .max(1);
+        let min_dwell_secs = std::env::var("VERDICT_MIN_DWELL_SECS")
+            .ok()
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .unwrap_or(d.min_dwell_secs);
+        let override_margin = std::env::var("VERDICT_OVERRIDE_MARGIN")
+            .ok()
+            .and_then(|v| v.trim().parse::<f32>().ok())
+            .filter(|m| (0.0..=1.0).contains(m));
+        Self {
+            confirmations,
+            min_dwell_secs,
+            override_margin,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Stable {
+    verdict: Verdict,
+    confidence: f32,
+    since_unix: u64,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    stable: Option<Stable>,
+    /// Candidate verdict and how many times in a row it was observed.
+    pending: Option<(Verdict, u32)>,
+}
+
+/// Thread-safe verdict hysteresis.
+#[derive(Debug)]
+pub struct VerdictStabilizer {
+    params: StabilizerParams,
+    inner: Mutex<State>,
+}
+
+impl VerdictStabilizer {
+    pub fn new(mut params: StabilizerParams) -> Self {
+        params.confirmations = params.confirmations.max(1);
+        Self {
+            params,
+            inner: Mutex::new(State::default()),
+        }
+    }
+
+    pub fn params(&self) -> StabilizerParams {
+        self.params
+    }
+
+    /// Current stable verdict, if any decision was seen yet.
+    pub fn stable_verdict(&self) -> Option<Verdict> {
+        let st = self.inner.lock().expect("stabilizer mutex poisoned");
+        st.stable.map(|s| s.verdict)
+    }
+
+    /// Stabilize `d` in place at time `now_unix`.
+    ///
+    /// Returns `true` when the raw verdict was suppressed (then `d.decision` and
+    /// `d.confidence` hold the stable verdict and `d.raw_decision` the raw one).
+    pub fn apply(&self, d: &mut Decision, now_unix: u64) -> bool {
+        let raw = d.decision;
+        let mut st = self.inner.lock().expect("stabilizer mutex poisoned");
+
+        let Some(stable) = st.stable else {
+            st.stable = Some(Stable {
+                verdict: raw,
+                confidence: d.confidence,
+                since_unix: now_unix,
+            });
+            st.pending = None;
+            return false;
+        };
+
+        if raw == stable.verdict {
+            st.stable = Some(Stable {
+                confidence: d.confidence,
+                ..stable
+            });
+            st.pending = None;
+            return false;
+        }
+
+        let seen = match st.pending {
+            Some((v, n)) if v == raw => n.saturating_add(1),
+            _ => 1,
+        };
+        let held_secs = now_unix.saturating_sub(stable.since_unix);
+        let confirmed = seen >= self.params.confirmations;
+        let dwell_ok = held_secs >= self.params.min_dwell_secs;
+        let margin_ok = self
+            .params
+            .override_margin
+            .is_some_and(|m| d.confidence >= stable.confidence + m);
+
+        if (confirmed && dwell_ok) || margin_ok {
+            st.stable = Some(Stable {
+                verdict: raw,
+                confidence: d.confidence,
+                since_unix: now_unix,
+            });
+            st.pending = None;
+            if margin_ok && !(confirmed && dwell_ok) {
+                d.reasons.push(
+                    Reason::new(format!(
+                        "Hysteresis: {} switched early on confidence margin ({:.3} >= {:.3} + {:.2})",
+                        label(raw),
+                        d.confidence,
+                        stable.confidence,
+                        self.params.override_margin.unwrap_or_default()
+                    ))
+                    .kind(ReasonKind::Threshold),
+                );
+            }
+            return false;
+        }
+
+        st.pending = Some((raw, seen));
+        let raw_confidence = d.confidence;
+        d.decision = stable.verdict;
+        d.confidence = stable.confidence;
+        d.raw_decision = Some(raw);
+        d.reasons.push(
+            Reason::new(format!(
+                "Hysteresis: raw {} ({:.3}) held back ({}/{} confirmations, dwell {}s/{}s) -> keeping {}",
+                label(raw),
+                raw_confidence,
+                seen,
+                self.params.confirmations,
+                held_secs,
+                self.params.min_dwell_secs,
+                label(stable.verdict)
+            ))
+            .kind(ReasonKind::Threshold),
+        );
+        true
+    }
+}
+
+fn label(v: Verdict) -> String {
+    format!("{:?}", v).to_uppercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(confirmations: u32, dwell: u64, margin: Option<f32>) -> StabilizerParams {
+        StabilizerParams {
+            confirmations,
+            min_dwell_secs: dwell,
+            override_margin: margin,
+        }
+    }
+
+    fn run(s: &VerdictStabilizer, v: Verdict, conf: f32, now: u64) -> Decision {
+        let mut d = Decision::new(v, conf);
+        s.apply(&mut d, now);
+        d
+    }
+
+    #[test]
+    fn defaults_are_pass_through() {
+        let s = VerdictStabilizer::new(StabilizerParams::default());
+        for (i, v) in [Verdict::Buy, Verdict::Hold, Verdict::Buy, Verdict::Sell]
+            .into_iter()
+            .enumerate()
+        {
+            let d = run(&s, v, 0.7, i as u64);
+            assert_eq!(d.decision, v);
+            assert!(d.raw_decision.is_none());
+        }
+    }
+
+    #[test]
+    fn flip_requires_consecutive_confirmations() {
+        let s = VerdictStabilizer::new(params(2, 0, None));
+        assert_eq!(run(&s, Verdict::Buy, 0.8, 0).decision, Verdict::Buy);
+
+        // Single HOLD blip is suppressed, raw verdict still reported; the
+        // confidence is BUY's, not HOLD's.
+        let d = run(&s, Verdict::Hold, 0.55, 10);
+        assert_eq!(d.decision, Verdict::Buy);
+        assert_eq!(d.confidence, 0.8);
+        assert_eq!(d.raw_decision, Some(Verdict::Hold));
+        assert!(d.reasons.iter().any(|r| r.message.contains("Hysteresis")));
+
+        // BUY again resets the pending counter.
+        assert_eq!(run(&s, Verdict::Buy, 0.8, 20).decision, Verdict::Buy);
+        assert_eq!(run(&s, Verdict::Hold, 0.55, 30).decision, Verdict::Buy);
+
+        // Second consecutive HOLD confirms the switch.
+        let d = run(&s, Verdict::Hold, 0.55, 40);
+        assert_eq!(d.decision, Verdict::Hold);
+        assert!(d.raw_decision.is_none());
+        assert_eq!(s.stable_verdict(), Some(Verdict::Hold));
+    }
+
+    #[test]
+    fn min_dwell_blocks_early_switch() {
+        let s = VerdictStabilizer::new(params(1, 300, None));
+        run(&s, Verdict::Sell, 0.8, 1_000);
+        assert_eq!(run(&s, Verdict::Buy, 0.8, 1_100).decision, Verdict::Sell);
+        assert_eq!(run(&s, Verdict::Buy, 0.8, 1_300).decision, Verdict::Buy);
+    }
+
+    #[test]
+    fn confidence_margin_overrides_confirmations() {
+        let s = VerdictStabilizer::new(params(3, 600, Some(0.10)));
+        run(&s, Verdict::Hold, 0.55, 0);
+
+        let weak = run(&s, Verdict::Sell, 0.60, 5);
+        assert_eq!(weak.decision, Verdict::Hold);
+        assert_eq!(weak.confidence, 0.55);
+        assert_eq!(weak.raw_decision, Some(Verdict::Sell));
+        assert!(weak.reasons.iter().any(|r| r.message.contains("(0.600)")));
+
+        let strong = run(&s, Verdict::Sell, 0.90, 6);
+        assert_eq!(strong.decision, Verdict::Sell);
+        assert!(strong
+            .reasons
+            .iter()
+            .any(|r| r.message.contains("confidence margin")));
+    }
+}
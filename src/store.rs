@@ -0,0 +1,582 @@
+//! # Decision Store (durable history)
+//! Append-only segment log on local disk for `History` entries, rolling-window
+//! samples and the AI daily counter, replayed on startup so a redeploy keeps context.
+//!
+//! - Enabled by `HISTORY_STORE_DIR` (unset = in-memory only, as before).
+//! - Records are JSON lines in `segment-<n>.jsonl`; the active segment rolls over at
+//!   `HISTORY_SEGMENT_BYTES` (default 4 MiB).
+//! - Retention: history older than `HISTORY_RETENTION_DAYS` (default 30) and beyond
+//!   `HISTORY_STORE_MAX_ENTRIES` (default 10,000, never below the history capacity), and
+//!   rolling samples older than the rolling window, are
+//!   dropped on replay and compaction. Only the latest AI counter is kept.
+//! - Compaction writes the live records into a new segment that starts with a
+//!   `checkpoint` covering every older segment, then deletes those. It runs on open and
+//!   when more than `HISTORY_MAX_SEGMENTS` (default 8) segments exist. The new segment is
+//!   renamed into place only when complete, so a crash mid-compaction loses nothing.
+//! - A torn last line (crash during an append) is skipped on replay.
+//! - `append` only queues the record (`STORE_QUEUE` deep); one writer thread owns the
+//!   files and does the writes, rollovers and compactions, so request handlers never
+//!   wait on disk. Records land in the order they were queued. Dropping the store
+//!   drains the queue.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread::JoinHandle;
+
+use serde::{Deserialize, Serialize};
+
+use crate::clock::SharedClock;
+use crate::history::HistoryEntry;
+
+const SEGMENT_PREFIX: &str = "segment-";
+const SEGMENT_EXT: &str = "jsonl";
+
+/// Records queued for the writer thread before `append` waits (back-pressure).
+pub const STORE_QUEUE: usize = 4096;
+
+/// One line of the log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Record {
+    /// First line of a compacted segment: segments numbered `<= upto` are superseded.
+    Checkpoint {
+        upto: u64,
+    },
+    History(HistoryEntry),
+    Rolling {
+        ts_unix: u64,
+        score: i32,
+    },
+    AiDaily {
+        day: u64,
+        used: usize,
+    },
+}
+
+/// Store location, rollover and retention settings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoreConfig {
+    pub dir: PathBuf,
+    pub segment_bytes: u64,
+    pub max_segments: usize,
+    pub retention_secs: u64,
+    /// History entries kept (newest); at least the in-memory `History` capacity, so
+    /// `/history` can page past the ring.
+    pub max_history: usize,
+    /// Rolling samples older than this are dropped; match the rolling window.
+    pub rolling_secs: u64,
+}
+
+impl StoreConfig {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            segment_bytes: 4 * 1024 * 1024,
+            max_segments: 8,
+            retention_secs: 30 * 86_400,
+            max_history: 10_000,
+            rolling_secs: 48 * 3600,
+        }
+    }
+
+    /// `None` unless `HISTORY_STORE_DIR` is set.
+    pub fn from_env() -> Option<Self> {
+        let dir = std::env::var("HISTORY_STORE_DIR")
+            .ok()
+            .filter(|s| !s.trim().is_empty())?;
+        let mut cfg = Self::new(dir.trim());
+        let num = |key: &str| {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.trim().parse::<u64>().ok())
+                .filter(|n| *n > 0)
+        };
+        if let Some(n) = num("HISTORY_SEGMENT_BYTES") {
+            cfg.segment_bytes = n;
+        }
+        if let Some(n) = num("HISTORY_MAX_SEGMENTS") {
+            cfg.max_segments = n as usize;
+        }
+        if let Some(days) = num("HISTORY_RETENTION_DAYS") {
+            cfg.retention_secs = days * 86_400;
+        }
+        if let Some(n) = num("HISTORY_STORE_MAX_ENTRIES") {
+            cfg.max_history = n as usize;
+        }
+        Some(cfg)
+    }
+}
+
+/// Live state read back from disk (oldest first).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Replay {
+    pub history: Vec<HistoryEntry>,
+    pub rolling: Vec<(u64, i32)>,
+    pub ai_daily: Option<(u64, usize)>,
+}
+
+impl Replay {
+    fn apply(&mut self, rec: Record) {
+        match rec {
+            Record::Checkpoint { .. } => {}
+            Record::History(e) => self.history.push(e),
+            Record::Rolling { ts_unix, score } => self.rolling.push((ts_unix, score)),
+            Record::AiDaily { day, used } => self.ai_daily = Some((day, used)),
+        }
+    }
+
+    /// Drop what retention no longer keeps.
+    fn retain(&mut self, cfg: &StoreConfig, now: u64) {
+        let cutoff = now.saturating_sub(cfg.retention_secs);
+        self.history.retain(|e| e.ts_unix >= cutoff);
+        let excess = self.history.len().saturating_sub(cfg.max_history);
+        self.history.drain(..excess);
+        let rolling_cutoff = now.saturating_sub(cfg.rolling_secs);
+        self.rolling.retain(|(ts, _)| *ts >= rolling_cutoff);
+    }
+
+    fn records(&self) -> impl Iterator<Item = Record> + '_ {
+        self.history
+            .iter()
+            .cloned()
+            .map(Record::History)
+            .chain(
+                self.rolling
+                    .iter()
+                    .map(|&(ts_unix, score)| Record::Rolling { ts_unix, score }),
+            )
+            .chain(
+                self.ai_daily
+                    .map(|(day, used)| Record::AiDaily { day, used }),
+            )
+    }
+}
+
+#[derive(Debug)]
+struct Active {
+    file: File,
+    index: u64,
+    bytes: u64,
+    segments: usize,
+}
+
+/// Work for the writer thread.
+enum Op {
+    Append(Vec<u8>),
+    Compact(mpsc::SyncSender<io::Result<()>>),
+    Flush(mpsc::SyncSender<()>),
+}
+
+/// Append-only segment log (see module docs).
+#[derive(Debug)]
+pub struct DecisionStore {
+    cfg: StoreConfig,
+    tx: Option<mpsc::SyncSender<Op>>,
+    writer: Option<JoinHandle<()>>,
+    /// Segment files on disk, as last updated by the writer.
+    segments: Arc<AtomicUsize>,
+}
+
+/// State owned by the writer thread.
+struct Writer {
+    cfg: StoreConfig,
+    clock: SharedClock,
+    active: Active,
+    segments: Arc<AtomicUsize>,
+}
+
+impl DecisionStore {
+    /// Open (or create) the store, replay it and compact it, then start the writer.
+    pub fn open(cfg: StoreConfig, clock: SharedClock) -> io::Result<(Self, Replay)> {
+        fs::create_dir_all(&cfg.dir)?;
+        remove_tmp_files(&cfg.dir)?;
+        let replay = read_live(&cfg, clock.now_unix())?;
+        let active = compact_into(&cfg, &replay)?;
+        let segments = Arc::new(AtomicUsize::new(active.segments));
+        let (tx, rx) = mpsc::sync_channel(STORE_QUEUE);
+        let writer = Writer {
+            cfg: cfg.clone(),
+            clock,
+            active,
+            segments: segments.clone(),
+        };
+        let handle = std::thread::Builder::new()
+            .name("history-store".into())
+            .spawn(move || writer.run(rx))?;
+        let store = Self {
+            cfg,
+            tx: Some(tx),
+            writer: Some(handle),
+            segments,
+        };
+        Ok((store, replay))
+    }
+
+    pub fn config(&self) -> &StoreConfig {
+        &self.cfg
+    }
+
+    /// Queue one record for the writer; errors are logged, never returned to
+    /// request handlers. Waits only when `STORE_QUEUE` records are pending.
+    pub fn append(&self, rec: &Record) {
+        let line = match serde_json::to_vec(rec) {
+            Ok(mut line) => {
+                line.push(b'\n');
+                line
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "history store record not serializable");
+                return;
+            }
+        };
+        self.send(Op::Append(line));
+    }
+
+    /// Rewrite the live records into a single checkpointed segment (after the
+    /// queued appends) and wait for it.
+    pub fn compact(&self) -> io::Result<()> {
+        let (done, wait) = mpsc::sync_channel(1);
+        self.send(Op::Compact(done));
+        wait.recv()
+            .unwrap_or_else(|_| Err(io::Error::other("history store writer stopped")))
+    }
+
+    /// Wait until every queued record is written (e.g. before reading the segments).
+    pub fn flush(&self) {
+        let (done, wait) = mpsc::sync_channel(1);
+        self.send(Op::Flush(done));
+        let _ = wait.recv();
+    }
+
+    /// Live state as it is on disk once the queued records are written (e.g. for
+    /// another router opening the same directory).
+    pub fn replay(&self, now: u64) -> io::Result<Replay> {
+        self.flush();
+        read_live(&self.cfg, now)
+    }
+
+    /// Number of segment files on disk.
+    pub fn segment_count(&self) -> usize {
+        self.segments.load(Ordering::Relaxed)
+    }
+
+    fn send(&self, op: Op) {
+        let sent = self.tx.as_ref().is_some_and(|tx| tx.send(op).is_ok());
+        if !sent {
+            tracing::warn!(dir = %self.cfg.dir.display(), "history store writer stopped; record dropped");
+        }
+    }
+}
+
+impl Drop for DecisionStore {
+    fn drop(&mut self) {
+        // Closing the queue lets the writer finish what is queued and exit.
+        self.tx.take();
+        if let Some(handle) = self.writer.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Writer {
+    fn run(mut self, rx: mpsc::Receiver<Op>) {
+        for op in rx {
+            match op {
+                Op::Append(line) => {
+                    if let Err(e) = self.append(&line) {
+                        tracing::warn!(dir = %self.cfg.dir.display(), error = %e, "history store append failed");
+                    }
+                }
+                Op::Compact(done) => {
+                    let _ = done.send(self.compact());
+                }
+                Op::Flush(done) => {
+                    let _ = done.send(());
+                }
+            }
+            self.segments.store(self.active.segments, Ordering::Relaxed);
+        }
+    }
+
+    fn append(&mut self, line: &[u8]) -> io::Result<()> {
+        self.active.file.write_all(line)?;
+        self.active.bytes += line.len() as u64;
+
+        if self.active.bytes >= self.cfg.segment_bytes {
+            if self.active.segments >= self.cfg.max_segments {
+                self.compact()?;
+            } else {
+                let index = self.active.index + 1;
+                self.active = Active {
+                    file: open_append(&segment_path(&self.cfg.dir, index))?,
+                    index,
+                    bytes: 0,
+                    segments: self.active.segments + 1,
+                };
+            }
+        }
+        Ok(())
+    }
+
+    fn compact(&mut self) -> io::Result<()> {
+        let replay = read_live(&self.cfg, self.clock.now_unix())?;
+        self.active = compact_into(&self.cfg, &replay)?;
+        Ok(())
+    }
+}
+
+/// Live history in `cfg.dir` (oldest first) without opening the store for writing,
+/// e.g. from the `export` binary while the service keeps appending.
+pub fn read_history(cfg: &StoreConfig, now: u64) -> io::Result<Vec<HistoryEntry>> {
+    Ok(read_live(cfg, now)?.history)
+}
+
+fn segment_path(dir: &Path, index: u64) -> PathBuf {
+    dir.join(format!("{SEGMENT_PREFIX}{index:012}.{SEGMENT_EXT}"))
+}
+
+fn segment_index(path: &Path) -> Option<u64> {
+    if path.extension()?.to_str()? != SEGMENT_EXT {
+        return None;
+    }
+    path.file_stem()?
+        .to_str()?
+        .strip_prefix(SEGMENT_PREFIX)?
+        .parse()
+        .ok()
+}
+
+/// Segment files sorted by index.
+fn segments(dir: &Path) -> io::Result<Vec<(u64, PathBuf)>> {
+    let mut out: Vec<(u64, PathBuf)> = fs::read_dir(dir)?
+        .flatten()
+        .map(|e| e.path())
+        .filter_map(|p| segment_index(&p).map(|i| (i, p)))
+        .collect();
+    out.sort();
+    Ok(out)
+}
+
+fn remove_tmp_files(dir: &Path) -> io::Result<()> {
+    for entry in fs::read_dir(dir)?.flatten() {
+        let path = entry.path();
+        if path.extension().is_some_and(|e| e == "tmp") {
+            fs::remove_file(path)?;
+        }
+    }
+    Ok(())
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+/// Records of one segment; unparsable lines (torn writes) are skipped.
+fn read_segment(path: &Path) -> io::Result<Vec<Record>> {
+    let mut out = Vec::new();
+    for (n, line) in BufReader::new(File::open(path)?).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(&line) {
+            Ok(rec) => out.push(rec),
+            Err(e) => {
+                tracing::warn!(path = %path.display(), line = n + 1, error = %e, "skipping unreadable history record")
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Replay every segment not superseded by a checkpoint, then apply retention.
+fn read_live(cfg: &StoreConfig, now: u64) -> io::Result<Replay> {
+    let mut loaded = Vec::new();
+    for (index, path) in segments(&cfg.dir)? {
+        loaded.push((index, read_segment(&path)?));
+    }
+    let upto = loaded
+        .iter()
+        .filter_map(|(_, recs)| match recs.first() {
+            Some(Record::Checkpoint { upto }) => Some(*upto),
+            _ => None,
+        })
+        .max();
+
+    let mut replay = Replay::default();
+    for (index, recs) in loaded {
+        if upto.is_some_and(|u| index <= u) {
+            continue;
+        }
+        for rec in recs {
+            replay.apply(rec);
+        }
+    }
+    replay.retain(cfg, now);
+    Ok(replay)
+}
+
+/// Write `replay` as a new checkpointed segment and delete the ones it supersedes.
+fn compact_into(cfg: &StoreConfig, replay: &Replay) -> io::Result<Active> {
+    let old = segments(&cfg.dir)?;
+    let upto = old.last().map(|(i, _)| *i).unwrap_or(0);
+    let index = upto + 1;
+    let path = segment_path(&cfg.dir, index);
+    let tmp = path.with_extension("tmp");
+
+    let mut buf = Vec::new();
+    for rec in std::iter::once(Record::Checkpoint { upto }).chain(replay.records()) {
+        serde_json::to_writer(&mut buf, &rec)?;
+        buf.push(b'\n');
+    }
+    {
+        let mut f = File::create(&tmp)?;
+        f.write_all(&buf)?;
+        f.sync_all()?;
+    }
+    fs::rename(&tmp, &path)?;
+    for (_, p) in old {
+        fs::remove_file(p)?;
+    }
+
+    Ok(Active {
+        file: open_append(&path)?,
+        index,
+        bytes: buf.len() as u64,
+        segments: 1,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::decision::Verdict;
+    use std::sync::Arc;
+
+    const NOW: u64 = 1_756_733_400;
+
+    fn tmp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("store_{name}_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn entry(ts_unix: u64, verdict: Verdict) -> HistoryEntry {
+        HistoryEntry {
+            id: 0,
+            ts_unix,
+            verdict,
+            confidence: 0.6,
+            signal: None,
+            top_sources: vec!["Fed".into()],
+            top_scores: vec![2],
+            reasons: Vec::new(),
+            contributors: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn replays_after_reopen_and_skips_torn_lines() {
+        let dir = tmp_dir("reopen");
+        let clock: SharedClock = Arc::new(ManualClock::new(NOW));
+        {
+            let (store, replay) =
+                DecisionStore::open(StoreConfig::new(&dir), clock.clone()).unwrap();
+            assert_eq!(replay, Replay::default());
+            store.append(&Record::History(entry(NOW - 10, Verdict::Buy)));
+            store.append(&Record::Rolling {
+                ts_unix: NOW - 10,
+                score: 3,
+            });
+            store.append(&Record::AiDaily {
+                day: NOW / 86_400,
+                used: 1,
+            });
+            store.append(&Record::AiDaily {
+                day: NOW / 86_400,
+                used: 2,
+            });
+        }
+        // Crash mid-append: half a record at the end of the active segment.
+        let (_, last) = segments(&dir).unwrap().pop().unwrap();
+        let mut f = open_append(&last).unwrap();
+        f.write_all(br#"{"kind":"rolling","ts_un"#).unwrap();
+
+        let (store, replay) = DecisionStore::open(StoreConfig::new(&dir), clock).unwrap();
+        assert_eq!(replay.history, vec![entry(NOW - 10, Verdict::Buy)]);
+        assert_eq!(replay.rolling, vec![(NOW - 10, 3)]);
+        assert_eq!(replay.ai_daily, Some((NOW / 86_400, 2)));
+        assert_eq!(store.segment_count(), 1);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn retention_and_capacity_apply_on_replay() {
+        let dir = tmp_dir("retention");
+        let clock = Arc::new(ManualClock::new(NOW));
+        let mut cfg = StoreConfig::new(&dir);
+        cfg.retention_secs = 3600;
+        cfg.max_history = 2;
+        cfg.rolling_secs = 60;
+        {
+            let (store, _) = DecisionStore::open(cfg.clone(), clock.clone()).unwrap();
+            store.append(&Record::History(entry(NOW - 7200, Verdict::Sell)));
+            for i in 0..3 {
+                store.append(&Record::History(entry(NOW - 100 + i, Verdict::Hold)));
+            }
+            store.append(&Record::Rolling {
+                ts_unix: NOW - 120,
+                score: -1,
+            });
+            store.append(&Record::Rolling {
+                ts_unix: NOW - 30,
+                score: 4,
+            });
+        }
+        let (_, replay) = DecisionStore::open(cfg, clock).unwrap();
+        let ts: Vec<u64> = replay.history.iter().map(|e| e.ts_unix).collect();
+        assert_eq!(ts, vec![NOW - 99, NOW - 98]);
+        assert_eq!(replay.rolling, vec![(NOW - 30, 4)]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn rollover_compacts_and_checkpoint_supersedes_old_segments() {
+        let dir = tmp_dir("compact");
+        let clock = Arc::new(ManualClock::new(NOW));
+        let mut cfg = StoreConfig::new(&dir);
+        cfg.segment_bytes = 200;
+        cfg.max_segments = 3;
+        cfg.max_history = 5;
+
+        let (store, _) = DecisionStore::open(cfg.clone(), clock.clone()).unwrap();
+        for i in 0..40 {
+            store.append(&Record::History(entry(NOW - 40 + i, Verdict::Buy)));
+            assert!(store.segment_count() <= 3);
+        }
+        drop(store);
+
+        // A crash after the compacted segment was renamed in but before the old
+        // segments were deleted: the checkpoint makes replay ignore them.
+        let stale = segment_path(&dir, 0);
+        fs::write(
+            &stale,
+            serde_json::to_string(&Record::History(entry(NOW, Verdict::Sell))).unwrap() + "\n",
+        )
+        .unwrap();
+        fs::write(dir.join("segment-000000000999.tmp"), "partial").unwrap();
+
+        let (_, replay) = DecisionStore::open(cfg, clock).unwrap();
+        let ts: Vec<u64> = replay.history.iter().map(|e| e.ts_unix).collect();
+        assert_eq!(ts, (NOW - 5..NOW).collect::<Vec<_>>());
+        assert!(replay.history.iter().all(|e| e.verdict == Verdict::Buy));
+        assert!(!stale.exists());
+        assert_eq!(segments(&dir).unwrap().len(), 1);
+        assert!(!dir.join("segment-000000000999.tmp").exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,360 @@
+//! # Live Stream (`/api/stream`)
+//! Process-wide broadcast of decisions, verdict changes and ingested events,
+//! served to clients as Server-Sent Events or over a WebSocket.
+//!
+//! - `History` publishes a `decision` for every push and a `verdict_change`
+//!   when the verdict or its graded signal differs from the previous entry
+//!   (`BUY` → `STRONG_BUY` is a change too; see `History::with_bus`).
+//! - `ingest::ingest_and_decide` publishes each kept `SourceEvent` as `ingest`
+//!   (`ingest_and_decide_on` takes an explicit bus).
+//! - Every event carries a monotonically increasing `seq`; slow clients that
+//!   fall behind the buffer (`STREAM_BUFFER`, default 256) skip ahead.
+//! - Per-client filters come from the query string (`StreamFilter::from_query`).
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use crate::decision::{Decision, Verdict};
+use crate::grade::SignalGrade;
+use crate::ingest::types::SourceEvent;
+
+/// Default number of buffered events per subscriber.
+pub const DEFAULT_STREAM_BUFFER: usize = 256;
+
+/// One pushed event (`type` tag: `decision` | `verdict_change` | `ingest`).
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StreamEvent {
+    Decision {
+        ts_unix: u64,
+        decision: Decision,
+    },
+    /// The verdict changed, or it stayed and its grade moved (`from == to`,
+    /// `from_signal != signal`).
+    VerdictChange {
+        ts_unix: u64,
+        from: Verdict,
+        to: Verdict,
+        confidence: f32,
+        /// Grade of the previous entry.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        from_signal: Option<SignalGrade>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        signal: Option<SignalGrade>,
+    },
+    Ingest {
+        ts_unix: u64,
+        event: SourceEvent,
+    },
+}
+
+impl StreamEvent {
+    /// Wire name of the event type (SSE `event:` field, filter key).
+    pub fn kind(&self) -> &'static str {
+        match self {
+            StreamEvent::Decision { .. } => "decision",
+            StreamEvent::VerdictChange { .. } => "verdict_change",
+            StreamEvent::Ingest { .. } => "ingest",
+        }
+    }
+
+    /// Confidence of decision/change events (`None` for ingest).
+    pub fn confidence(&self) -> Option<f32> {
+        match self {
+            StreamEvent::Decision { decision, .. } => Some(decision.confidence),
+            StreamEvent::VerdictChange { confidence, .. } => Some(*confidence),
+            StreamEvent::Ingest { .. } => None,
+        }
+    }
+
+    /// Verdict of decision/change events (the new one for changes).
+    pub fn verdict(&self) -> Option<Verdict> {
+        match self {
+            StreamEvent::Decision { decision, .. } => Some(decision.decision),
+            StreamEvent::VerdictChange { to, .. } => Some(*to),
+            StreamEvent::Ingest { .. } => None,
+        }
+    }
+}
+
+/// An event with its sequence number, as sent to clients.
+#[derive(Debug, Clone, Serialize)]
+pub struct StreamMessage {
+    pub seq: u64,
+    #[serde(flatten)]
+    pub event: StreamEvent,
+}
+
+/// Broadcast channel shared by publishers and stream clients.
+#[derive(Debug, Clone)]
+pub struct DecisionBus {
+    tx: broadcast::Sender<StreamMessage>,
+    seq: std::sync::Arc<AtomicU64>,
+}
+
+impl DecisionBus {
+    pub fn new(capacity: usize) -> Self {
+        let (tx, _) = broadcast::channel(capacity.max(1));
+        Self {
+            tx,
+            seq: std::sync::Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Buffer size from `STREAM_BUFFER` (default 256).
+    pub fn from_env() -> Self {
+        let capacity = std::env::var("STREAM_BUFFER")
+            .ok()
+            .and_then(|v| v.trim().parse::<usize>().ok())
+            .filter(|n| *n > 0)
+            .unwrap_or(DEFAULT_STREAM_BUFFER);
+        Self::new(capacity)
+    }
+
+    /// Send `event` to all current subscribers (dropped when there are none).
+    pub fn publish(&self, event: StreamEvent) {
+        let seq = self.seq.fetch_add(1, Ordering::Relaxed) + 1;
+        let _ = self.tx.send(StreamMessage { seq, event });
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<StreamMessage> {
+        self.tx.subscribe()
+    }
+
+    /// Number of connected stream clients.
+    pub fn receiver_count(&self) -> usize {
+        self.tx.receiver_count()
+    }
+
+    /// Whether `other` is a handle to this same bus.
+    pub fn same_bus(&self, other: &DecisionBus) -> bool {
+        self.tx.same_channel(&other.tx)
+    }
+}
+
+static BUS: OnceLock<DecisionBus> = OnceLock::new();
+
+/// The process-wide bus (created from env on first use).
+pub fn bus() -> &'static DecisionBus {
+    BUS.get_or_init(DecisionBus::from_env)
+}
+
+/// Per-client selection of events.
+///
+/// Query parameters: `types=decision,verdict_change,ingest` (default: decisions and
+/// verdict changes), `changes_only=1` (only verdict changes), `min_confidence=0.6`
+/// and `verdicts=BUY,SELL` (both apply to decision/change events only).
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamFilter {
+    pub decisions: bool,
+    pub changes: bool,
+    pub ingest: bool,
+    pub min_confidence: Option<f32>,
+    pub verdicts: Option<Vec<Verdict>>,
+}
+
+impl Default for StreamFilter {
+    fn default() -> Self {
+        Self {
+            decisions: true,
+            changes: true,
+            ingest: false,
+            min_confidence: None,
+            verdicts: None,
+        }
+    }
+}
+
+impl StreamFilter {
+    /// Build from query parameters; unknown types or verdicts are an error.
+    pub fn from_query(q: &HashMap<String, String>) -> Result<Self, String> {
+        let mut f = Self::default();
+        if let Some(types) = q.get("types").filter(|s| !s.trim().is_empty()) {
+            f.decisions = false;
+            f.changes = false;
+            for t in types.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+                match t {
+                    "decision" => f.decisions = true,
+                    "verdict_change" => f.changes = true,
+                    "ingest" => f.ingest = true,
+                    other => return Err(format!("types: unknown event type `{other}`")),
+                }
+            }
+        }
+        if q.get("changes_only")
+            .is_some_and(|v| matches!(v.trim(), "1" | "true"))
+        {
+            f.decisions = false;
+            f.changes = true;
+            f.ingest = false;
+        }
+        if let Some(v) = q.get("min_confidence") {
+            let c = v
+                .trim()
+                .parse::<f32>()
+                .ok()
+                .filter(|c| (0.0..=1.0).contains(c))
+                .ok_or_else(|| format!("min_confidence: expected 0..1, got `{v}`"))?;
+            f.min_confidence = Some(c);
+        }
+        if let Some(vs) = q.get("verdicts").filter(|s| !s.trim().is_empty()) {
+            let mut out = Vec::new();
+            for v in vs.split(',').map(str::trim).filter(|v| !v.is_empty()) {
+                out.push(match v.to_ascii_uppercase().as_str() {
+                    "BUY" => Verdict::Buy,
+                    "SELL" => Verdict::Sell,
+                    "HOLD" => Verdict::Hold,
+                    _ => return Err(format!("verdicts: unknown verdict `{v}`")),
+                });
+            }
+            f.verdicts = Some(out);
+        }
+        Ok(f)
+    }
+
+    pub fn matches(&self, ev: &StreamEvent) -> bool {
+        let wanted = match ev {
+            StreamEvent::Decision { .. } => self.decisions,
+            StreamEvent::VerdictChange { .. } => self.changes,
+            StreamEvent::Ingest { .. } => self.ingest,
+        };
+        if !wanted {
+            return false;
+        }
+        if let (Some(min), Some(c)) = (self.min_confidence, ev.confidence()) {
+            if c < min {
+                return false;
+            }
+        }
+        if let (Some(vs), Some(v)) = (&self.verdicts, ev.verdict()) {
+            if !vs.contains(&v) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn q(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn decision(verdict: Verdict, confidence: f32) -> StreamEvent {
+        let mut d = Decision::hold(confidence);
+        d.decision = verdict;
+        StreamEvent::Decision {
+            ts_unix: 0,
+            decision: d,
+        }
+    }
+
+    #[test]
+    fn filter_by_type_confidence_and_verdict() {
+        let ingest = StreamEvent::Ingest {
+            ts_unix: 0,
+            event: SourceEvent {
+                source: "Fed".into(),
+                published_at: 0,
+                text: "t".into(),
+                url: None,
+                priority_hint: None,
+            },
+        };
+        let change = StreamEvent::VerdictChange {
+            ts_unix: 0,
+            from: Verdict::Hold,
+            to: Verdict::Buy,
+            confidence: 0.7,
+            from_signal: None,
+            signal: None,
+        };
+
+        let all = StreamFilter::default();
+        assert!(all.matches(&decision(Verdict::Buy, 0.4)));
+        assert!(all.matches(&change));
+        assert!(!all.matches(&ingest));
+
+        let f = StreamFilter::from_query(&q(&[
+            ("types", "decision,ingest"),
+            ("min_confidence", "0.6"),
+            ("verdicts", "buy,SELL"),
+        ]))
+        .unwrap();
+        assert!(f.matches(&decision(Verdict::Buy, 0.6)));
+        assert!(!f.matches(&decision(Verdict::Buy, 0.5)));
+        assert!(!f.matches(&decision(Verdict::Hold, 0.9)));
+        assert!(!f.matches(&change));
+        assert!(f.matches(&ingest));
+
+        let changes = StreamFilter::from_query(&q(&[("changes_only", "1")])).unwrap();
+        assert!(changes.matches(&change));
+        assert!(!changes.matches(&decision(Verdict::Buy, 0.9)));
+
+        assert!(StreamFilter::from_query(&q(&[("types", "trades")])).is_err());
+        assert!(StreamFilter::from_query(&q(&[("min_confidence", "2")])).is_err());
+    }
+
+    struct Stub(Vec<SourceEvent>);
+
+    #[async_trait::async_trait]
+    impl crate::ingest::types::SourceProvider for Stub {
+        async fn fetch_latest(&self) -> anyhow::Result<Vec<SourceEvent>> {
+            Ok(self.0.clone())
+        }
+        fn name(&self) -> &'static str {
+            "stub"
+        }
+    }
+
+    #[tokio::test]
+    async fn ingest_publishes_kept_events() {
+        let ev = |source: &str, text: &str| SourceEvent {
+            source: source.into(),
+            published_at: 100,
+            text: text.into(),
+            url: None,
+            priority_hint: None,
+        };
+        let provider = Stub(vec![
+            ev("Fed", "Rates unchanged"),
+            ev("Blog", "Not whitelisted"),
+        ]);
+        let bus = DecisionBus::new(8);
+        let mut rx = bus.subscribe();
+        crate::ingest::ingest_and_decide_on(&bus, &provider, 100, &["Fed".to_string()], 600).await;
+
+        let msg = rx.try_recv().expect("kept event published");
+        match msg.event {
+            StreamEvent::Ingest { ts_unix, event } => {
+                assert_eq!(ts_unix, 100);
+                assert_eq!(event.source, "Fed");
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+        assert!(rx.try_recv().is_err(), "filtered events are not published");
+    }
+
+    #[tokio::test]
+    async fn bus_numbers_events_for_subscribers() {
+        let bus = DecisionBus::new(4);
+        bus.publish(decision(Verdict::Hold, 0.5)); // no subscribers yet: dropped
+        let mut rx = bus.subscribe();
+        bus.publish(decision(Verdict::Buy, 0.8));
+        let msg = rx.recv().await.unwrap();
+        assert_eq!(msg.seq, 2);
+        let json = serde_json::to_value(&msg).unwrap();
+        assert_eq!(json["type"], "decision");
+        assert_eq!(json["decision"]["decision"], "BUY");
+    }
+}
@@ -0,0 +1,964 @@
+//! # Webhook subscriptions
+//! Outbound callbacks for downstream services, next to the Slack/Discord/email
+//! notifiers: every event on the `stream::DecisionBus` that passes a subscription's
+//! filter is POSTed to its URL.
+//!
+//! - Managed via `POST/GET/DELETE /api/subscriptions`; persisted as JSON in
+//!   `SUBSCRIPTIONS_PATH` (default `state/subscriptions.json`) and reloaded on startup.
+//!   An unreadable or invalid file is left alone: nothing is delivered and changes are
+//!   refused until it is fixed.
+//! - Targets must be public `http(s)` hosts: loopback, private, link-local and similar
+//!   addresses are refused on creation and again when the host is resolved for each
+//!   delivery, and redirects are not followed. `WEBHOOK_ALLOW_PRIVATE=1` lifts this
+//!   (local receivers in development and tests).
+//! - Body: the `/stream` message (`{"seq", "type", "ts_unix", ...}`).
+//! - Signed: `X-Webhook-Signature: sha256=<hex HMAC-SHA256(secret, "<timestamp>.<body>")>`
+//!   with the timestamp in `X-Webhook-Timestamp` (unix seconds).
+//! - Retried on network errors, 5xx, 408 and 429 with exponential backoff
+//!   (`WEBHOOK_BACKOFF_MS`, default 1000, doubling up to 60 s) for up to
+//!   `WEBHOOK_MAX_ATTEMPTS` (default 5) attempts; other 4xx fail at once.
+//! - Each subscription has one delivery worker fed by a bounded queue (`WEBHOOK_QUEUE`,
+//!   default 256), so events reach it in order; when a slow receiver lets the queue
+//!   fill up, further events are dropped and counted as failed.
+//! - Per-subscription delivery status (counters, last 20 deliveries) is kept in memory.
+//! - Routers of one process that use the same file share one instance (`shared`), and
+//!   each bus gets one dispatcher, so an event is POSTed once however many routers
+//!   publish on it.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
+use std::fs;
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock, Weak};
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use metrics::counter;
+use once_cell::sync::Lazy;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio::sync::watch;
+
+use crate::clock::SharedClock;
+use crate::decision::Verdict;
+use crate::stream::{DecisionBus, StreamFilter, StreamMessage};
+
+pub const DEFAULT_SUBSCRIPTIONS_PATH: &str = "state/subscriptions.json";
+pub const SIGNATURE_HEADER: &str = "x-webhook-signature";
+pub const TIMESTAMP_HEADER: &str = "x-webhook-timestamp";
+
+/// Deliveries remembered per subscription.
+const RECENT_DELIVERIES: usize = 20;
+const MAX_BACKOFF_MS: u64 = 60_000;
+
+/// Delivery and persistence settings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebhookConfig {
+    pub path: PathBuf,
+    pub max_attempts: u32,
+    pub backoff_ms: u64,
+    pub timeout_ms: u64,
+    /// Events queued per subscription while a delivery is in flight.
+    pub queue: usize,
+    /// Allow loopback / private / link-local targets (`WEBHOOK_ALLOW_PRIVATE`).
+    pub allow_private: bool,
+}
+
+impl WebhookConfig {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            max_attempts: 5,
+            backoff_ms: 1000,
+            timeout_ms: 5000,
+            queue: 256,
+            allow_private: false,
+        }
+    }
+
+    /// `SUBSCRIPTIONS_PATH`, `WEBHOOK_MAX_ATTEMPTS`, `WEBHOOK_BACKOFF_MS`, `WEBHOOK_TIMEOUT_MS`,
+    /// `WEBHOOK_QUEUE`, `WEBHOOK_ALLOW_PRIVATE`.
+    pub fn from_env() -> Self {
+        let path = std::env::var("SUBSCRIPTIONS_PATH")
+            .ok()
+            .filter(|s| !s.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_SUBSCRIPTIONS_PATH.to_string());
+        let mut cfg = Self::new(path.trim());
+        let num = |key: &str| {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.trim().parse::<u64>().ok())
+                .filter(|n| *n > 0)
+        };
+        if let Some(n) = num("WEBHOOK_MAX_ATTEMPTS") {
+            cfg.max_attempts = n as u32;
+        }
+        if let Some(n) = num("WEBHOOK_BACKOFF_MS") {
+            cfg.backoff_ms = n;
+        }
+        if let Some(n) = num("WEBHOOK_TIMEOUT_MS") {
+            cfg.timeout_ms = n;
+        }
+        if let Some(n) = num("WEBHOOK_QUEUE") {
+            cfg.queue = n as usize;
+        }
+        cfg.allow_private = std::env::var("WEBHOOK_ALLOW_PRIVATE")
+            .map(|v| matches!(v.trim(), "1" | "true"))
+            .unwrap_or(false);
+        cfg
+    }
+
+    /// Wait before attempt `attempt + 1` (after `attempt` failures).
+    fn backoff(&self, attempt: u32) -> Duration {
+        let ms = self
+            .backoff_ms
+            .saturating_mul(1u64 << (attempt.saturating_sub(1)).min(16));
+        Duration::from_millis(ms.min(MAX_BACKOFF_MS))
+    }
+}
+
+/// Which events a subscription receives (same semantics as the `/stream` filters).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct SubscriptionFilter {
+    /// `decision`, `verdict_change`, `ingest` (default: decisions and verdict changes).
+    #[serde(default = "default_events")]
+    pub events: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_confidence: Option<f32>,
+    /// Empty = all verdicts.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub verdicts: Vec<Verdict>,
+}
+
+fn default_events() -> Vec<String> {
+    vec!["decision".into(), "verdict_change".into()]
+}
+
+impl Default for SubscriptionFilter {
+    fn default() -> Self {
+        Self {
+            events: default_events(),
+            min_confidence: None,
+            verdicts: Vec::new(),
+        }
+    }
+}
+
+impl SubscriptionFilter {
+    /// The equivalent `/stream` filter; unknown event names or confidences out of 0..1 are an error.
+    pub fn to_stream_filter(&self) -> Result<StreamFilter, String> {
+        let mut f = StreamFilter {
+            decisions: false,
+            changes: false,
+            ingest: false,
+            min_confidence: None,
+            verdicts: None,
+        };
+        for e in &self.events {
+            match e.trim() {
+                "decision" => f.decisions = true,
+                "verdict_change" => f.changes = true,
+                "ingest" => f.ingest = true,
+                other => return Err(format!("events: unknown event type `{other}`")),
+            }
+        }
+        if self.events.is_empty() {
+            return Err("events: at least one event type is required".into());
+        }
+        if let Some(c) = self.min_confidence {
+            if !(0.0..=1.0).contains(&c) {
+                return Err(format!("min_confidence: expected 0..1, got {c}"));
+            }
+            f.min_confidence = Some(c);
+        }
+        if !self.verdicts.is_empty() {
+            f.verdicts = Some(self.verdicts.clone());
+        }
+        Ok(f)
+    }
+}
+
+/// A registered callback (persisted form, secret included).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Subscription {
+    pub id: String,
+    pub url: String,
+    pub secret: String,
+    #[serde(default)]
+    pub filter: SubscriptionFilter,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub created_unix: u64,
+}
+
+/// `POST /subscriptions` body.
+#[derive(Debug, Clone, Deserialize, utoipa::ToSchema)]
+pub struct NewSubscription {
+    /// `http(s)://` callback URL.
+    pub url: String,
+    #[serde(default)]
+    pub filter: SubscriptionFilter,
+    /// Signing secret; generated when absent (returned once, on creation).
+    #[serde(default)]
+    pub secret: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Outcome of one event delivery.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryOutcome {
+    Retrying,
+    Delivered,
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct DeliveryRecord {
+    /// Bus sequence number of the event.
+    pub seq: u64,
+    pub event: String,
+    pub attempts: u32,
+    pub outcome: DeliveryOutcome,
+    /// HTTP status of the last attempt, if a response arrived.
+    pub status: Option<u16>,
+    pub error: Option<String>,
+    pub ts_unix: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct DeliveryStats {
+    pub delivered: u64,
+    pub failed: u64,
+    /// Newest first.
+    #[schema(value_type = Vec<DeliveryRecord>)]
+    pub recent: VecDeque<DeliveryRecord>,
+}
+
+/// API view of a subscription (no secret).
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct SubscriptionView {
+    pub id: String,
+    pub url: String,
+    pub filter: SubscriptionFilter,
+    pub description: Option<String>,
+    pub created_unix: u64,
+    pub deliveries: DeliveryStats,
+}
+
+/// Why a subscription change was refused.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SubscriptionError {
+    /// Bad URL, filter or secret.
+    Invalid(String),
+    /// The subscriptions file could not be loaded; it is kept as is until fixed.
+    Unavailable(String),
+    /// The new list could not be written.
+    NotPersisted(String),
+}
+
+impl fmt::Display for SubscriptionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid(e) | Self::NotPersisted(e) => f.write_str(e),
+            Self::Unavailable(e) => write!(f, "subscriptions unavailable: {e}"),
+        }
+    }
+}
+
+/// One event waiting in a subscription's queue.
+#[derive(Debug)]
+struct Delivery {
+    seq: u64,
+    kind: &'static str,
+    body: Arc<str>,
+}
+
+/// Registered subscriptions, their delivery status and the HTTP client.
+#[derive(Debug)]
+pub struct Webhooks {
+    cfg: WebhookConfig,
+    clock: SharedClock,
+    client: reqwest::Client,
+    subs: RwLock<BTreeMap<String, Subscription>>,
+    stats: Mutex<HashMap<String, DeliveryStats>>,
+    /// Queue of each subscription's delivery worker (started on its first event).
+    queues: Mutex<HashMap<String, mpsc::Sender<Delivery>>>,
+    /// Why the subscriptions file could not be loaded (changes are refused meanwhile).
+    load_error: Option<String>,
+    /// Buses that already have a dispatcher feeding this instance.
+    dispatchers: Mutex<Vec<DecisionBus>>,
+    /// Dropped with the instance; its dispatchers stop then.
+    closed: watch::Sender<()>,
+}
+
+/// Live instances by subscriptions file (see `Webhooks::shared`).
+static SHARED: Lazy<Mutex<HashMap<PathBuf, Weak<Webhooks>>>> = Lazy::new(Default::default);
+
+/// Whether `ip` is a routable public address (not loopback, private, link-local,
+/// carrier-grade NAT, unspecified, broadcast or multicast).
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, ..] = v4.octets();
+            !(v4.is_loopback()
+                || v4.is_private()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_broadcast()
+                || v4.is_multicast()
+                || a == 0
+                || (a == 100 && (64..128).contains(&b)))
+        }
+        IpAddr::V6(v6) => {
+            if let Some(v4) = v6.to_ipv4_mapped() {
+                return is_public_ip(IpAddr::V4(v4));
+            }
+            let first = v6.segments()[0];
+            !(v6.is_loopback()
+                || v6.is_unspecified()
+                || v6.is_multicast()
+                || (first & 0xfe00) == 0xfc00
+                || (first & 0xffc0) == 0xfe80)
+        }
+    }
+}
+
+/// Refuse callback URLs that would point the service at itself or its network.
+fn check_target(url: &reqwest::Url, allow_private: bool) -> Result<(), String> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!(
+            "url: expected http or https, got `{}`",
+            url.scheme()
+        ));
+    }
+    let host = url.host_str().ok_or("url: a host is required")?;
+    if allow_private {
+        return Ok(());
+    }
+    let name = host.trim_start_matches('[').trim_end_matches(']');
+    let private = match name.parse::<IpAddr>() {
+        Ok(ip) => !is_public_ip(ip),
+        Err(_) => {
+            let d = name.trim_end_matches('.').to_ascii_lowercase();
+            d == "localhost" || d.ends_with(".localhost")
+        }
+    };
+    if private {
+        return Err(format!(
+            "url: `{host}` is a loopback, private or link-local address (set WEBHOOK_ALLOW_PRIVATE=1 to allow)"
+        ));
+    }
+    Ok(())
+}
+
+/// DNS resolver that drops non-public addresses, so a public-looking host name cannot
+/// send deliveries into the local network.
+struct PublicOnly;
+
+impl reqwest::dns::Resolve for PublicOnly {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|a| is_public_ip(a.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("`{host}` has no public address").into());
+            }
+            Ok(Box::new(addrs.into_iter()) as reqwest::dns::Addrs)
+        })
+    }
+}
+
+/// Lowercase hex of `bytes`.
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// `sha256=<hex>` signature of `body` sent at `ts` (see module docs).
+pub fn sign(secret: &str, ts: u64, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(ts.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    format!("sha256={}", hex(&mac.finalize().into_bytes()))
+}
+
+fn random_hex(bytes: usize) -> String {
+    let mut buf = vec![0u8; bytes];
+    rand::rng().fill(&mut buf[..]);
+    hex(&buf)
+}
+
+impl Webhooks {
+    /// Load persisted subscriptions from `cfg.path` (missing file = none). An
+    /// unreadable or invalid file is logged and kept: the set stays empty and
+    /// `create` / `delete` are refused (`load_error`) until it is fixed.
+    pub fn load(cfg: WebhookConfig, clock: SharedClock) -> Self {
+        let loaded = fs::read_to_string(&cfg.path)
+            .or_else(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => Ok("[]".to_string()),
+                _ => Err(e.to_string()),
+            })
+            .and_then(|raw| {
+                serde_json::from_str::<Vec<Subscription>>(&raw).map_err(|e| e.to_string())
+            });
+        let (subs, load_error) = match loaded {
+            Ok(list) => (list.into_iter().map(|s| (s.id.clone(), s)).collect(), None),
+            Err(e) => {
+                tracing::error!(path = %cfg.path.display(), error = %e, "subscriptions file unreadable or invalid; no webhooks are delivered and changes are refused until it is fixed");
+                (
+                    BTreeMap::new(),
+                    Some(format!("{}: {e}", cfg.path.display())),
+                )
+            }
+        };
+        let mut client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(cfg.timeout_ms))
+            .redirect(reqwest::redirect::Policy::none());
+        if !cfg.allow_private {
+            client = client.dns_resolver(Arc::new(PublicOnly));
+        }
+        Self {
+            cfg,
+            clock,
+            client: client.build().unwrap_or_default(),
+            subs: RwLock::new(subs),
+            stats: Mutex::new(HashMap::new()),
+            queues: Mutex::new(HashMap::new()),
+            load_error,
+            dispatchers: Mutex::new(Vec::new()),
+            closed: watch::channel(()).0,
+        }
+    }
+
+    pub fn from_env(clock: SharedClock) -> Self {
+        Self::load(WebhookConfig::from_env(), clock)
+    }
+
+    /// The instance for `cfg.path`, shared by everyone in this process using that file,
+    /// so they see and persist one set of subscriptions. Loaded by the first caller
+    /// (whose `cfg` and `clock` apply) and again once all holders have dropped it.
+    pub fn shared(cfg: WebhookConfig, clock: SharedClock) -> Arc<Self> {
+        let mut shared = SHARED.lock().expect("shared webhooks poisoned");
+        if let Some(this) = shared.get(&cfg.path).and_then(Weak::upgrade) {
+            return this;
+        }
+        shared.retain(|_, w| w.strong_count() > 0);
+        let path = cfg.path.clone();
+        let this = Arc::new(Self::load(cfg, clock));
+        shared.insert(path, Arc::downgrade(&this));
+        this
+    }
+
+    /// `shared` with the config from env.
+    pub fn shared_from_env(clock: SharedClock) -> Arc<Self> {
+        Self::shared(WebhookConfig::from_env(), clock)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.cfg.path
+    }
+
+    /// Why the subscriptions file could not be loaded, if it could not.
+    pub fn load_error(&self) -> Option<&str> {
+        self.load_error.as_deref()
+    }
+
+    fn check_loaded(&self) -> Result<(), SubscriptionError> {
+        match &self.load_error {
+            Some(e) => Err(SubscriptionError::Unavailable(e.clone())),
+            None => Ok(()),
+        }
+    }
+
+    /// Register a subscription and persist; returns it with its secret.
+    pub fn create(&self, req: NewSubscription) -> Result<Subscription, SubscriptionError> {
+        self.check_loaded()?;
+        let url = reqwest::Url::parse(req.url.trim())
+            .map_err(|e| SubscriptionError::Invalid(format!("url: {e}")))?;
+        check_target(&url, self.cfg.allow_private).map_err(SubscriptionError::Invalid)?;
+        req.filter
+            .to_stream_filter()
+            .map_err(SubscriptionError::Invalid)?;
+        let secret = match req.secret.map(|s| s.trim().to_string()) {
+            Some(s) if s.len() < 16 => {
+                return Err(SubscriptionError::Invalid(
+                    "secret: at least 16 characters".into(),
+                ))
+            }
+            Some(s) => s,
+            None => random_hex(32),
+        };
+        let sub = Subscription {
+            id: format!("sub_{}", random_hex(8)),
+            url: url.to_string(),
+            secret,
+            filter: req.filter,
+            description: req.description.filter(|d| !d.trim().is_empty()),
+            created_unix: self.clock.now_unix(),
+        };
+        let mut subs = self.subs.write().expect("subscriptions poisoned");
+        subs.insert(sub.id.clone(), sub.clone());
+        if let Err(e) = self.persist(&subs) {
+            subs.remove(&sub.id);
+            return Err(SubscriptionError::NotPersisted(e));
+        }
+        Ok(sub)
+    }
+
+    /// Remove `id` and persist; `false` when unknown. Its queued deliveries are dropped.
+    pub fn delete(&self, id: &str) -> Result<bool, SubscriptionError> {
+        self.check_loaded()?;
+        let mut subs = self.subs.write().expect("subscriptions poisoned");
+        let Some(removed) = subs.remove(id) else {
+            return Ok(false);
+        };
+        if let Err(e) = self.persist(&subs) {
+            subs.insert(id.to_string(), removed);
+            return Err(SubscriptionError::NotPersisted(e));
+        }
+        self.stats.lock().expect("stats poisoned").remove(id);
+        self.queues.lock().expect("queues poisoned").remove(id);
+        Ok(true)
+    }
+
+    pub fn get(&self, id: &str) -> Option<SubscriptionView> {
+        let subs = self.subs.read().expect("subscriptions poisoned");
+        subs.get(id).map(|s| self.view(s))
+    }
+
+    pub fn list(&self) -> Vec<SubscriptionView> {
+        let subs = self.subs.read().expect("subscriptions poisoned");
+        subs.values().map(|s| self.view(s)).collect()
+    }
+
+    fn view(&self, s: &Subscription) -> SubscriptionView {
+        let deliveries = self
+            .stats
+            .lock()
+            .expect("stats poisoned")
+            .get(&s.id)
+            .cloned()
+            .unwrap_or_default();
+        SubscriptionView {
+            id: s.id.clone(),
+            url: s.url.clone(),
+            filter: s.filter.clone(),
+            description: s.description.clone(),
+            created_unix: s.created_unix,
+            deliveries,
+        }
+    }
+
+    /// Write all subscriptions (temp file + rename, so a crash keeps the old file).
+    fn persist(&self, subs: &BTreeMap<String, Subscription>) -> Result<(), String> {
+        let list: Vec<&Subscription> = subs.values().collect();
+        let res = (|| -> std::io::Result<()> {
+            if let Some(dir) = self.cfg.path.parent().filter(|d| !d.as_os_str().is_empty()) {
+                fs::create_dir_all(dir)?;
+            }
+            let tmp = self.cfg.path.with_extension("tmp");
+            fs::write(&tmp, serde_json::to_vec_pretty(&list)?)?;
+            fs::rename(&tmp, &self.cfg.path)
+        })();
+        res.map_err(|e| {
+            tracing::error!(path = %self.cfg.path.display(), error = %e, "subscriptions not persisted");
+            format!("subscriptions not persisted: {e}")
+        })
+    }
+
+    /// Update (or start) the record for `seq` of subscription `id`.
+    fn track(&self, id: &str, rec: DeliveryRecord) {
+        let mut stats = self.stats.lock().expect("stats poisoned");
+        let st = stats.entry(id.to_string()).or_default();
+        match rec.outcome {
+            DeliveryOutcome::Delivered => st.delivered += 1,
+            DeliveryOutcome::Failed => st.failed += 1,
+            DeliveryOutcome::Retrying => {}
+        }
+        st.recent.retain(|r| r.seq != rec.seq);
+        st.recent.push_front(rec);
+        st.recent.truncate(RECENT_DELIVERIES);
+    }
+
+    /// Deliver `bus` events to matching subscriptions until the bus closes or this
+    /// instance is dropped. `None` when `bus` already has a dispatcher here, so each
+    /// event is queued once.
+    pub fn spawn(self: &Arc<Self>, bus: &DecisionBus) -> Option<tokio::task::JoinHandle<()>> {
+        {
+            let mut buses = self.dispatchers.lock().expect("dispatchers poisoned");
+            if buses.iter().any(|b| b.same_bus(bus)) {
+                return None;
+            }
+            buses.push(bus.clone());
+        }
+        let mut rx = bus.subscribe();
+        let mut closed = self.closed.subscribe();
+        let this: Weak<Self> = Arc::downgrade(self);
+        Some(tokio::spawn(async move {
+            loop {
+                let msg = tokio::select! {
+                    msg = rx.recv() => msg,
+                    _ = closed.changed() => break,
+                };
+                let Some(this) = this.upgrade() else { break };
+                match msg {
+                    Ok(msg) => this.dispatch(&msg),
+                    Err(RecvError::Lagged(n)) => {
+                        tracing::warn!(skipped = n, "webhook dispatcher lagged; events skipped")
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        }))
+    }
+
+    /// Queue `msg` for every subscription whose filter matches it.
+    pub fn dispatch(self: &Arc<Self>, msg: &StreamMessage) {
+        let targets: Vec<Subscription> = {
+            let subs = self.subs.read().expect("subscriptions poisoned");
+            subs.values()
+                .filter(|s| {
+                    s.filter
+                        .to_stream_filter()
+                        .is_ok_and(|f| f.matches(&msg.event))
+                })
+                .cloned()
+                .collect()
+        };
+        if targets.is_empty() {
+            return;
+        }
+        let Ok(body) = serde_json::to_string(msg) else {
+            return;
+        };
+        let body: Arc<str> = body.into();
+        let mut queues = self.queues.lock().expect("queues poisoned");
+        for sub in targets {
+            let job = Delivery {
+                seq: msg.seq,
+                kind: msg.event.kind(),
+                body: body.clone(),
+            };
+            let tx = queues
+                .entry(sub.id.clone())
+                .or_insert_with(|| self.start_worker(sub.clone()));
+            match tx.try_send(job) {
+                Ok(()) => {}
+                Err(TrySendError::Full(job)) => self.drop_delivery(&sub.id, &job),
+                Err(TrySendError::Closed(job)) => {
+                    // Worker gone (runtime shutting down): count it, start over next time.
+                    queues.remove(&sub.id);
+                    self.drop_delivery(&sub.id, &job);
+                }
+            }
+        }
+    }
+
+    /// Spawn the delivery worker of `sub`; it runs until its queue is dropped
+    /// (subscription deleted) or the `Webhooks` are gone.
+    fn start_worker(self: &Arc<Self>, sub: Subscription) -> mpsc::Sender<Delivery> {
+        let (tx, mut rx) = mpsc::channel(self.cfg.queue.max(1));
+        let this: Weak<Self> = Arc::downgrade(self);
+        tokio::spawn(async move {
+            while let Some(job) = rx.recv().await {
+                let Some(this) = this.upgrade() else { break };
+                this.deliver(&sub, &job).await;
+            }
+        });
+        tx
+    }
+
+    fn drop_delivery(&self, id: &str, job: &Delivery) {
+        counter!("webhook_deliveries_total", "outcome" => "dropped").increment(1);
+        tracing::warn!(subscription = %id, seq = job.seq, "webhook queue full; event dropped");
+        self.track(
+            id,
+            DeliveryRecord {
+                seq: job.seq,
+                event: job.kind.to_string(),
+                attempts: 0,
+                outcome: DeliveryOutcome::Failed,
+                status: None,
+                error: Some("delivery queue full".into()),
+                ts_unix: self.clock.now_unix(),
+            },
+        );
+    }
+
+    async fn deliver(&self, sub: &Subscription, job: &Delivery) {
+        let (seq, kind) = (job.seq, job.kind);
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let ts = self.clock.now_unix();
+            let res = self
+                .client
+                .post(&sub.url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .header(TIMESTAMP_HEADER, ts.to_string())
+                .header(SIGNATURE_HEADER, sign(&sub.secret, ts, &job.body))
+                .header("x-webhook-id", format!("{}-{seq}", sub.id))
+                .header("x-webhook-event", kind)
+                .body(job.body.to_string())
+                .send()
+                .await;
+            let (status, error, retryable) = match res {
+                Ok(r) if r.status().is_success() => (Some(r.status().as_u16()), None, false),
+                Ok(r) => {
+                    let code = r.status().as_u16();
+                    let retryable = r.status().is_server_error() || matches!(code, 408 | 429);
+                    (Some(code), Some(format!("HTTP {code}")), retryable)
+                }
+                Err(e) => (None, Some(e.to_string()), true),
+            };
+            let done = error.is_none() || !retryable || attempt >= self.cfg.max_attempts;
+            let outcome = match (&error, done) {
+                (None, _) => DeliveryOutcome::Delivered,
+                (Some(_), true) => DeliveryOutcome::Failed,
+                (Some(_), false) => DeliveryOutcome::Retrying,
+            };
+            let label = match outcome {
+                DeliveryOutcome::Delivered => "delivered",
+                DeliveryOutcome::Failed => "failed",
+                DeliveryOutcome::Retrying => "retry",
+            };
+            counter!("webhook_deliveries_total", "outcome" => label).increment(1);
+            self.track(
+                &sub.id,
+                DeliveryRecord {
+                    seq,
+                    event: kind.to_string(),
+                    attempts: attempt,
+                    outcome,
+                    status,
+                    error: error.clone(),
+                    ts_unix: ts,
+                },
+            );
+            if done {
+                if let Some(e) = error {
+                    tracing::warn!(subscription = %sub.id, seq, attempts = attempt, error = %e, "webhook delivery failed");
+                }
+                return;
+            }
+            tokio::time::sleep(self.cfg.backoff(attempt)).await;
+            // Deleted meanwhile: stop retrying.
+            if !self
+                .subs
+                .read()
+                .expect("subscriptions poisoned")
+                .contains_key(&sub.id)
+            {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+
+    fn tmp_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("webhooks_{name}_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir.join("subscriptions.json")
+    }
+
+    fn new_sub(url: &str) -> NewSubscription {
+        NewSubscription {
+            url: url.into(),
+            filter: SubscriptionFilter::default(),
+            secret: None,
+            description: Some("test".into()),
+        }
+    }
+
+    #[test]
+    fn hmac_matches_rfc_4231_vector() {
+        // Test case 2: key "Jefe", data "what do ya want for nothing?".
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"Jefe").unwrap();
+        mac.update(b"what do ya want for nothing?");
+        assert_eq!(
+            hex(&mac.finalize().into_bytes()),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        let sig = sign("s3cr3t", 1_700_000_000, r#"{"seq":1}"#);
+        assert!(sig.starts_with("sha256=") && sig.len() == 7 + 64);
+        assert_ne!(sig, sign("s3cr3t", 1_700_000_001, r#"{"seq":1}"#));
+    }
+
+    #[test]
+    fn subscriptions_persist_across_reload() {
+        let path = tmp_path("persist");
+        let clock: SharedClock = Arc::new(ManualClock::new(100));
+        let hooks = Webhooks::load(WebhookConfig::new(&path), clock.clone());
+        let a = hooks.create(new_sub("https://example.com/a")).unwrap();
+        let b = hooks
+            .create(new_sub("http://hooks.example.org:9000/b"))
+            .unwrap();
+        assert_eq!(a.secret.len(), 64);
+        assert_eq!(a.created_unix, 100);
+        assert!(hooks.delete(&b.id).unwrap());
+        assert!(!hooks.delete(&b.id).unwrap());
+
+        let reloaded = Webhooks::load(WebhookConfig::new(&path), clock);
+        let list = reloaded.list();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].id, a.id);
+        assert_eq!(list[0].url, "https://example.com/a");
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn invalid_subscriptions_are_rejected() {
+        let path = tmp_path("invalid");
+        let hooks = Webhooks::load(WebhookConfig::new(&path), Arc::new(ManualClock::new(0)));
+        assert!(hooks.create(new_sub("ftp://example.com")).is_err());
+        assert!(hooks.create(new_sub("not a url")).is_err());
+        let mut bad_filter = new_sub("https://example.com");
+        bad_filter.filter.events = vec!["trade".into()];
+        assert!(hooks.create(bad_filter).is_err());
+        let mut short = new_sub("https://example.com");
+        short.secret = Some("short".into());
+        assert!(hooks.create(short).is_err());
+        assert!(hooks.list().is_empty());
+        assert!(!path.exists(), "nothing persisted");
+    }
+
+    #[test]
+    fn private_targets_are_refused_unless_allowed() {
+        let path = tmp_path("private");
+        let clock: SharedClock = Arc::new(ManualClock::new(0));
+        let hooks = Webhooks::load(WebhookConfig::new(&path), clock.clone());
+        for url in [
+            "http://127.0.0.1:8080/hook",
+            "http://10.1.2.3/hook",
+            "http://192.168.0.10/hook",
+            "http://169.254.169.254/latest/meta-data",
+            "http://100.64.0.1/hook",
+            "http://0.0.0.0/hook",
+            "http://[::1]/hook",
+            "http://[fd00::1]/hook",
+            "http://[::ffff:127.0.0.1]/hook",
+            "http://localhost:8000/hook",
+            "http://api.localhost/hook",
+        ] {
+            assert!(
+                matches!(
+                    hooks.create(new_sub(url)),
+                    Err(SubscriptionError::Invalid(_))
+                ),
+                "{url}"
+            );
+        }
+        assert!(hooks.create(new_sub("https://93.184.215.14/hook")).is_ok());
+        assert!(is_public_ip("2606:4700::1111".parse().unwrap()));
+
+        let mut cfg = WebhookConfig::new(&path);
+        cfg.allow_private = true;
+        let hooks = Webhooks::load(cfg, clock);
+        assert!(hooks.create(new_sub("http://127.0.0.1:8080/hook")).is_ok());
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn unreadable_file_is_kept_and_changes_are_refused() {
+        let path = tmp_path("broken");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "[{ not json").unwrap();
+        let hooks = Webhooks::load(WebhookConfig::new(&path), Arc::new(ManualClock::new(0)));
+        assert!(hooks.load_error().is_some());
+        assert!(matches!(
+            hooks.create(new_sub("https://example.com/a")),
+            Err(SubscriptionError::Unavailable(_))
+        ));
+        assert!(matches!(
+            hooks.delete("sub_1"),
+            Err(SubscriptionError::Unavailable(_))
+        ));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "[{ not json");
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[tokio::test]
+    async fn a_full_queue_drops_instead_of_piling_up() {
+        use crate::decision::Decision;
+        use crate::stream::StreamEvent;
+
+        // Accepts connections but never answers, so the first delivery stays in flight.
+        let silent = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let path = tmp_path("queue");
+        let mut cfg = WebhookConfig::new(&path);
+        cfg.allow_private = true;
+        cfg.queue = 1;
+        let hooks = Arc::new(Webhooks::load(cfg, Arc::new(ManualClock::new(0))));
+        let sub = hooks
+            .create(new_sub(&format!(
+                "http://{}/hook",
+                silent.local_addr().unwrap()
+            )))
+            .unwrap();
+        for seq in 1..=6 {
+            hooks.dispatch(&StreamMessage {
+                seq,
+                event: StreamEvent::Decision {
+                    ts_unix: 0,
+                    decision: Decision::new(Verdict::Buy, 0.7),
+                },
+            });
+        }
+        // At most one in flight and one queued; the rest are dropped right away.
+        let view = hooks.get(&sub.id).unwrap();
+        assert!(view.deliveries.failed >= 4, "{:?}", view.deliveries);
+        assert!(view.deliveries.recent.iter().all(|r| r.attempts == 0));
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[tokio::test]
+    async fn one_instance_per_file_and_one_dispatcher_per_bus() {
+        let path = tmp_path("shared");
+        let clock: SharedClock = Arc::new(ManualClock::new(0));
+        let hooks = Webhooks::shared(WebhookConfig::new(&path), clock.clone());
+        assert!(Arc::ptr_eq(
+            &hooks,
+            &Webhooks::shared(WebhookConfig::new(&path), clock.clone())
+        ));
+
+        let (bus, other) = (DecisionBus::new(4), DecisionBus::new(4));
+        let task = hooks.spawn(&bus).expect("first dispatcher");
+        assert!(hooks.spawn(&bus.clone()).is_none());
+        assert!(hooks.spawn(&other).is_some());
+
+        // The dispatcher stops with the instance; a later caller loads the file afresh.
+        let weak = Arc::downgrade(&hooks);
+        drop(hooks);
+        tokio::time::timeout(Duration::from_secs(1), task)
+            .await
+            .expect("dispatcher ends")
+            .unwrap();
+        assert!(weak.upgrade().is_none());
+        let again = Webhooks::shared(WebhookConfig::new(&path), clock);
+        assert!(again.spawn(&bus).is_some());
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let cfg = WebhookConfig::new("x");
+        assert_eq!(cfg.backoff(1), Duration::from_millis(1000));
+        assert_eq!(cfg.backoff(2), Duration::from_millis(2000));
+        assert_eq!(cfg.backoff(4), Duration::from_millis(8000));
+        assert_eq!(cfg.backoff(30), Duration::from_millis(MAX_BACKOFF_MS));
+    }
+}
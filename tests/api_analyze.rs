@@ -0,0 +1,114 @@
+// tests/api_analyze.rs
+//
+// POST /analyze runs the real pipeline on the submitted text and cites recent
+// `/decide` contributors (with their URLs) as evidence.
+// Own test binary: the router reads its settings from process env.
+
+use std::sync::Arc;
+
+use serde_json::json;
+use serde_json::Value as Json;
+use shuttle_axum::axum::{
+    body::{self, Body},
+    http::{Request, StatusCode},
+    Router,
+};
+use tower::ServiceExt as _;
+
+use dow_sentiment_analyzer::api;
+use dow_sentiment_analyzer::clock::{ManualClock, SharedClock};
+use dow_sentiment_analyzer::relevance::AppState as RelevanceAppState;
+
+const BODY_LIMIT: usize = 1024 * 1024;
+const T0: u64 = 1_756_733_400; // 2025-09-01T13:30:00Z
+
+async fn post(app: &Router, uri: &str, payload: Json) -> Json {
+    let req = Request::builder()
+        .method("POST")
+        .uri(uri)
+        .header("content-type", "application/json")
+        .body(Body::from(payload.to_string()))
+        .expect("build request");
+    let resp = app.clone().oneshot(req).await.expect("oneshot");
+    assert_eq!(resp.status(), StatusCode::OK, "POST {uri}");
+    let bytes = body::to_bytes(resp.into_body(), BODY_LIMIT)
+        .await
+        .expect("read body");
+    serde_json::from_slice(&bytes).expect("json body")
+}
+
+#[tokio::test]
+async fn analyze_scores_text_and_cites_recent_contributors() {
+    std::env::set_var("AI_ENABLED", "0");
+    let clock: SharedClock = Arc::new(ManualClock::new(T0));
+    let app = api::router_with_clock(RelevanceAppState::from_env(), clock);
+
+    // No decisions yet: no evidence, no placeholder items.
+    let text = "Fed signals rate cuts; Dow futures rally strongly on dovish outlook.";
+    let v = post(&app, "/analyze", json!({ "text": text, "source": "Fed" })).await;
+    assert_eq!(v["evidence"], json!([]), "{v}");
+    assert_eq!(v["contributors"], json!([]));
+    let reasons: Vec<&str> = v["reasons"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(Json::as_str)
+        .collect();
+    assert!(reasons[0].starts_with("Sentiment score +"), "{reasons:?}");
+    assert!(reasons[1].starts_with("Relevance "), "{reasons:?}");
+    assert!(reasons.contains(&"NER rates: rates"), "{reasons:?}");
+    assert!(!reasons.iter().any(|r| r.contains("FOMC minutes")));
+
+    // Off-topic text is neutralized by the relevance gate.
+    let v = post(
+        &app,
+        "/analyze",
+        json!({ "text": "Great weather for a picnic" }),
+    )
+    .await;
+    assert_eq!(v["decision"], "HOLD");
+    assert!(v["reasons"][1]
+        .as_str()
+        .is_some_and(|r| r.starts_with("Relevance gate: not Dow-relevant")));
+
+    // A /decide call leaves contributors (with URLs) in history.
+    post(
+        &app,
+        "/decide",
+        json!([
+            { "source": "Fed", "text": text, "ts_unix": T0 - 60,
+              "url": "https://www.federalreserve.gov/newsevents.htm" },
+            { "source": "Reuters", "text": "Dow futures slide as tariff fears weigh on industrials",
+              "ts_unix": T0 - 30 }
+        ]),
+    )
+    .await;
+
+    let v = post(
+        &app,
+        "/analyze",
+        json!({ "text": "Dow futures rally on rate cuts" }),
+    )
+    .await;
+    let ev = v["evidence"].as_array().expect("evidence");
+    let fed = ev
+        .iter()
+        .find(|e| e["source"] == "Fed")
+        .expect("Fed evidence");
+    assert_eq!(fed["title"], text);
+    assert_eq!(fed["url"], "https://www.federalreserve.gov/newsevents.htm");
+    assert_eq!(fed["sentiment"], "pos");
+    assert_eq!(fed["time"], "2025-09-01T13:30:00Z");
+    let reuters = ev
+        .iter()
+        .find(|e| e["source"] == "Reuters")
+        .expect("Reuters");
+    assert!(reuters.get("url").is_none(), "no URL given: {reuters}");
+    assert_eq!(reuters["sentiment"], "neg");
+    assert_eq!(v["contributors"].as_array().map(Vec::len), Some(2));
+
+    // /analyze is read-only: its own texts never show up as evidence.
+    assert!(!ev
+        .iter()
+        .any(|e| e["title"] == "Dow futures rally on rate cuts"));
+}
@@ -0,0 +1,217 @@
+// tests/api_auth.rs
+//
+// With `config/api_keys.json` (here via API_KEYS_PATH), routes need a key with the right
+// scope, each key is rate limited, admin calls land in the audit log and rejections are
+// counted in Prometheus.
+// Own test binary: the router reads its settings from process env.
+
+use std::sync::Arc;
+
+use serde_json::json;
+use serde_json::Value as Json;
+use shuttle_axum::axum::{
+    body::{self, Body},
+    http::{Request, StatusCode},
+    Router,
+};
+use tower::ServiceExt as _;
+
+use dow_sentiment_analyzer::api;
+use dow_sentiment_analyzer::auth::{hash_key, AuditRecord};
+use dow_sentiment_analyzer::clock::{ManualClock, SharedClock};
+use dow_sentiment_analyzer::relevance::AppState as RelevanceAppState;
+
+const BODY_LIMIT: usize = 1024 * 1024;
+const T0: u64 = 1_756_733_400; // 2025-09-01T13:30:00Z
+
+async fn call(
+    app: &Router,
+    method: &str,
+    uri: &str,
+    auth: Option<(&str, &str)>,
+    payload: Option<Json>,
+) -> (StatusCode, Option<String>, String) {
+    let mut req = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json");
+    if let Some((name, value)) = auth {
+        req = req.header(name, value);
+    }
+    let body = payload
+        .map(|p| Body::from(p.to_string()))
+        .unwrap_or_default();
+    let resp = app
+        .clone()
+        .oneshot(req.body(body).expect("build request"))
+        .await
+        .expect("oneshot");
+    let status = resp.status();
+    let retry_after = resp
+        .headers()
+        .get("retry-after")
+        .map(|v| v.to_str().unwrap().to_string());
+    let bytes = body::to_bytes(resp.into_body(), BODY_LIMIT)
+        .await
+        .expect("read body");
+    (
+        status,
+        retry_after,
+        String::from_utf8_lossy(&bytes).into_owned(),
+    )
+}
+
+/// Audit records once the writer thread has written `n` of them.
+async fn audit_records(path: &std::path::Path, n: usize) -> Vec<AuditRecord> {
+    for _ in 0..100 {
+        let raw = std::fs::read_to_string(path).unwrap_or_default();
+        if raw.lines().count() >= n {
+            return raw
+                .lines()
+                .map(|l| serde_json::from_str(l).unwrap())
+                .collect();
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    panic!("audit log {} never reached {n} records", path.display());
+}
+
+fn error_code(body: &str) -> String {
+    let v: Json = serde_json::from_str(body).expect("error envelope");
+    v["error"]["code"].as_str().unwrap_or_default().to_string()
+}
+
+#[tokio::test]
+async fn keys_scopes_rate_limits_and_audit() {
+    let dir = std::env::temp_dir().join(format!("api_auth_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let keys = dir.join("api_keys.json");
+    let audit = dir.join("admin_audit.jsonl");
+    std::fs::write(
+        &keys,
+        json!({
+            "default_rate_limit": { "per_minute": 600, "burst": 50 },
+            "keys": [
+                { "id": "reader", "sha256": hash_key("read-secret"), "scopes": ["read"] },
+                { "id": "bot", "key_env": "API_AUTH_TEST_BOT_KEY", "scopes": ["decide", "read"] },
+                { "id": "ops", "key": "ops-secret", "scopes": ["admin"] },
+                { "id": "slow", "key": "slow-secret", "scopes": ["read"],
+                  "rate_limit": { "per_minute": 6, "burst": 2 } }
+            ]
+        })
+        .to_string(),
+    )
+    .unwrap();
+
+    std::env::set_var("AI_ENABLED", "0");
+    std::env::remove_var("DEBUG_ROUTES");
+    std::env::set_var("API_KEYS_PATH", &keys);
+    std::env::set_var("ADMIN_AUDIT_LOG", &audit);
+    std::env::set_var("API_AUTH_TEST_BOT_KEY", "bot-secret");
+    let clock = Arc::new(ManualClock::new(T0));
+    let shared: SharedClock = clock.clone();
+    let app = api::router_with_clock(RelevanceAppState::from_env(), shared);
+
+    const READ: Option<(&str, &str)> = Some(("x-api-key", "read-secret"));
+    const BOT: Option<(&str, &str)> = Some(("authorization", "Bearer bot-secret"));
+    const OPS: Option<(&str, &str)> = Some(("authorization", "Bearer ops-secret"));
+    let item = json!([{ "source": "Fed", "text": "Fed signals rate cuts; Dow futures rally.", "ts_unix": T0 }]);
+
+    // Public routes need no key.
+    assert_eq!(
+        call(&app, "GET", "/health", None, None).await.0,
+        StatusCode::OK
+    );
+
+    // No / unknown key.
+    let (status, _, body) = call(&app, "GET", "/decide", None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(error_code(&body), "unauthorized");
+    // `?api_key=` is only accepted on /stream.
+    let (status, _, _) = call(&app, "GET", "/history?api_key=read-secret", None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let bad = Some(("x-api-key", "guess"));
+    assert_eq!(
+        call(&app, "GET", "/decide", bad, None).await.0,
+        StatusCode::UNAUTHORIZED
+    );
+
+    // Scopes: read can look, not decide; decide (from an env secret) can do both.
+    assert_eq!(
+        call(&app, "GET", "/decide", READ, None).await.0,
+        StatusCode::OK
+    );
+    let (status, _, body) = call(&app, "POST", "/decide", READ, Some(item.clone())).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(error_code(&body), "forbidden");
+    assert_eq!(
+        call(&app, "POST", "/decide", BOT, Some(item.clone()))
+            .await
+            .0,
+        StatusCode::OK
+    );
+    assert_eq!(
+        call(&app, "GET", "/history", BOT, None).await.0,
+        StatusCode::OK
+    );
+
+    // Admin route is mounted without DEBUG_ROUTES and needs the admin scope.
+    let admin = "/admin/reload-source-weights";
+    assert_eq!(
+        call(&app, "POST", admin, BOT, None).await.0,
+        StatusCode::FORBIDDEN
+    );
+    let (status, _, body) = call(&app, "POST", admin, OPS, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, "reloaded");
+
+    // Token bucket: burst 2, then 429 with Retry-After until the clock refills it.
+    let slow = Some(("x-api-key", "slow-secret"));
+    assert_eq!(
+        call(&app, "GET", "/decide", slow, None).await.0,
+        StatusCode::OK
+    );
+    assert_eq!(
+        call(&app, "GET", "/decide", slow, None).await.0,
+        StatusCode::OK
+    );
+    let (status, retry_after, body) = call(&app, "GET", "/decide", slow, None).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(error_code(&body), "rate_limited");
+    assert_eq!(retry_after.as_deref(), Some("10"));
+    assert_eq!(
+        call(&app, "GET", "/decide", READ, None).await.0,
+        StatusCode::OK
+    );
+    clock.advance(10);
+    assert_eq!(
+        call(&app, "GET", "/decide", slow, None).await.0,
+        StatusCode::OK
+    );
+
+    // Admin calls, rejected ones included, are audited with the key id.
+    let records = audit_records(&audit, 2).await;
+    let seen: Vec<(Option<&str>, u16)> = records
+        .iter()
+        .map(|r| (r.key.as_deref(), r.status))
+        .collect();
+    assert_eq!(seen, vec![(Some("bot"), 403), (Some("ops"), 200)]);
+    assert!(records
+        .iter()
+        .all(|r| r.path == admin && r.method == "POST"));
+
+    // Rejections are counted per reason.
+    let (_, _, metrics) = call(&app, "GET", "/metrics", None, None).await;
+    for reason in ["unauthorized", "forbidden", "rate_limited"] {
+        assert!(
+            metrics
+                .lines()
+                .any(|l| l.starts_with("api_requests_rejected_total")
+                    && l.contains(&format!("reason=\"{reason}\""))),
+            "{reason} missing:\n{metrics}"
+        );
+    }
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,55 @@
+// tests/api_calibration.rs
+//
+// Calibration map from `CALIBRATION_PATH` is applied to `/decide` before grading.
+// Own test binary: the router reads its settings from process env.
+
+use std::sync::Arc;
+
+use serde_json::json;
+use serde_json::Value as Json;
+use shuttle_axum::axum::{
+    body::{self, Body},
+    http::{Request, StatusCode},
+};
+use tower::ServiceExt as _;
+
+use dow_sentiment_analyzer::api;
+use dow_sentiment_analyzer::clock::{ManualClock, SharedClock};
+use dow_sentiment_analyzer::relevance::AppState as RelevanceAppState;
+
+const BODY_LIMIT: usize = 1024 * 1024;
+const T0: u64 = 1_756_733_400; // 2025-09-01T13:30:00Z
+
+#[tokio::test]
+async fn decide_reports_calibrated_confidence() {
+    std::env::set_var("AI_ENABLED", "0");
+    std::env::set_var("CALIBRATION_PATH", "tests/fixtures/calibration_flat.json");
+    let clock: SharedClock = Arc::new(ManualClock::new(T0));
+    let app = api::router_with_clock(RelevanceAppState::from_env(), clock);
+
+    let payload = json!([{
+        "source": "Fed",
+        "text": "Fed signals rate cuts; Dow futures rally strongly on dovish outlook.",
+        "ts_unix": T0
+    }]);
+    let req = Request::builder()
+        .method("POST")
+        .uri("/decide")
+        .header("content-type", "application/json")
+        .body(Body::from(payload.to_string()))
+        .expect("build POST /decide");
+    let resp = app.oneshot(req).await.expect("oneshot /decide");
+    assert_eq!(resp.status(), StatusCode::OK);
+    let bytes = body::to_bytes(resp.into_body(), BODY_LIMIT)
+        .await
+        .expect("read body");
+    let v: Json = serde_json::from_slice(&bytes).expect("json body");
+
+    assert_eq!(v["decision"], "BUY", "{v}");
+    let conf = v["confidence"].as_f64().expect("confidence");
+    assert!((conf - 0.55).abs() < 1e-6, "calibrated confidence: {v}");
+    let raw = v["raw_confidence"].as_f64().expect("raw_confidence");
+    assert!(raw > 0.55, "raw confidence kept: {v}");
+    // Grade follows the calibrated value (below the 0.85 strong threshold).
+    assert_eq!(v["signal"], "BUY", "{v}");
+}
@@ -0,0 +1,65 @@
+// tests/api_clock.rs
+//
+// Time-travel through the HTTP API with an injected ManualClock.
+// Own test binary: the router reads its settings from process env.
+
+use std::sync::Arc;
+
+use serde_json::json;
+use serde_json::Value as Json;
+use shuttle_axum::axum::{
+    body::{self, Body},
+    http::{Request, StatusCode},
+    Router,
+};
+use tower::ServiceExt as _;
+
+use dow_sentiment_analyzer::api;
+use dow_sentiment_analyzer::clock::{ManualClock, SharedClock};
+use dow_sentiment_analyzer::relevance::AppState as RelevanceAppState;
+
+const BODY_LIMIT: usize = 1024 * 1024;
+const T0: u64 = 1_756_733_400; // 2025-09-01T13:30:00Z
+
+async fn decide(app: &Router, payload: &Json) -> Json {
+    let req = Request::builder()
+        .method("POST")
+        .uri("/decide")
+        .header("content-type", "application/json")
+        .body(Body::from(payload.to_string()))
+        .expect("build POST /decide");
+    let resp = app.clone().oneshot(req).await.expect("oneshot /decide");
+    assert_eq!(resp.status(), StatusCode::OK);
+    let bytes = body::to_bytes(resp.into_body(), BODY_LIMIT)
+        .await
+        .expect("read body");
+    serde_json::from_slice(&bytes).expect("json body")
+}
+
+#[tokio::test]
+async fn decide_ages_statements_against_injected_clock() {
+    std::env::set_var("AI_ENABLED", "0");
+    let clock = Arc::new(ManualClock::new(T0));
+    let shared: SharedClock = clock.clone();
+    let app = api::router_with_clock(RelevanceAppState::from_env(), shared);
+
+    let payload = json!([{
+        "source": "Fed",
+        "text": "Fed signals rate cuts; Dow futures rally strongly on dovish outlook.",
+        "ts_unix": T0
+    }]);
+
+    let fresh = decide(&app, &payload).await;
+    assert_eq!(
+        fresh["decision"], "BUY",
+        "fresh statement should trigger: {fresh}"
+    );
+
+    // Same statement, 31 minutes later: outside the trigger window.
+    clock.advance(31 * 60);
+    let stale = decide(&app, &payload).await;
+    assert_eq!(
+        stale["decision"], "HOLD",
+        "stale statement must not trigger: {stale}"
+    );
+}
@@ -0,0 +1,81 @@
+// tests/api_events.rs
+//
+// Economic event windows through the HTTP API: a mid-weight source that does
+// not trigger on its own does trigger inside a high-importance release window.
+// Own test binary: the router reads its settings from process env.
+
+use std::sync::Arc;
+
+use serde_json::json;
+use serde_json::Value as Json;
+use shuttle_axum::axum::{
+    body::{self, Body},
+    http::{Request, StatusCode},
+    Router,
+};
+use tower::ServiceExt as _;
+
+use dow_sentiment_analyzer::api;
+use dow_sentiment_analyzer::clock::{ManualClock, SharedClock};
+use dow_sentiment_analyzer::relevance::AppState as RelevanceAppState;
+
+const BODY_LIMIT: usize = 1024 * 1024;
+const CPI_TS: u64 = 1_760_531_400; // 2025-10-15T08:30:00-04:00
+
+async fn decide(app: &Router, payload: &Json) -> Json {
+    let req = Request::builder()
+        .method("POST")
+        .uri("/decide")
+        .header("content-type", "application/json")
+        .body(Body::from(payload.to_string()))
+        .expect("build POST /decide");
+    let resp = app.clone().oneshot(req).await.expect("oneshot /decide");
+    assert_eq!(resp.status(), StatusCode::OK);
+    let bytes = body::to_bytes(resp.into_body(), BODY_LIMIT)
+        .await
+        .expect("read body");
+    serde_json::from_slice(&bytes).expect("json body")
+}
+
+fn has_event_reason(v: &Json) -> bool {
+    v["reasons"].as_array().is_some_and(|rs| {
+        rs.iter().any(|r| {
+            r["message"]
+                .as_str()
+                .is_some_and(|m| m.starts_with("Event window: CPI (Sep)"))
+        })
+    })
+}
+
+#[tokio::test]
+async fn event_window_boosts_source_weight() {
+    std::env::set_var("AI_ENABLED", "0");
+    std::env::set_var("EVENT_CALENDAR_PATH", "tests/fixtures/events_cpi.json");
+    let clock = Arc::new(ManualClock::new(CPI_TS + 60));
+    let shared: SharedClock = clock.clone();
+    let app = api::router_with_clock(RelevanceAppState::from_env(), shared);
+
+    // "Fox Business" weighs 0.78 (< 0.80 trigger minimum); the high-importance
+    // window adds +0.10 for statements in a matching NER category ("rates").
+    let text = "Fed signals rate cuts; Dow futures rally.";
+    let inside = decide(
+        &app,
+        &json!([{ "source": "Fox Business", "text": text, "ts_unix": CPI_TS + 60 }]),
+    )
+    .await;
+    assert_eq!(inside["decision"], "BUY", "inside window: {inside}");
+    assert!(has_event_reason(&inside), "missing event reason: {inside}");
+
+    // A day later the same statement is back to its plain weight.
+    let later = clock.advance(86_400);
+    let outside = decide(
+        &app,
+        &json!([{ "source": "Fox Business", "text": text, "ts_unix": later }]),
+    )
+    .await;
+    assert_eq!(outside["decision"], "HOLD", "outside window: {outside}");
+    assert!(
+        !has_event_reason(&outside),
+        "unexpected event reason: {outside}"
+    );
+}
@@ -0,0 +1,157 @@
+// tests/api_export.rs
+//
+// GET /export dumps recorded decisions (or their contributors) as CSV / NDJSON with a
+// stable header, filtered by time range, optionally gzipped, rejecting other instruments.
+// Own test binary: the router reads its settings from process env.
+
+use std::io::Read as _;
+use std::sync::Arc;
+
+use serde_json::json;
+use serde_json::Value as Json;
+use shuttle_axum::axum::{
+    body::{self, Body},
+    http::{header, Request, StatusCode},
+    Router,
+};
+use tower::ServiceExt as _;
+
+use dow_sentiment_analyzer::api;
+use dow_sentiment_analyzer::clock::{ManualClock, SharedClock};
+use dow_sentiment_analyzer::export::{CONTRIBUTOR_COLUMNS, DECISION_COLUMNS};
+use dow_sentiment_analyzer::relevance::AppState as RelevanceAppState;
+
+const BODY_LIMIT: usize = 1024 * 1024;
+const T0: u64 = 1_756_733_400; // 2025-09-01T13:30:00Z
+
+/// Status, content type, content disposition and body bytes.
+async fn call(
+    app: &Router,
+    method: &str,
+    uri: &str,
+    payload: Option<Json>,
+) -> (StatusCode, String, String, Vec<u8>) {
+    let body = payload
+        .map(|p| Body::from(p.to_string()))
+        .unwrap_or_default();
+    let req = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json")
+        .body(body)
+        .expect("build request");
+    let resp = app.clone().oneshot(req).await.expect("oneshot");
+    let status = resp.status();
+    let headers = resp.headers().clone();
+    let text = |h| {
+        headers
+            .get(h)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_string()
+    };
+    let (ctype, disposition) = (
+        text(header::CONTENT_TYPE),
+        text(header::CONTENT_DISPOSITION),
+    );
+    let bytes = body::to_bytes(resp.into_body(), BODY_LIMIT)
+        .await
+        .expect("read body");
+    (status, ctype, disposition, bytes.to_vec())
+}
+
+#[tokio::test]
+async fn export_streams_history_as_csv_ndjson_and_gzip() {
+    std::env::set_var("AI_ENABLED", "0");
+    let clock = Arc::new(ManualClock::new(T0));
+    let shared: SharedClock = clock.clone();
+    let app = api::router_with_clock(RelevanceAppState::from_env(), shared);
+
+    for (i, text) in [
+        "Fed signals rate cuts; Dow futures rally strongly on dovish outlook.",
+        "Fed warns of recession risk; Dow futures plunge on hawkish stance.",
+    ]
+    .iter()
+    .enumerate()
+    {
+        let ts = T0 + 600 * i as u64;
+        clock.set(ts);
+        let item = json!([{ "source": "Fed", "text": text, "ts_unix": ts, "url": "https://example.com/fed" }]);
+        let (status, ..) = call(&app, "POST", "/decide", Some(item)).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    // Decisions CSV: stable header, oldest first, one row per decision.
+    let (status, ctype, disposition, bytes) = call(&app, "GET", "/export", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(ctype.starts_with("text/csv"), "{ctype}");
+    assert!(disposition.contains("decisions-YM.csv"), "{disposition}");
+    let csv = String::from_utf8(bytes).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], DECISION_COLUMNS.join(","));
+    assert_eq!(lines.len(), 3, "{csv}");
+    assert!(
+        lines[1].contains(&format!(",{T0},2025-09-01T13:30:00Z,YM,")),
+        "{}",
+        lines[1]
+    );
+
+    // Time range applies to the decision timestamp.
+    let (_, _, _, bytes) = call(&app, "GET", &format!("/export?from={}", T0 + 600), None).await;
+    let csv = String::from_utf8(bytes).unwrap();
+    assert_eq!(csv.lines().count(), 2);
+    assert!(csv
+        .lines()
+        .nth(1)
+        .unwrap()
+        .contains(&format!(",{},", T0 + 600)));
+
+    // Contributors NDJSON, gzipped: keys in column order, URL kept.
+    let (status, ctype, disposition, bytes) = call(
+        &app,
+        "GET",
+        "/export?kind=contributors&format=ndjson&gzip=1&instrument=ym",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(ctype, "application/gzip");
+    assert!(
+        disposition.contains("contributors-YM.ndjson.gz"),
+        "{disposition}"
+    );
+    let mut ndjson = String::new();
+    flate2::read::GzDecoder::new(&bytes[..])
+        .read_to_string(&mut ndjson)
+        .expect("gunzip");
+    let rows: Vec<Json> = ndjson
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(rows.len(), 2);
+    let keys: Vec<&str> = rows[0]
+        .as_object()
+        .unwrap()
+        .keys()
+        .map(String::as_str)
+        .collect();
+    let mut expected = CONTRIBUTOR_COLUMNS.to_vec();
+    expected.sort();
+    assert_eq!(keys, expected);
+    assert!(ndjson
+        .lines()
+        .next()
+        .unwrap()
+        .starts_with("{\"decision_id\":"));
+    assert_eq!(rows[0]["source"], "Fed");
+    assert_eq!(rows[0]["url"], "https://example.com/fed");
+    assert_eq!(rows[0]["rank"], 1);
+
+    // Untracked instrument and bad parameters are rejected.
+    for bad in ["?instrument=ES", "?format=xlsx", "?kind=trades", "?to=soon"] {
+        let (status, _, _, bytes) = call(&app, "GET", &format!("/export{bad}"), None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{bad}");
+        let body: Json = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["error"]["code"], "invalid_filter", "{bad}: {body}");
+    }
+}
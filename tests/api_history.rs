@@ -0,0 +1,142 @@
+// tests/api_history.rs
+//
+// GET /history returns recorded decisions newest first with reasons and contributors,
+// filtered by time range, verdict, confidence and source, and paged by cursor.
+// Own test binary: the router reads its settings from process env.
+
+use std::sync::Arc;
+
+use serde_json::json;
+use serde_json::Value as Json;
+use shuttle_axum::axum::{
+    body::{self, Body},
+    http::{Request, StatusCode},
+    Router,
+};
+use tower::ServiceExt as _;
+
+use dow_sentiment_analyzer::api;
+use dow_sentiment_analyzer::clock::{ManualClock, SharedClock};
+use dow_sentiment_analyzer::relevance::AppState as RelevanceAppState;
+
+const BODY_LIMIT: usize = 1024 * 1024;
+const T0: u64 = 1_756_733_400; // 2025-09-01T13:30:00Z
+
+async fn call(app: &Router, method: &str, uri: &str, payload: Option<Json>) -> (StatusCode, Json) {
+    let body = payload
+        .map(|p| Body::from(p.to_string()))
+        .unwrap_or_default();
+    let req = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json")
+        .body(body)
+        .expect("build request");
+    let resp = app.clone().oneshot(req).await.expect("oneshot");
+    let status = resp.status();
+    let bytes = body::to_bytes(resp.into_body(), BODY_LIMIT)
+        .await
+        .expect("read body");
+    (status, serde_json::from_slice(&bytes).expect("json body"))
+}
+
+async fn history(app: &Router, query: &str) -> Json {
+    let (status, body) = call(app, "GET", &format!("/history{query}"), None).await;
+    assert_eq!(status, StatusCode::OK, "GET /history{query}: {body}");
+    body
+}
+
+fn ts_of(page: &Json) -> Vec<u64> {
+    page["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["ts_unix"].as_u64().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn history_is_filtered_and_paged() {
+    std::env::set_var("AI_ENABLED", "0");
+    let clock = Arc::new(ManualClock::new(T0));
+    let shared: SharedClock = clock.clone();
+    let app = api::router_with_clock(RelevanceAppState::from_env(), shared);
+
+    let posts = [
+        (
+            "Fed",
+            "Fed signals rate cuts; Dow futures rally strongly on dovish outlook.",
+        ),
+        (
+            "Reuters",
+            "Fed warns of recession risk; Dow futures plunge on hawkish stance.",
+        ),
+        (
+            "Fed",
+            "Powell says inflation is cooling; Dow futures climb.",
+        ),
+    ];
+    for (i, (source, text)) in posts.iter().enumerate() {
+        clock.set(T0 + 600 * i as u64);
+        let item = json!([{ "source": source, "text": text, "ts_unix": T0 + 600 * i as u64 }]);
+        let (status, _) = call(&app, "POST", "/decide", Some(item)).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    // Newest first, with the decision's reasons and contributors.
+    let all = history(&app, "").await;
+    assert_eq!(ts_of(&all), vec![T0 + 1200, T0 + 600, T0]);
+    assert!(all["next_cursor"].is_null());
+    let newest = &all["entries"][0];
+    assert!(
+        !newest["reasons"].as_array().unwrap().is_empty(),
+        "{newest}"
+    );
+    assert_eq!(newest["contributors"][0]["source"], "Fed");
+    assert!(newest["contributors"][0]["text"]
+        .as_str()
+        .unwrap()
+        .contains("Powell"));
+
+    // Time range: `from` inclusive, `to` exclusive; RFC 3339 accepted.
+    let range = history(&app, &format!("?from={}&to={}", T0 + 600, T0 + 1200)).await;
+    assert_eq!(ts_of(&range), vec![T0 + 600]);
+    let rfc = history(&app, "?from=2025-09-01T13:40:00Z").await;
+    assert_eq!(ts_of(&rfc), vec![T0 + 1200, T0 + 600]);
+
+    // Source substring, case-insensitive.
+    let reuters = history(&app, "?source=reut").await;
+    assert_eq!(ts_of(&reuters), vec![T0 + 600]);
+
+    // Verdict and confidence filters agree with the entries they keep.
+    let verdict = all["entries"][1]["verdict"].as_str().unwrap().to_string();
+    let by_verdict = history(&app, &format!("?verdict={}", verdict.to_lowercase())).await;
+    assert!(!by_verdict["entries"].as_array().unwrap().is_empty());
+    assert!(by_verdict["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|e| e["verdict"] == verdict.as_str()));
+    let none = history(&app, "?min_confidence=1").await;
+    assert!(none["entries"].as_array().unwrap().is_empty());
+
+    // Cursor paging walks the same entries without overlap.
+    let first = history(&app, "?limit=2").await;
+    assert_eq!(ts_of(&first), vec![T0 + 1200, T0 + 600]);
+    let cursor = first["next_cursor"].as_u64().expect("next_cursor");
+    let second = history(&app, &format!("?limit=2&cursor={cursor}")).await;
+    assert_eq!(ts_of(&second), vec![T0]);
+    assert!(second["next_cursor"].is_null());
+
+    // Malformed filters are rejected with the error envelope.
+    for bad in [
+        "?verdict=MAYBE",
+        "?limit=0",
+        "?from=yesterday",
+        "?min_confidence=2",
+    ] {
+        let (status, body) = call(&app, "GET", &format!("/history{bad}"), None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{bad}");
+        assert_eq!(body["error"]["code"], "invalid_filter", "{bad}: {body}");
+    }
+}
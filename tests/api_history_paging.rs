@@ -0,0 +1,98 @@
+// tests/api_history_paging.rs
+//
+// With HISTORY_STORE_DIR set, entries older than the in-memory ring (2000) are still
+// served: GET /history pages on into the segment log and GET /export streams all of it.
+// Own test binary: the router reads its settings from process env.
+
+use std::sync::Arc;
+
+use serde_json::Value as Json;
+use shuttle_axum::axum::{
+    body::{self, Body},
+    http::{Request, StatusCode},
+    Router,
+};
+use tower::ServiceExt as _;
+
+use dow_sentiment_analyzer::api;
+use dow_sentiment_analyzer::clock::{ManualClock, SharedClock};
+use dow_sentiment_analyzer::decision::Verdict;
+use dow_sentiment_analyzer::history::HistoryEntry;
+use dow_sentiment_analyzer::relevance::AppState as RelevanceAppState;
+use dow_sentiment_analyzer::store::{DecisionStore, Record, StoreConfig};
+
+const BODY_LIMIT: usize = 16 * 1024 * 1024;
+const T0: u64 = 1_756_733_400; // 2025-09-01T13:30:00Z
+const STORED: u64 = 2_050;
+
+async fn get(app: &Router, uri: &str) -> Vec<u8> {
+    let req = Request::builder()
+        .uri(uri)
+        .body(Body::empty())
+        .expect("build request");
+    let resp = app.clone().oneshot(req).await.expect("oneshot");
+    assert_eq!(resp.status(), StatusCode::OK, "GET {uri}");
+    body::to_bytes(resp.into_body(), BODY_LIMIT)
+        .await
+        .expect("read body")
+        .to_vec()
+}
+
+#[tokio::test]
+async fn history_and_export_reach_past_the_in_memory_ring() {
+    let dir = std::env::temp_dir().join(format!("api_history_paging_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let clock: SharedClock = Arc::new(ManualClock::new(T0));
+
+    // "Previous process": more decisions than the ring holds.
+    {
+        let (store, _) = DecisionStore::open(StoreConfig::new(&dir), clock.clone()).unwrap();
+        for id in 1..=STORED {
+            store.append(&Record::History(HistoryEntry {
+                id,
+                ts_unix: T0 - 3 * 3600 + id,
+                verdict: Verdict::Hold,
+                confidence: 0.55,
+                signal: None,
+                top_sources: vec!["Reuters".into()],
+                top_scores: vec![0],
+                reasons: Vec::new(),
+                contributors: Vec::new(),
+            }));
+        }
+    }
+
+    std::env::set_var("AI_ENABLED", "0");
+    std::env::set_var("HISTORY_STORE_DIR", &dir);
+    let app = api::router_with_clock(RelevanceAppState::from_env(), clock);
+
+    let mut ids = Vec::new();
+    let mut uri = "/history?limit=500".to_string();
+    loop {
+        let page: Json = serde_json::from_slice(&get(&app, &uri).await).expect("json page");
+        ids.extend(
+            page["entries"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|e| e["id"].as_u64().unwrap()),
+        );
+        match page["next_cursor"].as_u64() {
+            Some(c) => uri = format!("/history?limit=500&cursor={c}"),
+            None => break,
+        }
+    }
+    assert_eq!(ids, (1..=STORED).rev().collect::<Vec<_>>());
+
+    let ndjson = get(&app, "/export?format=ndjson").await;
+    let rows: Vec<Json> = ndjson
+        .split(|b| *b == b'\n')
+        .filter(|l| !l.is_empty())
+        .map(|l| serde_json::from_slice(l).expect("ndjson row"))
+        .collect();
+    assert_eq!(rows.len() as u64, STORED);
+    assert_eq!(rows[0]["id"], 1);
+    assert_eq!(rows[rows.len() - 1]["id"], STORED);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
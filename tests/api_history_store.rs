@@ -0,0 +1,153 @@
+// tests/api_history_store.rs
+//
+// With HISTORY_STORE_DIR set, the router replays the previous process's history on
+// startup (GET /decide answers from it) and appends new decisions to the segment log.
+// Own test binary: the router reads its settings from process env.
+
+use std::sync::Arc;
+
+use serde_json::json;
+use serde_json::Value as Json;
+use shuttle_axum::axum::{
+    body::{self, Body},
+    http::{Request, StatusCode},
+    Router,
+};
+use tower::ServiceExt as _;
+
+use dow_sentiment_analyzer::api;
+use dow_sentiment_analyzer::clock::{ManualClock, SharedClock};
+use dow_sentiment_analyzer::decision::Verdict;
+use dow_sentiment_analyzer::grade::SignalGrade;
+use dow_sentiment_analyzer::history::HistoryEntry;
+use dow_sentiment_analyzer::relevance::AppState as RelevanceAppState;
+use dow_sentiment_analyzer::store::{DecisionStore, Record, StoreConfig};
+
+const BODY_LIMIT: usize = 1024 * 1024;
+const T0: u64 = 1_756_733_400; // 2025-09-01T13:30:00Z
+
+async fn call(app: &Router, method: &str, uri: &str, payload: Option<Json>) -> Json {
+    let body = payload
+        .map(|p| Body::from(p.to_string()))
+        .unwrap_or_default();
+    let req = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json")
+        .body(body)
+        .expect("build request");
+    let resp = app.clone().oneshot(req).await.expect("oneshot");
+    assert_eq!(resp.status(), StatusCode::OK, "{method} {uri}");
+    let bytes = body::to_bytes(resp.into_body(), BODY_LIMIT)
+        .await
+        .expect("read body");
+    serde_json::from_slice(&bytes).expect("json body")
+}
+
+/// History records across all segment files.
+fn stored_history(dir: &std::path::Path) -> Vec<HistoryEntry> {
+    let mut files: Vec<_> = std::fs::read_dir(dir)
+        .unwrap()
+        .flatten()
+        .map(|e| e.path())
+        .collect();
+    files.sort();
+    files
+        .iter()
+        .flat_map(|p| {
+            std::fs::read_to_string(p)
+                .unwrap()
+                .lines()
+                .filter_map(|l| match serde_json::from_str(l) {
+                    Ok(Record::History(e)) => Some(e),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn history_survives_restart_and_new_decisions_are_appended() {
+    let dir = std::env::temp_dir().join(format!("api_history_store_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let clock: SharedClock = Arc::new(ManualClock::new(T0));
+
+    // "Previous process": one SELL decision an hour ago.
+    {
+        let (store, _) = DecisionStore::open(StoreConfig::new(&dir), clock.clone()).unwrap();
+        store.append(&Record::History(HistoryEntry {
+            id: 1,
+            ts_unix: T0 - 3600,
+            verdict: Verdict::Sell,
+            confidence: 0.71,
+            signal: Some(SignalGrade::Sell),
+            top_sources: vec!["Reuters".into()],
+            top_scores: vec![-3],
+            reasons: Vec::new(),
+            contributors: Vec::new(),
+        }));
+    }
+
+    std::env::set_var("AI_ENABLED", "0");
+    std::env::set_var("HISTORY_STORE_DIR", &dir);
+    let app = api::router_with_clock(RelevanceAppState::from_env(), clock);
+
+    let d = call(&app, "GET", "/decide", None).await;
+    assert_eq!(d["decision"], "SELL", "{d}");
+    assert_eq!(d["signal"], "SELL");
+    assert!((d["confidence"].as_f64().unwrap() - 0.71).abs() < 1e-6);
+
+    let posted = call(
+        &app,
+        "POST",
+        "/decide",
+        Some(json!([{
+            "source": "Fed",
+            "text": "Fed signals rate cuts; Dow futures rally strongly on dovish outlook.",
+            "ts_unix": T0
+        }])),
+    )
+    .await;
+
+    let stored = stored_history(&dir);
+    assert_eq!(stored.len(), 2, "replayed entry + new decision");
+    assert_eq!(stored[0].verdict, Verdict::Sell);
+    assert_eq!(stored[1].ts_unix, T0);
+    assert_eq!(
+        serde_json::to_value(stored[1].verdict).unwrap(),
+        posted["decision"]
+    );
+
+    // A second router on the same directory shares the store and sees the decision.
+    let clock: SharedClock = Arc::new(ManualClock::new(T0 + 60));
+    let second = api::router_with_clock(RelevanceAppState::from_env(), clock.clone());
+    let d = call(&second, "GET", "/decide", None).await;
+    assert_eq!(d["decision"], posted["decision"], "{d}");
+    call(
+        &second,
+        "POST",
+        "/decide",
+        Some(json!([{
+            "source": "Reuters",
+            "text": "Dow futures slide as recession fears deepen.",
+            "ts_unix": T0 + 60
+        }])),
+    )
+    .await;
+    assert_eq!(stored_history(&dir).len(), 3);
+
+    // Once every router is gone, the next one opens the directory again.
+    drop((app, second));
+    let third = api::router_with_clock(RelevanceAppState::from_env(), clock);
+    call(
+        &third,
+        "POST",
+        "/decide",
+        Some(json!([{ "source": "Fed", "text": "Fed holds rates steady.", "ts_unix": T0 + 60 }])),
+    )
+    .await;
+    assert_eq!(stored_history(&dir).len(), 4, "appended after reopening");
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
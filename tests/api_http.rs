@@ -163,3 +163,31 @@ async fn api_decide_sets_ai_headers_and_includes_ai_metadata() {
         "ai.cache_hit / ai.limited missing"
     );
 }
+
+#[tokio::test]
+async fn api_decide_get_exposes_graded_signal() {
+    let app = test_router();
+
+    let req = Request::builder()
+        .method("GET")
+        .uri("/decide")
+        .body(Body::empty())
+        .expect("build GET /decide");
+
+    let resp = app.oneshot(req).await.expect("oneshot GET /decide");
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let bytes = body::to_bytes(resp.into_body(), BODY_LIMIT)
+        .await
+        .expect("read json")
+        .to_vec();
+    let v: Json = serde_json::from_slice(&bytes).expect("parse decide json");
+
+    let decision = v["decision"].as_str().expect("decision string");
+    assert!(["BUY", "SELL", "HOLD"].contains(&decision));
+    let signal = v["signal"].as_str().expect("signal string");
+    assert!(
+        ["STRONG_BUY", "BUY", "HOLD", "SELL", "STRONG_SELL"].contains(&signal),
+        "unexpected signal '{signal}'"
+    );
+}
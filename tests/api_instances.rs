@@ -0,0 +1,161 @@
+// tests/api_instances.rs
+//
+// Routers built in one process are independent: each has its own clock, history,
+// AI cache and (with `router_with_bus`) stream bus. Routers on one bus and one
+// subscriptions file share the webhooks and POST each event once.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::json;
+use serde_json::Value as Json;
+use shuttle_axum::axum::{
+    self,
+    body::{self, Body, Bytes},
+    http::{Request, StatusCode},
+    routing::post,
+    Router,
+};
+use tokio::sync::{broadcast, mpsc};
+use tower::ServiceExt as _;
+
+use dow_sentiment_analyzer::api;
+use dow_sentiment_analyzer::clock::{ManualClock, SharedClock};
+use dow_sentiment_analyzer::relevance::AppState as RelevanceAppState;
+use dow_sentiment_analyzer::stream::{DecisionBus, StreamMessage};
+
+const BODY_LIMIT: usize = 1024 * 1024;
+const T0: u64 = 1_756_733_400; // 2025-09-01T13:30:00Z
+
+async fn call(app: &Router, method: &str, uri: &str, payload: Option<Json>) -> (String, Json) {
+    let body = payload
+        .map(|p| Body::from(p.to_string()))
+        .unwrap_or_default();
+    let req = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json")
+        .body(body)
+        .expect("build request");
+    let resp = app.clone().oneshot(req).await.expect("oneshot");
+    assert_eq!(resp.status(), StatusCode::OK, "{method} {uri}");
+    let cache = resp
+        .headers()
+        .get("x-ai-cache")
+        .map(|v| v.to_str().unwrap().to_string())
+        .unwrap_or_default();
+    let bytes = body::to_bytes(resp.into_body(), BODY_LIMIT)
+        .await
+        .expect("read body");
+    (cache, serde_json::from_slice(&bytes).expect("json body"))
+}
+
+/// Decision messages already published on the bus.
+fn decisions(rx: &mut broadcast::Receiver<StreamMessage>) -> usize {
+    std::iter::from_fn(|| rx.try_recv().ok())
+        .filter(|m| m.event.kind() == "decision")
+        .count()
+}
+
+fn build(ts: u64, bus: &DecisionBus) -> Router {
+    let clock: SharedClock = Arc::new(ManualClock::new(ts));
+    api::router_with_bus(RelevanceAppState::from_env(), clock, bus.clone())
+}
+
+#[tokio::test]
+async fn routers_do_not_share_state() {
+    std::env::set_var("AI_ENABLED", "0");
+    let (bus_a, bus_b) = (DecisionBus::new(16), DecisionBus::new(16));
+    let a = build(T0, &bus_a);
+    let b = build(T0 + 3600, &bus_b);
+    let mut rx_a = bus_a.subscribe();
+    let mut rx_b = bus_b.subscribe();
+
+    let item =
+        json!([{ "source": "Fed", "text": "Fed signals rate cuts; Dow futures rally strongly." }]);
+    let (cache_a, _) = call(&a, "POST", "/decide", Some(item.clone())).await;
+    // Same request on the other router: its AI cache is empty.
+    let (cache_b, _) = call(&b, "POST", "/decide", Some(item.clone())).await;
+    assert_eq!((cache_a.as_str(), cache_b.as_str()), ("miss", "miss"));
+    let (cache_a, _) = call(&a, "POST", "/decide", Some(item)).await;
+    assert_eq!(cache_a, "hit");
+
+    // Each router records with its own clock into its own history.
+    let (_, hist_a) = call(&a, "GET", "/history", None).await;
+    let (_, hist_b) = call(&b, "GET", "/history", None).await;
+    let ts = |page: &Json| -> Vec<u64> {
+        page["entries"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| e["ts_unix"].as_u64().unwrap())
+            .collect()
+    };
+    assert_eq!(ts(&hist_a), [T0, T0]);
+    assert_eq!(ts(&hist_b), [T0 + 3600]);
+
+    // Decisions go out on the router's own bus only.
+    assert_eq!(decisions(&mut rx_a), 2);
+    assert_eq!(decisions(&mut rx_b), 1);
+}
+
+#[tokio::test]
+async fn routers_on_one_bus_deliver_each_webhook_once() {
+    // Its own day, so deliveries of decisions from the other test are told apart.
+    const T1: u64 = T0 + 86_400;
+    let dir = std::env::temp_dir().join(format!("api_instances_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    std::env::set_var("AI_ENABLED", "0");
+    std::env::set_var("DEBUG_ROUTES", "1");
+    std::env::set_var("ADMIN_AUDIT_LOG", dir.join("audit.jsonl"));
+    std::env::set_var("WEBHOOK_ALLOW_PRIVATE", "1");
+    std::env::set_var("SUBSCRIPTIONS_PATH", dir.join("subscriptions.json"));
+
+    let (tx, mut hits) = mpsc::unbounded_channel::<Json>();
+    let receiver = Router::new().route(
+        "/hook",
+        post(move |body: Bytes| {
+            let _ = tx.send(serde_json::from_slice(&body).unwrap_or(Json::Null));
+            async { StatusCode::OK }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, receiver).await.unwrap() });
+
+    let bus = DecisionBus::new(16);
+    let (a, b) = (build(T1, &bus), build(T1, &bus));
+    let req = Request::builder()
+        .method("POST")
+        .uri("/subscriptions")
+        .header("content-type", "application/json")
+        .body(Body::from(
+            json!({ "url": url, "filter": { "events": ["decision"] } }).to_string(),
+        ))
+        .unwrap();
+    let resp = a.clone().oneshot(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    // Registered through one router, visible through the other.
+    let (_, list) = call(&b, "GET", "/subscriptions", None).await;
+    assert_eq!(list["subscriptions"].as_array().unwrap().len(), 1);
+
+    let item =
+        json!([{ "source": "Fed", "text": "Fed signals rate cuts; Dow futures rally strongly." }]);
+    call(&a, "POST", "/decide", Some(item.clone())).await;
+    call(&b, "POST", "/decide", Some(item)).await;
+
+    // Two decisions on the bus, two deliveries, then nothing more.
+    let mut seqs = Vec::new();
+    while let Ok(Some(event)) = tokio::time::timeout(Duration::from_millis(500), hits.recv()).await
+    {
+        if event["ts_unix"] == T1 {
+            seqs.push(event["seq"].as_u64().unwrap());
+        }
+    }
+    seqs.sort_unstable();
+    assert_eq!(seqs.len(), 2, "{seqs:?}");
+    assert_ne!(seqs[0], seqs[1]);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,402 @@
+// tests/api_openapi.rs
+//
+// The served OpenAPI document must match the handlers: every documented operation is
+// called with its request examples, the status must be documented and the JSON body
+// must validate against the response schema (no undocumented fields). Every path the
+// router registers (`/debug/routes`) is in the spec unless it is on `UNDOCUMENTED`.
+// Own test binary: paper trading and debug routes are enabled through process env.
+
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use serde_json::json;
+use serde_json::Value as Json;
+use shuttle_axum::axum::{
+    body::{self, Body},
+    http::{header, Request, StatusCode},
+    Router,
+};
+use tower::ServiceExt as _;
+
+use dow_sentiment_analyzer::api;
+use dow_sentiment_analyzer::clock::{ManualClock, SharedClock};
+use dow_sentiment_analyzer::relevance::AppState as RelevanceAppState;
+
+const BODY_LIMIT: usize = 4 * 1024 * 1024;
+const T0: u64 = 1_756_733_400; // first bar of tests/fixtures/backtest_ym.csv
+
+/// Registered paths deliberately left out of the spec (exact path, or prefix ending in `/`).
+const UNDOCUMENTED: &[&str] = &[
+    // WebSocket upgrade; OpenAPI 3 cannot describe it (see the `stream` tag).
+    "/stream/ws",
+    // Operator-only surfaces behind DEBUG_ROUTES or the admin scope.
+    "/admin/",
+    "/debug/",
+];
+
+fn undocumented(path: &str) -> bool {
+    UNDOCUMENTED
+        .iter()
+        .any(|u| path == *u || (u.ends_with('/') && path.starts_with(u)))
+}
+
+async fn call(
+    app: &Router,
+    method: &str,
+    uri: &str,
+    payload: Option<&Json>,
+) -> (StatusCode, String, Vec<u8>) {
+    let body = payload
+        .map(|p| Body::from(p.to_string()))
+        .unwrap_or_default();
+    let req = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json")
+        .body(body)
+        .expect("build request");
+    let resp = app.clone().oneshot(req).await.expect("oneshot");
+    let status = resp.status();
+    let ctype = resp
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    // Event streams never end; the status and content type are what we check.
+    if ctype.starts_with("text/event-stream") {
+        return (status, ctype, Vec::new());
+    }
+    let bytes = body::to_bytes(resp.into_body(), BODY_LIMIT)
+        .await
+        .expect("read body");
+    (status, ctype, bytes.to_vec())
+}
+
+// ---- minimal JSON Schema (OpenAPI 3.1 subset emitted by utoipa) ----
+
+fn resolve<'a>(spec: &'a Json, schema: &'a Json) -> &'a Json {
+    match schema.get("$ref").and_then(Json::as_str) {
+        Some(r) => {
+            let name = r
+                .strip_prefix("#/components/schemas/")
+                .unwrap_or_else(|| panic!("unsupported $ref {r}"));
+            let target = &spec["components"]["schemas"][name];
+            assert!(!target.is_null(), "dangling $ref {r}");
+            resolve(spec, target)
+        }
+        None => schema,
+    }
+}
+
+/// Property names declared by `schema` (following `$ref` and `allOf`).
+fn declared_props(spec: &Json, schema: &Json) -> BTreeSet<String> {
+    let schema = resolve(spec, schema);
+    let mut out: BTreeSet<String> = schema
+        .get("properties")
+        .and_then(Json::as_object)
+        .map(|p| p.keys().cloned().collect())
+        .unwrap_or_default();
+    for s in schema["allOf"].as_array().into_iter().flatten() {
+        out.extend(declared_props(spec, s));
+    }
+    out
+}
+
+fn type_matches(ty: &str, v: &Json) -> bool {
+    match ty {
+        "null" => v.is_null(),
+        "boolean" => v.is_boolean(),
+        "integer" => v.is_i64() || v.is_u64(),
+        "number" => v.is_number(),
+        "string" => v.is_string(),
+        "array" => v.is_array(),
+        "object" => v.is_object(),
+        other => panic!("unsupported type {other}"),
+    }
+}
+
+fn validate(
+    spec: &Json,
+    schema: &Json,
+    v: &Json,
+    at: &str,
+    extra: &BTreeSet<String>,
+) -> Vec<String> {
+    let schema = resolve(spec, schema);
+    let mut errs = Vec::new();
+
+    if let Some(all) = schema["allOf"].as_array() {
+        let props = declared_props(spec, schema);
+        for s in all {
+            errs.extend(validate(spec, s, v, at, &props));
+        }
+    }
+    if let Some(one) = schema["oneOf"].as_array() {
+        let ok = one
+            .iter()
+            .filter(|s| validate(spec, s, v, at, extra).is_empty())
+            .count();
+        if ok != 1 {
+            errs.push(format!(
+                "{at}: {ok} of {} oneOf branches match {v}",
+                one.len()
+            ));
+        }
+    }
+    if let Some(values) = schema["enum"].as_array() {
+        if !values.contains(v) {
+            errs.push(format!("{at}: {v} not in enum {values:?}"));
+        }
+    }
+    let types: Vec<&str> = match &schema["type"] {
+        Json::String(t) => vec![t.as_str()],
+        Json::Array(ts) => ts.iter().filter_map(Json::as_str).collect(),
+        _ => Vec::new(),
+    };
+    if !types.is_empty() && !types.iter().any(|t| type_matches(t, v)) {
+        errs.push(format!("{at}: expected {types:?}, got {v}"));
+        return errs;
+    }
+
+    if let Some(obj) = v.as_object() {
+        for r in schema["required"].as_array().into_iter().flatten() {
+            let r = r.as_str().unwrap_or_default();
+            if !obj.contains_key(r) {
+                errs.push(format!("{at}: missing required `{r}`"));
+            }
+        }
+        let props = schema["properties"].as_object();
+        for (k, val) in obj {
+            let here = format!("{at}.{k}");
+            match props.and_then(|p| p.get(k)) {
+                Some(s) => errs.extend(validate(spec, s, val, &here, &BTreeSet::new())),
+                None => match &schema["additionalProperties"] {
+                    s @ Json::Object(_) => {
+                        errs.extend(validate(spec, s, val, &here, &BTreeSet::new()))
+                    }
+                    _ if props.is_some() && !extra.contains(k) => {
+                        errs.push(format!("{here}: not documented"))
+                    }
+                    _ => {}
+                },
+            }
+        }
+    }
+
+    if let Some(arr) = v.as_array() {
+        let prefix = schema["prefixItems"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or(&[]);
+        for (i, item) in arr.iter().enumerate() {
+            let here = format!("{at}[{i}]");
+            match (prefix.get(i), &schema["items"]) {
+                (Some(s), _) => errs.extend(validate(spec, s, item, &here, &BTreeSet::new())),
+                (None, Json::Bool(false)) => errs.push(format!("{here}: extra tuple item")),
+                (None, s @ Json::Object(_)) => {
+                    errs.extend(validate(spec, s, item, &here, &BTreeSet::new()))
+                }
+                _ => {}
+            }
+        }
+    }
+    errs
+}
+
+/// Check one response against the operation's documented responses.
+fn check_response(
+    spec: &Json,
+    op: &Json,
+    what: &str,
+    (status, ctype, bytes): &(StatusCode, String, Vec<u8>),
+) -> Vec<String> {
+    let Some(doc) = op["responses"].get(status.as_str()) else {
+        return vec![format!("{what}: undocumented status {status}")];
+    };
+    let content = doc["content"].as_object().cloned().unwrap_or_default();
+    // Documented without a body (e.g. 204).
+    if content.is_empty() && bytes.is_empty() {
+        return Vec::new();
+    }
+    let Some((media, body)) = content
+        .iter()
+        .find(|(media, _)| ctype.starts_with(media.as_str()))
+    else {
+        return vec![format!(
+            "{what}: content-type `{ctype}` not in {:?}",
+            content.keys().collect::<Vec<_>>()
+        )];
+    };
+    if media != "application/json" {
+        return Vec::new();
+    }
+    match serde_json::from_slice::<Json>(bytes) {
+        Ok(v) => validate(spec, &body["schema"], &v, what, &BTreeSet::new()),
+        Err(e) => vec![format!("{what}: invalid JSON body: {e}")],
+    }
+}
+
+/// Request bodies to send: every documented example (`example` or `examples`).
+fn request_examples(op: &Json) -> Vec<Option<Json>> {
+    let Some(media) = op["requestBody"]["content"].get("application/json") else {
+        return vec![None];
+    };
+    let mut out: Vec<Option<Json>> = media
+        .get("example")
+        .cloned()
+        .map(Some)
+        .into_iter()
+        .collect();
+    for ex in media["examples"]
+        .as_object()
+        .into_iter()
+        .flat_map(|m| m.values())
+    {
+        out.push(Some(ex["value"].clone()));
+    }
+    assert!(!out.is_empty(), "request body without example: {op}");
+    out
+}
+
+#[tokio::test]
+async fn handlers_match_the_openapi_spec() {
+    std::env::set_var("AI_ENABLED", "0");
+    std::env::set_var("PAPER_TRADING", "1");
+    std::env::set_var("PAPER_PRICE_CSV", "tests/fixtures/backtest_ym.csv");
+    std::env::set_var("PAPER_CONFIG_PATH", "config/paper.json");
+    std::env::set_var("DEBUG_ROUTES", "1");
+    let subs = std::env::temp_dir().join(format!("openapi_subs_{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&subs);
+    std::env::set_var("SUBSCRIPTIONS_PATH", &subs);
+    let audit = std::env::temp_dir().join(format!("openapi_audit_{}.jsonl", std::process::id()));
+    std::env::set_var("ADMIN_AUDIT_LOG", &audit);
+
+    let clock: SharedClock = Arc::new(ManualClock::new(T0));
+    let app = api::router_with_clock(RelevanceAppState::from_env(), clock);
+
+    // The served document is the generated one.
+    let (status, _, bytes) = call(&app, "GET", "/openapi.json", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let spec: Json = serde_json::from_slice(&bytes).expect("spec json");
+    assert_eq!(spec, serde_json::to_value(api::ApiDoc::spec()).unwrap());
+    assert!(spec["openapi"].as_str().unwrap().starts_with("3."));
+    assert_eq!(spec["servers"][0]["url"], "/api");
+
+    // Registered routes and documented paths agree, up to the allowlist.
+    let (status, _, bytes) = call(&app, "GET", "/debug/routes", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let registered: BTreeSet<String> = serde_json::from_slice(&bytes).expect("routes json");
+    let documented: BTreeSet<String> = spec["paths"]
+        .as_object()
+        .expect("paths")
+        .keys()
+        .cloned()
+        .collect();
+    let missing: Vec<_> = registered
+        .iter()
+        .filter(|p| !documented.contains(*p) && !undocumented(p))
+        .collect();
+    assert!(
+        missing.is_empty(),
+        "registered but not in the spec: {missing:?}"
+    );
+    let stale: Vec<_> = documented.difference(&registered).collect();
+    assert!(
+        stale.is_empty(),
+        "in the spec but not registered: {stale:?}"
+    );
+    let listed: Vec<_> = documented.iter().filter(|p| undocumented(p)).collect();
+    assert!(
+        listed.is_empty(),
+        "documented paths on the allowlist: {listed:?}"
+    );
+
+    // POSTs first so GET /decide and /paper/* have something to report; DELETEs last.
+    let mut ops: Vec<(String, String, Json)> = Vec::new();
+    for (path, item) in spec["paths"].as_object().expect("paths") {
+        for (method, op) in item.as_object().expect("path item") {
+            ops.push((method.to_uppercase(), path.clone(), op.clone()));
+        }
+    }
+    let rank = |m: &str| match m {
+        "POST" => 0,
+        "DELETE" => 2,
+        _ => 1,
+    };
+    ops.sort_by_key(|(m, p, _)| (rank(m), p.clone()));
+    assert!(ops.len() >= 10, "too few operations: {}", ops.len());
+
+    // `{id}` is filled with the subscription created by `POST /subscriptions`.
+    let mut sub_id = String::new();
+    let mut errors = Vec::new();
+    for (method, path, op) in &ops {
+        for payload in request_examples(op) {
+            let what = format!("{method} {path}");
+            let uri = path.replace("{id}", &sub_id);
+            let resp = call(&app, method, &uri, payload.as_ref()).await;
+            if path == "/subscriptions" && method == "POST" {
+                let created: Json = serde_json::from_slice(&resp.2).unwrap_or_default();
+                sub_id = created["id"].as_str().unwrap_or_default().to_string();
+            }
+            if !resp.0.is_success() {
+                errors.push(format!("{what}: example returned {}", resp.0));
+            }
+            errors.extend(check_response(&spec, op, &what, &resp));
+        }
+    }
+
+    // Documented error responses validate too.
+    let op = &spec["paths"]["/rules/dry-run"]["post"];
+    let bad =
+        json!({ "rules": { "rules": [{ "name": "bad", "when": { "regex": "(" } }] }, "texts": [] });
+    let resp = call(&app, "POST", "/rules/dry-run", Some(&bad)).await;
+    assert_eq!(resp.0, StatusCode::UNPROCESSABLE_ENTITY);
+    errors.extend(check_response(
+        &spec,
+        op,
+        "POST /rules/dry-run (422)",
+        &resp,
+    ));
+
+    let op = &spec["paths"]["/v1/decide"]["post"];
+    let bad = json!([{ "source": "Fed", "text": "" }]);
+    let resp = call(&app, "POST", "/v1/decide", Some(&bad)).await;
+    assert_eq!(resp.0, StatusCode::UNPROCESSABLE_ENTITY);
+    errors.extend(check_response(&spec, op, "POST /v1/decide (422)", &resp));
+
+    // The docs page points at the spec and loads a pinned Swagger UI release.
+    std::env::set_var("DOCS_SWAGGER_JS_SRI", "sha384-test");
+    let (_, _, html) = call(&app, "GET", "/docs", None).await;
+    let html = String::from_utf8_lossy(&html);
+    assert!(html.contains("openapi.json"));
+    let pinned = format!("swagger-ui-dist@{}/", api::SWAGGER_UI_VERSION);
+    assert_eq!(html.matches(&pinned).count(), 2);
+    assert!(html.contains(r#"swagger-ui-bundle.js" integrity="sha384-test" crossorigin"#));
+
+    let _ = std::fs::remove_file(&subs);
+    let _ = std::fs::remove_file(&audit);
+    assert!(errors.is_empty(), "spec drift:\n{}", errors.join("\n"));
+}
+
+#[test]
+fn validator_rejects_undocumented_and_mistyped_fields() {
+    let spec = serde_json::to_value(api::ApiDoc::spec()).unwrap();
+    let schema = json!({ "$ref": "#/components/schemas/DecideOut" });
+    let ok = json!({ "decision": "BUY", "confidence": 0.7, "reasons": [], "signal": "BUY" });
+    assert!(validate(&spec, &schema, &ok, "$", &BTreeSet::new()).is_empty());
+
+    let mut extra = ok.clone();
+    extra["surprise"] = json!(1);
+    assert_eq!(
+        validate(&spec, &schema, &extra, "$", &BTreeSet::new()),
+        vec!["$.surprise: not documented"]
+    );
+
+    let mistyped =
+        json!({ "decision": "BUY", "confidence": "high", "reasons": [], "signal": "BUY" });
+    assert_eq!(
+        validate(&spec, &schema, &mistyped, "$", &BTreeSet::new()).len(),
+        1
+    );
+}
@@ -0,0 +1,104 @@
+// tests/api_paper.rs
+//
+// Paper trading through the HTTP API: decisions open/reverse simulated YM
+// positions, marked to the fixture CSV on a ManualClock by decisions and the
+// background marker; the GET views never mark.
+// Own test binary: the router reads its settings from process env.
+
+use std::sync::Arc;
+
+use serde_json::json;
+use serde_json::Value as Json;
+use shuttle_axum::axum::{
+    body::{self, Body},
+    http::{Request, StatusCode},
+    Router,
+};
+use tower::ServiceExt as _;
+
+use dow_sentiment_analyzer::api;
+use dow_sentiment_analyzer::clock::{ManualClock, SharedClock};
+use dow_sentiment_analyzer::relevance::AppState as RelevanceAppState;
+
+const BODY_LIMIT: usize = 1024 * 1024;
+const T0: u64 = 1_756_733_400; // first bar of tests/fixtures/backtest_ym.csv
+
+async fn call(app: &Router, method: &str, uri: &str, payload: Option<Json>) -> Json {
+    let body = payload
+        .map(|p| Body::from(p.to_string()))
+        .unwrap_or_default();
+    let req = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json")
+        .body(body)
+        .expect("build request");
+    let resp = app.clone().oneshot(req).await.expect("oneshot");
+    assert_eq!(resp.status(), StatusCode::OK, "{method} {uri}");
+    let bytes = body::to_bytes(resp.into_body(), BODY_LIMIT)
+        .await
+        .expect("read body");
+    serde_json::from_slice(&bytes).expect("json body")
+}
+
+#[tokio::test]
+async fn decisions_drive_paper_positions_fills_and_pnl() {
+    std::env::set_var("AI_ENABLED", "0");
+    std::env::set_var("PAPER_TRADING", "1");
+    std::env::set_var("PAPER_PRICE_CSV", "tests/fixtures/backtest_ym.csv");
+    std::env::set_var("PAPER_CONFIG_PATH", "config/paper.json");
+    std::env::set_var("PAPER_MARK_INTERVAL_MS", "20");
+
+    let clock = Arc::new(ManualClock::new(T0));
+    let shared: SharedClock = clock.clone();
+    let app = api::router_with_clock(RelevanceAppState::from_env(), shared);
+
+    let buy = json!([{
+        "source": "Fed",
+        "text": "Fed signals rate cuts; Dow futures rally strongly on dovish outlook.",
+        "ts_unix": T0
+    }]);
+    let d = call(&app, "POST", "/decide", Some(buy)).await;
+    assert_eq!(d["decision"], "BUY");
+
+    let pos = call(&app, "GET", "/paper/positions", None).await;
+    let p = &pos["positions"][0];
+    assert!(p["qty"].as_i64().unwrap() > 0, "long expected: {pos}");
+    assert_eq!(p["entry_price"], 45000.0);
+
+    // One hour later the market is 120 points higher; the marker picks it up.
+    clock.advance(3600);
+    let mut pnl = Json::Null;
+    for _ in 0..100 {
+        pnl = call(&app, "GET", "/paper/pnl", None).await;
+        if pnl["last_mark_unix"] == T0 + 3600 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    assert_eq!(pnl["last_mark_unix"], T0 + 3600, "{pnl}");
+    assert!(pnl["unrealized_points"].as_f64().unwrap() > 0.0, "{pnl}");
+    assert_eq!(pnl["trades"], 0);
+
+    let sell = json!([{
+        "source": "Fed",
+        "text": "Fed warns of recession risk; Dow futures plunge on hawkish stance.",
+        "ts_unix": T0 + 3600
+    }]);
+    let d = call(&app, "POST", "/decide", Some(sell)).await;
+    assert_eq!(d["decision"], "SELL");
+
+    let fills = call(&app, "GET", "/paper/fills", None).await;
+    let reasons: Vec<&str> = fills["fills"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| f["reason"].as_str().unwrap())
+        .collect();
+    assert_eq!(reasons, vec!["open", "reverse", "open"]);
+
+    let pnl = call(&app, "GET", "/paper/pnl", None).await;
+    assert_eq!(pnl["trades"], 1);
+    assert_eq!(pnl["wins"], 1);
+    assert!(pnl["realized_usd"].as_f64().unwrap() > 0.0);
+}
@@ -0,0 +1,103 @@
+// tests/api_pipeline.rs
+//
+// Phase-3 stages (antispam, rerank, NER, weights, rules) on the real `/decide` path.
+// Own test binary: the router reads its settings from process env.
+
+use std::sync::Arc;
+
+use serde_json::json;
+use serde_json::Value as Json;
+use shuttle_axum::axum::{
+    body::{self, Body},
+    http::{Request, StatusCode},
+    Router,
+};
+use tower::ServiceExt as _;
+
+use dow_sentiment_analyzer::api;
+use dow_sentiment_analyzer::clock::{ManualClock, SharedClock};
+use dow_sentiment_analyzer::relevance::AppState as RelevanceAppState;
+
+const BODY_LIMIT: usize = 1024 * 1024;
+const T0: u64 = 1_756_733_400; // 2025-09-01T13:30:00Z
+
+fn has_reason(v: &Json, msg: &str) -> bool {
+    v["reasons"]
+        .as_array()
+        .is_some_and(|rs| rs.iter().any(|r| r["message"] == msg))
+}
+
+async fn decide(app: &Router, payload: Json) -> Json {
+    let req = Request::builder()
+        .method("POST")
+        .uri("/decide")
+        .header("content-type", "application/json")
+        .body(Body::from(payload.to_string()))
+        .expect("build POST /decide");
+    let resp = app.clone().oneshot(req).await.expect("oneshot /decide");
+    assert_eq!(resp.status(), StatusCode::OK);
+    let bytes = body::to_bytes(resp.into_body(), BODY_LIMIT)
+        .await
+        .expect("read body");
+    serde_json::from_slice(&bytes).expect("json body")
+}
+
+#[tokio::test]
+async fn decide_reports_pipeline_stages() {
+    // Source-heavy calibrated weights lower the trigger quality a little.
+    let weights = std::env::temp_dir().join(format!("api_pipeline_w_{}.json", std::process::id()));
+    std::fs::write(
+        &weights,
+        r#"{"w_source":2.4,"w_strength":0.3,"w_recency":0.3}"#,
+    )
+    .unwrap();
+    std::env::set_var("AI_ENABLED", "0");
+    std::env::set_var("RULES_CONFIG_PATH", "tests/fixtures/rules_pipeline.json");
+    std::env::set_var("WEIGHTS_CONFIG_PATH", &weights);
+    let clock: SharedClock = Arc::new(ManualClock::new(T0));
+    let app = api::router_with_clock(RelevanceAppState::from_env(), clock);
+
+    let text = "Fed signals rate cuts; Dow futures rally strongly on dovish outlook.";
+    let payload = json!([
+        { "source": "Fed", "text": text, "ts_unix": T0 - 60 },
+        { "source": "Reuters", "text": format!("{text}!"), "ts_unix": T0 - 30 },
+        { "source": "Trump", "text": "Great jobs numbers, stock market strong!", "ts_unix": T0 }
+    ]);
+    let v = decide(&app, payload.clone()).await;
+
+    let p = &v["pipeline"];
+    assert_eq!(
+        p["stages"],
+        json!(["antispam", "rerank", "ner", "weights", "rules"])
+    );
+    assert_eq!(p["antispam_dropped"], 1, "{v}");
+    assert!(has_reason(&v, "Anti-spam dropped 1/3 near-duplicate items"));
+
+    let ner = p["ner"].as_array().expect("ner reasons");
+    assert!(ner.iter().any(|r| r == "rates: rates"), "{v}");
+    assert!(has_reason(&v, "NER rates: rates"));
+
+    // "Federal Reserve" matches source "Fed" through the alias table; the
+    // higher-priority rule runs first.
+    assert_eq!(
+        p["rules_matched"],
+        json!(["fed-source-bullish", "dovish-fed"])
+    );
+    assert_eq!(
+        p["rule_reasons"],
+        json!(["Fed itself signals easing", "dovish Fed language"])
+    );
+    assert!(has_reason(&v, "Rule: dovish Fed language"), "{v}");
+
+    // Calibrated weights moved the confidence (1/1/1 would leave it alone).
+    let delta = p["weights_delta"].as_f64().expect("weights_delta");
+    assert!(delta < 0.0 && delta > -0.1, "{v}");
+
+    // Anti-spam works per batch: posting the same batch again keeps every item, and the
+    // verdict with it (clients that re-send their recent feed must not flip to HOLD).
+    let again = decide(&app, payload).await;
+    assert_eq!(again["pipeline"]["antispam_dropped"], 1, "{again}");
+    assert_eq!(again["decision"], v["decision"], "{again}");
+
+    let _ = std::fs::remove_file(&weights);
+}
@@ -0,0 +1,122 @@
+// tests/api_rules_dry_run.rs
+//
+// POST /rules/dry-run evaluates a candidate rule set without touching the live rules file.
+// Own test binary: the router reads its settings from process env.
+
+use std::sync::Arc;
+
+use serde_json::json;
+use serde_json::Value as Json;
+use shuttle_axum::axum::{
+    body::{self, Body},
+    http::{Request, StatusCode},
+};
+use tower::ServiceExt as _;
+
+use dow_sentiment_analyzer::analyze;
+use dow_sentiment_analyzer::api;
+use dow_sentiment_analyzer::clock::{ManualClock, SharedClock};
+use dow_sentiment_analyzer::relevance::AppState as RelevanceAppState;
+
+const BODY_LIMIT: usize = 1024 * 1024;
+const T0: u64 = 1_756_733_400; // 2025-09-01T13:30:00Z
+
+async fn dry_run(payload: Json) -> (StatusCode, Json) {
+    let clock: SharedClock = Arc::new(ManualClock::new(T0));
+    let app = api::router_with_clock(RelevanceAppState::from_env(), clock);
+    let req = Request::builder()
+        .method("POST")
+        .uri("/rules/dry-run")
+        .header("content-type", "application/json")
+        .body(Body::from(payload.to_string()))
+        .expect("build POST /rules/dry-run");
+    let resp = app.oneshot(req).await.expect("oneshot /rules/dry-run");
+    let status = resp.status();
+    let bytes = body::to_bytes(resp.into_body(), BODY_LIMIT)
+        .await
+        .expect("read body");
+    (status, serde_json::from_slice(&bytes).expect("json body"))
+}
+
+#[tokio::test]
+async fn dry_run_reports_fires_without_touching_live_rules() {
+    std::env::set_var("AI_ENABLED", "0");
+    let live = std::env::temp_dir().join(format!("rules_dry_run_{}.json", std::process::id()));
+    let live_body = r#"{"rules":[{"name":"live","when":{},"then":{"set_action":"SELL"}}]}"#;
+    std::fs::write(&live, live_body).expect("write live rules");
+    std::env::set_var("RULES_CONFIG_PATH", &live);
+
+    let candidate = json!({ "rules": [
+        { "name": "fed-source", "priority": 10,
+          "when": { "source": ["Federal Reserve"], "regex": "rate\\s+cuts?" },
+          "then": { "set_action": "BUY", "boost_confidence": 0.1 } },
+        { "name": "tariffs", "when": { "any_contains": ["tariff"] },
+          "then": { "set_action": "SELL", "add_reason": "tariff risk" } },
+        { "name": "never", "when": { "any_contains": ["zzz"] } }
+    ]});
+    let (status, v) = dry_run(json!({
+        "rules": candidate,
+        "texts": [
+            { "source": "Fed", "text": "Fed signals rate cuts ahead" },
+            "New tariff threats weigh on stocks",
+            { "source": "Fed", "text": "Rate cuts despite tariff worries" },
+            "Nothing to see here"
+        ]
+    }))
+    .await;
+    assert_eq!(status, StatusCode::OK, "{v}");
+
+    assert_eq!(v["texts"], 4);
+    assert_eq!(v["matched_texts"], 3);
+    assert_eq!(v["actions"], json!({ "BUY": 2, "SELL": 1 }));
+    assert_eq!(
+        v["fire_counts"],
+        json!([
+            { "rule": "fed-source", "fires": 2 },
+            { "rule": "tariffs", "fires": 2 },
+            { "rule": "never", "fires": 0 }
+        ])
+    );
+
+    let r = &v["results"];
+    assert_eq!(r[0]["matched"], json!(["fed-source"]));
+    assert_eq!(r[0]["action"], "BUY");
+    assert!((r[0]["confidence_delta"].as_f64().unwrap() - 0.1).abs() < 1e-6);
+    assert_eq!(r[1]["action"], "SELL");
+    assert_eq!(r[1]["reasons"], json!(["tariff risk"]));
+    // Higher priority wins; the losing action is reported as a conflict.
+    assert_eq!(r[2]["action_rule"], "fed-source");
+    assert_eq!(r[2]["conflicts"].as_array().map(Vec::len), Some(1));
+    assert_eq!(r[3]["matched"], json!([]));
+    assert_eq!(r[3]["action"], Json::Null);
+
+    // The live rule set is untouched.
+    assert_eq!(std::fs::read_to_string(&live).unwrap(), live_body);
+    let current = analyze::current_rules();
+    assert_eq!(current.rules.len(), 1);
+    assert_eq!(current.rules[0].name.as_deref(), Some("live"));
+
+    // Invalid candidates are rejected with the validation errors.
+    let (status, v) = dry_run(json!({
+        "rules": { "rules": [ { "name": "bad", "when": { "regex": "(unclosed" } } ] },
+        "texts": ["anything"]
+    }))
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(v["error"]["code"], "validation_failed");
+    assert_eq!(v["error"]["details"][0]["field"], "bad");
+    assert!(v["error"]["details"][0]["problem"]
+        .as_str()
+        .is_some_and(|e| e.starts_with("invalid regex")));
+
+    let (status, v) = dry_run(json!({ "rules": { "rules": "nope" }, "texts": [] })).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(v["error"]["details"][0]["field"], "rules");
+
+    // A body that is not a dry-run request is a 400 in the same envelope.
+    let (status, v) = dry_run(json!({ "texts": [] })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(v["error"]["code"], "invalid_json");
+
+    let _ = std::fs::remove_file(&live);
+}
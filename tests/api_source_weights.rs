@@ -0,0 +1,204 @@
+// tests/api_source_weights.rs
+//
+// /admin/source-weights: admin-only GET/PUT/PATCH with validation (weights in 0..1,
+// alias targets must exist), atomic writes to SOURCE_WEIGHTS_PATH, a diff in the
+// response and a change history attributed to the API key.
+// Own test binary: the router reads its settings from process env.
+
+use std::sync::Arc;
+
+use serde_json::json;
+use serde_json::Value as Json;
+use shuttle_axum::axum::{
+    body::{self, Body},
+    http::{Request, StatusCode},
+    Router,
+};
+use tower::ServiceExt as _;
+
+use dow_sentiment_analyzer::api;
+use dow_sentiment_analyzer::clock::{ManualClock, SharedClock};
+use dow_sentiment_analyzer::relevance::AppState as RelevanceAppState;
+
+const BODY_LIMIT: usize = 1024 * 1024;
+const T0: u64 = 1_756_733_400; // 2025-09-01T13:30:00Z
+const ADMIN: &str = "admin-secret-key";
+const READER: &str = "reader-secret-key";
+
+async fn call(
+    app: &Router,
+    method: &str,
+    uri: &str,
+    key: &str,
+    body: Option<String>,
+) -> (StatusCode, Json) {
+    let req = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json")
+        .header("x-api-key", key)
+        .body(body.map(Body::from).unwrap_or_default())
+        .expect("build request");
+    let resp = app.clone().oneshot(req).await.expect("oneshot");
+    let status = resp.status();
+    let bytes = body::to_bytes(resp.into_body(), BODY_LIMIT)
+        .await
+        .expect("read body");
+    (status, serde_json::from_slice(&bytes).unwrap_or(Json::Null))
+}
+
+#[tokio::test]
+async fn admin_edits_are_validated_persisted_and_logged() {
+    let dir = std::env::temp_dir().join(format!("api_source_weights_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let keys = dir.join("api_keys.json");
+    let weights = dir.join("source_weights.json");
+    let changes = dir.join("changes.jsonl");
+    std::fs::write(
+        &keys,
+        json!({ "keys": [
+            { "id": "ops", "key": ADMIN, "scopes": ["admin"] },
+            { "id": "notebook", "key": READER, "scopes": ["read"] }
+        ] })
+        .to_string(),
+    )
+    .unwrap();
+    std::fs::write(
+        &weights,
+        json!({
+            "default_weight": 0.6,
+            "weights": { "fed": 0.9, "reuters": 0.85, "Barron's": 0.8 },
+            "aliases": { "federal reserve": "fed" }
+        })
+        .to_string(),
+    )
+    .unwrap();
+    std::env::set_var("AI_ENABLED", "0");
+    std::env::set_var("API_KEYS_PATH", &keys);
+    std::env::set_var("ADMIN_AUDIT_LOG", dir.join("audit.jsonl"));
+    std::env::set_var("SOURCE_WEIGHTS_PATH", &weights);
+    std::env::set_var("SOURCE_WEIGHTS_CHANGE_LOG", &changes);
+
+    let clock: SharedClock = Arc::new(ManualClock::new(T0));
+    let app = api::router_with_clock(RelevanceAppState::from_env(), clock);
+    let path = "/admin/source-weights";
+
+    // Admin scope only.
+    let (status, _) = call(&app, "GET", path, READER, None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // Loaded from SOURCE_WEIGHTS_PATH with keys normalized like lookups.
+    let (status, cfg) = call(&app, "GET", path, ADMIN, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(cfg["weights"]["barron s"], 0.8);
+    assert_eq!(cfg["aliases"]["federal reserve"], "fed");
+
+    // Out-of-range weights and dangling aliases are rejected; nothing changes.
+    let before = std::fs::read_to_string(&weights).unwrap();
+    let bad = json!({ "weights": { "fed": 1.2 }, "aliases": { "powell": "jerome powell" } });
+    let (status, err) = call(&app, "PATCH", path, ADMIN, Some(bad.to_string())).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(err["error"]["code"], "validation_failed");
+    let fields: Vec<&str> = err["error"]["details"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| d["field"].as_str().unwrap())
+        .collect();
+    assert_eq!(fields, ["aliases.powell", "weights.fed"]);
+    let (status, err) = call(&app, "PUT", path, ADMIN, Some("{ nope".into())).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(err["error"]["code"], "invalid_json");
+
+    // Names that normalize alike would silently merge; they are rejected instead.
+    let clash = json!({ "weights": { "Fed": 0.9, "fed": 0.8 } });
+    for method in ["PUT", "PATCH"] {
+        let (status, err) = call(&app, method, path, ADMIN, Some(clash.to_string())).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{method}");
+        assert_eq!(err["error"]["details"][0]["field"], "weights.fed");
+    }
+    assert_eq!(std::fs::read_to_string(&weights).unwrap(), before);
+
+    // PATCH: set, add and remove entries; the response carries the diff.
+    let patch = json!({
+        "weights": { "Fed": 0.95, "Jerome Powell": 0.93, "reuters": null },
+        "aliases": { "powell": "jerome powell" }
+    });
+    let (status, out) = call(&app, "PATCH", path, ADMIN, Some(patch.to_string())).await;
+    assert_eq!(status, StatusCode::OK, "{out}");
+    let diff = &out["diff"];
+    assert_eq!(diff["weights"]["changed"]["fed"]["from"], 0.9);
+    assert_eq!(diff["weights"]["changed"]["fed"]["to"], 0.95);
+    assert_eq!(diff["weights"]["added"]["jerome powell"], 0.93);
+    assert_eq!(diff["weights"]["removed"]["reuters"], 0.85);
+    assert_eq!(diff["aliases"]["added"]["powell"], "jerome powell");
+    assert_eq!(out["config"]["weights"]["fed"], 0.95);
+
+    // Persisted: the file parses to the same config.
+    let saved: Json = serde_json::from_str(&std::fs::read_to_string(&weights).unwrap()).unwrap();
+    assert_eq!(saved, out["config"]);
+
+    // PUT replaces everything.
+    let full = json!({ "default_weight": 0.5, "weights": { "fed": 0.9 }, "aliases": {} });
+    let (status, out) = call(&app, "PUT", path, ADMIN, Some(full.to_string())).await;
+    assert_eq!(status, StatusCode::OK, "{out}");
+    assert_eq!(out["diff"]["default_weight"]["from"], 0.6);
+    assert_eq!(out["diff"]["default_weight"]["to"], 0.5);
+    assert_eq!(out["diff"]["aliases"]["removed"]["powell"], "jerome powell");
+    let (_, cfg) = call(&app, "GET", path, ADMIN, None).await;
+    assert_eq!(cfg, out["config"]);
+
+    // Unchanged PUT: empty diff, not logged.
+    let (status, out) = call(&app, "PUT", path, ADMIN, Some(full.to_string())).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(out["diff"]["weights"]["changed"]
+        .as_object()
+        .unwrap()
+        .is_empty());
+
+    // Change history, newest first, attributed to the key.
+    let (status, hist) = call(&app, "GET", &format!("{path}/history"), ADMIN, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let entries = hist["changes"].as_array().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0]["action"], "put");
+    assert_eq!(entries[1]["action"], "patch");
+    assert_eq!(entries[1]["key"], "ops");
+    assert_eq!(entries[1]["ts_unix"], T0);
+    assert_eq!(
+        std::fs::read_to_string(&changes).unwrap().lines().count(),
+        2
+    );
+    let (status, _) = call(&app, "GET", &format!("{path}/history?limit=0"), ADMIN, None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // An invalid file on disk is refused by the reload route; live weights stay.
+    let reload = "/admin/reload-source-weights";
+    let invalid = r#"{"weights": {"fed": 7}}"#;
+    std::fs::write(&weights, invalid).unwrap();
+    let (status, err) = call(&app, "POST", reload, ADMIN, None).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(err["error"]["code"], "validation_failed");
+    assert_eq!(err["error"]["details"][0]["field"], "weights.fed");
+    let (_, cfg) = call(&app, "GET", path, ADMIN, None).await;
+    assert_eq!(cfg["weights"]["fed"], 0.9);
+
+    // A router started on that file serves the seed but never overwrites the file:
+    // edits answer 409 until it is fixed and reloaded.
+    let clock: SharedClock = Arc::new(ManualClock::new(T0));
+    let app = api::router_with_clock(RelevanceAppState::from_env(), clock);
+    let edit = json!({ "weights": { "fed": 0.5 } }).to_string();
+    let (status, err) = call(&app, "PATCH", path, ADMIN, Some(edit.clone())).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(err["error"]["code"], "source_weights_unavailable");
+    assert_eq!(std::fs::read_to_string(&weights).unwrap(), invalid);
+    std::fs::write(&weights, full.to_string()).unwrap();
+    let (status, _) = call(&app, "POST", reload, ADMIN, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, out) = call(&app, "PATCH", path, ADMIN, Some(edit)).await;
+    assert_eq!(status, StatusCode::OK, "{out}");
+    assert_eq!(out["config"]["weights"]["fed"], 0.5);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,196 @@
+// tests/api_stream.rs
+//
+// /stream (SSE) and /stream/ws (WebSocket) push decisions as they reach `History`,
+// verdict changes, and ingested events, filtered per client.
+// Own test binary: env and the stream bus are process-wide.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::json;
+use serde_json::Value as Json;
+use shuttle_axum::axum::{
+    body::{self, Body},
+    http::{Request, StatusCode},
+    Router,
+};
+use tokio_stream::StreamExt as _;
+use tokio_tungstenite::tungstenite::Message;
+use tower::ServiceExt as _;
+
+use dow_sentiment_analyzer::api;
+use dow_sentiment_analyzer::clock::{ManualClock, SharedClock};
+use dow_sentiment_analyzer::ingest::types::SourceEvent;
+use dow_sentiment_analyzer::relevance::AppState as RelevanceAppState;
+use dow_sentiment_analyzer::stream::{self, StreamEvent};
+
+const BODY_LIMIT: usize = 1024 * 1024;
+const T0: u64 = 1_756_733_400; // 2025-09-01T13:30:00Z
+const WAIT: Duration = Duration::from_secs(5);
+
+const BUY: &str = "Fed signals rate cuts; Dow futures rally strongly on dovish outlook.";
+const SELL: &str = "Fed warns of recession risk; Dow futures plunge on hawkish stance.";
+// Reworded so the router's anti-spam window does not drop it as a repost of BUY.
+const BUY_AGAIN: &str = "Fed signals rate cuts; Dow futures rally strongly.";
+
+async fn decide(app: &Router, text: &str) -> Json {
+    let payload = json!([{ "source": "Fed", "text": text, "ts_unix": T0 }]);
+    let req = Request::builder()
+        .method("POST")
+        .uri("/decide")
+        .header("content-type", "application/json")
+        .body(Body::from(payload.to_string()))
+        .expect("build POST /decide");
+    let resp = app.clone().oneshot(req).await.expect("oneshot /decide");
+    assert_eq!(resp.status(), StatusCode::OK);
+    let bytes = body::to_bytes(resp.into_body(), BODY_LIMIT)
+        .await
+        .expect("read body");
+    serde_json::from_slice(&bytes).expect("json body")
+}
+
+/// Parsed SSE frames (`event`, `id`, JSON `data`) from a response body.
+struct SseReader {
+    body: body::BodyDataStream,
+    buf: String,
+}
+
+impl SseReader {
+    async fn next(&mut self) -> (String, String, Json) {
+        loop {
+            if let Some(end) = self.buf.find("\n\n") {
+                let frame: String = self.buf.drain(..end + 2).collect();
+                let field = |name: &str| {
+                    frame
+                        .lines()
+                        .find_map(|l| l.strip_prefix(name))
+                        .map(|v| v.trim_start().to_string())
+                        .unwrap_or_default()
+                };
+                // Keep-alive comments carry no event.
+                if field("event:").is_empty() {
+                    continue;
+                }
+                let data = serde_json::from_str(&field("data:")).expect("json data");
+                return (field("event:"), field("id:"), data);
+            }
+            let chunk = tokio::time::timeout(WAIT, self.body.next())
+                .await
+                .expect("SSE event in time")
+                .expect("stream open")
+                .expect("body chunk");
+            self.buf
+                .push_str(std::str::from_utf8(&chunk).expect("utf-8"));
+        }
+    }
+}
+
+async fn sse(app: &Router, query: &str) -> SseReader {
+    let req = Request::builder()
+        .uri(format!("/stream{query}"))
+        .body(Body::empty())
+        .expect("build GET /stream");
+    let resp = app.clone().oneshot(req).await.expect("oneshot /stream");
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers()["content-type"], "text/event-stream");
+    SseReader {
+        body: resp.into_body().into_data_stream(),
+        buf: String::new(),
+    }
+}
+
+/// Next text message from a WebSocket, as JSON.
+async fn next_ws<S>(ws: &mut S) -> Json
+where
+    S: tokio_stream::Stream<Item = Result<Message, tokio_tungstenite::tungstenite::Error>> + Unpin,
+{
+    loop {
+        let msg = tokio::time::timeout(WAIT, ws.next())
+            .await
+            .expect("ws message in time")
+            .expect("ws open")
+            .expect("ws frame");
+        if let Message::Text(t) = msg {
+            return serde_json::from_str(t.as_str()).expect("json message");
+        }
+    }
+}
+
+#[tokio::test]
+async fn stream_pushes_decisions_changes_and_ingest_events() {
+    std::env::set_var("AI_ENABLED", "0");
+    let clock: SharedClock = Arc::new(ManualClock::new(T0));
+    let app = api::router_with_clock(RelevanceAppState::from_env(), clock);
+
+    // --- SSE: default filter = decisions + verdict changes ---
+    let mut all = sse(&app, "").await;
+    let mut changes = sse(&app, "?changes_only=1").await;
+
+    let d = decide(&app, BUY).await;
+    assert_eq!(d["decision"], "BUY");
+    let (event, id, data) = all.next().await;
+    assert_eq!(event, "decision");
+    assert_eq!(data["type"], "decision");
+    assert_eq!(data["seq"].to_string(), id);
+    assert_eq!(data["decision"]["decision"], "BUY");
+    assert_eq!(data["decision"]["confidence"], d["confidence"]);
+
+    let d = decide(&app, SELL).await;
+    assert_eq!(d["decision"], "SELL");
+    let (event, _, data) = all.next().await;
+    assert_eq!(
+        (event.as_str(), &data["decision"]["decision"]),
+        ("decision", &json!("SELL"))
+    );
+    let (event, _, data) = all.next().await;
+    assert_eq!(event, "verdict_change");
+    assert_eq!(
+        (&data["from"], &data["to"]),
+        (&json!("BUY"), &json!("SELL"))
+    );
+
+    // The changes-only client saw nothing but the change.
+    let (event, _, data) = changes.next().await;
+    assert_eq!(event, "verdict_change");
+    assert_eq!(data["to"], "SELL");
+
+    // Invalid filters are rejected up front.
+    let req = Request::builder()
+        .uri("/stream?min_confidence=7")
+        .body(Body::empty())
+        .unwrap();
+    let resp = app.clone().oneshot(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    // --- WebSocket: real connection, ingest events opted in ---
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = app.clone();
+    tokio::spawn(async move {
+        shuttle_axum::axum::serve(listener, server).await.unwrap();
+    });
+    let url = format!("ws://{addr}/stream/ws?types=ingest,verdict_change&min_confidence=0");
+    let (mut ws, _) = tokio_tungstenite::connect_async(url)
+        .await
+        .expect("websocket handshake");
+
+    stream::bus().publish(StreamEvent::Ingest {
+        ts_unix: T0,
+        event: SourceEvent {
+            source: "Fed".into(),
+            published_at: T0,
+            text: "FOMC statement released".into(),
+            url: Some("https://www.federalreserve.gov/".into()),
+            priority_hint: None,
+        },
+    });
+    let m = next_ws(&mut ws).await;
+    assert_eq!(m["type"], "ingest");
+    assert_eq!(m["event"]["source"], "Fed");
+
+    // A plain decision is filtered out; the verdict change comes through.
+    decide(&app, BUY_AGAIN).await;
+    let m = next_ws(&mut ws).await;
+    assert_eq!(m["type"], "verdict_change");
+    assert_eq!((&m["from"], &m["to"]), (&json!("SELL"), &json!("BUY")));
+}
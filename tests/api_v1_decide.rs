@@ -0,0 +1,138 @@
+// tests/api_v1_decide.rs
+//
+// POST /v1/decide validates strictly (422 with index/field/problem per error, 400 for
+// invalid JSON) while POST /decide stays lenient.
+// Own test binary: limits come from env read when the router is built.
+
+use std::sync::Arc;
+
+use serde_json::json;
+use serde_json::Value as Json;
+use shuttle_axum::axum::{
+    body::{self, Body},
+    http::{Request, StatusCode},
+    Router,
+};
+use tower::ServiceExt as _;
+
+use dow_sentiment_analyzer::api;
+use dow_sentiment_analyzer::clock::{ManualClock, SharedClock};
+use dow_sentiment_analyzer::relevance::AppState as RelevanceAppState;
+
+const BODY_LIMIT: usize = 1024 * 1024;
+const T0: u64 = 1_756_733_400; // 2025-09-01T13:30:00Z
+
+async fn post(app: &Router, uri: &str, body: String) -> (StatusCode, Json) {
+    let req = Request::builder()
+        .method("POST")
+        .uri(uri)
+        .header("content-type", "application/json")
+        .body(Body::from(body))
+        .expect("build request");
+    let resp = app.clone().oneshot(req).await.expect("oneshot");
+    let status = resp.status();
+    let bytes = body::to_bytes(resp.into_body(), BODY_LIMIT)
+        .await
+        .expect("read body");
+    (status, serde_json::from_slice(&bytes).expect("json body"))
+}
+
+fn has_detail(v: &Json, index: Option<usize>, field: &str, problem: &str) -> bool {
+    v["error"]["details"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .any(|d| {
+            d.get("index").and_then(Json::as_u64) == index.map(|i| i as u64)
+                && d["field"] == field
+                && d["problem"]
+                    .as_str()
+                    .is_some_and(|p| p.starts_with(problem))
+        })
+}
+
+#[tokio::test]
+async fn v1_decide_rejects_invalid_items_with_details() {
+    std::env::set_var("AI_ENABLED", "0");
+    std::env::set_var("DECIDE_MAX_ITEMS", "4");
+    std::env::set_var("DECIDE_MAX_TEXT_CHARS", "40");
+
+    let clock: SharedClock = Arc::new(ManualClock::new(T0));
+    let app = api::router_with_clock(RelevanceAppState::from_env(), clock);
+
+    // Valid: same response shape as /decide.
+    let ok = json!({ "items": [{
+        "source": "Fed",
+        "text": "Fed signals rate cuts; Dow rallies.",
+        "ts_unix": T0,
+        "url": "https://example.com/fed"
+    }]});
+    let (status, v) = post(&app, "/v1/decide", ok.to_string()).await;
+    assert_eq!(status, StatusCode::OK, "{v}");
+    assert!(v["decision"].is_string());
+    assert!(v["pipeline"].is_object());
+
+    // Every problem is reported with its item index and field.
+    let bad = json!([
+        { "source": "Fed", "text": "   " },
+        "not an item",
+        { "source": 7, "text": "ok", "ts_unix": -5, "url": "ftp://x", "extra": true },
+        { "source": "Reuters", "text": "x".repeat(41) }
+    ]);
+    let (status, v) = post(&app, "/v1/decide", bad.to_string()).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{v}");
+    assert_eq!(v["error"]["code"], "validation_failed");
+    assert_eq!(
+        v["error"]["details"].as_array().map(Vec::len),
+        Some(7),
+        "{v}"
+    );
+    assert!(has_detail(&v, Some(0), "text", "must not be empty"));
+    assert!(has_detail(&v, Some(1), "item", "expected an object"));
+    assert!(has_detail(&v, Some(2), "source", "expected a string"));
+    assert!(has_detail(
+        &v,
+        Some(2),
+        "ts_unix",
+        "expected a non-negative integer"
+    ));
+    assert!(has_detail(&v, Some(2), "url", "expected an http(s) URL"));
+    assert!(has_detail(&v, Some(2), "extra", "unknown field"));
+    assert!(has_detail(
+        &v,
+        Some(3),
+        "text",
+        "too long: 41 chars (max 40)"
+    ));
+
+    // The lenient endpoint keeps skipping what it cannot parse.
+    let (status, _) = post(&app, "/decide", bad.to_string()).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Body-level errors: limits, wrong shape, empty batch.
+    let item = json!({ "source": "Fed", "text": "Fed holds rates" });
+    let too_many = json!([item, item, item, item, item]);
+    let (status, v) = post(&app, "/v1/decide", too_many.to_string()).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(
+        has_detail(&v, None, "items", "too many items: 5 (max 4)"),
+        "{v}"
+    );
+
+    let (status, v) = post(&app, "/v1/decide", "42".into()).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(has_detail(&v, None, "body", "expected an array"), "{v}");
+
+    let (status, v) = post(&app, "/v1/decide", json!({ "items": [] }).to_string()).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(
+        has_detail(&v, None, "items", "must contain at least 1 item"),
+        "{v}"
+    );
+
+    // Unparsable JSON is a 400 in the same envelope.
+    let (status, v) = post(&app, "/v1/decide", "[{".into()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(v["error"]["code"], "invalid_json");
+    assert!(v["error"].get("details").is_none());
+}
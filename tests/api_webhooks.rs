@@ -0,0 +1,252 @@
+// tests/api_webhooks.rs
+//
+// /subscriptions: only mounted with API keys (`webhooks` scope) or DEBUG_ROUTES; a
+// registered callback receives each decision as a signed POST, a failing first attempt
+// is retried, delivery status is reported per subscription, and subscriptions survive
+// in SUBSCRIPTIONS_PATH until deleted. The receiver is local, so the test opts in with
+// WEBHOOK_ALLOW_PRIVATE.
+// Own test binary: env and the stream bus are process-wide.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::json;
+use serde_json::Value as Json;
+use shuttle_axum::axum::{
+    self,
+    body::{self, Body, Bytes},
+    http::{HeaderMap, Request, StatusCode},
+    routing::post,
+    Router,
+};
+use tokio::sync::mpsc;
+use tower::ServiceExt as _;
+
+use dow_sentiment_analyzer::api;
+use dow_sentiment_analyzer::auth::hash_key;
+use dow_sentiment_analyzer::clock::{ManualClock, SharedClock};
+use dow_sentiment_analyzer::relevance::AppState as RelevanceAppState;
+use dow_sentiment_analyzer::webhooks::{self, SIGNATURE_HEADER, TIMESTAMP_HEADER};
+
+const BODY_LIMIT: usize = 1024 * 1024;
+const T0: u64 = 1_756_733_400; // 2025-09-01T13:30:00Z
+const HOOKS_KEY: &str = "hooks-secret";
+
+async fn call(app: &Router, method: &str, uri: &str, payload: Option<Json>) -> (StatusCode, Json) {
+    call_as(app, method, uri, Some(HOOKS_KEY), payload).await
+}
+
+async fn call_as(
+    app: &Router,
+    method: &str,
+    uri: &str,
+    key: Option<&str>,
+    payload: Option<Json>,
+) -> (StatusCode, Json) {
+    let body = payload
+        .map(|p| Body::from(p.to_string()))
+        .unwrap_or_default();
+    let mut req = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json");
+    if let Some(key) = key {
+        req = req.header("x-api-key", key);
+    }
+    let req = req.body(body).expect("build request");
+    let resp = app.clone().oneshot(req).await.expect("oneshot");
+    let status = resp.status();
+    let bytes = body::to_bytes(resp.into_body(), BODY_LIMIT)
+        .await
+        .expect("read body");
+    let json = if bytes.is_empty() {
+        Json::Null
+    } else {
+        serde_json::from_slice(&bytes).expect("json body")
+    };
+    (status, json)
+}
+
+/// Receiver that answers 500 to the first request and 200 afterwards.
+async fn receiver() -> (String, mpsc::UnboundedReceiver<(HeaderMap, String)>) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let hits = Arc::new(AtomicUsize::new(0));
+    let app = Router::new().route(
+        "/hook",
+        post(move |headers: HeaderMap, body: Bytes| {
+            let (tx, hits) = (tx.clone(), hits.clone());
+            async move {
+                let body = String::from_utf8_lossy(&body).into_owned();
+                let _ = tx.send((headers, body));
+                if hits.fetch_add(1, Ordering::SeqCst) == 0 {
+                    StatusCode::INTERNAL_SERVER_ERROR
+                } else {
+                    StatusCode::OK
+                }
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (format!("http://{addr}/hook"), rx)
+}
+
+#[tokio::test]
+async fn subscriptions_receive_signed_retried_deliveries() {
+    let dir = std::env::temp_dir().join(format!("api_webhooks_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("subscriptions.json");
+    let keys = dir.join("api_keys.json");
+    std::env::set_var("AI_ENABLED", "0");
+    std::env::remove_var("DEBUG_ROUTES");
+    std::env::set_var("API_KEYS_PATH", &keys);
+    std::env::set_var("ADMIN_AUDIT_LOG", dir.join("audit.jsonl"));
+    std::env::set_var("SUBSCRIPTIONS_PATH", &path);
+    std::env::set_var("WEBHOOK_BACKOFF_MS", "10");
+    let clock: SharedClock = Arc::new(ManualClock::new(T0));
+
+    // Without API keys or DEBUG_ROUTES the routes are not mounted at all.
+    let open = api::router_with_clock(RelevanceAppState::from_env(), clock.clone());
+    let (status, _) = call_as(
+        &open,
+        "POST",
+        "/subscriptions",
+        None,
+        Some(json!({ "url": "https://hooks.example.com/dow" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    std::fs::write(
+        &keys,
+        json!({ "keys": [
+            { "id": "reader", "sha256": hash_key("read-secret"), "scopes": ["read"] },
+            { "id": "hooks", "sha256": hash_key(HOOKS_KEY), "scopes": ["webhooks", "decide"] }
+        ] })
+        .to_string(),
+    )
+    .unwrap();
+    let app = api::router_with_clock(RelevanceAppState::from_env(), clock.clone());
+
+    // With keys: the `webhooks` scope is required, and local targets are refused...
+    let hook = Some(json!({ "url": "https://hooks.example.com/dow" }));
+    let (status, _) = call_as(&app, "GET", "/subscriptions", None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = call_as(&app, "POST", "/subscriptions", Some("read-secret"), hook).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, body) = call(
+        &app,
+        "POST",
+        "/subscriptions",
+        Some(json!({ "url": "http://127.0.0.1:9/hook" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["error"]["code"], "invalid_subscription");
+
+    // ...unless WEBHOOK_ALLOW_PRIVATE opts in. It is read when the subscriptions file is
+    // first loaded, and routers on one file share it, so drop the ones built so far.
+    drop((open, app));
+    std::env::set_var("WEBHOOK_ALLOW_PRIVATE", "1");
+    let app = api::router_with_clock(RelevanceAppState::from_env(), clock);
+    let (url, mut rx) = receiver().await;
+
+    // Validation: bad scheme, unknown event type.
+    let (status, body) = call(
+        &app,
+        "POST",
+        "/subscriptions",
+        Some(json!({ "url": "ftp://example.com/hook" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["error"]["code"], "invalid_subscription");
+    let (status, _) = call(
+        &app,
+        "POST",
+        "/subscriptions",
+        Some(json!({ "url": url, "filter": { "events": ["trade"] } })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let secret = "0123456789abcdef-test";
+    let (status, created) = call(
+        &app,
+        "POST",
+        "/subscriptions",
+        Some(json!({ "url": url, "secret": secret, "filter": { "events": ["decision"] } })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{created}");
+    assert_eq!(created["secret"], secret);
+    let id = created["id"].as_str().unwrap().to_string();
+
+    // Persisted right away, secret included.
+    let saved: Json = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(saved[0]["id"], id.as_str());
+    assert_eq!(saved[0]["secret"], secret);
+
+    let (status, _) = call(
+        &app,
+        "POST",
+        "/decide",
+        Some(json!([{ "source": "Fed", "text": "Fed signals rate cuts; Dow futures rally strongly." }])),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // First attempt gets a 500, the retry succeeds with the same signed body.
+    let mut attempts = Vec::new();
+    for _ in 0..2 {
+        let got = tokio::time::timeout(Duration::from_secs(10), rx.recv())
+            .await
+            .expect("delivery within 10s")
+            .expect("receiver open");
+        attempts.push(got);
+    }
+    for (headers, body) in &attempts {
+        let ts: u64 = headers[TIMESTAMP_HEADER].to_str().unwrap().parse().unwrap();
+        assert_eq!(ts, T0);
+        assert_eq!(
+            headers[SIGNATURE_HEADER].to_str().unwrap(),
+            webhooks::sign(secret, ts, body)
+        );
+        assert_eq!(headers["x-webhook-event"], "decision");
+        let event: Json = serde_json::from_str(body).unwrap();
+        assert_eq!(event["type"], "decision");
+    }
+    assert_eq!(attempts[0].1, attempts[1].1);
+
+    // Status is tracked per subscription; the secret is never shown again.
+    let mut view = Json::Null;
+    for _ in 0..100 {
+        let (status, v) = call(&app, "GET", &format!("/subscriptions/{id}"), None).await;
+        assert_eq!(status, StatusCode::OK);
+        view = v;
+        if view["deliveries"]["delivered"] == 1 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(view["deliveries"]["delivered"], 1, "{view}");
+    assert_eq!(view["deliveries"]["recent"][0]["attempts"], 2);
+    assert_eq!(view["deliveries"]["recent"][0]["status"], 200);
+    assert!(view.get("secret").is_none());
+
+    let (_, list) = call(&app, "GET", "/subscriptions", None).await;
+    assert_eq!(list["subscriptions"].as_array().unwrap().len(), 1);
+
+    let (status, _) = call(&app, "DELETE", &format!("/subscriptions/{id}"), None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, body) = call(&app, "GET", &format!("/subscriptions/{id}"), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["error"]["code"], "not_found");
+    let saved: Json = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(saved, json!([]));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,246 @@
+// tests/backtest_replay.rs
+//
+// End-to-end backtest over fixtures: statements JSONL → replay on a simulated
+// clock → join against a small YM OHLC CSV. A replayed batch is decided exactly
+// like the same batch posted to `/decide`.
+
+use std::sync::Arc;
+
+use serde_json::json;
+use serde_json::Value as Json;
+use shuttle_axum::axum::{
+    body::{self, Body},
+    http::{Request, StatusCode},
+};
+use tower::ServiceExt as _;
+
+use dow_sentiment_analyzer::api;
+use dow_sentiment_analyzer::backtest::{self, Backtester, ReplayStatement};
+use dow_sentiment_analyzer::clock::{ManualClock, SharedClock};
+use dow_sentiment_analyzer::decision::Verdict;
+use dow_sentiment_analyzer::relevance::{
+    AppState as RelevanceAppState, RelevanceEngine, RelevanceHandle,
+};
+use dow_sentiment_analyzer::source_weights::SourceWeightsConfig;
+use dow_sentiment_analyzer::stabilizer::StabilizerParams;
+
+const T0: u64 = 1_756_733_400; // 2025-09-01T13:30:00Z
+const CPI_TS: u64 = 1_760_531_400; // 2025-10-15T08:30:00-04:00
+
+fn backtester() -> Backtester {
+    let toml = std::fs::read_to_string("config/relevance.toml").expect("relevance config");
+    let relevance = RelevanceHandle::new(RelevanceEngine::from_toml_str(&toml).unwrap());
+    Backtester::new(
+        relevance,
+        SourceWeightsConfig::load_from_file("source_weights.json"),
+    )
+}
+
+#[test]
+fn replay_uses_simulated_clock_and_scores_against_prices() {
+    let statements = backtest::load_statements("tests/fixtures/backtest_statements.jsonl").unwrap();
+    let bars = backtest::load_bars("tests/fixtures/backtest_ym.csv").unwrap();
+    assert_eq!(statements.len(), 3);
+
+    let decisions = backtester().replay(&statements);
+    let verdicts: Vec<(u64, Verdict)> = decisions.iter().map(|d| (d.ts_unix, d.verdict)).collect();
+    // The BUY trigger is still fresh 5 min later (same lookback window), the
+    // hawkish statement an hour later is decided on its own.
+    assert_eq!(
+        verdicts,
+        vec![
+            (T0, Verdict::Buy),
+            (T0 + 300, Verdict::Buy),
+            (T0 + 3600, Verdict::Sell),
+        ]
+    );
+
+    let report = backtest::evaluate(&decisions, &bars, &[300, 900, 9000]);
+    assert_eq!(report.decisions, 3);
+    assert_eq!(report.directional, 3);
+    assert_eq!(report.hit_rate, Some(1.0));
+    assert_eq!(report.horizons[0].samples, 3);
+    assert!(report.horizons[1].mean_points.unwrap() > 0.0);
+    // 2.5h after the SELL lies beyond the last bar.
+    assert_eq!(report.horizons[2].samples, 2);
+    assert_eq!(report.position_changes, 2);
+    assert!((report.turnover - 3.0).abs() < 1e-9);
+    assert!(report.total_return > 0.0);
+    assert!(report.max_drawdown < 1e-9);
+}
+
+#[test]
+fn stabilizer_params_apply_during_replay() {
+    let statements = backtest::load_statements("tests/fixtures/backtest_statements.jsonl").unwrap();
+    let decisions = backtester()
+        .stabilizer(StabilizerParams {
+            confirmations: 2,
+            min_dwell_secs: 0,
+            override_margin: None,
+        })
+        .replay(&statements);
+
+    let last = decisions.last().unwrap();
+    assert_eq!(last.verdict, Verdict::Buy);
+    assert_eq!(last.raw_verdict, Some(Verdict::Sell));
+}
+
+#[tokio::test]
+async fn replay_matches_the_decide_handler() {
+    // Event window, rules and anti-spam all in play.
+    std::env::set_var("AI_ENABLED", "0");
+    std::env::set_var("EVENT_CALENDAR_PATH", "tests/fixtures/events_cpi.json");
+    std::env::set_var("RULES_CONFIG_PATH", "tests/fixtures/rules_pipeline.json");
+    std::env::set_var("SOURCE_WEIGHTS_PATH", "source_weights.json");
+    let now = CPI_TS + 60;
+    let batch = [
+        // 0.78 on its own; the CPI window lifts it over the trigger minimum.
+        ("Fox Business", "Fed signals rate cuts; Dow futures rally."),
+        // Near-duplicate: dropped by anti-spam.
+        ("Reuters", "Fed signals rate cuts; Dow futures rally!"),
+        (
+            "Federal Reserve",
+            "Dovish Fed signals rate cuts as inflation cools.",
+        ),
+    ];
+
+    let clock: SharedClock = Arc::new(ManualClock::new(now));
+    let app = api::router_with_clock(RelevanceAppState::from_env(), clock);
+    let items: Vec<Json> = batch
+        .iter()
+        .map(|(source, text)| json!({ "source": source, "text": text, "ts_unix": now }))
+        .collect();
+    let req = Request::builder()
+        .method("POST")
+        .uri("/decide")
+        .header("content-type", "application/json")
+        .body(Body::from(Json::from(items).to_string()))
+        .unwrap();
+    let resp = app.oneshot(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let bytes = body::to_bytes(resp.into_body(), 1024 * 1024).await.unwrap();
+    let live: Json = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(live["pipeline"]["antispam_dropped"], 1, "{live}");
+    assert!(!live["pipeline"]["rules_matched"]
+        .as_array()
+        .unwrap()
+        .is_empty());
+
+    let statements: Vec<ReplayStatement> = batch
+        .iter()
+        .map(|(source, text)| ReplayStatement {
+            ts_unix: now,
+            source: source.to_string(),
+            text: text.to_string(),
+        })
+        .collect();
+    let relevance = RelevanceHandle::new(RelevanceEngine::from_toml().unwrap());
+    let replayed = Backtester::from_env(
+        relevance,
+        SourceWeightsConfig::load_from_file("source_weights.json"),
+    )
+    .replay(&statements);
+    assert_eq!(replayed.len(), 1);
+    let d = &replayed[0];
+    assert_eq!(serde_json::to_value(d.verdict).unwrap(), live["decision"]);
+    assert_eq!(d.verdict, Verdict::Buy);
+    let confidence = live["confidence"].as_f64().unwrap() as f32;
+    assert!(
+        (d.confidence - confidence).abs() < 1e-6,
+        "{} vs {confidence}",
+        d.confidence
+    );
+    assert_eq!(serde_json::to_value(d.signal).unwrap(), live["signal"]);
+}
+
+#[tokio::test]
+async fn repeated_batch_replays_like_the_handler() {
+    // A client re-sends its last 30 minutes with each new statement; replay posts
+    // its lookback window the same way, so both see the same batches.
+    std::env::set_var("AI_ENABLED", "0");
+    std::env::set_var("EVENT_CALENDAR_PATH", "tests/fixtures/events_cpi.json");
+    std::env::set_var("RULES_CONFIG_PATH", "tests/fixtures/rules_pipeline.json");
+    std::env::set_var("SOURCE_WEIGHTS_PATH", "source_weights.json");
+    let now = CPI_TS + 60;
+    let statement = |ts_unix: u64, source: &str, text: &str| ReplayStatement {
+        ts_unix,
+        source: source.into(),
+        text: text.into(),
+    };
+    let statements = vec![
+        statement(
+            now,
+            "Fox Business",
+            "Fed signals rate cuts; Dow futures rally.",
+        ),
+        statement(
+            now,
+            "Federal Reserve",
+            "Dovish Fed signals rate cuts as inflation cools.",
+        ),
+        // Near-duplicate of the first, five minutes later.
+        statement(
+            now + 300,
+            "Reuters",
+            "Fed signals rate cuts; Dow futures rally!",
+        ),
+        // The first two have left the window; the Reuters line is alone in it now.
+        statement(
+            now + 1860,
+            "Fox Business",
+            "Dow futures steady ahead of the open.",
+        ),
+    ];
+    let lookback = 30 * 60;
+
+    let clock = Arc::new(ManualClock::new(now));
+    let shared: SharedClock = clock.clone();
+    let app = api::router_with_clock(RelevanceAppState::from_env(), shared);
+    let mut live = Vec::new();
+    for t in [now, now + 300, now + 1860] {
+        clock.set(t);
+        let items: Vec<Json> = statements
+            .iter()
+            .filter(|s| s.ts_unix + lookback > t && s.ts_unix <= t)
+            .map(|s| json!({ "source": s.source, "text": s.text, "ts_unix": s.ts_unix }))
+            .collect();
+        let req = Request::builder()
+            .method("POST")
+            .uri("/decide")
+            .header("content-type", "application/json")
+            .body(Body::from(Json::from(items).to_string()))
+            .unwrap();
+        let resp = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let bytes = body::to_bytes(resp.into_body(), 1024 * 1024).await.unwrap();
+        live.push(serde_json::from_slice::<Json>(&bytes).unwrap());
+    }
+    // Re-sent statements stay in; only the in-batch near-duplicate is dropped.
+    assert_eq!(live[1]["pipeline"]["antispam_dropped"], 1, "{}", live[1]);
+    assert_eq!(live[2]["pipeline"]["antispam_dropped"], 0, "{}", live[2]);
+
+    let relevance = RelevanceHandle::new(RelevanceEngine::from_toml().unwrap());
+    let replayed = Backtester::from_env(
+        relevance,
+        SourceWeightsConfig::load_from_file("source_weights.json"),
+    )
+    .lookback_secs(lookback)
+    .replay(&statements);
+    assert_eq!(replayed.len(), live.len());
+    for (d, v) in replayed.iter().zip(&live) {
+        assert_eq!(
+            serde_json::to_value(d.verdict).unwrap(),
+            v["decision"],
+            "at {}",
+            d.ts_unix
+        );
+        let confidence = v["confidence"].as_f64().unwrap() as f32;
+        assert!(
+            (d.confidence - confidence).abs() < 1e-6,
+            "{} vs {confidence} at {}",
+            d.confidence,
+            d.ts_unix
+        );
+        assert_eq!(serde_json::to_value(d.signal).unwrap(), v["signal"]);
+    }
+}
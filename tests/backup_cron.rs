@@ -12,4 +12,10 @@ async fn backup_sink_is_called_with_config_files() {
     let first = &calls[0];
     // Expect at least one JSON config in payload
     assert!(first.iter().any(|(path, _content)| path.ends_with(".json")));
+    // NER patterns live in their own dir and are backed up too.
+    assert!(first.iter().any(|(path, _content)| {
+        std::path::Path::new(path)
+            .parent()
+            .is_some_and(|p| p.ends_with("config/ner"))
+    }));
 }
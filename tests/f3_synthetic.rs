@@ -182,6 +182,7 @@ fn f3_antispam_filters_near_duplicates_within_window() {
         window_size: 16,
         similarity_threshold: 0.90,
         time_window_secs: 600,
+        ..Default::default()
     };
     let mut anti = AntiSpam::new(params);
 
@@ -15,6 +15,11 @@ async fn build_app() -> Router {
 fn set_metrics_env(suffix: &str) {
     // Gate for debug routes (/metrics)
     std::env::set_var("DEBUG_ROUTES", "1");
+    // Keep audited calls out of the repo's state/admin_audit.jsonl
+    std::env::set_var(
+        "ADMIN_AUDIT_LOG",
+        std::env::temp_dir().join(format!("test_metrics_audit_{}.jsonl", suffix)),
+    );
     // Keep AI in mock mode so /decide is deterministic & fast
     std::env::set_var("AI_TEST_MODE", "mock");
     std::env::set_var("AI_ENABLED", "1");